@@ -1,16 +1,25 @@
+use std::io::Cursor;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::Duration;
 
 use anyhow::{Result, anyhow, bail};
 use clap::Parser;
-use march5::db;
+use march5::cid::CidEncoding;
+use march5::guard::load_guard_info;
+use march5::inet::load_agent_info;
+use march5::node::{NodeKind, load_node_info};
 use march5::prim::load_prim_info;
+use march5::types::render_comma_list;
 use march5::word::load_word_info;
 use march5::{
-    TypeTag, cid, create_store, derive_db_path, get_name, list_names_for_cid, load_object_cbor,
-    open_store,
+    Budget, TypeTag, Value, cid, create_store, derive_db_path, get_name, load_object_cbor,
+    open_store, run_word_budgeted, yaml,
 };
-use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+use march5::{db, namespace};
+use percent_encoding::{AsciiSet, CONTROLS, percent_decode_str, utf8_percent_encode};
 use rusqlite::Connection;
 use serde::Deserialize;
 use serde_bytes::ByteBuf;
@@ -29,6 +38,10 @@ struct Args {
     /// Path to the March database
     #[arg(long = "db", default_value = "march5.db")]
     db_path: PathBuf,
+
+    /// Value for the `Access-Control-Allow-Origin` header (e.g. `*`); omitted when unset
+    #[arg(long = "allow-origin")]
+    allow_origin: Option<String>,
 }
 
 fn main() -> Result<()> {
@@ -47,30 +60,154 @@ fn main() -> Result<()> {
     let server = Server::http(&args.listen)
         .map_err(|err| anyhow!("failed to bind {}: {err}", args.listen))?;
     println!("March web UI listening on http://{}", args.listen);
+    install_shutdown_handler();
+    serve_until(&server, db_path, args.allow_origin, WORKERS, &SHUTDOWN);
+    println!("March web UI stopped");
+    Ok(())
+}
+
+/// Request-handling threads; each opens its own store connection per request.
+const WORKERS: usize = 4;
+
+/// How often the accept loop checks for a shutdown request.
+const SHUTDOWN_POLL: Duration = Duration::from_millis(200);
+
+/// Set by SIGINT/SIGTERM; the accept loop stops once it sees it.
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn request_shutdown(_signal: libc::c_int) {
+    SHUTDOWN.store(true, Ordering::SeqCst);
+}
+
+fn install_shutdown_handler() {
+    let handler = request_shutdown as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+/// Hand requests to `workers` threads until `stop` is set, then let them drain and join.
+fn serve_until(
+    server: &Server,
+    db_path: PathBuf,
+    allow_origin: Option<String>,
+    workers: usize,
+    stop: &AtomicBool,
+) {
+    let (sender, receiver) = mpsc::channel::<Request>();
+    let receiver = Arc::new(Mutex::new(receiver));
     let db_path = Arc::new(db_path);
+    let allow_origin = Arc::new(allow_origin);
+    let handles: Vec<_> = (0..workers.max(1))
+        .map(|_| {
+            let receiver = Arc::clone(&receiver);
+            let db_path = Arc::clone(&db_path);
+            let allow_origin = Arc::clone(&allow_origin);
+            thread::spawn(move || {
+                loop {
+                    // Hold the lock only while waiting, not while handling.
+                    let next = receiver.lock().expect("request queue poisoned").recv();
+                    let Ok(request) = next else { break };
+                    if let Err(err) = handle_request(&db_path, allow_origin.as_deref(), request) {
+                        eprintln!("error handling request: {err}");
+                    }
+                }
+            })
+        })
+        .collect();
 
-    for request in server.incoming_requests() {
-        let db_path = Arc::clone(&db_path);
-        if let Err(err) = handle_request(&db_path, request) {
-            eprintln!("error handling request: {err}");
+    while !stop.load(Ordering::SeqCst) {
+        match server.recv_timeout(SHUTDOWN_POLL) {
+            Ok(Some(request)) => {
+                if sender.send(request).is_err() {
+                    break;
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!("error accepting request: {err}");
+                break;
+            }
+        }
+    }
+    drop(sender);
+    for handle in handles {
+        if handle.join().is_err() {
+            eprintln!("request worker panicked");
         }
     }
+}
+
+type HttpResponse = Response<Cursor<Vec<u8>>>;
+
+fn handle_request(db_path: &Path, allow_origin: Option<&str>, request: Request) -> Result<()> {
+    let if_none_match = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("If-None-Match"))
+        .map(|h| h.value.to_string());
+    let response = serve(db_path, allow_origin, request.method(), request.url());
+    let response = conditional(response, if_none_match.as_deref());
+    request.respond(response)?;
     Ok(())
 }
 
-fn handle_request(db_path: &Path, request: Request) -> Result<()> {
-    if *request.method() != Method::Get {
-        let response = Response::from_string("Only GET supported")
+/// Swap a response for `304 Not Modified` when the client already holds its ETag.
+fn conditional(response: HttpResponse, if_none_match: Option<&str>) -> HttpResponse {
+    let Some(etag) = response
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("ETag"))
+        .map(|h| h.value.to_string())
+    else {
+        return response;
+    };
+    let matches = if_none_match.is_some_and(|tags| {
+        tags.split(',').any(|tag| {
+            let tag = tag.trim();
+            tag == "*" || tag.trim_start_matches("W/") == etag
+        })
+    });
+    if !matches {
+        return response;
+    }
+    let mut not_modified = Response::from_string("").with_status_code(StatusCode(304));
+    for kept in response.headers() {
+        if !kept.field.equiv("Content-Type") {
+            not_modified.add_header(kept.clone());
+        }
+    }
+    not_modified
+}
+
+/// Answer CORS preflights and tag routed responses with the configured origin.
+fn serve(db_path: &Path, allow_origin: Option<&str>, method: &Method, url: &str) -> HttpResponse {
+    let response = if *method == Method::Options {
+        Response::from_string("")
+            .with_status_code(StatusCode(204))
+            .with_header(header("Access-Control-Allow-Methods", "GET, OPTIONS"))
+            .with_header(header("Access-Control-Allow-Headers", "Content-Type"))
+    } else {
+        route(db_path, method, url)
+    };
+    match allow_origin {
+        Some(origin) => response.with_header(header("Access-Control-Allow-Origin", origin)),
+        None => response,
+    }
+}
+
+fn route(db_path: &Path, method: &Method, url: &str) -> HttpResponse {
+    if *method != Method::Get {
+        return Response::from_string("Only GET supported")
             .with_status_code(StatusCode(405))
             .with_header(content_type("text/plain"));
-        request.respond(response)?;
-        return Ok(());
     }
 
-    let url = request.url();
     let (path, query) = split_query(url);
     let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
-    let response = match segments.as_slice() {
+    match segments.as_slice() {
         [] | [""] => match open_store(db_path) {
             Ok(conn) => match build_index_html(&conn) {
                 Ok(html) => html_response(html),
@@ -81,53 +218,126 @@ fn handle_request(db_path: &Path, request: Request) -> Result<()> {
         ["api", "iface", rest @ ..] if !rest.is_empty() => {
             let name = rest.join("/");
             match fetch_named_json(db_path, "iface", "interface", &name) {
-                Ok(json) => json_response(json),
+                Ok((cid_bytes, json)) => object_response(&cid_bytes, json),
                 Err(err) => error_response(404, err),
             }
         }
         ["api", "agent", rest @ ..] if !rest.is_empty() => {
             let name = rest.join("/");
             match fetch_named_json(db_path, "agent", "agent", &name) {
-                Ok(json) => json_response(json),
+                Ok((cid_bytes, json)) => object_response(&cid_bytes, json),
                 Err(err) => error_response(404, err),
             }
         }
         ["api", "rule", rest @ ..] if !rest.is_empty() => {
             let name = rest.join("/");
             match fetch_named_json(db_path, "rule", "rule", &name) {
-                Ok(json) => json_response(json),
+                Ok((cid_bytes, json)) => object_response(&cid_bytes, json),
                 Err(err) => error_response(404, err),
             }
         }
         ["api", "namespace", rest @ ..] if !rest.is_empty() => {
             let name = rest.join("/");
             match fetch_named_json(db_path, "namespace", "namespace", &name) {
-                Ok(json) => json_response(json),
+                Ok((cid_bytes, json)) => object_response(&cid_bytes, json),
                 Err(err) => error_response(404, err),
             }
         }
         ["api", "word", rest @ ..] if !rest.is_empty() => {
             let name = rest.join("/");
-            match fetch_named_json(db_path, "word", "word", &name) {
-                Ok(json) => json_response(json),
+            match fetch_word_json(db_path, &name, cid_encoding(query)) {
+                Ok((cid_bytes, json)) => object_response(&cid_bytes, json),
+                Err(err) => error_response(404, err),
+            }
+        }
+        ["api", "node", text] => match cid::parse(text) {
+            Ok(cid_bytes) => match fetch_node_json(db_path, &cid_bytes, cid_encoding(query)) {
+                Ok(json) => object_response(&cid_bytes, json),
                 Err(err) => error_response(404, err),
+            },
+            Err(err) => error_response(400, err),
+        },
+        ["api", "run", rest @ ..] if !rest.is_empty() => {
+            let name = rest.join("/");
+            let args = query.and_then(|q| query_param(q, "args"));
+            run_word_response(db_path, &name, args.as_deref())
+        }
+        ["api", "search"] => {
+            let needle = query.and_then(|q| query_param(q, "q")).unwrap_or_default();
+            let scope = query
+                .and_then(|q| query_param(q, "scope"))
+                .filter(|s| !s.is_empty());
+            if needle.is_empty() {
+                error_response(400, anyhow!("missing `q` query parameter"))
+            } else {
+                match search_entries(db_path, &needle, scope.as_deref()) {
+                    Ok(entries) => json_response(entries),
+                    Err(err) => error_response(500, err),
+                }
             }
         }
         ["api", "list", scope] => {
-            let prefix = query.and_then(|q| parse_prefix(q));
+            let prefix = query.and_then(parse_prefix);
             match list_scope_entries(db_path, scope, prefix.as_deref()) {
                 Ok(entries) => json_response(entries),
                 Err(err) => error_response(500, err),
             }
         }
         _ => error_response(404, anyhow!("unrecognised path")),
+    }
+}
+
+/// Node evaluations allowed per `/api/run` request.
+const RUN_BUDGET: u64 = 1_000_000;
+
+/// Run a named word with YAML/JSON-encoded arguments and return its outputs as JSON.
+fn run_word_response(db_path: &Path, name: &str, args: Option<&str>) -> HttpResponse {
+    let conn = match open_store(db_path) {
+        Ok(conn) => conn,
+        Err(err) => return error_response(500, err),
+    };
+    let word_cid = match get_name(&conn, "word", name) {
+        Ok(Some(cid)) => cid,
+        Ok(None) => return error_response(404, anyhow!("word `{name}` not found")),
+        Err(err) => return error_response(500, err),
     };
+    let outcome = yaml::parse_values_from_str(args.unwrap_or(""))
+        .and_then(|values| {
+            let mut budget = Budget::new(RUN_BUDGET);
+            run_word_budgeted(&conn, &word_cid, &values, &mut budget)
+        })
+        .and_then(|outputs| {
+            let json = JsonValue::Array(outputs.iter().map(value_to_json).collect());
+            Ok(serde_json::to_string_pretty(&json)?)
+        });
+    match outcome {
+        Ok(body) => json_response(body),
+        Err(err) => error_response(400, err),
+    }
+}
 
-    request.respond(response)?;
-    Ok(())
+fn value_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::I64(n) => JsonValue::from(*n),
+        Value::F64(x) if x.is_finite() => JsonValue::from(*x),
+        Value::F64(_) => JsonValue::Null,
+        Value::U64(n) | Value::Ptr(n) => JsonValue::from(*n),
+        Value::Bytes(bytes) => JsonValue::String(bytes_to_hex(bytes)),
+        Value::Text(text) => JsonValue::String(text.clone()),
+        Value::Unit => JsonValue::Null,
+        Value::Tuple(items) => JsonValue::Array(items.iter().map(value_to_json).collect()),
+        Value::Quote(qid) => JsonValue::String(cid::to_hex(qid)),
+        Value::Token(_) => JsonValue::String(value.to_string()),
+    }
 }
 
-fn fetch_named_json(db_path: &Path, scope: &str, label: &str, name: &str) -> Result<String> {
+/// JSON for the object bound to `name`, with the CID it resolved to.
+fn fetch_named_json(
+    db_path: &Path,
+    scope: &str,
+    label: &str,
+    name: &str,
+) -> Result<([u8; 32], String)> {
     let conn = open_store(db_path)?;
     let cid_bytes =
         get_name(&conn, scope, name)?.ok_or_else(|| anyhow!("{label} `{name}` not found"))?;
@@ -137,9 +347,89 @@ fn fetch_named_json(db_path: &Path, scope: &str, label: &str, name: &str) -> Res
         map.insert("_cid".into(), JsonValue::String(cid::to_hex(&cid_bytes)));
         map.insert("_kind".into(), JsonValue::String(kind));
     }
+    Ok((cid_bytes, serde_json::to_string_pretty(&value)?))
+}
+
+/// Decoded word view; the root node links to the node endpoint for drilling into the graph.
+fn fetch_word_json(db_path: &Path, name: &str, enc: CidEncoding) -> Result<([u8; 32], String)> {
+    let conn = open_store(db_path)?;
+    let cid_bytes =
+        get_name(&conn, "word", name)?.ok_or_else(|| anyhow!("word `{name}` not found"))?;
+    let info = load_word_info(&conn, &cid_bytes)?;
+    let value = json!({
+        "_cid": cid::to_hex(&cid_bytes),
+        "_kind": "word",
+        "root": cid::to_hex(&info.root),
+        "root_href": make_node_href(&info.root, enc),
+        "params": info.params.iter().map(|t| t.as_atom()).collect::<Vec<_>>(),
+        "results": info.results.iter().map(|t| t.as_atom()).collect::<Vec<_>>(),
+        "effects": info.effects.iter().map(cid::to_hex).collect::<Vec<_>>(),
+        "effect_mask": info.effect_mask,
+        "guards": info.guards.iter().map(cid::to_hex).collect::<Vec<_>>(),
+    });
+    Ok((cid_bytes, serde_json::to_string_pretty(&value)?))
+}
+
+fn fetch_node_json(db_path: &Path, cid_bytes: &[u8; 32], enc: CidEncoding) -> Result<String> {
+    let conn = open_store(db_path)?;
+    let (kind, _) = load_object_cbor(&conn, cid_bytes)?;
+    if kind != "node" {
+        bail!("object {} is a {kind}, not a node", cid::to_hex(cid_bytes));
+    }
+    let info = load_node_info(&conn, cid_bytes)?;
+    let inputs: Vec<JsonValue> = info
+        .inputs
+        .iter()
+        .map(|input| node_ref_json(&input.cid, input.port, enc))
+        .collect();
+    let mut value = json!({
+        "_cid": cid::to_hex(cid_bytes),
+        "_kind": "node",
+        "kind": info.kind.name(),
+        "out": info.out,
+        "inputs": inputs,
+        "effects": info.effects.iter().map(cid::to_hex).collect::<Vec<_>>(),
+    });
+    // RETURN nodes carry their value and dependency edges in the payload.
+    if info.kind == NodeKind::Return
+        && let CborValue::Array(items) = &info.payload
+        && let [vals, deps] = items.as_slice()
+    {
+        value["vals"] = JsonValue::Array(payload_refs_json(vals, enc)?);
+        value["deps"] = JsonValue::Array(payload_refs_json(deps, enc)?);
+    }
+    value["payload"] = cbor_value_to_json(info.payload);
     Ok(serde_json::to_string_pretty(&value)?)
 }
 
+fn node_ref_json(cid_bytes: &[u8; 32], port: u32, enc: CidEncoding) -> JsonValue {
+    json!({
+        "cid": cid::to_hex(cid_bytes),
+        "port": port,
+        "href": make_node_href(cid_bytes, enc),
+    })
+}
+
+fn payload_refs_json(value: &CborValue, enc: CidEncoding) -> Result<Vec<JsonValue>> {
+    let CborValue::Array(entries) = value else {
+        bail!("expected an array of node references");
+    };
+    entries
+        .iter()
+        .map(|entry| match entry {
+            CborValue::Array(pair) => match pair.as_slice() {
+                [CborValue::Bytes(bytes), CborValue::Integer(port)] => Ok(node_ref_json(
+                    &cid::from_slice(bytes)?,
+                    u32::try_from(*port)?,
+                    enc,
+                )),
+                _ => bail!("malformed node reference"),
+            },
+            _ => bail!("malformed node reference"),
+        })
+        .collect()
+}
+
 fn list_scope_entries(db_path: &Path, scope: &str, prefix: Option<&str>) -> Result<String> {
     let conn = open_store(db_path)?;
     let entries = db::list_names(&conn, scope, prefix)?;
@@ -155,11 +445,31 @@ fn list_scope_entries(db_path: &Path, scope: &str, prefix: Option<&str>) -> Resu
     Ok(serde_json::to_string_pretty(&json_entries)?)
 }
 
+fn search_entries(db_path: &Path, needle: &str, scope: Option<&str>) -> Result<String> {
+    let conn = open_store(db_path)?;
+    let entries = db::search_names(&conn, needle, scope)?;
+    let json_entries: Vec<JsonValue> = entries
+        .into_iter()
+        .map(|entry| {
+            json!({
+                "scope": entry.scope,
+                "name": entry.name,
+                "cid": cid::to_hex(&entry.cid),
+            })
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&json_entries)?)
+}
+
 fn build_index_html(conn: &Connection) -> Result<String> {
     let namespaces = collect_namespace_rows(conn)?;
     let interfaces = collect_interface_rows(conn)?;
     let words = collect_word_rows(conn)?;
     let prims = collect_prim_rows(conn)?;
+    let agents = collect_agent_rows(conn)?;
+    let rules = collect_rule_rows(conn)?;
+    let effects = collect_effect_rows(conn)?;
+    let guards = collect_guard_rows(conn)?;
 
     let mut html = String::new();
     html.push_str(
@@ -174,6 +484,10 @@ fn build_index_html(conn: &Connection) -> Result<String> {
     html.push_str(render_interface_section(&interfaces).as_str());
     html.push_str(render_word_section(&words).as_str());
     html.push_str(render_prim_section(&prims).as_str());
+    html.push_str(render_agent_section(&agents).as_str());
+    html.push_str(render_rule_section(&rules).as_str());
+    html.push_str(render_effect_section(&effects).as_str());
+    html.push_str(render_guard_section(&guards).as_str());
 
     html.push_str("</body></html>");
     Ok(html)
@@ -232,16 +546,17 @@ fn render_word_section(rows: &[WordRow]) -> String {
         out.push_str("<p>No words registered.</p></section>");
         return out;
     }
-    out.push_str("<table class=\"grid\"><thead><tr><th>Name</th><th>CID</th><th>Signature</th><th>Effects</th></tr></thead><tbody>");
+    out.push_str("<table class=\"grid\"><thead><tr><th>Name</th><th>CID</th><th>Signature</th><th>Effects</th><th>Doc</th></tr></thead><tbody>");
     for row in rows {
         let _ = write!(
             out,
-            "<tr><td><a href=\"{}\">{}</a></td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+            "<tr><td><a href=\"{}\">{}</a></td><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
             make_api_href("word", &row.name),
             escape_html(&row.name),
             escape_html(&row.cid_hex),
             escape_html(&row.signature),
-            escape_html(&render_effects(&row.effects))
+            escape_html(&render_effects(&row.effects)),
+            escape_html(row.doc.as_deref().unwrap_or(""))
         );
     }
     out.push_str("</tbody></table></section>");
@@ -255,15 +570,108 @@ fn render_prim_section(rows: &[PrimRow]) -> String {
         out.push_str("<p>No primitives registered.</p></section>");
         return out;
     }
-    out.push_str("<table class=\"grid\"><thead><tr><th>Name</th><th>CID</th><th>Signature</th><th>Effects</th></tr></thead><tbody>");
+    out.push_str("<table class=\"grid\"><thead><tr><th>Name</th><th>CID</th><th>Signature</th><th>Effects</th><th>Doc</th></tr></thead><tbody>");
     for row in rows {
         let _ = write!(
             out,
-            "<tr><td><a href=\"{}\">{}</a></td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+            "<tr><td><a href=\"{}\">{}</a></td><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
             make_api_href("prim", &row.name),
             escape_html(&row.name),
             escape_html(&row.cid_hex),
             escape_html(&row.signature),
+            escape_html(&render_effects(&row.effects)),
+            escape_html(row.doc.as_deref().unwrap_or(""))
+        );
+    }
+    out.push_str("</tbody></table></section>");
+    out
+}
+
+fn render_agent_section(rows: &[AgentRow]) -> String {
+    let mut out = String::new();
+    out.push_str("<section><h2>Agents</h2>");
+    if rows.is_empty() {
+        out.push_str("<p>No agents registered.</p></section>");
+        return out;
+    }
+    out.push_str("<table class=\"grid\"><thead><tr><th>Name</th><th>CID</th><th>Kind</th><th>Ports</th><th>Doc</th></tr></thead><tbody>");
+    for row in rows {
+        let _ = write!(
+            out,
+            "<tr><td><a href=\"{}\">{}</a></td><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            make_api_href("agent", &row.name),
+            escape_html(&row.name),
+            escape_html(&row.cid_hex),
+            escape_html(&row.kind),
+            escape_html(&row.ports.join(", ")),
+            escape_html(row.doc.as_deref().unwrap_or(""))
+        );
+    }
+    out.push_str("</tbody></table></section>");
+    out
+}
+
+fn render_rule_section(rows: &[RuleRow]) -> String {
+    let mut out = String::new();
+    out.push_str("<section><h2>Rules</h2>");
+    if rows.is_empty() {
+        out.push_str("<p>No rules registered.</p></section>");
+        return out;
+    }
+    out.push_str("<table class=\"grid\"><thead><tr><th>Name</th><th>CID</th><th>Active pair</th><th>Rewire</th></tr></thead><tbody>");
+    for row in rows {
+        let _ = write!(
+            out,
+            "<tr><td><a href=\"{}\">{}</a></td><td><code>{}</code></td><td>{} ⋈ {}</td><td><code>{}</code></td></tr>",
+            make_api_href("rule", &row.name),
+            escape_html(&row.name),
+            escape_html(&row.cid_hex),
+            escape_html(&row.lhs.0),
+            escape_html(&row.lhs.1),
+            escape_html(&row.rewire)
+        );
+    }
+    out.push_str("</tbody></table></section>");
+    out
+}
+
+fn render_effect_section(rows: &[EffectRow]) -> String {
+    let mut out = String::new();
+    out.push_str("<section><h2>Effects</h2>");
+    if rows.is_empty() {
+        out.push_str("<p>No effects registered.</p></section>");
+        return out;
+    }
+    out.push_str("<table class=\"grid\"><thead><tr><th>Name</th><th>CID</th><th>Effect</th><th>Doc</th></tr></thead><tbody>");
+    for row in rows {
+        let _ = write!(
+            out,
+            "<tr><td>{}</td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+            escape_html(&row.name),
+            escape_html(&row.cid_hex),
+            escape_html(&row.effect_name),
+            escape_html(row.doc.as_deref().unwrap_or(""))
+        );
+    }
+    out.push_str("</tbody></table></section>");
+    out
+}
+
+fn render_guard_section(rows: &[GuardRow]) -> String {
+    let mut out = String::new();
+    out.push_str("<section><h2>Guards</h2>");
+    if rows.is_empty() {
+        out.push_str("<p>No guards registered.</p></section>");
+        return out;
+    }
+    out.push_str("<table class=\"grid\"><thead><tr><th>Name</th><th>CID</th><th>Signature</th><th>Effects</th></tr></thead><tbody>");
+    for row in rows {
+        let _ = write!(
+            out,
+            "<tr><td>{}</td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+            escape_html(&row.name),
+            escape_html(&row.cid_hex),
+            escape_html(&row.signature),
             escape_html(&render_effects(&row.effects))
         );
     }
@@ -300,6 +708,7 @@ struct WordRow {
     cid_hex: String,
     signature: String,
     effects: Vec<String>,
+    doc: Option<String>,
 }
 
 #[derive(Debug)]
@@ -308,6 +717,40 @@ struct PrimRow {
     cid_hex: String,
     signature: String,
     effects: Vec<String>,
+    doc: Option<String>,
+}
+
+#[derive(Debug)]
+struct AgentRow {
+    name: String,
+    cid_hex: String,
+    kind: String,
+    ports: Vec<String>,
+    doc: Option<String>,
+}
+
+#[derive(Debug)]
+struct RuleRow {
+    name: String,
+    cid_hex: String,
+    lhs: (String, String),
+    rewire: String,
+}
+
+#[derive(Debug)]
+struct EffectRow {
+    name: String,
+    cid_hex: String,
+    effect_name: String,
+    doc: Option<String>,
+}
+
+#[derive(Debug)]
+struct GuardRow {
+    name: String,
+    cid_hex: String,
+    signature: String,
+    effects: Vec<String>,
 }
 
 const SEGMENT_ENCODE: &AsciiSet = &CONTROLS
@@ -328,30 +771,17 @@ fn collect_namespace_rows(conn: &Connection) -> Result<Vec<NamespaceRow>> {
     for entry in entries {
         let db::NameEntry { name, cid } = entry;
         let cid_bytes = cid;
-        let (_, cbor) = load_object_cbor(conn, &cid_bytes)?;
-        let record: NamespaceRecord = serde_cbor::from_slice(&cbor)?;
-        let iface_hex = cid::to_hex(&bytebuf_to_array(&record.iface)?);
-        let exports = record
-            .exports
+        let ns = namespace::load_namespace(conn, &cid_bytes)?;
+        let iface_hex = cid::to_hex(&ns.iface);
+        let exports = namespace::export_rows(conn, &ns)?
             .into_iter()
-            .map(|e| {
-                let word_arr = bytebuf_to_array(&e.word)?;
-                let word_cid_hex = cid::to_hex(&word_arr);
-                let word_name = list_names_for_cid(conn, "word", &word_arr)?
-                    .into_iter()
-                    .next();
-                Ok(NsExport {
-                    alias: e.name,
-                    word_cid_hex,
-                    word_name,
-                })
+            .map(|row| NsExport {
+                alias: row.alias,
+                word_cid_hex: cid::to_hex(&row.word),
+                word_name: row.word_name,
             })
-            .collect::<Result<Vec<_>>>()?;
-        let imports = record
-            .imports
-            .into_iter()
-            .map(|buf| Ok(cid::to_hex(&bytebuf_to_array(&buf)?)))
-            .collect::<Result<Vec<_>>>()?;
+            .collect();
+        let imports = ns.imports.iter().map(cid::to_hex).collect();
         out.push(NamespaceRow {
             name,
             cid_hex: cid::to_hex(&cid_bytes),
@@ -415,6 +845,7 @@ fn collect_word_rows(conn: &Connection) -> Result<Vec<WordRow>> {
             cid_hex: cid::to_hex(&cid_bytes),
             signature,
             effects,
+            doc: info.doc,
         });
     }
     Ok(out)
@@ -438,6 +869,80 @@ fn collect_prim_rows(conn: &Connection) -> Result<Vec<PrimRow>> {
             cid_hex: cid::to_hex(&cid_bytes),
             signature,
             effects,
+            doc: info.doc,
+        });
+    }
+    Ok(out)
+}
+
+fn collect_agent_rows(conn: &Connection) -> Result<Vec<AgentRow>> {
+    let entries = db::list_names(conn, "agent", None)?;
+    let mut out = Vec::new();
+    for entry in entries {
+        let db::NameEntry { name, cid } = entry;
+        let info = load_agent_info(conn, &cid)?;
+        out.push(AgentRow {
+            name,
+            cid_hex: cid::to_hex(&cid),
+            kind: info.kind,
+            ports: info.ports,
+            doc: info.doc,
+        });
+    }
+    Ok(out)
+}
+
+fn collect_rule_rows(conn: &Connection) -> Result<Vec<RuleRow>> {
+    let entries = db::list_names(conn, "rule", None)?;
+    let mut out = Vec::new();
+    for entry in entries {
+        let db::NameEntry { name, cid } = entry;
+        let (_, cbor) = load_object_cbor(conn, &cid)?;
+        let record: RuleRecord = serde_cbor::from_slice(&cbor)?;
+        out.push(RuleRow {
+            name,
+            cid_hex: cid::to_hex(&cid),
+            lhs: record.lhs,
+            rewire: record.rewire,
+        });
+    }
+    Ok(out)
+}
+
+fn collect_effect_rows(conn: &Connection) -> Result<Vec<EffectRow>> {
+    let entries = db::list_names(conn, "effect", None)?;
+    let mut out = Vec::new();
+    for entry in entries {
+        let db::NameEntry { name, cid } = entry;
+        let (_, cbor) = load_object_cbor(conn, &cid)?;
+        let record: EffectRecord = serde_cbor::from_slice(&cbor)?;
+        out.push(EffectRow {
+            name,
+            cid_hex: cid::to_hex(&cid),
+            effect_name: record.name,
+            doc: record.doc,
+        });
+    }
+    Ok(out)
+}
+
+fn collect_guard_rows(conn: &Connection) -> Result<Vec<GuardRow>> {
+    let entries = db::list_names(conn, "guard", None)?;
+    let mut out = Vec::new();
+    for entry in entries {
+        let db::NameEntry { name, cid } = entry;
+        let info = load_guard_info(conn, &cid)?;
+        let signature = format_signature(&info.params, &info.results);
+        let effects = info
+            .effects
+            .into_iter()
+            .map(|cid| cid::to_hex(&cid))
+            .collect();
+        out.push(GuardRow {
+            name,
+            cid_hex: cid::to_hex(&cid),
+            signature,
+            effects,
         });
     }
     Ok(out)
@@ -488,17 +993,11 @@ fn render_effects(effects: &[String]) -> String {
 }
 
 fn format_signature(params: &[TypeTag], results: &[TypeTag]) -> String {
-    let param_list = params
-        .iter()
-        .map(|t| t.as_atom())
-        .collect::<Vec<_>>()
-        .join(", ");
-    let result_list = results
-        .iter()
-        .map(|t| t.as_atom())
-        .collect::<Vec<_>>()
-        .join(", ");
-    format!("({}) → ({})", param_list, result_list)
+    format!(
+        "({}) → ({})",
+        render_comma_list(params),
+        render_comma_list(results)
+    )
 }
 
 fn escape_html(input: &str) -> String {
@@ -524,6 +1023,18 @@ fn make_api_href(scope: &str, name: &str) -> String {
     format!("/api/{}/{}", scope, encoded)
 }
 
+fn make_node_href(cid_bytes: &[u8; 32], enc: CidEncoding) -> String {
+    format!("/api/node/{}", enc.encode(cid_bytes))
+}
+
+/// `?enc=base32` switches generated node links to the shorter base32 form.
+fn cid_encoding(query: Option<&str>) -> CidEncoding {
+    match query.and_then(|q| query_param(q, "enc")).as_deref() {
+        Some("base32") => CidEncoding::Base32,
+        _ => CidEncoding::Hex,
+    }
+}
+
 fn bytebuf_to_array(buf: &ByteBuf) -> Result<[u8; 32]> {
     let slice = buf.as_slice();
     if slice.len() != 32 {
@@ -544,12 +1055,12 @@ fn cbor_value_to_json(value: CborValue) -> JsonValue {
         CborValue::Null => JsonValue::Null,
         CborValue::Bool(b) => JsonValue::Bool(b),
         CborValue::Integer(i) => {
-            if let Some(v) = i64::try_from(i).ok() {
+            if let Ok(v) = i64::try_from(i) {
                 JsonValue::from(v)
-            } else if let Some(v) = u64::try_from(i).ok() {
+            } else if let Ok(v) = u64::try_from(i) {
                 JsonValue::from(v)
             } else {
-                JsonValue::String(i128::from(i).to_string())
+                JsonValue::String(i.to_string())
             }
         }
         CborValue::Float(f) => {
@@ -570,9 +1081,9 @@ fn cbor_value_to_json(value: CborValue) -> JsonValue {
                 let key = match k {
                     CborValue::Text(s) => s,
                     CborValue::Integer(i) => {
-                        if let Some(val) = i64::try_from(i).ok() {
+                        if let Ok(val) = i64::try_from(i) {
                             val.to_string()
-                        } else if let Some(val) = u64::try_from(i).ok() {
+                        } else if let Ok(val) = u64::try_from(i) {
                             val.to_string()
                         } else {
                             format!("{:?}", i)
@@ -599,22 +1110,6 @@ fn bytes_to_hex(bytes: &[u8]) -> String {
     out
 }
 
-#[derive(Deserialize)]
-struct NamespaceRecord {
-    #[allow(dead_code)]
-    kind: String,
-    #[serde(default)]
-    imports: Vec<ByteBuf>,
-    exports: Vec<NamespaceExportRecord>,
-    iface: ByteBuf,
-}
-
-#[derive(Deserialize)]
-struct NamespaceExportRecord {
-    name: String,
-    word: ByteBuf,
-}
-
 #[derive(Deserialize)]
 struct InterfaceRecord {
     #[allow(dead_code)]
@@ -637,19 +1132,37 @@ struct InterfaceTypeRecord {
     results: Vec<String>,
 }
 
-fn html_response(body: String) -> Response<std::io::Cursor<Vec<u8>>> {
+#[derive(Deserialize)]
+struct RuleRecord {
+    lhs: (String, String),
+    rewire: String,
+}
+
+#[derive(Deserialize)]
+struct EffectRecord {
+    name: String,
+    #[serde(default)]
+    doc: Option<String>,
+}
+
+fn html_response(body: String) -> HttpResponse {
     Response::from_string(body)
         .with_header(content_type("text/html; charset=utf-8"))
         .with_status_code(StatusCode(200))
 }
 
-fn json_response(body: String) -> Response<std::io::Cursor<Vec<u8>>> {
+fn json_response(body: String) -> HttpResponse {
     Response::from_string(body)
         .with_header(content_type("application/json"))
         .with_status_code(StatusCode(200))
 }
 
-fn error_response(status: u16, err: anyhow::Error) -> Response<std::io::Cursor<Vec<u8>>> {
+/// JSON for an immutable object; its CID doubles as a strong ETag.
+fn object_response(cid_bytes: &[u8; 32], body: String) -> HttpResponse {
+    json_response(body).with_header(header("ETag", &format!("\"{}\"", cid::to_hex(cid_bytes))))
+}
+
+fn error_response(status: u16, err: anyhow::Error) -> HttpResponse {
     let body = json!({ "error": err.to_string() }).to_string();
     Response::from_string(body)
         .with_header(content_type("application/json"))
@@ -657,7 +1170,11 @@ fn error_response(status: u16, err: anyhow::Error) -> Response<std::io::Cursor<V
 }
 
 fn content_type(value: &str) -> Header {
-    Header::from_bytes(&b"Content-Type"[..], value.as_bytes()).unwrap()
+    header("Content-Type", value)
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
 }
 
 fn split_query(url: &str) -> (&str, Option<&str>) {
@@ -667,13 +1184,308 @@ fn split_query(url: &str) -> (&str, Option<&str>) {
     }
 }
 
+fn query_param(query: &str, key: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        if name != key {
+            return None;
+        }
+        let value = value.replace('+', " ");
+        Some(percent_decode_str(&value).decode_utf8_lossy().into_owned())
+    })
+}
+
 fn parse_prefix(query: &str) -> Option<String> {
     for pair in query.split('&') {
-        if let Some((key, value)) = pair.split_once('=') {
-            if key == "prefix" {
-                return Some(value.to_string());
-            }
+        if let Some((key, value)) = pair.split_once('=')
+            && key == "prefix"
+        {
+            return Some(value.to_string());
         }
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::GraphBuilder;
+    use std::io::Read;
+
+    fn read_body(response: HttpResponse) -> String {
+        let mut body = String::new();
+        response.into_reader().read_to_string(&mut body).unwrap();
+        body
+    }
+
+    fn demo_store(dir: &tempfile::TempDir) -> Result<PathBuf> {
+        let path = dir.path().join("demo.march5.db");
+        let conn = create_store(&path)?;
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.push_lit_i64(42)?;
+        builder.finish_word(&[], &[TypeTag::I64], Some("demo/answer"))?;
+        Ok(path)
+    }
+
+    #[test]
+    fn run_endpoint_returns_outputs() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = demo_store(&dir)?;
+
+        let response = route(&path, &Method::Get, "/api/run/demo/answer");
+        assert_eq!(response.status_code(), StatusCode(200));
+        let json: JsonValue = serde_json::from_str(&read_body(response))?;
+        assert_eq!(json, json!([42]));
+
+        let response = route(&path, &Method::Get, "/api/run/demo/answer?args=%5B1%5D");
+        assert_eq!(response.status_code(), StatusCode(400));
+
+        let response = route(&path, &Method::Get, "/api/run/demo/missing");
+        assert_eq!(response.status_code(), StatusCode(404));
+        Ok(())
+    }
+
+    #[test]
+    fn namespace_rows_decode_the_current_encoding() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = demo_store(&dir)?;
+        let conn = open_store(&path)?;
+        let answer = get_name(&conn, "word", "demo/answer")?.expect("demo/answer registered");
+        let ns = march5::NamespaceCanon {
+            imports: Vec::new(),
+            exports: vec![march5::NamespaceExport {
+                name: "answer".to_string(),
+                word: answer,
+            }],
+            iface: [0x22; 32],
+        };
+        let ns_cid = namespace::store_namespace(&conn, &ns)?.cid;
+        march5::put_name(&conn, "namespace", "demo", &ns_cid)?;
+
+        let rows = collect_namespace_rows(&conn)?;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].iface_hex, cid::to_hex(&[0x22; 32]));
+        assert_eq!(rows[0].exports[0].alias, "answer");
+        assert_eq!(rows[0].exports[0].word_name.as_deref(), Some("demo/answer"));
+        Ok(())
+    }
+
+    #[test]
+    fn index_lists_agents() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = demo_store(&dir)?;
+        let conn = open_store(&path)?;
+        let agent = march5::InetAgentCanon {
+            name: "Dup",
+            ports: &["principal", "left", "right"],
+            doc: None,
+        };
+        let outcome = march5::inet::store_agent(&conn, &agent)?;
+        db::put_name(&conn, "agent", "demo/dup", &outcome.cid)?;
+
+        let rule = march5::InetRuleCanon {
+            lhs_a: "Dup",
+            lhs_b: "Dup",
+            body_syntax: "(wire (A 1) (B 1))",
+        };
+        let outcome = march5::inet::store_rule(&conn, &rule)?;
+        db::put_name(&conn, "rule", "demo/dup-dup", &outcome.cid)?;
+
+        let html = build_index_html(&conn)?;
+        assert!(html.contains("<h2>Agents</h2>"));
+        assert!(html.contains("<td>Dup</td>"));
+        assert!(html.contains("principal, left, right"));
+        assert!(html.contains("Dup ⋈ Dup"));
+        assert!(html.contains("(wire (A 1) (B 1))"));
+        assert!(html.contains("No effects registered."));
+        Ok(())
+    }
+
+    #[test]
+    fn node_endpoint_decodes_word_root() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = demo_store(&dir)?;
+
+        let response = route(&path, &Method::Get, "/api/word/demo/answer");
+        assert_eq!(response.status_code(), StatusCode(200));
+        let word: JsonValue = serde_json::from_str(&read_body(response))?;
+        let href = word["root_href"].as_str().unwrap().to_string();
+        assert!(href.starts_with("/api/node/"));
+
+        let response = route(&path, &Method::Get, &href);
+        assert_eq!(response.status_code(), StatusCode(200));
+        let node: JsonValue = serde_json::from_str(&read_body(response))?;
+        assert_eq!(node["kind"], json!("RETURN"));
+        assert_eq!(node["out"], json!(["i64"]));
+        let input_href = node["vals"][0]["href"].as_str().unwrap().to_string();
+
+        let response = route(&path, &Method::Get, &input_href);
+        let lit: JsonValue = serde_json::from_str(&read_body(response))?;
+        assert_eq!(lit["kind"], json!("LIT"));
+        assert_eq!(lit["payload"], json!(42));
+
+        let response = route(&path, &Method::Get, "/api/node/not-hex");
+        assert_eq!(response.status_code(), StatusCode(400));
+
+        let response = route(&path, &Method::Get, "/api/word/demo/answer?enc=base32");
+        let word: JsonValue = serde_json::from_str(&read_body(response))?;
+        let short_href = word["root_href"].as_str().unwrap().to_string();
+        assert!(short_href.len() < href.len());
+        let response = route(&path, &Method::Get, &short_href);
+        assert_eq!(response.status_code(), StatusCode(200));
+        Ok(())
+    }
+
+    fn http_get(addr: std::net::SocketAddr, path: &str) -> Result<String> {
+        use std::io::Write;
+        let mut stream = std::net::TcpStream::connect(addr)?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+        )?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply)?;
+        Ok(reply)
+    }
+
+    #[test]
+    fn concurrent_gets_are_served_and_shutdown_joins() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = demo_store(&dir)?;
+        let server = Server::http("127.0.0.1:0").map_err(|err| anyhow!("{err}"))?;
+        let addr = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| anyhow!("server is not bound to an IP address"))?;
+        let stop = AtomicBool::new(false);
+
+        thread::scope(|scope| -> Result<()> {
+            let serving = scope.spawn(|| serve_until(&server, path.clone(), None, 2, &stop));
+            let clients: Vec<_> = ["/api/run/demo/answer", "/api/list/word"]
+                .into_iter()
+                .map(|url| scope.spawn(move || http_get(addr, url)))
+                .collect();
+            for client in clients {
+                let reply = client.join().expect("client thread panicked")?;
+                assert!(reply.starts_with("HTTP/1.1 200"), "{reply}");
+            }
+            stop.store(true, Ordering::SeqCst);
+            serving.join().expect("server thread panicked");
+            Ok(())
+        })
+    }
+
+    fn header_value(response: &HttpResponse, name: &'static str) -> Option<String> {
+        response
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv(name))
+            .map(|h| h.value.to_string())
+    }
+
+    #[test]
+    fn matching_if_none_match_yields_not_modified() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = demo_store(&dir)?;
+
+        let response = route(&path, &Method::Get, "/api/word/demo/answer");
+        assert_eq!(response.status_code(), StatusCode(200));
+        let etag = header_value(&response, "ETag").expect("word responses carry an ETag");
+        let body: JsonValue = serde_json::from_str(&read_body(response))?;
+        assert_eq!(etag, format!("\"{}\"", body["_cid"].as_str().unwrap()));
+
+        let response = route(&path, &Method::Get, "/api/word/demo/answer");
+        let cached = conditional(response, Some(&etag));
+        assert_eq!(cached.status_code(), StatusCode(304));
+        assert_eq!(header_value(&cached, "ETag"), Some(etag.clone()));
+        assert!(read_body(cached).is_empty());
+
+        let response = route(&path, &Method::Get, "/api/word/demo/answer");
+        let fresh = conditional(response, Some("\"stale\""));
+        assert_eq!(fresh.status_code(), StatusCode(200));
+
+        let listing = route(&path, &Method::Get, "/api/list/word");
+        assert!(header_value(&listing, "ETag").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn cors_header_follows_allow_origin() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = demo_store(&dir)?;
+
+        let response = serve(&path, Some("*"), &Method::Get, "/api/list/word");
+        assert_eq!(response.status_code(), StatusCode(200));
+        assert_eq!(
+            header_value(&response, "Access-Control-Allow-Origin").as_deref(),
+            Some("*")
+        );
+
+        let response = serve(&path, Some("*"), &Method::Get, "/api/word/demo/missing");
+        assert_eq!(response.status_code(), StatusCode(404));
+        assert!(header_value(&response, "Access-Control-Allow-Origin").is_some());
+
+        let response = serve(&path, None, &Method::Get, "/api/list/word");
+        assert!(header_value(&response, "Access-Control-Allow-Origin").is_none());
+
+        let response = serve(
+            &path,
+            Some("https://example.org"),
+            &Method::Options,
+            "/api/list/word",
+        );
+        assert_eq!(response.status_code(), StatusCode(204));
+        assert_eq!(
+            header_value(&response, "Access-Control-Allow-Origin").as_deref(),
+            Some("https://example.org")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn search_matches_substrings_across_scopes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = demo_store(&dir)?;
+        let conn = open_store(&path)?;
+        let word_cid = get_name(&conn, "word", "demo/answer")?.unwrap();
+        db::put_name(&conn, "namespace", "answers.core", &word_cid)?;
+        db::put_name(&conn, "word", "demo/question", &word_cid)?;
+        db::put_name(&conn, "word", "demo/100%_sure", &word_cid)?;
+
+        let response = route(&path, &Method::Get, "/api/search?q=answer");
+        assert_eq!(response.status_code(), StatusCode(200));
+        let json: JsonValue = serde_json::from_str(&read_body(response))?;
+        let hits: Vec<(&str, &str)> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| (e["scope"].as_str().unwrap(), e["name"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            hits,
+            vec![("namespace", "answers.core"), ("word", "demo/answer")]
+        );
+
+        let response = route(&path, &Method::Get, "/api/search?q=answer&scope=word");
+        let json: JsonValue = serde_json::from_str(&read_body(response))?;
+        assert_eq!(json.as_array().unwrap().len(), 1);
+
+        // LIKE wildcards in the query are matched literally.
+        let response = route(&path, &Method::Get, "/api/search?q=%25_");
+        let json: JsonValue = serde_json::from_str(&read_body(response))?;
+        assert_eq!(json.as_array().unwrap().len(), 1);
+        assert_eq!(json[0]["name"], json!("demo/100%_sure"));
+        Ok(())
+    }
+
+    #[test]
+    fn query_param_decodes_values() {
+        assert_eq!(
+            query_param("x=1&args=%5B1%2C+2%5D", "args").as_deref(),
+            Some("[1, 2]")
+        );
+        assert_eq!(query_param("x=1", "args"), None);
+    }
+}
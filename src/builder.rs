@@ -13,6 +13,7 @@ use rusqlite::Connection;
 use smallvec::SmallVec;
 
 use crate::db;
+use crate::effect::EffectCanon;
 use crate::guard;
 use crate::node::{self, NodeCanon, NodeInput, NodeKind, NodePayload};
 use crate::prim::{self, PrimInfo};
@@ -27,6 +28,7 @@ struct StackItem {
     ty: TypeTag,
 }
 
+#[derive(Clone)]
 struct TokenPool {
     map: HashMap<EffectDomain, NodeInput>,
 }
@@ -50,6 +52,17 @@ impl TokenPool {
     }
 }
 
+/// Stack and effect-tracking state captured by [`GraphBuilder::checkpoint`].
+#[derive(Clone)]
+struct Checkpoint {
+    stack: Vec<StackItem>,
+    accumulated_effects: Vec<[u8; 32]>,
+    effect_frontier: BTreeMap<[u8; 32], NodeInput>,
+    token_pool: TokenPool,
+    accumulated_mask: EffectMask,
+    attached_guards: Vec<[u8; 32]>,
+}
+
 /// Incrementally builds graphs by pushing nodes and consuming stack items.
 pub struct GraphBuilder<'conn> {
     conn: &'conn Connection,
@@ -63,6 +76,8 @@ pub struct GraphBuilder<'conn> {
     token_pool: TokenPool,
     accumulated_mask: EffectMask,
     attached_guards: Vec<[u8; 32]>,
+    checkpoints: Vec<Checkpoint>,
+    auto_effects: bool,
 }
 
 pub struct DispatchSpec<'a> {
@@ -88,9 +103,46 @@ impl<'conn> GraphBuilder<'conn> {
             token_pool: TokenPool::new(),
             accumulated_mask: effect_mask::NONE,
             attached_guards: Vec::new(),
+            checkpoints: Vec::new(),
+            auto_effects: false,
         }
     }
 
+    /// When enabled, finished words also list a canonical effect descriptor
+    /// (named after the domain, e.g. `io`) for every domain in their mask, so
+    /// mask-only effects still surface as effect CIDs.
+    pub fn with_auto_effects(mut self, enabled: bool) -> Self {
+        self.auto_effects = enabled;
+        self
+    }
+
+    /// Remember the current stack and effect state so [`rollback`](Self::rollback) can restore it.
+    /// Nodes stored after the checkpoint stay in the store; only the builder state reverts.
+    pub fn checkpoint(&mut self) {
+        self.checkpoints.push(Checkpoint {
+            stack: self.stack.clone(),
+            accumulated_effects: self.accumulated_effects.clone(),
+            effect_frontier: self.effect_frontier.clone(),
+            token_pool: self.token_pool.clone(),
+            accumulated_mask: self.accumulated_mask,
+            attached_guards: self.attached_guards.clone(),
+        });
+    }
+
+    /// Restore the state saved by the most recent [`checkpoint`](Self::checkpoint).
+    pub fn rollback(&mut self) -> Result<()> {
+        let Some(saved) = self.checkpoints.pop() else {
+            bail!("nothing to undo");
+        };
+        self.stack = saved.stack;
+        self.accumulated_effects = saved.accumulated_effects;
+        self.effect_frontier = saved.effect_frontier;
+        self.token_pool = saved.token_pool;
+        self.accumulated_mask = saved.accumulated_mask;
+        self.attached_guards = saved.attached_guards;
+        Ok(())
+    }
+
     #[allow(dead_code)]
     /// Close the current branch with an IF node pairing two continuations.
     pub fn branch_if(
@@ -142,6 +194,7 @@ impl<'conn> GraphBuilder<'conn> {
     /// Start assembling a word with the given parameter types, seeding ARG nodes.
     pub fn begin_word(&mut self, params: &[TypeTag]) -> Result<()> {
         self.stack.clear();
+        self.checkpoints.clear();
         self.param_types = params.to_vec();
         self.param_inputs.clear();
         self.accumulated_effects.clear();
@@ -185,6 +238,36 @@ impl<'conn> GraphBuilder<'conn> {
         self.stack.len()
     }
 
+    /// Snapshot of every stack slot, bottom to top, as `(producer cid, port, type)`.
+    pub fn stack_snapshot(&self) -> Vec<([u8; 32], u32, TypeTag)> {
+        self.stack
+            .iter()
+            .map(|item| (item.cid, item.port, item.ty))
+            .collect()
+    }
+
+    /// Parameter types recorded by `begin_word`/`begin_guard`.
+    pub fn inferred_params(&self) -> Vec<TypeTag> {
+        self.param_types.clone()
+    }
+
+    /// Types on the stack, bottom to top: the results a finish would have to declare now.
+    pub fn inferred_results(&self) -> Vec<TypeTag> {
+        self.stack.iter().map(|item| item.ty).collect()
+    }
+
+    /// Effect domains with a live token in the current build, in canonical order.
+    pub fn effect_domains_in_scope(&self) -> Vec<EffectDomain> {
+        let mut domains: Vec<EffectDomain> = self.token_pool.map.keys().copied().collect();
+        domains.sort();
+        domains
+    }
+
+    /// The token the next effectful node in `domain` will consume, if one is live.
+    pub fn current_token(&self, domain: EffectDomain) -> Option<NodeInput> {
+        self.token_pool.current(domain)
+    }
+
     /// Peek the types of the top `n` stack items (left-to-right order of consumption).
     /// Does not modify the stack.
     pub fn peek_top_types(&self, n: usize) -> Result<Vec<TypeTag>> {
@@ -198,6 +281,7 @@ impl<'conn> GraphBuilder<'conn> {
         Ok(self.stack[start..].iter().map(|item| item.ty).collect())
     }
 
+    #[allow(dead_code)]
     fn peek_top_inputs(&self, n: usize) -> Result<Vec<NodeInput>> {
         if n > self.stack.len() {
             bail!(
@@ -329,6 +413,7 @@ impl<'conn> GraphBuilder<'conn> {
                     effects: Vec::new(),
                     effect_mask: effect_mask::NONE,
                     guards: Vec::new(),
+                    doc: None,
                 };
                 let guard_word_outcome = word::store_word(self.conn, &guard_word)?;
 
@@ -405,6 +490,34 @@ impl<'conn> GraphBuilder<'conn> {
         params: &[TypeTag],
         results: &[TypeTag],
         symbol: Option<&str>,
+    ) -> Result<[u8; 32]> {
+        let guard_cid = self.build_guard(params, results, true)?;
+        if let Some(name) = symbol {
+            db::put_name(self.conn, "guard", name, &guard_cid)?;
+        }
+
+        self.param_types.clear();
+        self.stack.clear();
+        self.accumulated_effects.clear();
+        self.effect_frontier.clear();
+        self.token_pool.clear();
+        self.accumulated_mask = effect_mask::NONE;
+        self.attached_guards.clear();
+        self.checkpoints.clear();
+        Ok(guard_cid)
+    }
+
+    /// CID that [`finish_guard`](Self::finish_guard) would produce, without writing
+    /// anything to the store or ending the current build.
+    pub fn plan_guard(&self, params: &[TypeTag], results: &[TypeTag]) -> Result<[u8; 32]> {
+        self.build_guard(params, results, false)
+    }
+
+    fn build_guard(
+        &self,
+        params: &[TypeTag],
+        results: &[TypeTag],
+        persist: bool,
     ) -> Result<[u8; 32]> {
         if params != self.param_types {
             bail!(
@@ -460,28 +573,20 @@ impl<'conn> GraphBuilder<'conn> {
             effects: Vec::new(),
             payload: NodePayload::Return,
         };
-        let return_outcome = node::store_node(self.conn, &return_node)?;
+        let return_cid = self.emit_node(&return_node, persist)?;
 
         let guard = guard::GuardCanon {
-            root: return_outcome.cid,
+            root: return_cid,
             params: params.iter().map(|t| t.as_atom().to_string()).collect(),
             results: results.iter().map(|t| t.as_atom().to_string()).collect(),
             effects: Vec::new(),
             effect_mask: effect_mask::NONE,
         };
-        let outcome = guard::store_guard(self.conn, &guard)?;
-        if let Some(name) = symbol {
-            db::put_name(self.conn, "guard", name, &outcome.cid)?;
+        if persist {
+            Ok(guard::store_guard(self.conn, &guard)?.cid)
+        } else {
+            Ok(crate::cid::compute(&guard::encode(&guard)))
         }
-
-        self.param_types.clear();
-        self.stack.clear();
-        self.accumulated_effects.clear();
-        self.effect_frontier.clear();
-        self.token_pool.clear();
-        self.accumulated_mask = effect_mask::NONE;
-        self.attached_guards.clear();
-        Ok(outcome.cid)
     }
 
     /// Push a literal i64 node on the stack.
@@ -504,6 +609,74 @@ impl<'conn> GraphBuilder<'conn> {
         Ok(outcome.cid)
     }
 
+    /// Guard the top value's runtime type, deopting with a recorded reason on mismatch.
+    ///
+    /// Values typed `ptr` (for example `tuple_get` results) may hold anything at
+    /// runtime; after the guard the slot is typed `expected`.
+    pub fn guard_type(&mut self, expected: TypeTag) -> Result<[u8; 32]> {
+        let item = self
+            .stack
+            .pop()
+            .ok_or_else(|| anyhow!("stack underflow: guard_type"))?;
+        let value = NodeInput {
+            cid: item.cid,
+            port: item.port,
+        };
+        let deopt = NodeCanon {
+            kind: NodeKind::Deopt,
+            out: vec![expected.as_atom().to_string()],
+            inputs: Vec::new(),
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::Deopt(Some(format!("expected {}", expected.as_atom()))),
+        };
+        let deopt_cid = node::store_node(self.conn, &deopt)?.cid;
+        let guard = NodeCanon {
+            kind: NodeKind::Guard,
+            out: vec![expected.as_atom().to_string()],
+            inputs: vec![value],
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::Guard {
+                type_key: Self::guard_type_key(expected),
+                match_cont: value,
+                else_cont: NodeInput {
+                    cid: deopt_cid,
+                    port: 0,
+                },
+            },
+        };
+        let outcome = node::store_node(self.conn, &guard)?;
+        self.stack.push(StackItem {
+            cid: outcome.cid,
+            port: 0,
+            ty: expected,
+        });
+        Ok(outcome.cid)
+    }
+
+    /// Push a LOAD_GLOBAL node reading the global keyed by `key` as a value of type `ty`.
+    pub fn push_load_global(&mut self, key: [u8; 32], ty: TypeTag) -> Result<[u8; 32]> {
+        let node = NodeCanon {
+            kind: NodeKind::LoadGlobal,
+            out: vec![ty.as_atom().to_string()],
+            inputs: Vec::new(),
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::Global(key),
+        };
+        let outcome = node::store_node(self.conn, &node)?;
+        self.stack.push(StackItem {
+            cid: outcome.cid,
+            port: 0,
+            ty,
+        });
+        Ok(outcome.cid)
+    }
+
     /// Pair the top two stack values into a single tuple value.
     pub fn pair(&mut self) -> Result<[u8; 32]> {
         let right = self
@@ -578,6 +751,81 @@ impl<'conn> GraphBuilder<'conn> {
         Ok(outcome.cid)
     }
 
+    /// Pack the top `n` stack values (left-to-right) into a single tuple value.
+    pub fn tuple(&mut self, n: usize) -> Result<[u8; 32]> {
+        if n == 0 {
+            bail!("tuple arity must be at least 1");
+        }
+        if n > self.stack.len() {
+            bail!(
+                "stack underflow: tuple needs {n} value(s), have {}",
+                self.stack.len()
+            );
+        }
+        let items = self.stack.split_off(self.stack.len() - n);
+        let node = NodeCanon {
+            kind: NodeKind::Pair,
+            out: vec![TypeTag::Ptr.as_atom().to_string()],
+            inputs: items
+                .iter()
+                .map(|item| NodeInput {
+                    cid: item.cid,
+                    port: item.port,
+                })
+                .collect(),
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: tuple_payload(n)?,
+        };
+        let outcome = node::store_node(self.conn, &node)?;
+        self.stack.push(StackItem {
+            cid: outcome.cid,
+            port: 0,
+            ty: TypeTag::Ptr,
+        });
+        Ok(outcome.cid)
+    }
+
+    /// Expand the top tuple value into `n` outputs typed by `types`.
+    pub fn untuple(&mut self, n: usize, types: &[TypeTag]) -> Result<[u8; 32]> {
+        if n == 0 {
+            bail!("untuple arity must be at least 1");
+        }
+        if types.len() != n {
+            bail!(
+                "untuple of {n} element(s) needs {n} type(s), got {}",
+                types.len()
+            );
+        }
+        let tuple = self
+            .stack
+            .pop()
+            .ok_or_else(|| anyhow!("stack underflow: untuple"))?;
+
+        let node = NodeCanon {
+            kind: NodeKind::Unpair,
+            out: types.iter().map(|ty| ty.as_atom().to_string()).collect(),
+            inputs: vec![NodeInput {
+                cid: tuple.cid,
+                port: tuple.port,
+            }],
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: tuple_payload(n)?,
+        };
+        let outcome = node::store_node(self.conn, &node)?;
+        for (port, ty) in types.iter().enumerate() {
+            self.stack.push(StackItem {
+                cid: outcome.cid,
+                port: port as u32,
+                ty: *ty,
+            });
+        }
+        Ok(outcome.cid)
+    }
+
     /// Push a quotation literal on the stack.
     pub fn quote(&mut self, qid: [u8; 32]) -> Result<[u8; 32]> {
         let node = NodeCanon {
@@ -625,7 +873,7 @@ impl<'conn> GraphBuilder<'conn> {
         if n < 2 {
             bail!("stack underflow: over");
         }
-        let item = self.stack[n - 2].clone();
+        let item = self.stack[n - 2];
         self.stack.push(item);
         Ok(())
     }
@@ -786,11 +1034,28 @@ impl<'conn> GraphBuilder<'conn> {
     }
 
     /// Apply a primitive by CID, deriving arity/result/effects from storage.
+    ///
+    /// Variadic primitives receive no trailing arguments; use
+    /// [`GraphBuilder::apply_prim_variadic`] to pass some.
     pub fn apply_prim(&mut self, prim_cid: [u8; 32]) -> Result<[u8; 32]> {
+        self.apply_prim_variadic(prim_cid, 0)
+    }
+
+    /// Apply a primitive, popping `extra` trailing arguments for its variadic tail.
+    pub fn apply_prim_variadic(&mut self, prim_cid: [u8; 32], extra: usize) -> Result<[u8; 32]> {
         let info = self.prim_info(&prim_cid)?;
+        let mut params = info.params.clone();
+        match info.variadic {
+            Some(tag) => params.extend(std::iter::repeat_n(tag, extra)),
+            None if extra > 0 => bail!(
+                "primitive {} is not variadic but was given {extra} trailing argument(s)",
+                crate::cid::to_hex(&prim_cid)
+            ),
+            None => {}
+        }
         self.apply_general(
-            info.params.len(),
-            &info.params,
+            params.len(),
+            &params,
             &info.results,
             &info.effects,
             info.effect_mask,
@@ -831,6 +1096,122 @@ impl<'conn> GraphBuilder<'conn> {
         )
     }
 
+    /// Apply a bounded `while` loop to the `state_ty` value on top of the stack.
+    ///
+    /// `cond_build` sees the state on top of the stack and must replace it with
+    /// an `i64` condition; `body_build` must replace it with the next state. Both
+    /// run in a fresh builder and must be pure.
+    ///
+    /// Content addressing rules out a word that CALLs itself, so the loop is
+    /// lowered to `max_iterations` nested helper words: each one evaluates the
+    /// condition and either CALLs the next helper on the updated state or
+    /// returns the state (via IF). The innermost helper deopts if the condition
+    /// still holds, so exceeding the bound is an error rather than a silent
+    /// truncation. Runs still count against the evaluator's depth and fuel
+    /// limits. Returns the CID of the CALL node.
+    pub fn while_loop(
+        &mut self,
+        state_ty: TypeTag,
+        max_iterations: usize,
+        mut cond_build: impl FnMut(&mut GraphBuilder<'conn>) -> Result<()>,
+        mut body_build: impl FnMut(&mut GraphBuilder<'conn>) -> Result<()>,
+    ) -> Result<[u8; 32]> {
+        let top = self.peek_top_types(1)?;
+        if top[0] != state_ty {
+            bail!(
+                "while loop state type mismatch: expected {:?}, got {:?}",
+                state_ty,
+                top[0]
+            );
+        }
+        let mut next: Option<[u8; 32]> = None;
+        for _ in 0..=max_iterations {
+            next = Some(self.build_loop_step(state_ty, next, &mut cond_build, &mut body_build)?);
+        }
+        let loop_word = next.expect("loop has at least one step");
+        self.apply_word(loop_word)
+    }
+
+    /// One helper word of [`while_loop`](Self::while_loop): `cond ? next(body(state)) : state`.
+    /// Without a `next` helper the true branch deopts.
+    fn build_loop_step(
+        &self,
+        state_ty: TypeTag,
+        next: Option<[u8; 32]>,
+        cond_build: &mut impl FnMut(&mut GraphBuilder<'conn>) -> Result<()>,
+        body_build: &mut impl FnMut(&mut GraphBuilder<'conn>) -> Result<()>,
+    ) -> Result<[u8; 32]> {
+        let params = [state_ty];
+        let mut step = GraphBuilder::new(self.conn);
+        step.begin_word(&params)?;
+        let state = step.stack[0];
+
+        cond_build(&mut step)?;
+        if step.stack.len() != 1 || step.stack[0].ty != TypeTag::I64 {
+            bail!("while loop condition must leave exactly one i64 on the stack");
+        }
+        let cond = step.stack[0];
+
+        let true_cont = match next {
+            Some(next_word) => {
+                step.stack = vec![state];
+                body_build(&mut step)?;
+                if step.stack.len() != 1 || step.stack[0].ty != state_ty {
+                    bail!("while loop body must leave exactly one {state_ty:?} on the stack");
+                }
+                step.apply_word(next_word)?;
+                step.stack[0]
+            }
+            None => {
+                let deopt = NodeCanon {
+                    kind: NodeKind::Deopt,
+                    out: vec![state_ty.as_atom().to_string()],
+                    inputs: Vec::new(),
+                    vals: Vec::new(),
+                    deps: Vec::new(),
+                    effects: Vec::new(),
+                    payload: NodePayload::Deopt(None),
+                };
+                StackItem {
+                    cid: step.emit_node(&deopt, true)?,
+                    port: 0,
+                    ty: state_ty,
+                }
+            }
+        };
+        if step.accumulated_mask != effect_mask::NONE || !step.accumulated_effects.is_empty() {
+            bail!("while loop condition and body must be pure");
+        }
+
+        let if_node = NodeCanon {
+            kind: NodeKind::If,
+            out: vec![state_ty.as_atom().to_string()],
+            inputs: vec![NodeInput {
+                cid: cond.cid,
+                port: cond.port,
+            }],
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::If {
+                true_cont: NodeInput {
+                    cid: true_cont.cid,
+                    port: true_cont.port,
+                },
+                false_cont: NodeInput {
+                    cid: state.cid,
+                    port: state.port,
+                },
+            },
+        };
+        step.stack = vec![StackItem {
+            cid: step.emit_node(&if_node, true)?,
+            port: 0,
+            ty: state_ty,
+        }];
+        step.finish_word(&params, &params, None)
+    }
+
     /// Expose the top CID without consuming it.
     pub fn peek_cid(&self) -> Result<[u8; 32]> {
         self.stack
@@ -845,6 +1226,35 @@ impl<'conn> GraphBuilder<'conn> {
         params: &[TypeTag],
         results: &[TypeTag],
         symbol: Option<&str>,
+    ) -> Result<[u8; 32]> {
+        let word_cid = self.build_word(params, results, true)?;
+        if let Some(name) = symbol {
+            db::put_name(self.conn, "word", name, &word_cid)?;
+        }
+
+        // Leave the final results on the stack for inspection, but reset tracking.
+        self.param_types.clear();
+        self.param_inputs.clear();
+        self.accumulated_effects.clear();
+        self.effect_frontier.clear();
+        self.token_pool.clear();
+        self.accumulated_mask = effect_mask::NONE;
+        self.attached_guards.clear();
+        self.checkpoints.clear();
+        Ok(word_cid)
+    }
+
+    /// CID that [`finish_word`](Self::finish_word) would produce, without writing
+    /// anything to the store or ending the current build.
+    pub fn plan_word(&mut self, params: &[TypeTag], results: &[TypeTag]) -> Result<[u8; 32]> {
+        self.build_word(params, results, false)
+    }
+
+    fn build_word(
+        &mut self,
+        params: &[TypeTag],
+        results: &[TypeTag],
+        persist: bool,
     ) -> Result<[u8; 32]> {
         if params != self.param_types {
             bail!(
@@ -897,7 +1307,7 @@ impl<'conn> GraphBuilder<'conn> {
                     .ok_or_else(|| anyhow!("effectful word missing token output"))?;
                 prefixed.push(token);
             }
-            prefixed.extend(vals.into_iter());
+            prefixed.extend(vals);
             vals = prefixed;
         }
 
@@ -908,6 +1318,16 @@ impl<'conn> GraphBuilder<'conn> {
         });
         deps.dedup_by(|a, b| a.cid == b.cid && a.port == b.port);
 
+        if self.auto_effects {
+            for domain in &domains {
+                let effect = EffectCanon {
+                    name: domain.as_str(),
+                    doc: None,
+                };
+                let effect_cid = self.emit_effect(&effect, persist)?;
+                self.accumulated_effects.push(effect_cid);
+            }
+        }
         self.accumulated_effects.sort_unstable();
         self.accumulated_effects.dedup();
 
@@ -958,8 +1378,9 @@ impl<'conn> GraphBuilder<'conn> {
                 effects: Vec::new(),
                 effect_mask: effect_mask::NONE,
                 guards: Vec::new(),
+                doc: None,
             };
-            let guard_word_outcome = word::store_word(self.conn, &guard_word)?;
+            let guard_word_cid = self.emit_word(&guard_word, persist)?;
 
             // Build CALL node to compute condition
             let call_node = NodeCanon {
@@ -974,9 +1395,9 @@ impl<'conn> GraphBuilder<'conn> {
                 vals: Vec::new(),
                 deps: Vec::new(),
                 effects: Vec::new(),
-                payload: NodePayload::Word(guard_word_outcome.cid),
+                payload: NodePayload::Word(guard_word_cid),
             };
-            let call_outcome = node::store_node(self.conn, &call_node)?;
+            let call_cid = self.emit_node(&call_node, persist)?;
 
             // Success branch: literal 1 (ignored), ensures unified out type
             let lit_node = NodeCanon {
@@ -988,9 +1409,9 @@ impl<'conn> GraphBuilder<'conn> {
                 effects: Vec::new(),
                 payload: NodePayload::LitI64(1),
             };
-            let lit_outcome = node::store_node(self.conn, &lit_node)?;
+            let lit_cid = self.emit_node(&lit_node, persist)?;
 
-            // Failure branch: DEOPT
+            // Failure branch: DEOPT (the runner names the guard from the word's guard list)
             let deopt_node = NodeCanon {
                 kind: NodeKind::Deopt,
                 out: vec![TypeTag::Unit.as_atom().to_string()],
@@ -998,16 +1419,16 @@ impl<'conn> GraphBuilder<'conn> {
                 vals: Vec::new(),
                 deps: Vec::new(),
                 effects: Vec::new(),
-                payload: NodePayload::Deopt,
+                payload: NodePayload::Deopt(None),
             };
-            let deopt_outcome = node::store_node(self.conn, &deopt_node)?;
+            let deopt_cid = self.emit_node(&deopt_node, persist)?;
 
             // IF node consumes condition and selects branch (ignored output)
             let if_node = NodeCanon {
                 kind: NodeKind::If,
                 out: vec![TypeTag::I64.as_atom().to_string()],
                 inputs: vec![NodeInput {
-                    cid: call_outcome.cid,
+                    cid: call_cid,
                     port: 0,
                 }],
                 vals: Vec::new(),
@@ -1015,18 +1436,18 @@ impl<'conn> GraphBuilder<'conn> {
                 effects: Vec::new(),
                 payload: NodePayload::If {
                     true_cont: NodeInput {
-                        cid: lit_outcome.cid,
+                        cid: lit_cid,
                         port: 0,
                     },
                     false_cont: NodeInput {
-                        cid: deopt_outcome.cid,
+                        cid: deopt_cid,
                         port: 0,
                     },
                 },
             };
-            let if_outcome = node::store_node(self.conn, &if_node)?;
+            let if_cid = self.emit_node(&if_node, persist)?;
             deps.push(NodeInput {
-                cid: if_outcome.cid,
+                cid: if_cid,
                 port: 0,
             });
         }
@@ -1047,30 +1468,43 @@ impl<'conn> GraphBuilder<'conn> {
             effects: Vec::new(),
             payload: NodePayload::Return,
         };
-        let return_outcome = node::store_node(self.conn, &return_node)?;
+        let return_cid = self.emit_node(&return_node, persist)?;
 
         let word = WordCanon {
-            root: return_outcome.cid,
+            root: return_cid,
             params: params.iter().map(|t| t.as_atom().to_string()).collect(),
             results: results.iter().map(|t| t.as_atom().to_string()).collect(),
             effects: self.accumulated_effects.clone(),
             effect_mask: word_effect_mask,
             guards: guard_list,
+            doc: None,
         };
-        let outcome = word::store_word(self.conn, &word)?;
-        if let Some(name) = symbol {
-            db::put_name(self.conn, "word", name, &outcome.cid)?;
+        self.emit_word(&word, persist)
+    }
+
+    /// Store `node` (or, when not persisting, only hash it) and return its CID.
+    fn emit_node(&self, node: &NodeCanon, persist: bool) -> Result<[u8; 32]> {
+        if persist {
+            Ok(node::store_node(self.conn, node)?.cid)
+        } else {
+            Ok(crate::cid::compute(&node::encode(node)?))
         }
+    }
 
-        // Leave the final results on the stack for inspection, but reset tracking.
-        self.param_types.clear();
-        self.param_inputs.clear();
-        self.accumulated_effects.clear();
-        self.effect_frontier.clear();
-        self.token_pool.clear();
-        self.accumulated_mask = effect_mask::NONE;
-        self.attached_guards.clear();
-        Ok(outcome.cid)
+    fn emit_effect(&self, effect: &EffectCanon, persist: bool) -> Result<[u8; 32]> {
+        if persist {
+            Ok(crate::effect::store_effect(self.conn, effect)?.cid)
+        } else {
+            Ok(crate::cid::compute(&crate::effect::encode(effect)))
+        }
+    }
+
+    fn emit_word(&self, word: &WordCanon, persist: bool) -> Result<[u8; 32]> {
+        if persist {
+            Ok(word::store_word(self.conn, word)?.cid)
+        } else {
+            Ok(crate::cid::compute(&word::encode(word)))
+        }
     }
 
     #[inline]
@@ -1134,6 +1568,15 @@ impl<'conn> GraphBuilder<'conn> {
     }
 }
 
+/// Binary tuples reuse the plain PAIR/UNPAIR encoding; other arities are recorded.
+fn tuple_payload(n: usize) -> Result<NodePayload> {
+    if n == 2 {
+        Ok(NodePayload::Empty)
+    } else {
+        Ok(NodePayload::Arity(u32::try_from(n)?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1143,6 +1586,24 @@ mod tests {
     use crate::{Value, run_word};
     use serde_cbor::Value as CborValue;
 
+    #[test]
+    fn inferred_signature_tracks_params_and_stack() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[TypeTag::F64])?;
+        builder.drop()?;
+        builder.push_lit_i64(1)?;
+        builder.push_lit_i64(2)?;
+        assert_eq!(builder.inferred_params(), vec![TypeTag::F64]);
+        assert_eq!(builder.inferred_results(), vec![TypeTag::I64, TypeTag::I64]);
+
+        let params = builder.inferred_params();
+        let results = builder.inferred_results();
+        builder.finish_word(&params, &results, None)?;
+        Ok(())
+    }
+
     #[test]
     fn build_add_word() -> Result<()> {
         let conn = Connection::open_in_memory()?;
@@ -1155,6 +1616,9 @@ mod tests {
             results: &result_tags,
             effects: &[],
             effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let prim_outcome = prim::store_prim(&conn, &prim)?;
 
@@ -1176,6 +1640,54 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn while_loop_counts_down_to_zero() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let binary = [TypeTag::I64, TypeTag::I64];
+        let unary = [TypeTag::I64];
+        let sub = PrimCanon {
+            params: &binary,
+            results: &unary,
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let sub_cid = prim::store_prim(&conn, &sub)?.cid;
+        db::put_name(&conn, "prim", "core/sub_i64", &sub_cid)?;
+
+        // while n != 0 { n = n - 1 }: the state itself is the condition.
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&unary)?;
+        builder.while_loop(
+            TypeTag::I64,
+            8,
+            |_| Ok(()),
+            |b| {
+                b.push_lit_i64(1)?;
+                b.apply_prim(sub_cid)?;
+                Ok(())
+            },
+        )?;
+        let countdown = builder.finish_word(&unary, &unary, Some("demo/countdown"))?;
+
+        let run = |n: i64| run_word(&conn, &countdown, &[Value::I64(n)]);
+        assert_eq!(run(0)?, vec![Value::I64(0)]);
+        assert_eq!(run(5)?, vec![Value::I64(0)]);
+        assert_eq!(run(8)?, vec![Value::I64(0)]);
+        for beyond_bound in [9, -1] {
+            let err = run(beyond_bound).unwrap_err();
+            assert!(
+                matches!(err.downcast_ref(), Some(crate::EvalError::Deopt { .. })),
+                "{err}"
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn dup_swap_over_are_wiring_only() -> Result<()> {
         let conn = Connection::open_in_memory()?;
@@ -1336,6 +1848,84 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn io_prim_brings_its_token_into_scope() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let unary = [TypeTag::I64];
+        let prim = PrimCanon {
+            params: &unary,
+            results: &unary,
+            effects: &[],
+            effect_mask: effect_mask::IO,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let prim_cid = prim::store_prim(&conn, &prim)?.cid;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.push_lit_i64(1)?;
+        assert!(builder.effect_domains_in_scope().is_empty());
+        assert!(builder.current_token(EffectDomain::Io).is_none());
+
+        let node_cid = builder.apply_prim(prim_cid)?;
+        assert_eq!(builder.effect_domains_in_scope(), vec![EffectDomain::Io]);
+        let token = builder
+            .current_token(EffectDomain::Io)
+            .expect("IO token is live");
+        assert_eq!((token.cid, token.port), (node_cid, 0));
+        assert!(builder.current_token(EffectDomain::State).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn auto_effects_attach_domain_descriptors() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let unary = [TypeTag::I64];
+        let prim = PrimCanon {
+            params: &unary,
+            results: &unary,
+            effects: &[],
+            effect_mask: effect_mask::IO,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let prim_cid = prim::store_prim(&conn, &prim)?.cid;
+
+        let build = |auto: bool| -> Result<[u8; 32]> {
+            let mut builder = GraphBuilder::new(&conn).with_auto_effects(auto);
+            builder.begin_word(&[])?;
+            builder.push_lit_i64(1)?;
+            builder.apply_prim(prim_cid)?;
+            builder.finish_word(&[], &unary, None)
+        };
+
+        let plain = word::load_word_info(&conn, &build(false)?)?;
+        assert_eq!(plain.effect_mask, effect_mask::IO);
+        assert!(plain.effects.is_empty());
+
+        let first = build(true)?;
+        assert_eq!(build(true)?, first);
+        let io_effect = crate::effect::store_effect(
+            &conn,
+            &EffectCanon {
+                name: "io",
+                doc: None,
+            },
+        )?;
+        assert!(!io_effect.inserted, "descriptor stored by finish_word");
+        let info = word::load_word_info(&conn, &first)?;
+        assert_eq!(info.effect_mask, effect_mask::IO);
+        assert_eq!(info.effects, vec![io_effect.cid]);
+        Ok(())
+    }
+
     #[test]
     fn finish_word_tracks_effect_dependencies() -> Result<()> {
         let conn = Connection::open_in_memory()?;
@@ -1349,6 +1939,9 @@ mod tests {
             results: &result_tags,
             effects: &[effect],
             effect_mask: effect_mask::IO,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let prim_outcome = prim::store_prim(&conn, &prim)?;
 
@@ -1518,6 +2111,100 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn rollback_restores_the_previous_stack() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.checkpoint();
+        let first = builder.push_lit_i64(1)?;
+        builder.checkpoint();
+        builder.push_lit_i64(2)?;
+        assert_eq!(builder.depth(), 2);
+
+        builder.rollback()?;
+        assert_eq!(builder.depth(), 1);
+        assert_eq!(builder.peek_cid()?, first);
+        builder.rollback()?;
+        assert_eq!(builder.depth(), 0);
+        assert!(builder.rollback().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn planned_cids_match_persisted_ones() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let objects =
+            |conn: &Connection| -> Result<usize> { Ok(db::list_all_objects(conn)?.len()) };
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_guard(&[])?;
+        builder.push_lit_i64(1)?;
+        let before = objects(&conn)?;
+        let planned_guard = builder.plan_guard(&[], &[TypeTag::I64])?;
+        assert_eq!(objects(&conn)?, before);
+        let guard_cid = builder.finish_guard(&[], &[TypeTag::I64], None)?;
+        assert_eq!(planned_guard, guard_cid);
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[TypeTag::I64])?;
+        builder.attach_guard(guard_cid);
+        let before = objects(&conn)?;
+        let planned_word = builder.plan_word(&[TypeTag::I64], &[TypeTag::I64])?;
+        assert_eq!(objects(&conn)?, before);
+        assert_eq!(builder.depth(), 1);
+        let word_cid = builder.finish_word(&[TypeTag::I64], &[TypeTag::I64], None)?;
+        assert_eq!(planned_word, word_cid);
+        assert!(objects(&conn)? > before);
+        Ok(())
+    }
+
+    #[test]
+    fn tuple_and_untuple_roundtrip_three_elements() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let triple = [TypeTag::I64, TypeTag::I64, TypeTag::I64];
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        for n in [1, 2, 3] {
+            builder.push_lit_i64(n)?;
+        }
+        builder.tuple(3)?;
+        let packed = builder.finish_word(&[], &[TypeTag::Ptr], Some("demo/triple"))?;
+        assert_eq!(
+            run_word(&conn, &packed, &[])?,
+            vec![Value::Tuple(vec![
+                Value::I64(1),
+                Value::I64(2),
+                Value::I64(3)
+            ])]
+        );
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&triple)?;
+        builder.tuple(3)?;
+        builder.untuple(3, &triple)?;
+        let roundtrip = builder.finish_word(&triple, &triple, Some("demo/untriple"))?;
+        let args = [Value::I64(7), Value::I64(8), Value::I64(9)];
+        assert_eq!(run_word(&conn, &roundtrip, &args)?, args.to_vec());
+
+        // Arity 2 stays byte-identical with pair/unpair.
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.push_lit_i64(10)?;
+        builder.push_lit_i64(20)?;
+        let pair_cid = builder.pair()?;
+        builder.drop()?;
+        builder.push_lit_i64(10)?;
+        builder.push_lit_i64(20)?;
+        assert_eq!(builder.tuple(2)?, pair_cid);
+        assert!(builder.untuple(3, &[TypeTag::I64]).is_err());
+        Ok(())
+    }
+
     #[test]
     fn apply_quotation_invokes_target() -> Result<()> {
         let conn = Connection::open_in_memory()?;
@@ -1563,4 +2250,71 @@ mod tests {
         assert_eq!(guard_info.effect_mask, effect_mask::NONE);
         Ok(())
     }
+
+    #[test]
+    fn guard_deopts_name_the_failing_guard() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_guard(&[])?;
+        builder.push_lit_i64(0)?;
+        let never = builder.finish_guard(&[], &[TypeTag::I64], Some("demo/never"))?;
+        builder.begin_word(&[])?;
+        builder.push_lit_i64(1)?;
+        builder.attach_guard(never);
+        let guarded = builder.finish_word(&[], &[TypeTag::I64], None)?;
+        let err = run_word(&conn, &guarded, &[]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<crate::EvalError>(),
+            Some(&crate::EvalError::Deopt {
+                reason: Some(format!("guard {} failed", crate::cid::to_hex(&never)))
+            })
+        );
+        // The lowered DEOPT carries no reason, so the guard's CID stays out of the graph.
+        let reasonless = NodeCanon {
+            kind: NodeKind::Deopt,
+            out: vec![TypeTag::Unit.as_atom().to_string()],
+            inputs: Vec::new(),
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::Deopt(None),
+        };
+        assert!(!node::store_node(&conn, &reasonless)?.inserted);
+
+        // A text guard whose else path is an f64 guard: only the inner one reports.
+        builder.begin_word(&[])?;
+        let lit = NodeInput {
+            cid: builder.push_lit_i64(5)?,
+            port: 0,
+        };
+        let inner = NodeInput {
+            cid: builder.guard_type(TypeTag::F64)?,
+            port: 0,
+        };
+        builder.stack.pop();
+        let outer = NodeCanon {
+            kind: NodeKind::Guard,
+            out: vec![TypeTag::I64.as_atom().to_string()],
+            inputs: vec![lit],
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::Guard {
+                type_key: GraphBuilder::guard_type_key(TypeTag::Text),
+                match_cont: lit,
+                else_cont: inner,
+            },
+        };
+        builder.stack.push(StackItem {
+            cid: node::store_node(&conn, &outer)?.cid,
+            port: 0,
+            ty: TypeTag::I64,
+        });
+        let nested = builder.finish_word(&[], &[TypeTag::I64], None)?;
+        let err = run_word(&conn, &nested, &[]).unwrap_err();
+        assert_eq!(err.to_string(), "deopt triggered: expected f64, got i64");
+        Ok(())
+    }
 }
@@ -0,0 +1,249 @@
+//! Portable whole-store bundles for moving objects and names between databases.
+
+use anyhow::{Result, bail};
+use rusqlite::Connection;
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+
+use crate::cbor::{push_array, push_bytes, push_text, push_u32};
+use crate::{cid, db};
+
+/// Magic string identifying a bundle file.
+pub const BUNDLE_FORMAT: &str = "march5-bundle";
+/// Current bundle layout version.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Counters describing what an import changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub objects_inserted: usize,
+    pub objects_skipped: usize,
+    pub names_inserted: usize,
+    pub names_skipped: usize,
+}
+
+/// Encode every object and name-index row into a self-describing CBOR bundle.
+///
+/// Layout: `["march5-bundle", version, [[cid, kind, cbor]...], [[scope, name, cid]...]]`.
+pub fn export_bundle(conn: &Connection) -> Result<Vec<u8>> {
+    let objects = db::list_all_objects(conn)?;
+    let names = db::list_all_names(conn)?;
+
+    let mut buf = Vec::new();
+    push_array(&mut buf, 4);
+    push_text(&mut buf, BUNDLE_FORMAT);
+    push_u32(&mut buf, BUNDLE_VERSION);
+
+    push_array(&mut buf, objects.len() as u64);
+    for object in &objects {
+        push_array(&mut buf, 3);
+        push_bytes(&mut buf, &object.cid);
+        push_text(&mut buf, &object.kind);
+        push_bytes(&mut buf, &object.cbor);
+    }
+
+    push_array(&mut buf, names.len() as u64);
+    for entry in &names {
+        push_array(&mut buf, 3);
+        push_text(&mut buf, &entry.scope);
+        push_text(&mut buf, &entry.name);
+        push_bytes(&mut buf, &entry.cid);
+    }
+
+    Ok(buf)
+}
+
+/// Insert the contents of a bundle, validating CIDs and skipping rows already present.
+///
+/// The import runs in a single transaction; a CID mismatch, non-canonical object
+/// bytes or a name already bound to a different CID abort it without touching the store.
+pub fn import_bundle(conn: &Connection, bytes: &[u8]) -> Result<ImportReport> {
+    let BundleRecord(format, version, objects, names) = serde_cbor::from_slice(bytes)?;
+    if format != BUNDLE_FORMAT {
+        bail!("not a march5 bundle (format `{format}`)");
+    }
+    if version != BUNDLE_VERSION {
+        bail!("unsupported bundle version {version} (expected {BUNDLE_VERSION})");
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let mut report = ImportReport::default();
+
+    for BundleObjectRecord(cid_buf, kind, cbor) in objects {
+        let recorded = cid::from_slice(cid_buf.as_ref())?;
+        let computed = cid::compute(cbor.as_ref());
+        if recorded != computed {
+            bail!(
+                "bundle object `{}` does not hash to its CID (computed {})",
+                cid::to_hex(&recorded),
+                cid::to_hex(&computed)
+            );
+        }
+        if db::put_object_canonical(&tx, &computed, &kind, cbor.as_ref())? {
+            report.objects_inserted += 1;
+        } else {
+            report.objects_skipped += 1;
+        }
+    }
+
+    for BundleNameRecord(scope, name, cid_buf) in names {
+        let target = cid::from_slice(cid_buf.as_ref())?;
+        match db::get_name(&tx, &scope, &name)? {
+            Some(existing) if existing == target => report.names_skipped += 1,
+            Some(existing) => bail!(
+                "name `{scope}/{name}` already bound to {} (bundle has {})",
+                cid::to_hex(&existing),
+                cid::to_hex(&target)
+            ),
+            None => {
+                db::put_name(&tx, &scope, &name, &target)?;
+                report.names_inserted += 1;
+            }
+        }
+    }
+
+    tx.commit()?;
+    Ok(report)
+}
+
+#[derive(Deserialize)]
+struct BundleRecord(String, u32, Vec<BundleObjectRecord>, Vec<BundleNameRecord>);
+
+#[derive(Deserialize)]
+struct BundleObjectRecord(ByteBuf, String, ByteBuf);
+
+#[derive(Deserialize)]
+struct BundleNameRecord(String, String, ByteBuf);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effect::{EffectCanon, store_effect};
+    use crate::word::{WordCanon, store_word};
+
+    fn populate(conn: &Connection) -> Result<()> {
+        let effect = EffectCanon {
+            name: "io",
+            doc: Some("performs IO"),
+        };
+        let effect_cid = store_effect(conn, &effect)?.cid;
+        db::put_name(conn, "effect", "demo/io", &effect_cid)?;
+        let word = WordCanon {
+            root: [0x42; 32],
+            params: vec!["i64".to_string()],
+            results: vec!["i64".to_string()],
+            effects: vec![effect_cid],
+            effect_mask: crate::types::effect_mask::IO,
+            guards: Vec::new(),
+            doc: None,
+        };
+        let word_cid = store_word(conn, &word)?.cid;
+        db::put_name(conn, "word", "demo/echo", &word_cid)?;
+        Ok(())
+    }
+
+    #[test]
+    fn export_import_roundtrip() -> Result<()> {
+        let source = Connection::open_in_memory()?;
+        db::install_schema(&source)?;
+        populate(&source)?;
+        let bundle = export_bundle(&source)?;
+
+        let target = Connection::open_in_memory()?;
+        db::install_schema(&target)?;
+        let report = import_bundle(&target, &bundle)?;
+        assert_eq!(report.objects_inserted, 2);
+        assert_eq!(report.names_inserted, 2);
+
+        for (scope, name) in [("effect", "demo/io"), ("word", "demo/echo")] {
+            let original = db::get_name(&source, scope, name)?;
+            assert!(original.is_some());
+            assert_eq!(db::get_name(&target, scope, name)?, original);
+            let cid = original.unwrap();
+            assert_eq!(
+                db::load_object_cbor(&target, &cid)?,
+                db::load_object_cbor(&source, &cid)?
+            );
+        }
+
+        let again = import_bundle(&target, &bundle)?;
+        assert_eq!(again.objects_inserted, 0);
+        assert_eq!(again.objects_skipped, 2);
+        assert_eq!(again.names_inserted, 0);
+        assert_eq!(again.names_skipped, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn import_rejects_tampered_objects() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let mut buf = Vec::new();
+        push_array(&mut buf, 4);
+        push_text(&mut buf, BUNDLE_FORMAT);
+        push_u32(&mut buf, BUNDLE_VERSION);
+        push_array(&mut buf, 1);
+        push_array(&mut buf, 3);
+        push_bytes(&mut buf, &[0u8; 32]);
+        push_text(&mut buf, "effect");
+        push_bytes(&mut buf, b"not the preimage");
+        push_array(&mut buf, 0);
+
+        assert!(import_bundle(&conn, &buf).is_err());
+        assert_eq!(db::list_all_objects(&conn)?.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn import_rejects_non_canonical_objects() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        // `{"kind": "effect", "name": "io"}` with the map length spelled in two bytes.
+        let mut object = vec![0xb8, 0x02];
+        push_text(&mut object, "kind");
+        push_text(&mut object, "effect");
+        push_text(&mut object, "name");
+        push_text(&mut object, "io");
+        let mut buf = Vec::new();
+        push_array(&mut buf, 4);
+        push_text(&mut buf, BUNDLE_FORMAT);
+        push_u32(&mut buf, BUNDLE_VERSION);
+        push_array(&mut buf, 1);
+        push_array(&mut buf, 3);
+        push_bytes(&mut buf, &cid::compute(&object));
+        push_text(&mut buf, "effect");
+        push_bytes(&mut buf, &object);
+        push_array(&mut buf, 0);
+
+        let err = import_bundle(&conn, &buf).unwrap_err();
+        assert!(format!("{err:#}").contains("non-minimal"), "{err:#}");
+        assert_eq!(db::list_all_objects(&conn)?.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn import_rejects_trailing_items_after_an_object() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let mut object = crate::effect::encode(&crate::effect::EffectCanon {
+            name: "io",
+            doc: None,
+        });
+        push_text(&mut object, "smuggled");
+        let mut buf = Vec::new();
+        push_array(&mut buf, 4);
+        push_text(&mut buf, BUNDLE_FORMAT);
+        push_u32(&mut buf, BUNDLE_VERSION);
+        push_array(&mut buf, 1);
+        push_array(&mut buf, 3);
+        push_bytes(&mut buf, &cid::compute(&object));
+        push_text(&mut buf, "effect");
+        push_bytes(&mut buf, &object);
+        push_array(&mut buf, 0);
+
+        let err = import_bundle(&conn, &buf).unwrap_err();
+        assert!(format!("{err:#}").contains("trailing"), "{err:#}");
+        assert_eq!(db::list_all_objects(&conn)?.len(), 0);
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 //! Utilities for emitting canonical CBOR fragments used across March objects.
 
+use anyhow::{Result, anyhow, bail};
+
 /// Append a map header with `len` entries to `buf`.
 pub fn push_map(buf: &mut Vec<u8>, len: u64) {
     push_header(buf, 5, len);
@@ -44,6 +46,16 @@ pub fn push_f64(buf: &mut Vec<u8>, value: f64) {
     buf.extend_from_slice(&value.to_bits().to_be_bytes());
 }
 
+/// Append an unsigned 64-bit integer.
+pub fn push_u64(buf: &mut Vec<u8>, value: u64) {
+    push_unsigned(buf, value);
+}
+
+/// Append a CBOR `null` simple value.
+pub fn push_null(buf: &mut Vec<u8>) {
+    buf.push(0xf6);
+}
+
 fn push_unsigned(buf: &mut Vec<u8>, value: u64) {
     push_header(buf, 0, value);
 }
@@ -67,7 +79,173 @@ pub fn push_header(buf: &mut Vec<u8>, major: u8, len: u64) {
         }
         _ => {
             buf.push((major << 5) | 27);
-            buf.extend_from_slice(&(len as u64).to_be_bytes());
+            buf.extend_from_slice(&len.to_be_bytes());
         }
     }
 }
+
+/// Map keys in the order the object encoders write them.
+///
+/// Canonical key order is fixed per layout rather than sorted (see
+/// DESIGN-II), and every map layout the crate emits is a subsequence of this
+/// list: effects, agents, rules and type signatures.
+const MAP_KEY_ORDER: &[&str] = &[
+    "kind", "name", "ports", "lhs", "rewire", "version", "doc", "params", "results",
+];
+
+/// Check that `bytes` is exactly one item using the encodings the `push_*`
+/// helpers emit.
+///
+/// Every header must use its shortest form, lengths must be definite and map
+/// keys must be known text keys appearing in [`MAP_KEY_ORDER`] order (which
+/// also rules out duplicates). Trailing bytes after the item are rejected.
+pub fn check_canonical(bytes: &[u8]) -> Result<()> {
+    let mut reader = CanonicalReader { bytes, pos: 0 };
+    reader.item()?;
+    if reader.pos != bytes.len() {
+        bail!("trailing CBOR after the object at byte {}", reader.pos);
+    }
+    Ok(())
+}
+
+struct CanonicalReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> CanonicalReader<'a> {
+    fn take(&mut self, len: u64) -> Result<&'a [u8]> {
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.pos.checked_add(len))
+            .filter(|end| *end <= self.bytes.len());
+        let Some(end) = end else {
+            bail!("truncated CBOR at byte {}", self.pos);
+        };
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    /// Read a map key and return its position in [`MAP_KEY_ORDER`].
+    fn map_key(&mut self) -> Result<usize> {
+        let start = self.pos;
+        self.item()?;
+        let key = &self.bytes[start..self.pos];
+        MAP_KEY_ORDER
+            .iter()
+            .position(|known| {
+                let mut expected = Vec::new();
+                push_text(&mut expected, known);
+                expected == key
+            })
+            .ok_or_else(|| anyhow!("unexpected CBOR map key at byte {start}"))
+    }
+
+    fn item(&mut self) -> Result<()> {
+        let start = self.pos;
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let arg = match info {
+            0..=23 => u64::from(info),
+            24..=27 => {
+                let width = 1u64 << (info - 24);
+                let value = self
+                    .take(width)?
+                    .iter()
+                    .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte));
+                // Floats (major 7, widths 2..8) carry bits, not a length.
+                let is_float = major == 7 && info > 24;
+                let shortest = match info {
+                    24 => 24,
+                    25 => 0x100,
+                    26 => 0x1_0000,
+                    _ => 0x1_0000_0000,
+                };
+                if !is_float && value < shortest {
+                    bail!("non-minimal CBOR header at byte {start}");
+                }
+                value
+            }
+            31 => bail!("indefinite-length CBOR item at byte {start}"),
+            _ => bail!("reserved CBOR header at byte {start}"),
+        };
+        match major {
+            2 => {
+                self.take(arg)?;
+            }
+            3 => {
+                std::str::from_utf8(self.take(arg)?)?;
+            }
+            4 => {
+                for _ in 0..arg {
+                    self.item()?;
+                }
+            }
+            5 => {
+                let mut last_rank = None;
+                for _ in 0..arg {
+                    let key_start = self.pos;
+                    let rank = self.map_key()?;
+                    if last_rank.is_some_and(|last| rank <= last) {
+                        bail!("CBOR map key out of order at byte {key_start}");
+                    }
+                    last_rank = Some(rank);
+                    self.item()?;
+                }
+            }
+            6 => self.item()?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_check_rejects_long_headers_and_indefinite_lengths() -> Result<()> {
+        let mut ok = Vec::new();
+        push_array(&mut ok, 3);
+        push_u64(&mut ok, 300);
+        push_text(&mut ok, "kind");
+        push_f64(&mut ok, 1.5);
+        check_canonical(&ok)?;
+
+        assert!(check_canonical(&[0x18, 0x05]).is_err()); // 5 as a one-byte argument
+        assert!(check_canonical(&[0x9f, 0x01, 0xff]).is_err()); // indefinite array
+        assert!(check_canonical(&[0xa2, 0x61, b'a', 0x01, 0x61, b'a', 0x02]).is_err());
+        assert!(check_canonical(&[0x82, 0x01]).is_err()); // truncated
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_check_requires_one_item_with_ordered_keys() -> Result<()> {
+        let effect = crate::effect::encode(&crate::effect::EffectCanon {
+            name: "io",
+            doc: Some("console"),
+        });
+        check_canonical(&effect)?;
+
+        let mut trailing = effect.clone();
+        push_u64(&mut trailing, 7);
+        assert!(check_canonical(&trailing).is_err());
+
+        let mut reordered = Vec::new();
+        push_map(&mut reordered, 2);
+        push_text(&mut reordered, "name");
+        push_text(&mut reordered, "io");
+        push_text(&mut reordered, "kind");
+        push_text(&mut reordered, "effect");
+        assert!(check_canonical(&reordered).is_err());
+
+        let mut unknown = Vec::new();
+        push_map(&mut unknown, 1);
+        push_text(&mut unknown, "extra");
+        push_null(&mut unknown);
+        assert!(check_canonical(&unknown).is_err());
+        Ok(())
+    }
+}
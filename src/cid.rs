@@ -1,16 +1,154 @@
 //! SHA-256 based content identifiers used throughout March.
+//!
+//! Store CIDs are bare 32-byte SHA-256 digests. Other algorithms are available
+//! through [`compute_with`], which tags the digest with its algorithm.
 
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow, bail};
 use sha2::{Digest, Sha256};
 
+/// Hash algorithms that can back a content identifier.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HashAlg {
+    /// SHA-256; the algorithm behind every store CID.
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+impl HashAlg {
+    /// Multihash code identifying the algorithm in tagged CIDs.
+    pub fn code(self) -> u8 {
+        match self {
+            HashAlg::Sha256 => 0x12,
+            HashAlg::Blake3 => 0x1e,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x12 => Some(HashAlg::Sha256),
+            0x1e => Some(HashAlg::Blake3),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlg::Sha256 => "sha256",
+            HashAlg::Blake3 => "blake3",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sha256" => Some(HashAlg::Sha256),
+            "blake3" => Some(HashAlg::Blake3),
+            _ => None,
+        }
+    }
+
+    /// Raw 32-byte digest of `bytes`.
+    pub fn digest(self, bytes: &[u8]) -> [u8; 32] {
+        match self {
+            HashAlg::Sha256 => {
+                let mut hasher = Hasher::new();
+                hasher.update(bytes);
+                hasher.finalize()
+            }
+            HashAlg::Blake3 => *blake3::hash(bytes).as_bytes(),
+        }
+    }
+}
+
+/// A digest tagged with the algorithm that produced it.
+///
+/// Serialises multihash-style as `[code, 32, digest...]` (34 bytes).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TaggedCid {
+    pub alg: HashAlg,
+    pub digest: [u8; 32],
+}
+
+impl TaggedCid {
+    pub fn to_bytes(&self) -> [u8; 34] {
+        let mut out = [0u8; 34];
+        out[0] = self.alg.code();
+        out[1] = 32;
+        out[2..].copy_from_slice(&self.digest);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let [code, len, rest @ ..] = data else {
+            bail!("tagged CID too short ({} bytes)", data.len());
+        };
+        let alg = HashAlg::from_code(*code)
+            .ok_or_else(|| anyhow!("unknown hash algorithm code 0x{code:02x}"))?;
+        if *len != 32 {
+            bail!("tagged CID digest length must be 32, got {len}");
+        }
+        Ok(Self {
+            alg,
+            digest: from_slice(rest)?,
+        })
+    }
+
+    /// Hex rendering of the tagged form (68 characters).
+    pub fn to_hex(&self) -> String {
+        format!("{:02x}{:02x}{}", self.alg.code(), 32, to_hex(&self.digest))
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self> {
+        if s.len() != 68 || !s.is_ascii() {
+            bail!(
+                "tagged CID must be 68 hex characters, got length {}",
+                s.len()
+            );
+        }
+        let code = u8::from_str_radix(&s[0..2], 16)?;
+        let len = u8::from_str_radix(&s[2..4], 16)?;
+        let mut data = vec![code, len];
+        data.extend_from_slice(&from_hex(&s[4..])?);
+        Self::from_bytes(&data)
+    }
+}
+
+/// Incremental store-CID hasher, for encoders that emit CBOR in chunks.
+///
+/// Feeding the same bytes in any split gives the same CID as [`compute`].
+#[derive(Clone, Default)]
+pub struct Hasher(Sha256);
+
+impl Hasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    pub fn finalize(self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&self.0.finalize());
+        out
+    }
+}
+
 /// Compute the 32-byte content ID (CID) as SHA-256 of the given bytes.
 pub fn compute(bytes: &[u8]) -> [u8; 32] {
-    let mut hasher = Sha256::new();
-    hasher.update(bytes);
-    let digest = hasher.finalize();
-    let mut out = [0u8; 32];
-    out.copy_from_slice(&digest);
-    out
+    HashAlg::Sha256.digest(bytes)
+}
+
+/// Compute a CID with an explicit algorithm, keeping the algorithm recoverable.
+///
+/// `compute_with(HashAlg::Sha256, bytes).digest` equals `compute(bytes)`, so the
+/// store (which keys objects by bare digest) stays consistent.
+pub fn compute_with(alg: HashAlg, bytes: &[u8]) -> TaggedCid {
+    TaggedCid {
+        alg,
+        digest: alg.digest(bytes),
+    }
 }
 
 /// Render a CID as lowercase hexadecimal for human output.
@@ -41,6 +179,80 @@ pub fn from_hex(s: &str) -> Result<[u8; 32]> {
     Ok(out)
 }
 
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Render a CID as lowercase RFC 4648 base32 without padding (52 characters).
+pub fn to_base32(cid: &[u8; 32]) -> String {
+    let mut out = String::with_capacity(52);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in cid {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+/// Parse an unpadded base32 CID (case-insensitive).
+pub fn from_base32(s: &str) -> Result<[u8; 32]> {
+    if s.len() != 52 {
+        bail!("base32 CID must be 52 characters, got length {}", s.len());
+    }
+    let mut out = Vec::with_capacity(32);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for ch in s.chars() {
+        let value = match ch.to_ascii_lowercase() {
+            c @ 'a'..='z' => c as u32 - 'a' as u32,
+            c @ '2'..='7' => c as u32 - '2' as u32 + 26,
+            other => bail!("invalid base32 digit `{other}`"),
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    if buffer & ((1 << bits) - 1) != 0 {
+        bail!("base32 CID has non-zero trailing bits");
+    }
+    from_slice(&out)
+}
+
+/// Parse a CID written either as 64-char hex or 52-char base32.
+pub fn parse(s: &str) -> Result<[u8; 32]> {
+    if s.len() == 64 {
+        from_hex(s)
+    } else {
+        from_base32(s)
+    }
+}
+
+/// Text encoding used when printing CIDs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CidEncoding {
+    #[default]
+    Hex,
+    Base32,
+}
+
+impl CidEncoding {
+    pub fn encode(self, cid: &[u8; 32]) -> String {
+        match self {
+            CidEncoding::Hex => to_hex(cid),
+            CidEncoding::Base32 => to_base32(cid),
+        }
+    }
+}
+
 /// Convert a raw slice into a CID array, validating the length.
 pub fn from_slice(data: &[u8]) -> Result<[u8; 32]> {
     if data.len() != 32 {
@@ -64,6 +276,15 @@ fn hex_value(byte: u8) -> Option<u8> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn streaming_hash_matches_compute() {
+        let mut hasher = Hasher::new();
+        hasher.update(b"canonical ");
+        hasher.update(b"cbor");
+        assert_eq!(hasher.finalize(), compute(b"canonical cbor"));
+        assert_eq!(Hasher::new().finalize(), compute(&[]));
+    }
+
     #[test]
     fn roundtrip_hex() {
         let cid = [0xabu8; 32];
@@ -73,6 +294,50 @@ mod tests {
         assert_eq!(parsed, cid);
     }
 
+    #[test]
+    fn compute_with_distinguishes_algorithms() -> Result<()> {
+        let sha = compute_with(HashAlg::Sha256, b"abc");
+        let blake = compute_with(HashAlg::Blake3, b"abc");
+        assert_eq!(
+            to_hex(&sha.digest),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            to_hex(&blake.digest),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert_ne!(sha, blake);
+        assert_eq!(sha.digest, compute(b"abc"));
+        assert_eq!(compute_with(HashAlg::Blake3, b"abc"), blake);
+
+        for tagged in [sha, blake] {
+            assert_eq!(TaggedCid::from_bytes(&tagged.to_bytes())?, tagged);
+            assert_eq!(TaggedCid::from_hex(&tagged.to_hex())?, tagged);
+        }
+        assert!(blake.to_hex().starts_with("1e20"));
+        assert!(TaggedCid::from_bytes(&[0xff, 32]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_base32() -> Result<()> {
+        let cid = compute(b"march");
+        let encoded = to_base32(&cid);
+        assert_eq!(
+            encoded,
+            "7j23lqs7ycextdhzmr4uotna5puey3fxioq7cailwbp5q5lcow3q"
+        );
+        assert!(encoded.len() < to_hex(&cid).len());
+        assert!(encoded.bytes().all(|b| BASE32_ALPHABET.contains(&b)));
+        assert_eq!(from_base32(&encoded)?, cid);
+        assert_eq!(from_base32(&encoded.to_ascii_uppercase())?, cid);
+        assert_eq!(parse(&encoded)?, cid);
+        assert_eq!(parse(&to_hex(&cid))?, cid);
+        assert_eq!(to_base32(&[0u8; 32]), "a".repeat(52));
+        assert!(from_base32("1").is_err());
+        Ok(())
+    }
+
     #[test]
     fn from_slice_checks_length() {
         assert!(from_slice(&[0u8; 31]).is_err());
@@ -4,6 +4,7 @@ use std::path::Path;
 use anyhow::{Result, anyhow, bail};
 
 use crate::cli::commands::util::{lookup_named_cid, parse_type_tags};
+use march5::types::render_comma_list;
 use march5::{TypeTag, open_store};
 
 pub(crate) fn cmd_builder(store: &Path) -> Result<()> {
@@ -12,9 +13,10 @@ pub(crate) fn cmd_builder(store: &Path) -> Result<()> {
     let stdin = io::stdin();
     let mut input = String::new();
     let mut current_params: Option<Vec<TypeTag>> = None;
+    let mut dry_run = false;
 
     println!(
-        "March builder REPL. Commands: begin, begin-guard, lit, prim, call, dup, swap, over, attach-guard <name|cid>, stack, finish, finish-guard, reset, help, quit."
+        "March builder REPL. Commands: begin, begin-guard, lit, prim, call, dup, swap, over, attach-guard <name|cid>, stack, undo, dry, finish, finish-auto, finish-guard, reset, help, quit."
     );
     loop {
         print!("builder> ");
@@ -31,10 +33,19 @@ pub(crate) fn cmd_builder(store: &Path) -> Result<()> {
         let cmd = parts.next().unwrap();
         let remaining: Vec<&str> = parts.collect();
 
+        let mutating = matches!(
+            cmd,
+            "lit" | "prim" | "call" | "attach-guard" | "dup" | "swap" | "over"
+        );
+        if mutating {
+            ensure_builder_begun(&mut builder, &mut current_params)?;
+            builder.checkpoint();
+        }
+
         let result = match cmd {
             "help" => {
                 println!(
-                    "Commands:\n  begin [types...]             start a word with parameter types (e.g. begin i64 i64)\n  begin-guard [types...]       start a guard with parameter types\n  lit <i64>                    push literal\n  prim <primCID|name>          apply primitive by CID or name\n  call <wordCID|name>          call existing word by CID or name\n  dup|swap|over                stack shuffles\n  attach-guard <name|cid>      attach a guard to current word\n  stack                        show current stack depth\n  finish <result> [name]       finish word with result type and optional name\n  finish-guard <result> [name] finish guard with result type (i64 expected) and optional name\n  reset                        abandon current build\n  quit/exit                    leave the REPL"
+                    "Commands:\n  begin [types...]             start a word with parameter types (e.g. begin i64 i64)\n  begin-guard [types...]       start a guard with parameter types\n  lit <i64>                    push literal\n  prim <primCID|name>          apply primitive by CID or name\n  call <wordCID|name>          call existing word by CID or name\n  dup|swap|over                stack shuffles\n  attach-guard <name|cid>      attach a guard to current word\n  undo                         revert the last lit/prim/call/shuffle/attach-guard\n  stack [-v]                   show current stack depth (-v: each slot's type and producer)\n  dry [on|off]                 toggle dry-run: finish/finish-guard only print the CID\n  finish <result> [name]       finish word with result type and optional name\n  finish-auto [name]           finish word with the current stack's types as results\n  finish-guard <result> [name] finish guard with result type (i64 expected) and optional name\n  reset                        abandon current build\n  quit/exit                    leave the REPL"
                 );
                 Ok(())
             }
@@ -115,8 +126,26 @@ pub(crate) fn cmd_builder(store: &Path) -> Result<()> {
                 ensure_builder_begun(&mut builder, &mut current_params)?;
                 builder.over()
             }
+            "dry" => {
+                dry_run = match remaining.as_slice() {
+                    [] => !dry_run,
+                    ["on"] => true,
+                    ["off"] => false,
+                    _ => bail!("dry expects `on`, `off`, or no argument"),
+                };
+                println!("dry run {}", if dry_run { "on" } else { "off" });
+                Ok(())
+            }
+            "undo" => builder.rollback().map(|()| {
+                println!("undone; stack depth: {}", builder.depth());
+            }),
             "stack" => {
                 println!("stack depth: {}", builder.depth());
+                if remaining == ["-v"] {
+                    for line in format_stack(&builder.stack_snapshot()) {
+                        println!("{line}");
+                    }
+                }
                 Ok(())
             }
             "finish" => {
@@ -133,9 +162,37 @@ pub(crate) fn cmd_builder(store: &Path) -> Result<()> {
                 let params = current_params
                     .as_ref()
                     .ok_or_else(|| anyhow!("no word in progress; use begin first"))?;
-                let cid = builder.finish_word(params, &[result_tag], name.as_deref())?;
-                println!("stored word with cid {}", march5::cid::to_hex(&cid));
-                current_params = None;
+                if dry_run {
+                    let cid = builder.plan_word(params, &[result_tag])?;
+                    println!("dry run: word would have cid {}", march5::cid::to_hex(&cid));
+                    Ok(())
+                } else {
+                    let cid = builder.finish_word(params, &[result_tag], name.as_deref())?;
+                    println!("stored word with cid {}", march5::cid::to_hex(&cid));
+                    current_params = None;
+                    Ok(())
+                }
+            }
+            "finish-auto" => {
+                ensure_builder_begun(&mut builder, &mut current_params)?;
+                if remaining.len() > 1 {
+                    bail!("finish-auto takes at most a name");
+                }
+                let params = builder.inferred_params();
+                let results = builder.inferred_results();
+                println!(
+                    "signature: [{}] -> [{}]",
+                    render_comma_list(&params),
+                    render_comma_list(&results)
+                );
+                if dry_run {
+                    let cid = builder.plan_word(&params, &results)?;
+                    println!("dry run: word would have cid {}", march5::cid::to_hex(&cid));
+                } else {
+                    let cid = builder.finish_word(&params, &results, remaining.first().copied())?;
+                    println!("stored word with cid {}", march5::cid::to_hex(&cid));
+                    current_params = None;
+                }
                 Ok(())
             }
             "finish-guard" => {
@@ -152,15 +209,27 @@ pub(crate) fn cmd_builder(store: &Path) -> Result<()> {
                 let params = current_params
                     .as_ref()
                     .ok_or_else(|| anyhow!("no guard in progress; use begin-guard first"))?;
-                let cid = builder.finish_guard(params, &[result_tag], name.as_deref())?;
-                println!("stored guard with cid {}", march5::cid::to_hex(&cid));
-                current_params = None;
-                Ok(())
+                if dry_run {
+                    let cid = builder.plan_guard(params, &[result_tag])?;
+                    println!(
+                        "dry run: guard would have cid {}",
+                        march5::cid::to_hex(&cid)
+                    );
+                    Ok(())
+                } else {
+                    let cid = builder.finish_guard(params, &[result_tag], name.as_deref())?;
+                    println!("stored guard with cid {}", march5::cid::to_hex(&cid));
+                    current_params = None;
+                    Ok(())
+                }
             }
             _ => bail!("unknown command `{cmd}`"),
         };
 
         if let Err(err) = result {
+            if mutating {
+                builder.rollback()?;
+            }
             eprintln!("error: {err}");
         }
     }
@@ -178,3 +247,40 @@ fn ensure_builder_begun(
     }
     Ok(())
 }
+
+/// One line per stack slot, bottom first: index, type atom, and producer `cid-prefix:port`.
+fn format_stack(slots: &[([u8; 32], u32, TypeTag)]) -> Vec<String> {
+    slots
+        .iter()
+        .enumerate()
+        .map(|(idx, (cid, port, ty))| {
+            let hex = march5::cid::to_hex(cid);
+            format!("  [{idx}] {:<10} {}:{port}", ty.as_atom(), &hex[..12])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::db;
+    use rusqlite::Connection;
+
+    #[test]
+    fn verbose_stack_lists_types_bottom_to_top() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let mut builder = march5::GraphBuilder::new(&conn);
+        builder.begin_word(&[TypeTag::F64])?;
+        let lit = builder.push_lit_i64(5)?;
+        builder.dup()?;
+
+        let lines = format_stack(&builder.stack_snapshot());
+        let lit_prefix = &march5::cid::to_hex(&lit)[..12];
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("  [0] f64"), "{}", lines[0]);
+        assert_eq!(lines[1], format!("  [1] i64        {lit_prefix}:0"));
+        assert_eq!(lines[2], format!("  [2] i64        {lit_prefix}:0"));
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use march5::bundle;
+use march5::open_store;
+
+pub(crate) fn cmd_export(store: &Path, file: &Path) -> Result<()> {
+    let conn = open_store(store)?;
+    let bytes = bundle::export_bundle(&conn)?;
+    fs::write(file, &bytes).with_context(|| format!("failed to write {}", file.display()))?;
+    println!("exported {} bytes to {}", bytes.len(), file.display());
+    Ok(())
+}
+
+pub(crate) fn cmd_import(store: &Path, file: &Path) -> Result<()> {
+    let conn = open_store(store)?;
+    let bytes = fs::read(file).with_context(|| format!("failed to read {}", file.display()))?;
+    let report = bundle::import_bundle(&conn, &bytes)?;
+    println!(
+        "imported {} object(s) ({} already present), {} name(s) ({} already present)",
+        report.objects_inserted,
+        report.objects_skipped,
+        report.names_inserted,
+        report.names_skipped
+    );
+    Ok(())
+}
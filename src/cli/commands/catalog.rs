@@ -1,19 +1,36 @@
 use std::collections::BTreeMap;
+use std::fs;
 use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Result, bail};
 use rusqlite::Connection;
 
+use crate::cli::commands::catalog_export::export_word_yaml;
 use crate::cli::commands::util::{lookup_named_cid, parse_effect_mask_flags, require_store_path};
 use march5::effect::{self, EffectCanon};
 use march5::global_store::{GlobalStoreSnapshot, store_snapshot};
 use march5::prim::{self, PrimCanon};
-use march5::types::EffectMask;
+use march5::types::{EffectMask, render_compact_list};
 use march5::yaml::{self, CatalogItem, WordOp};
 use march5::{TypeTag, Value, cid, get_name, open_store, put_name};
 
-pub(crate) fn cmd_catalog(store: Option<&Path>, file: &Path, dry_run: bool) -> Result<()> {
+pub(crate) fn cmd_catalog(
+    store: Option<&Path>,
+    file: &Path,
+    dry_run: bool,
+    no_clobber: bool,
+    watch: bool,
+) -> Result<()> {
+    if watch {
+        if dry_run {
+            bail!("--watch cannot be combined with --dry-run");
+        }
+        return watch_catalog(require_store_path(store)?, file, no_clobber);
+    }
     let catalog = yaml::parse_catalog_from_file(file)?;
+    warn_if_empty(file, &catalog);
     if dry_run {
         for (namespace, entries) in &catalog {
             for (symbol, item) in entries {
@@ -28,9 +45,37 @@ pub(crate) fn cmd_catalog(store: Option<&Path>, file: &Path, dry_run: bool) -> R
 
     let store_path = require_store_path(store)?;
     let conn = open_store(store_path)?;
+    apply_catalog(&conn, catalog, no_clobber)
+}
+
+fn warn_if_empty(file: &Path, catalog: &yaml::Catalog) {
+    if yaml::catalog_entry_count(catalog) == 0 {
+        eprintln!(
+            "warning: catalog {} contained no entries (check its indentation)",
+            file.display()
+        );
+    }
+}
+
+/// Store every entry of a parsed catalog and bind its names.
+///
+/// Runs in one transaction, so a failing entry (a `no_clobber` conflict, a
+/// cycle) leaves no earlier entry of the same catalog behind.
+fn apply_catalog(conn: &Connection, catalog: yaml::Catalog, no_clobber: bool) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    apply_catalog_entries(&tx, catalog, no_clobber)?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn apply_catalog_entries(
+    conn: &Connection,
+    catalog: yaml::Catalog,
+    no_clobber: bool,
+) -> Result<()> {
     for (namespace, entries) in catalog {
         let mut guard_items = Vec::new();
-        let mut word_items = Vec::new();
+        let mut word_items: Vec<WordItem> = Vec::new();
         let mut overload_items: Vec<(String, String, Vec<yaml::OverloadSpec>)> = Vec::new();
         let mut snapshot_items = Vec::new();
         for (symbol, item) in entries {
@@ -41,8 +86,8 @@ pub(crate) fn cmd_catalog(store: Option<&Path>, file: &Path, dry_run: bool) -> R
                         name: &full_name,
                         doc: doc.as_deref(),
                     };
-                    let outcome = effect::store_effect(&conn, &spec)?;
-                    put_name(&conn, "effect", &full_name, &outcome.cid)?;
+                    let outcome = effect::store_effect(conn, &spec)?;
+                    bind_name(conn, "effect", &full_name, &outcome.cid, no_clobber)?;
                     println!(
                         "stored effect `{full_name}` with cid {}",
                         cid::to_hex(&outcome.cid)
@@ -60,11 +105,14 @@ pub(crate) fn cmd_catalog(store: Option<&Path>, file: &Path, dry_run: bool) -> R
                         results: &results,
                         effects: effects.as_slice(),
                         effect_mask,
+                        doc: None,
+                        variadic: None,
+                        builtin: prim::builtin_id(&full_name),
                     };
-                    let outcome = prim::store_prim(&conn, &spec)?;
-                    put_name(&conn, "prim", &full_name, &outcome.cid)?;
-                    if get_name(&conn, "prim", &symbol)?.is_none() {
-                        put_name(&conn, "prim", &symbol, &outcome.cid)?;
+                    let outcome = prim::store_prim(conn, &spec)?;
+                    bind_name(conn, "prim", &full_name, &outcome.cid, no_clobber)?;
+                    if get_name(conn, "prim", &symbol)?.is_none() {
+                        put_name(conn, "prim", &symbol, &outcome.cid)?;
                     }
                     println!(
                         "stored prim `{full_name}` with cid {}",
@@ -96,10 +144,10 @@ pub(crate) fn cmd_catalog(store: Option<&Path>, file: &Path, dry_run: bool) -> R
         }
 
         for (symbol, full_name, params, results, stack) in guard_items {
-            apply_guard_catalog(&conn, &full_name, &params, &results, &stack)?;
-            if get_name(&conn, "guard", &symbol)?.is_none() {
-                let cid = lookup_named_cid(&conn, "guard", &full_name)?;
-                put_name(&conn, "guard", &symbol, &cid)?;
+            apply_guard_catalog(conn, &full_name, &params, &results, &stack, no_clobber)?;
+            if get_name(conn, "guard", &symbol)?.is_none() {
+                let cid = lookup_named_cid(conn, "guard", &full_name)?;
+                put_name(conn, "guard", &symbol, &cid)?;
             }
         }
 
@@ -115,12 +163,13 @@ pub(crate) fn cmd_catalog(store: Option<&Path>, file: &Path, dry_run: bool) -> R
                     format!("{full_name}#{}${}", sig, *counter)
                 };
                 apply_word_catalog(
-                    &conn,
+                    conn,
                     &derived,
                     &entry.params,
                     &entry.results,
                     &entry.stack,
                     &entry.guards,
+                    no_clobber,
                 )?;
             }
             println!(
@@ -129,18 +178,19 @@ pub(crate) fn cmd_catalog(store: Option<&Path>, file: &Path, dry_run: bool) -> R
             );
         }
 
-        for (symbol, full_name, params, results, stack, guards) in word_items {
-            apply_word_catalog(&conn, &full_name, &params, &results, &stack, &guards)?;
-            if get_name(&conn, "word", &symbol)?.is_none() {
-                let cid = lookup_named_cid(&conn, "word", &full_name)?;
-                put_name(&conn, "word", &symbol, &cid)?;
+        for idx in word_dependency_order(&word_items)? {
+            let (symbol, full_name, params, results, stack, guards) = &word_items[idx];
+            apply_word_catalog(conn, full_name, params, results, stack, guards, no_clobber)?;
+            if get_name(conn, "word", symbol)?.is_none() {
+                let cid = lookup_named_cid(conn, "word", full_name)?;
+                put_name(conn, "word", symbol, &cid)?;
             }
         }
 
         for (_symbol, full_name, values) in snapshot_items {
             let snapshot = GlobalStoreSnapshot::from_entries(values);
-            let outcome = store_snapshot(&conn, &snapshot)?;
-            put_name(&conn, "gstate", &full_name, &outcome.cid)?;
+            let outcome = store_snapshot(conn, &snapshot)?;
+            bind_name(conn, "gstate", &full_name, &outcome.cid, no_clobber)?;
             println!(
                 "stored snapshot `{full_name}` with cid {}",
                 cid::to_hex(&outcome.cid)
@@ -151,6 +201,161 @@ pub(crate) fn cmd_catalog(store: Option<&Path>, file: &Path, dry_run: bool) -> R
     Ok(())
 }
 
+/// A catalog `!word` entry: symbol, full name, params, results, stack, guards.
+type WordItem = (
+    String,
+    String,
+    Vec<TypeTag>,
+    Vec<TypeTag>,
+    Vec<WordOp>,
+    Vec<String>,
+);
+
+/// Indices of `items` ordered so every word comes after the namespace words it calls.
+///
+/// Calls are resolved against all of the namespace's words up front, so a word may
+/// call one defined later in the file. A word's CID covers its callees' CIDs, so a
+/// cycle can never be stored and is reported with the names along it.
+fn word_dependency_order(items: &[WordItem]) -> Result<Vec<usize>> {
+    let local = |name: &str| {
+        items
+            .iter()
+            .position(|(symbol, full_name, ..)| name == symbol || name == full_name)
+    };
+    let callees: Vec<Vec<usize>> = items
+        .iter()
+        .map(|(_, _, _, _, stack, _)| {
+            stack
+                .iter()
+                .filter_map(|op| match op {
+                    WordOp::Word(name) => local(name),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect();
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Unvisited,
+        Active,
+        Done,
+    }
+    fn visit(
+        idx: usize,
+        callees: &[Vec<usize>],
+        marks: &mut [Mark],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> Option<Vec<usize>> {
+        match marks[idx] {
+            Mark::Done => return None,
+            Mark::Active => {
+                let start = path.iter().position(|&entry| entry == idx).unwrap_or(0);
+                let mut cycle = path[start..].to_vec();
+                cycle.push(idx);
+                return Some(cycle);
+            }
+            Mark::Unvisited => {}
+        }
+        marks[idx] = Mark::Active;
+        path.push(idx);
+        for &callee in &callees[idx] {
+            if let Some(cycle) = visit(callee, callees, marks, path, order) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        marks[idx] = Mark::Done;
+        order.push(idx);
+        None
+    }
+
+    let mut marks = vec![Mark::Unvisited; items.len()];
+    let mut order = Vec::with_capacity(items.len());
+    for idx in 0..items.len() {
+        if let Some(cycle) = visit(idx, &callees, &mut marks, &mut Vec::new(), &mut order) {
+            let names: Vec<&str> = cycle.iter().map(|&i| items[i].1.as_str()).collect();
+            bail!(
+                "catalog words call each other in a cycle: {} (words cannot be recursive)",
+                names.join(" -> ")
+            );
+        }
+    }
+    Ok(order)
+}
+
+/// Tracks a catalog file's mtime so `--watch` re-applies it only after edits.
+struct CatalogWatcher<'a> {
+    file: &'a Path,
+    seen: Option<SystemTime>,
+}
+
+impl<'a> CatalogWatcher<'a> {
+    fn new(file: &'a Path) -> Self {
+        CatalogWatcher { file, seen: None }
+    }
+
+    /// True the first time and whenever the mtime moved since the last call.
+    ///
+    /// A file that is briefly missing (editors often save by renaming) is not a change.
+    fn changed(&mut self) -> bool {
+        let Ok(modified) = fs::metadata(self.file).and_then(|meta| meta.modified()) else {
+            return false;
+        };
+        if self.seen == Some(modified) {
+            return false;
+        }
+        self.seen = Some(modified);
+        true
+    }
+}
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Re-apply `file` whenever it changes; parse and apply errors are reported, not fatal.
+fn watch_catalog(store: &Path, file: &Path, no_clobber: bool) -> Result<()> {
+    let conn = open_store(store)?;
+    let mut watcher = CatalogWatcher::new(file);
+    println!("watching {} (Ctrl-C to stop)", file.display());
+    loop {
+        if watcher.changed() {
+            let outcome = reload_catalog(&conn, file, no_clobber);
+            let stamp = utc_time_of_day(SystemTime::now());
+            match outcome {
+                Ok(count) => println!("[{stamp}] applied {} ({count} entries)", file.display()),
+                Err(err) => eprintln!("[{stamp}] {} failed: {err:#}", file.display()),
+            }
+        }
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+/// Parse and apply `file`, returning how many entries it held.
+fn reload_catalog(conn: &Connection, file: &Path, no_clobber: bool) -> Result<usize> {
+    let catalog = yaml::parse_catalog_from_file(file)?;
+    let count = yaml::catalog_entry_count(&catalog);
+    warn_if_empty(file, &catalog);
+    apply_catalog(conn, catalog, no_clobber)?;
+    Ok(count)
+}
+
+/// `HH:MM:SS` (UTC) for watch-mode log lines.
+fn utc_time_of_day(now: SystemTime) -> String {
+    let secs = now
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+        % 86_400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+pub(crate) fn cmd_catalog_export(store: &Path, word: &str) -> Result<()> {
+    let conn = open_store(store)?;
+    print!("{}", export_word_yaml(&conn, word)?);
+    Ok(())
+}
+
 fn describe_catalog_item(item: &CatalogItem) -> &'static str {
     match item {
         CatalogItem::Effect { .. } => "effect",
@@ -169,6 +374,7 @@ fn apply_word_catalog(
     results: &[TypeTag],
     stack: &[WordOp],
     guards: &[String],
+    no_clobber: bool,
 ) -> Result<()> {
     let mut builder = march5::GraphBuilder::new(conn);
     builder.begin_word(params)?;
@@ -177,8 +383,8 @@ fn apply_word_catalog(
         builder.attach_guard(cid);
     }
     apply_stack_ops(&mut builder, conn, full_name, stack)?;
-    let word_cid = builder.finish_word(params, results, Some(full_name))?;
-    put_name(conn, "word", full_name, &word_cid)?;
+    let word_cid = builder.finish_word(params, results, None)?;
+    bind_name(conn, "word", full_name, &word_cid, no_clobber)?;
     println!(
         "stored word `{full_name}` with cid {}",
         cid::to_hex(&word_cid)
@@ -186,18 +392,38 @@ fn apply_word_catalog(
     Ok(())
 }
 
+/// `put_name`, except that with `no_clobber` a name already bound to a different
+/// CID is an error instead of being overwritten.
+fn bind_name(
+    conn: &Connection,
+    scope: &str,
+    name: &str,
+    target: &[u8; 32],
+    no_clobber: bool,
+) -> Result<()> {
+    if no_clobber
+        && let Some(existing) = get_name(conn, scope, name)?
+        && existing != *target
+    {
+        bail!(
+            "{scope} `{name}` is already bound to {} (catalog defines {}); refusing to clobber",
+            cid::to_hex(&existing),
+            cid::to_hex(target)
+        );
+    }
+    put_name(conn, scope, name, target)
+}
+
+/// Overload suffix such as `i64,i64->i64`.
+///
+/// The suffix becomes part of registered word names, so it stays compact
+/// (no spaces) to keep names from earlier catalog loads stable.
 fn format_signature(params: &[TypeTag], results: &[TypeTag]) -> String {
-    let left = params
-        .iter()
-        .map(|t| t.as_atom())
-        .collect::<Vec<_>>()
-        .join(",");
-    let right = results
-        .iter()
-        .map(|t| t.as_atom())
-        .collect::<Vec<_>>()
-        .join(",");
-    format!("{left}->{right}")
+    format!(
+        "{}->{}",
+        render_compact_list(params),
+        render_compact_list(results)
+    )
 }
 
 fn apply_guard_catalog(
@@ -206,12 +432,13 @@ fn apply_guard_catalog(
     params: &[TypeTag],
     results: &[TypeTag],
     stack: &[WordOp],
+    no_clobber: bool,
 ) -> Result<()> {
     let mut builder = march5::GraphBuilder::new(conn);
     builder.begin_guard(params)?;
     apply_stack_ops(&mut builder, conn, full_name, stack)?;
-    let guard_cid = builder.finish_guard(params, results, Some(full_name))?;
-    put_name(conn, "guard", full_name, &guard_cid)?;
+    let guard_cid = builder.finish_guard(params, results, None)?;
+    bind_name(conn, "guard", full_name, &guard_cid, no_clobber)?;
     println!(
         "stored guard `{full_name}` with cid {}",
         cid::to_hex(&guard_cid)
@@ -251,11 +478,22 @@ fn apply_stack_ops(
             WordOp::Quote(cid_bytes) => {
                 builder.quote(*cid_bytes)?;
             }
+            WordOp::LoadGlobal(key, ty) => {
+                builder.push_load_global(*key, *ty)?;
+            }
         }
     }
     Ok(())
 }
 
+type OverloadMatch = (
+    [u8; 32],
+    Vec<TypeTag>,
+    Vec<TypeTag>,
+    Vec<[u8; 32]>,
+    EffectMask,
+);
+
 fn apply_overloaded_symbol(
     builder: &mut march5::GraphBuilder<'_>,
     conn: &Connection,
@@ -272,13 +510,7 @@ fn apply_overloaded_symbol(
         bail!("word `{base_name}` not found and no overloads registered");
     }
 
-    let mut matches: Vec<(
-        [u8; 32],
-        Vec<TypeTag>,
-        Vec<TypeTag>,
-        Vec<[u8; 32]>,
-        EffectMask,
-    )> = Vec::new();
+    let mut matches: Vec<OverloadMatch> = Vec::new();
     for (cid, params, results) in &candidates {
         let arity = params.len();
         let top_types = builder.peek_top_types(arity)?;
@@ -313,3 +545,210 @@ fn apply_overloaded_symbol(
     builder.apply_dispatch(&specs)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::node::{NodeKind, load_node_info};
+    use march5::word::load_word_info;
+    use march5::{db, global_store, run_word};
+    use serde_cbor::Value as CborValue;
+
+    fn apply_catalog_str(conn: &Connection, doc: &str) -> Result<()> {
+        for (namespace, entries) in yaml::parse_catalog_from_str(doc)? {
+            for (symbol, item) in entries {
+                let full_name = format!("{namespace}/{symbol}");
+                match item {
+                    CatalogItem::Word {
+                        params,
+                        results,
+                        stack,
+                        guards,
+                    } => apply_word_catalog(
+                        conn, &full_name, &params, &results, &stack, &guards, false,
+                    )?,
+                    other => bail!(
+                        "unexpected {} in test catalog",
+                        describe_catalog_item(&other)
+                    ),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn exported_word_reimports_to_the_same_cid() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let params = [TypeTag::I64, TypeTag::I64];
+        let results = [TypeTag::I64];
+        let spec = PrimCanon {
+            params: &params,
+            results: &results,
+            effects: &[],
+            effect_mask: march5::types::effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let sub = prim::store_prim(&conn, &spec)?.cid;
+        put_name(&conn, "prim", "demo/sub_i64", &sub)?;
+        let doc = "demo:\n  mix: !word\n    params: [i64, i64]\n    results: [i64]\n    stack:\n      - !swap\n      - !lit 10\n      - !prim demo/sub_i64\n      - !swap\n      - !dup\n      - !prim demo/sub_i64\n      - !prim demo/sub_i64\n";
+        apply_catalog_str(&conn, doc)?;
+        let original = lookup_named_cid(&conn, "word", "demo/mix")?;
+
+        let exported = export_word_yaml(&conn, "demo/mix")?;
+        let fresh = Connection::open_in_memory()?;
+        db::install_schema(&fresh)?;
+        prim::store_prim(&fresh, &spec)?;
+        put_name(&fresh, "prim", "demo/sub_i64", &sub)?;
+        apply_catalog_str(&fresh, &exported)?;
+        assert_eq!(lookup_named_cid(&fresh, "word", "demo/mix")?, original);
+        Ok(())
+    }
+
+    #[test]
+    fn watcher_reapplies_after_the_file_changes() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("live.yaml");
+        let one =
+            "demo:\n  one: !word\n    params: []\n    results: [i64]\n    stack:\n      - !lit 1\n";
+        fs::write(&path, one)?;
+
+        let mut watcher = CatalogWatcher::new(&path);
+        assert!(watcher.changed());
+        assert_eq!(reload_catalog(&conn, &path, false)?, 1);
+        assert!(!watcher.changed());
+
+        let two = format!(
+            "{one}  two: !word\n    params: []\n    results: [i64]\n    stack:\n      - !lit 2\n"
+        );
+        fs::write(&path, two)?;
+        // Force a distinct mtime; coarse filesystem clocks may not tick between writes.
+        fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(SystemTime::now() + Duration::from_secs(5))?;
+        assert!(watcher.changed());
+        assert_eq!(reload_catalog(&conn, &path, false)?, 2);
+        assert!(get_name(&conn, "word", "demo/two")?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn no_clobber_is_idempotent_but_rejects_redefinitions() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = dir.path().join("catalog.march5.db");
+        db::create_store(&store)?;
+        let file = dir.path().join("catalog.yaml");
+        let catalog = |value: i64| {
+            format!(
+                "demo:\n  io: !effect\n    doc: console\n  answer: !word\n    params: []\n    results: [i64]\n    stack:\n      - !lit {value}\n"
+            )
+        };
+
+        std::fs::write(&file, catalog(42))?;
+        cmd_catalog(Some(&store), &file, false, true, false)?;
+        let conn = open_store(&store)?;
+        let first = lookup_named_cid(&conn, "word", "demo/answer")?;
+        cmd_catalog(Some(&store), &file, false, true, false)?;
+        assert_eq!(lookup_named_cid(&conn, "word", "demo/answer")?, first);
+
+        // The conflict rolls back the whole catalog, including the new effect.
+        let redefined = format!("{}  fresh: !effect\n", catalog(7));
+        std::fs::write(&file, redefined)?;
+        let err = cmd_catalog(Some(&store), &file, false, true, false).unwrap_err();
+        assert!(err.to_string().contains("refusing to clobber"), "{err}");
+        assert_eq!(lookup_named_cid(&conn, "word", "demo/answer")?, first);
+        assert!(get_name(&conn, "effect", "demo/fresh")?.is_none());
+
+        std::fs::write(&file, catalog(7))?;
+
+        cmd_catalog(Some(&store), &file, false, false, false)?;
+        assert_ne!(lookup_named_cid(&conn, "word", "demo/answer")?, first);
+        Ok(())
+    }
+
+    #[test]
+    fn words_may_call_later_words_but_not_form_cycles() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let forward = "demo:\n  answer: !word\n    params: []\n    results: [i64]\n    stack:\n      - !word base\n  base: !word\n    params: []\n    results: [i64]\n    stack:\n      - !lit 42\n";
+        apply_catalog(&conn, yaml::parse_catalog_from_str(forward)?, false)?;
+        let answer = load_word_info(&conn, &lookup_named_cid(&conn, "word", "demo/answer")?)?;
+        let root = load_node_info(&conn, &answer.root)?;
+        assert_eq!(root.kind, NodeKind::Return);
+        assert!(lookup_named_cid(&conn, "word", "demo/base").is_ok());
+
+        let cyclic = "loop:\n  ping: !word\n    params: []\n    results: [i64]\n    stack:\n      - !word pong\n  pong: !word\n    params: []\n    results: [i64]\n    stack:\n      - !word loop/ping\n";
+        let err = apply_catalog(&conn, yaml::parse_catalog_from_str(cyclic)?, false).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("loop/ping -> loop/pong -> loop/ping"),
+            "{err}"
+        );
+        assert!(get_name(&conn, "word", "loop/ping")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn word_can_load_and_return_a_global() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let key = "ab".repeat(32);
+        let doc = format!(
+            "demo:\n  counter: !word\n    params: []\n    results: [i64]\n    stack:\n      - !load-global {key}\n"
+        );
+        let catalog = yaml::parse_catalog_from_str(&doc)?;
+        let Some(CatalogItem::Word {
+            params,
+            results,
+            stack,
+            guards,
+        }) = catalog
+            .get("demo")
+            .and_then(|entries| entries.get("counter"))
+        else {
+            bail!("catalog did not decode `demo/counter` as a word");
+        };
+        apply_word_catalog(&conn, "demo/counter", params, results, stack, guards, false)?;
+
+        let word_cid = lookup_named_cid(&conn, "word", "demo/counter")?;
+        let word = load_word_info(&conn, &word_cid)?;
+        assert_eq!(word.results, vec![TypeTag::I64]);
+        let root = load_node_info(&conn, &word.root)?;
+        assert_eq!(root.kind, NodeKind::Return);
+        let CborValue::Array(payload) = &root.payload else {
+            bail!("RETURN payload should be an array");
+        };
+        let Some(CborValue::Array(vals)) = payload.first() else {
+            bail!("RETURN payload should start with its value edges");
+        };
+        let Some(CborValue::Array(edge)) = vals.first() else {
+            bail!("word should return one value");
+        };
+        let Some(CborValue::Bytes(global_cid)) = edge.first() else {
+            bail!("value edge should start with a node CID");
+        };
+        let global = load_node_info(&conn, &cid::from_slice(global_cid)?)?;
+        assert_eq!(global.kind, NodeKind::LoadGlobal);
+        assert_eq!(global.out, vec!["i64".to_string()]);
+        assert_eq!(
+            global.payload,
+            CborValue::Bytes(cid::from_hex(&key)?.to_vec())
+        );
+
+        let err = run_word(&conn, &word_cid, &[]).unwrap_err();
+        assert!(err.to_string().contains("not found"), "{err}");
+        global_store::write(key.clone(), Value::I64(42));
+        assert_eq!(run_word(&conn, &word_cid, &[])?, vec![Value::I64(42)]);
+        global_store::write(key.clone(), Value::Text("forty-two".to_string()));
+        let err = run_word(&conn, &word_cid, &[]).unwrap_err();
+        assert!(err.to_string().contains("type mismatch"), "{err}");
+        global_store::remove(&key);
+        Ok(())
+    }
+}
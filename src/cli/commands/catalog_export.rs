@@ -0,0 +1,306 @@
+//! Reverse of `apply_stack_ops`: turn a stored pure word back into a catalog `!word` entry.
+
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow, bail};
+use rusqlite::Connection;
+use serde_cbor::Value as CborValue;
+
+use march5::node::{NodeInfo, NodeInput, NodeKind, load_node_info};
+use march5::types::{effect_mask, render_comma_list};
+use march5::word::{WordInfo, load_word_info};
+use march5::yaml::WordOp;
+use march5::{TypeTag, Value, cid, list_names_for_cid};
+
+/// Render the word registered as `name` (`namespace/symbol`) as a YAML catalog document.
+pub(crate) fn export_word_yaml(conn: &Connection, name: &str) -> Result<String> {
+    let Some((namespace, symbol)) = name.rsplit_once('/') else {
+        bail!("word name `{name}` must look like `namespace/symbol` to export");
+    };
+    let word_cid = super::util::lookup_named_cid(conn, "word", name)?;
+    let info = load_word_info(conn, &word_cid)?;
+    let ops = word_stack_ops(conn, &info)
+        .map_err(|err| anyhow!("word `{name}` is not representable as stack ops: {err}"))?;
+    let mut guards = Vec::with_capacity(info.guards.len());
+    for guard in &info.guards {
+        guards.push(preferred_name(conn, "guard", guard)?);
+    }
+
+    let mut out = format!("{namespace}:\n  {symbol}: !word\n");
+    out.push_str(&format!(
+        "    params: [{}]\n",
+        render_comma_list(&info.params)
+    ));
+    out.push_str(&format!(
+        "    results: [{}]\n",
+        render_comma_list(&info.results)
+    ));
+    if !guards.is_empty() {
+        out.push_str(&format!("    guards: [{}]\n", guards.join(", ")));
+    }
+    if ops.is_empty() {
+        out.push_str("    stack: []\n");
+    } else {
+        out.push_str("    stack:\n");
+        for op in &ops {
+            out.push_str(&format!("      - {}\n", render_op(op)?));
+        }
+    }
+    Ok(out)
+}
+
+/// Stack slot in the simulated builder stack.
+#[derive(Clone, Copy)]
+struct Slot {
+    input: NodeInput,
+    /// An argument still waiting for its first use (not yet an operand).
+    reserved: bool,
+}
+
+struct Decompiler<'a> {
+    conn: &'a Connection,
+    nodes: HashMap<[u8; 32], NodeInfo>,
+    arg_uses: HashMap<[u8; 32], usize>,
+    stack: Vec<Slot>,
+    ops: Vec<WordOp>,
+}
+
+/// Linear stack program that rebuilds `info`'s graph via the catalog builder.
+///
+/// Shared pure subgraphs are re-emitted rather than `dup`ed; content addressing
+/// makes the rebuilt nodes identical. Each argument must be within one slot of
+/// the top when it is next needed.
+fn word_stack_ops(conn: &Connection, info: &WordInfo) -> Result<Vec<WordOp>> {
+    if info.effect_mask != effect_mask::NONE || !info.effects.is_empty() {
+        bail!("effectful words are not supported");
+    }
+    let root = load_node_info(conn, &info.root)?;
+    if root.kind != NodeKind::Return {
+        bail!("word root is not a RETURN node");
+    }
+    let (vals, deps) = return_edges(&root.payload)?;
+    if !deps.is_empty() && info.guards.is_empty() {
+        bail!("RETURN carries dependencies that stack ops cannot express");
+    }
+
+    let mut decompiler = Decompiler {
+        conn,
+        nodes: HashMap::new(),
+        arg_uses: HashMap::new(),
+        stack: Vec::new(),
+        ops: Vec::new(),
+    };
+    for val in &vals {
+        decompiler.count_args(val)?;
+    }
+    for idx in 0..info.params.len() {
+        let arg = decompiler
+            .arg_uses
+            .keys()
+            .copied()
+            .find(|cid| decompiler.arg_index(cid) == Some(idx as u32));
+        let Some(arg) = arg else {
+            bail!("parameter {idx} is never used");
+        };
+        decompiler.stack.push(Slot {
+            input: NodeInput { cid: arg, port: 0 },
+            reserved: true,
+        });
+    }
+    for val in &vals {
+        decompiler.emit(val)?;
+    }
+    if decompiler.stack.len() != vals.len() || decompiler.stack.iter().any(|slot| slot.reserved) {
+        bail!("stack does not end with exactly the returned values");
+    }
+    Ok(decompiler.ops)
+}
+
+impl Decompiler<'_> {
+    fn node(&mut self, cid: &[u8; 32]) -> Result<&NodeInfo> {
+        if !self.nodes.contains_key(cid) {
+            let info = load_node_info(self.conn, cid)?;
+            self.nodes.insert(*cid, info);
+        }
+        Ok(&self.nodes[cid])
+    }
+
+    fn arg_index(&self, cid: &[u8; 32]) -> Option<u32> {
+        match self.nodes.get(cid) {
+            Some(NodeInfo {
+                kind: NodeKind::Arg,
+                payload: CborValue::Integer(idx),
+                ..
+            }) => u32::try_from(*idx).ok(),
+            _ => None,
+        }
+    }
+
+    fn count_args(&mut self, input: &NodeInput) -> Result<()> {
+        let node = self.node(&input.cid)?;
+        if node.kind == NodeKind::Arg {
+            *self.arg_uses.entry(input.cid).or_insert(0) += 1;
+            return Ok(());
+        }
+        let inputs = node.inputs.clone();
+        for inner in &inputs {
+            self.count_args(inner)?;
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, input: &NodeInput) -> Result<()> {
+        let node = self.node(&input.cid)?.clone();
+        if node.out.len() != 1 || input.port != 0 {
+            bail!("{} node with {} outputs", node.kind.name(), node.out.len());
+        }
+        if node.out[0].ends_with(".token") || !node.effects.is_empty() {
+            bail!("{} node threads effects", node.kind.name());
+        }
+        if node.kind == NodeKind::Arg {
+            return self.emit_arg(input);
+        }
+
+        for inner in &node.inputs {
+            self.emit(inner)?;
+        }
+        let op = match node.kind {
+            NodeKind::Lit => match node.payload {
+                CborValue::Integer(value) => WordOp::Lit(Value::I64(i64::try_from(value)?)),
+                _ => bail!("LIT payload is not an integer"),
+            },
+            NodeKind::Prim => WordOp::Prim(preferred_name(
+                self.conn,
+                "prim",
+                &payload_cid(&node.payload)?,
+            )?),
+            NodeKind::Call => WordOp::Word(preferred_name(
+                self.conn,
+                "word",
+                &payload_cid(&node.payload)?,
+            )?),
+            NodeKind::Quote => WordOp::Quote(payload_cid(&node.payload)?),
+            NodeKind::LoadGlobal => WordOp::LoadGlobal(
+                payload_cid(&node.payload)?,
+                TypeTag::from_atom(&node.out[0])?,
+            ),
+            other => bail!("{} nodes have no stack op", other.name()),
+        };
+        self.ops.push(op);
+        let arity = node.inputs.len();
+        if self.stack.len() < arity
+            || self.stack[self.stack.len() - arity..]
+                .iter()
+                .any(|s| s.reserved)
+        {
+            bail!(
+                "operands of {} are not on top of the stack",
+                node.kind.name()
+            );
+        }
+        self.stack.truncate(self.stack.len() - arity);
+        self.stack.push(Slot {
+            input: *input,
+            reserved: false,
+        });
+        Ok(())
+    }
+
+    fn emit_arg(&mut self, input: &NodeInput) -> Result<()> {
+        let depth = self
+            .stack
+            .iter()
+            .rev()
+            .position(|slot| slot.reserved && slot.input.cid == input.cid)
+            .ok_or_else(|| anyhow!("argument used after it was consumed"))?;
+        let uses = self
+            .arg_uses
+            .get_mut(&input.cid)
+            .ok_or_else(|| anyhow!("unexpected argument node"))?;
+        *uses -= 1;
+        let last_use = *uses == 0;
+        // Bring the unused argument to the top, then either consume it or
+        // copy it so the original stays available for later uses.
+        let top = self.stack.len() - 1;
+        match depth {
+            0 => {}
+            1 => {
+                self.ops.push(WordOp::Swap);
+                self.stack.swap(top, top - 1);
+            }
+            _ => bail!("argument is buried {depth} slots deep"),
+        }
+        if last_use {
+            self.stack[top].reserved = false;
+        } else {
+            self.ops.push(WordOp::Dup);
+            self.stack.push(Slot {
+                input: *input,
+                reserved: false,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn return_edges(payload: &CborValue) -> Result<(Vec<NodeInput>, Vec<NodeInput>)> {
+    match payload {
+        CborValue::Array(parts) if parts.len() == 2 => {
+            Ok((edge_list(&parts[0])?, edge_list(&parts[1])?))
+        }
+        _ => bail!("malformed RETURN payload"),
+    }
+}
+
+fn edge_list(value: &CborValue) -> Result<Vec<NodeInput>> {
+    let CborValue::Array(items) = value else {
+        bail!("malformed RETURN edge list");
+    };
+    items
+        .iter()
+        .map(|item| match item {
+            CborValue::Array(pair) if pair.len() == 2 => match (&pair[0], &pair[1]) {
+                (CborValue::Bytes(bytes), CborValue::Integer(port)) => Ok(NodeInput {
+                    cid: cid::from_slice(bytes)?,
+                    port: u32::try_from(*port)?,
+                }),
+                _ => bail!("malformed RETURN edge"),
+            },
+            _ => bail!("malformed RETURN edge"),
+        })
+        .collect()
+}
+
+fn payload_cid(payload: &CborValue) -> Result<[u8; 32]> {
+    match payload {
+        CborValue::Bytes(bytes) => cid::from_slice(bytes),
+        _ => bail!("node payload is not a CID"),
+    }
+}
+
+/// A qualified (`ns/sym`) registered name if there is one, else any name, else the hex CID.
+fn preferred_name(conn: &Connection, scope: &str, target: &[u8; 32]) -> Result<String> {
+    let names = list_names_for_cid(conn, scope, target)?;
+    Ok(names
+        .iter()
+        .find(|name| name.contains('/'))
+        .or(names.first())
+        .cloned()
+        .unwrap_or_else(|| cid::to_hex(target)))
+}
+
+fn render_op(op: &WordOp) -> Result<String> {
+    Ok(match op {
+        WordOp::Prim(name) => format!("!prim {name}"),
+        WordOp::Word(name) => format!("!word {name}"),
+        WordOp::Dup => "!dup".to_string(),
+        WordOp::Swap => "!swap".to_string(),
+        WordOp::Over => "!over".to_string(),
+        WordOp::Lit(Value::I64(n)) => format!("!lit {n}"),
+        WordOp::Lit(other) => bail!("cannot render literal {other:?}"),
+        WordOp::Quote(qid) => format!("!quote {}", cid::to_hex(qid)),
+        WordOp::LoadGlobal(key, ty) => {
+            format!("!load-global [{}, {}]", cid::to_hex(key), ty.as_atom())
+        }
+    })
+}
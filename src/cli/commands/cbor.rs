@@ -0,0 +1,204 @@
+use std::path::Path;
+
+use anyhow::Result;
+use serde_cbor::Value as CborValue;
+
+use super::util::render_named_object;
+use crate::cli::OutputFormat;
+use march5::{cid, load_object_cbor, open_store};
+
+pub(crate) fn cmd_cbor(store: &Path, cid_text: &str) -> Result<()> {
+    let conn = open_store(store)?;
+    let object_cid = cid::parse(cid_text)?;
+    let (kind, cbor) = load_object_cbor(&conn, &object_cid)?;
+    println!("{kind} {} ({} bytes)", cid::to_hex(&object_cid), cbor.len());
+    print!("{}", dump_cbor(&serde_cbor::from_slice(&cbor)?));
+    Ok(())
+}
+
+/// `show` output in a raw encoding selected by `--format`.
+pub(crate) fn cmd_show_encoded(
+    store: &Path,
+    scope: &str,
+    name: &str,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let conn = open_store(store)?;
+    println!(
+        "{}",
+        render_named_object(&conn, scope, scope, name, format, compact)?
+    );
+    Ok(())
+}
+
+/// Indented one-item-per-line rendering of a CBOR tree, with container lengths.
+fn dump_cbor(value: &CborValue) -> String {
+    let mut out = String::new();
+    dump_into(&mut out, value, 0);
+    out
+}
+
+fn dump_into(out: &mut String, value: &CborValue, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match value {
+        CborValue::Null => out.push_str(&format!("{indent}null\n")),
+        CborValue::Bool(b) => out.push_str(&format!("{indent}bool {b}\n")),
+        CborValue::Integer(n) => out.push_str(&format!("{indent}int {n}\n")),
+        CborValue::Float(x) => out.push_str(&format!("{indent}float {x:?}\n")),
+        CborValue::Bytes(bytes) => {
+            let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+            out.push_str(&format!("{indent}bytes({}) {hex}\n", bytes.len()));
+        }
+        CborValue::Text(text) => out.push_str(&format!("{indent}text {text:?}\n")),
+        CborValue::Array(items) => {
+            out.push_str(&format!("{indent}array({})\n", items.len()));
+            for item in items {
+                dump_into(out, item, depth + 1);
+            }
+        }
+        CborValue::Map(entries) => {
+            out.push_str(&format!("{indent}map({})\n", entries.len()));
+            for (key, val) in entries {
+                dump_into(out, key, depth + 1);
+                dump_into(out, val, depth + 2);
+            }
+        }
+        CborValue::Tag(tag, inner) => {
+            out.push_str(&format!("{indent}tag({tag})\n"));
+            dump_into(out, inner, depth + 1);
+        }
+        other => out.push_str(&format!("{indent}{other:?}\n")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::iface::{self, IfaceCanon, IfaceSymbol};
+    use march5::prim::{self, PrimCanon};
+    use march5::types::effect_mask;
+    use march5::{TypeTag, db};
+    use rusqlite::Connection;
+
+    #[test]
+    fn cbor_hex_show_round_trips_to_the_stored_prim() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let prim = PrimCanon {
+            params: &[TypeTag::I64],
+            results: &[TypeTag::I64],
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let prim_cid = prim::store_prim(&conn, &prim)?.cid;
+        db::put_name(&conn, "prim", "demo/inc", &prim_cid)?;
+
+        let hex = render_named_object(
+            &conn,
+            "prim",
+            "prim",
+            "demo/inc",
+            OutputFormat::CborHex,
+            false,
+        )?;
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()?;
+        let (_, stored) = load_object_cbor(&conn, &prim_cid)?;
+        assert_eq!(bytes, stored);
+        assert_eq!(cid::compute(&bytes), prim_cid);
+
+        let diag = render_named_object(
+            &conn,
+            "prim",
+            "prim",
+            "demo/inc",
+            OutputFormat::CborDiag,
+            false,
+        )?;
+        assert_eq!(
+            diag,
+            format!("[0, h'{}', [\"i64\"], [\"i64\"], [], 0]", "00".repeat(32))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn dump_shows_prim_tag_and_param_atoms() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let prim = PrimCanon {
+            params: &[TypeTag::I64, TypeTag::F64],
+            results: &[TypeTag::Text],
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let prim_cid = prim::store_prim(&conn, &prim)?.cid;
+        let (_, cbor) = load_object_cbor(&conn, &prim_cid)?;
+
+        let dump = dump_cbor(&serde_cbor::from_slice(&cbor)?);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines[0], "array(6)");
+        assert_eq!(lines[1], "  int 0");
+        assert!(lines[2].starts_with("  bytes(32) 0000"), "{dump}");
+        assert_eq!(
+            &lines[3..6],
+            ["  array(2)", "    text \"i64\"", "    text \"f64\""]
+        );
+        assert!(dump.contains("    text \"text\""), "{dump}");
+        Ok(())
+    }
+
+    #[test]
+    fn dump_and_diag_cover_ifaces() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let demo = IfaceCanon {
+            names: vec![IfaceSymbol {
+                name: "inc".to_string(),
+                params: vec!["i64".to_string()],
+                results: vec!["i64".to_string()],
+                effects: Vec::new(),
+            }],
+        };
+        let iface_cid = iface::store_iface(&conn, &demo)?.cid;
+        db::put_name(&conn, "iface", "demo", &iface_cid)?;
+        let (_, cbor) = load_object_cbor(&conn, &iface_cid)?;
+
+        let dump = dump_cbor(&serde_cbor::from_slice(&cbor)?);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "array(2)",
+                "  int 3",
+                "  array(1)",
+                "    array(4)",
+                "      text \"inc\"",
+                "      array(1)",
+                "        text \"i64\"",
+                "      array(1)",
+                "        text \"i64\"",
+                "      array(0)",
+            ]
+        );
+        let diag = render_named_object(
+            &conn,
+            "iface",
+            "iface",
+            "demo",
+            OutputFormat::CborDiag,
+            false,
+        )?;
+        assert_eq!(diag, "[3, [[\"inc\", [\"i64\"], [\"i64\"], []]]]");
+        Ok(())
+    }
+}
@@ -1,20 +1,39 @@
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
+use rusqlite::Connection;
 
+use super::util::{list_scope, show_named_object};
 use crate::cli::EffectCommand;
 use march5::effect::{self, EffectCanon};
-use march5::{cid, open_store};
+use march5::{cid, load_object_cbor, open_store, put_name};
 
-pub(crate) fn cmd_effect(store: &Path, command: EffectCommand) -> Result<()> {
+pub(crate) fn cmd_effect(store: &Path, command: EffectCommand, compact: bool) -> Result<()> {
     match command {
-        EffectCommand::Add { name, doc } => {
+        EffectCommand::Add {
+            name,
+            cid: Some(cid_text),
+            ..
+        } => {
+            let conn = open_store(store)?;
+            let effect_cid = register_effect_cid(&conn, &name, &cid_text)?;
+            println!("registered effect `{name}` -> {}", cid::to_hex(&effect_cid));
+        }
+        EffectCommand::Add {
+            name,
+            doc,
+            register,
+            cid: None,
+        } => {
             let conn = open_store(store)?;
             let spec = EffectCanon {
                 name: &name,
                 doc: doc.as_deref(),
             };
             let outcome = effect::store_effect(&conn, &spec)?;
+            if register {
+                put_name(&conn, "effect", &name, &outcome.cid)?;
+            }
             let cid_hex = cid::to_hex(&outcome.cid);
             if outcome.inserted {
                 println!("stored effect `{name}` with cid {cid_hex}");
@@ -22,6 +41,147 @@ pub(crate) fn cmd_effect(store: &Path, command: EffectCommand) -> Result<()> {
                 println!("effect `{name}` already present with cid {cid_hex}");
             }
         }
+        EffectCommand::List { prefix } => {
+            let conn = open_store(store)?;
+            list_scope(&conn, "effect", prefix.as_deref(), "no effects registered")?;
+        }
+        EffectCommand::Show { name } => {
+            let conn = open_store(store)?;
+            show_named_object(&conn, "effect", "effect", &name, compact)?;
+        }
     }
     Ok(())
 }
+
+/// Bind `name` to an effect object that is already in the store.
+fn register_effect_cid(conn: &Connection, name: &str, cid_text: &str) -> Result<[u8; 32]> {
+    let effect_cid = cid::parse(cid_text)?;
+    let (kind, _) = load_object_cbor(conn, &effect_cid)
+        .map_err(|err| anyhow!("no object with cid {cid_text}: {err}"))?;
+    if kind != "effect" {
+        bail!("object {cid_text} is a {kind}, not an effect");
+    }
+    put_name(conn, "effect", name, &effect_cid)?;
+    Ok(effect_cid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::commands::util::{named_object_json, scope_lines};
+    use march5::{create_store, get_name};
+
+    #[test]
+    fn add_registers_effect_for_list_and_show() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("effects.march5.db");
+        create_store(&path)?;
+        cmd_effect(
+            &path,
+            EffectCommand::Add {
+                name: "demo/io".to_string(),
+                doc: Some("performs IO".to_string()),
+                register: true,
+                cid: None,
+            },
+            false,
+        )?;
+
+        let conn = open_store(&path)?;
+        let cid = cid::compute(&effect::encode(&EffectCanon {
+            name: "demo/io",
+            doc: Some("performs IO"),
+        }));
+        assert_eq!(
+            scope_lines(&conn, "effect", Some("demo/"))?,
+            vec![format!("demo/io -> {}", cid::to_hex(&cid))]
+        );
+        let json: serde_json::Value = serde_json::from_str(&named_object_json(
+            &conn, "effect", "effect", "demo/io", false,
+        )?)?;
+        assert_eq!(json["name"], "demo/io");
+        assert_eq!(json["doc"], "performs IO");
+
+        cmd_effect(
+            &path,
+            EffectCommand::Add {
+                name: "demo/quiet".to_string(),
+                doc: None,
+                register: false,
+                cid: None,
+            },
+            false,
+        )?;
+        assert!(get_name(&conn, "effect", "demo/quiet")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn compact_json_stays_on_one_line() -> Result<()> {
+        let conn = rusqlite::Connection::open_in_memory()?;
+        march5::db::install_schema(&conn)?;
+        let spec = EffectCanon {
+            name: "demo/io",
+            doc: Some("performs IO"),
+        };
+        let outcome = effect::store_effect(&conn, &spec)?;
+        put_name(&conn, "effect", "demo/io", &outcome.cid)?;
+
+        let pretty = named_object_json(&conn, "effect", "effect", "demo/io", false)?;
+        let compact = named_object_json(&conn, "effect", "effect", "demo/io", true)?;
+        assert!(pretty.contains('\n'));
+        assert!(!compact.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&pretty)?,
+            serde_json::from_str::<serde_json::Value>(&compact)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn add_with_cid_registers_another_name_for_an_existing_effect() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("effects.march5.db");
+        create_store(&path)?;
+        let add = |name: &str, cid: Option<String>| {
+            cmd_effect(
+                &path,
+                EffectCommand::Add {
+                    name: name.to_string(),
+                    doc: None,
+                    register: true,
+                    cid,
+                },
+                false,
+            )
+        };
+        add("demo/io", None)?;
+        let conn = open_store(&path)?;
+        let io = get_name(&conn, "effect", "demo/io")?.expect("demo/io registered");
+
+        add("shared/io", Some(cid::to_hex(&io)))?;
+        assert_eq!(get_name(&conn, "effect", "shared/io")?, Some(io));
+
+        let missing = add("shared/none", Some("00".repeat(32))).unwrap_err();
+        assert!(missing.to_string().contains("no object"), "{missing}");
+        let prim = march5::prim::store_prim(
+            &conn,
+            &march5::prim::PrimCanon {
+                params: &[],
+                results: &[],
+                effects: &[],
+                effect_mask: march5::types::effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            },
+        )?;
+        let wrong_kind = add("shared/prim", Some(cid::to_hex(&prim.cid))).unwrap_err();
+        assert!(
+            wrong_kind.to_string().contains("not an effect"),
+            "{wrong_kind}"
+        );
+        assert!(get_name(&conn, "effect", "shared/prim")?.is_none());
+        Ok(())
+    }
+}
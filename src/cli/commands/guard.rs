@@ -1,13 +1,14 @@
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Result, bail};
+use rusqlite::Connection;
 
-use super::util::{list_scope, show_named_object};
+use super::util::{list_scope, lookup_named_cid, parse_cli_value, show_named_object, store_report};
 use crate::cli::GuardCommand;
 use march5::types::effect_mask;
-use march5::{cid, open_store, put_name};
+use march5::{Value, cid, open_store, put_name, run_guard};
 
-pub(crate) fn cmd_guard(store: &Path, command: GuardCommand) -> Result<()> {
+pub(crate) fn cmd_guard(store: &Path, command: GuardCommand, compact: bool) -> Result<()> {
     match command {
         GuardCommand::Add {
             name,
@@ -32,17 +33,10 @@ pub(crate) fn cmd_guard(store: &Path, command: GuardCommand) -> Result<()> {
                 effect_mask: effect_mask::NONE,
             };
             let outcome = march5::guard::store_guard(&conn, &guard)?;
-            if !no_register {
-                if let Some(name) = &name {
-                    put_name(&conn, "guard", name, &outcome.cid)?;
-                }
-            }
-            let cid_hex = march5::cid::to_hex(&outcome.cid);
-            if outcome.inserted {
-                println!("stored guard with cid {cid_hex}");
-            } else {
-                println!("guard already present with cid {cid_hex}");
+            if !no_register && let Some(name) = &name {
+                put_name(&conn, "guard", name, &outcome.cid)?;
             }
+            println!("{}", store_report("guard", &outcome.cid, outcome.inserted));
         }
         GuardCommand::List { prefix } => {
             let conn = open_store(store)?;
@@ -50,8 +44,66 @@ pub(crate) fn cmd_guard(store: &Path, command: GuardCommand) -> Result<()> {
         }
         GuardCommand::Show { name } => {
             let conn = open_store(store)?;
-            show_named_object(&conn, "guard", "guard", &name)?;
+            show_named_object(&conn, "guard", "guard", &name, compact)?;
+        }
+        GuardCommand::Run { name, args } => {
+            let conn = open_store(store)?;
+            let values = args
+                .iter()
+                .map(|s| parse_cli_value(s))
+                .collect::<Result<Vec<_>>>()?;
+            println!("{}", eval_guard(&conn, &name, &values)?);
         }
     }
     Ok(())
 }
+
+/// Run a named guard against exactly its declared arguments, yielding 1 or 0.
+pub(crate) fn eval_guard(conn: &Connection, name: &str, args: &[Value]) -> Result<i64> {
+    let guard_cid = lookup_named_cid(conn, "guard", name)?;
+    let info = march5::guard::load_guard_info(conn, &guard_cid)?;
+    if info.params.len() != args.len() {
+        bail!(
+            "guard `{name}` expects {} argument(s), got {}",
+            info.params.len(),
+            args.len()
+        );
+    }
+    Ok(i64::from(run_guard(conn, &guard_cid, args)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::prim::{self, PrimCanon};
+    use march5::{GraphBuilder, TypeTag, db};
+
+    #[test]
+    fn run_reports_guard_outcome() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let params = [TypeTag::I64, TypeTag::I64];
+        let gt = PrimCanon {
+            params: &params,
+            results: &[TypeTag::I64],
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let gt_cid = prim::store_prim(&conn, &gt)?.cid;
+        put_name(&conn, "prim", "core/gt_i64", &gt_cid)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_guard(&[TypeTag::I64])?;
+        builder.push_lit_i64(0)?;
+        builder.apply_prim(gt_cid)?;
+        builder.finish_guard(&[TypeTag::I64], &[TypeTag::I64], Some("demo/positive"))?;
+
+        assert_eq!(eval_guard(&conn, "demo/positive", &[Value::I64(5)])?, 1);
+        assert_eq!(eval_guard(&conn, "demo/positive", &[Value::I64(-5)])?, 0);
+        assert!(eval_guard(&conn, "demo/positive", &[]).is_err());
+        Ok(())
+    }
+}
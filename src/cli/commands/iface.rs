@@ -1,30 +1,45 @@
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
+use rusqlite::Connection;
 
-use super::util::{list_scope, parse_iface_spec, show_named_object};
+use super::util::{
+    format_types, list_scope, lookup_named_cid, parse_iface_spec, parse_type_tags,
+    show_named_object,
+};
 use crate::cli::IfaceCommand;
-use march5::iface::{self, IfaceCanon};
-use march5::{cid, open_store, put_name};
+use march5::iface::{self, IfaceCanon, IfaceSymbol};
+use march5::word::load_word_info;
+use march5::{cid, db, open_store, put_name};
 
-pub(crate) fn cmd_iface(store: &Path, command: IfaceCommand) -> Result<()> {
+pub(crate) fn cmd_iface(store: &Path, command: IfaceCommand, compact: bool) -> Result<()> {
     match command {
         IfaceCommand::Add {
             register,
             names,
             no_register,
+            check_words,
         } => {
             let conn = open_store(store)?;
             let mut parsed = Vec::with_capacity(names.len());
             for spec in names {
                 parsed.push(parse_iface_spec(&spec)?);
             }
+            for entry in &check_words {
+                let Some((symbol, word_name)) = entry.split_once('=') else {
+                    bail!("invalid --check-word `{entry}`; expected symbol=word-name");
+                };
+                let symbol = symbol.trim();
+                let spec = parsed
+                    .iter()
+                    .find(|candidate| candidate.name == symbol)
+                    .ok_or_else(|| anyhow!("interface has no export named `{symbol}`"))?;
+                check_symbol_against_word(&conn, spec, word_name.trim())?;
+            }
             let iface = IfaceCanon { names: parsed };
             let outcome = iface::store_iface(&conn, &iface)?;
-            if !no_register {
-                if let Some(name) = &register {
-                    put_name(&conn, "iface", name, &outcome.cid)?;
-                }
+            if !no_register && let Some(name) = &register {
+                put_name(&conn, "iface", name, &outcome.cid)?;
             }
             let cid_hex = cid::to_hex(&outcome.cid);
             if outcome.inserted {
@@ -33,6 +48,19 @@ pub(crate) fn cmd_iface(store: &Path, command: IfaceCommand) -> Result<()> {
                 println!("iface already present with cid {cid_hex}");
             }
         }
+        IfaceCommand::Merge { a, b, name } => {
+            let conn = open_store(store)?;
+            let left = iface::load_iface(&conn, &lookup_named_cid(&conn, "iface", &a)?)?;
+            let right = iface::load_iface(&conn, &lookup_named_cid(&conn, "iface", &b)?)?;
+            let merged = iface::merge(&left, &right)?;
+            let outcome = iface::store_iface(&conn, &merged)?;
+            put_name(&conn, "iface", &name, &outcome.cid)?;
+            println!(
+                "merged {} symbols into iface `{name}` with cid {}",
+                merged.names.len(),
+                cid::to_hex(&outcome.cid)
+            );
+        }
         IfaceCommand::List { prefix } => {
             let conn = open_store(store)?;
             list_scope(
@@ -42,10 +70,162 @@ pub(crate) fn cmd_iface(store: &Path, command: IfaceCommand) -> Result<()> {
                 "no interfaces registered",
             )?;
         }
-        IfaceCommand::Show { name } => {
+        IfaceCommand::Show { name, resolve } => {
             let conn = open_store(store)?;
-            show_named_object(&conn, "iface", "interface", &name)?;
+            show_named_object(&conn, "iface", "interface", &name, compact)?;
+            if resolve {
+                let iface = iface::load_iface(&conn, &lookup_named_cid(&conn, "iface", &name)?)?;
+                for line in resolve_symbols(&conn, &iface)? {
+                    println!("{line}");
+                }
+            }
         }
     }
     Ok(())
 }
+
+/// Bail unless the word registered as `word_name` has exactly the symbol's params, results and effects.
+pub(crate) fn check_symbol_against_word(
+    conn: &Connection,
+    symbol: &IfaceSymbol,
+    word_name: &str,
+) -> Result<()> {
+    let info = load_word_info(conn, &lookup_named_cid(conn, "word", word_name)?)?;
+    let params = parse_type_tags(&symbol.params)?;
+    let results = parse_type_tags(&symbol.results)?;
+    let name = &symbol.name;
+    if params != info.params {
+        bail!(
+            "export `{name}` declares params {} but word `{word_name}` takes {}",
+            format_types(&params),
+            format_types(&info.params)
+        );
+    }
+    if results != info.results {
+        bail!(
+            "export `{name}` declares results {} but word `{word_name}` returns {}",
+            format_types(&results),
+            format_types(&info.results)
+        );
+    }
+    let mut declared = symbol.effects.clone();
+    declared.sort();
+    let mut actual = info.effects.clone();
+    actual.sort();
+    if declared != actual {
+        bail!("export `{name}` declares different effects than word `{word_name}`");
+    }
+    Ok(())
+}
+
+/// One `symbol -> word` line per interface symbol, naming a registered word
+/// with the same signature. Words whose last name segment equals the symbol win.
+pub(crate) fn resolve_symbols(conn: &Connection, iface: &IfaceCanon) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    for entry in db::list_names(conn, "word", None)? {
+        // Best effort: skip names that point at something unreadable.
+        if let Ok(info) = load_word_info(conn, &entry.cid) {
+            words.push((entry, info));
+        }
+    }
+    let mut lines = Vec::with_capacity(iface.names.len());
+    for symbol in &iface.names {
+        let params = parse_type_tags(&symbol.params)?;
+        let results = parse_type_tags(&symbol.results)?;
+        let mut effects = symbol.effects.clone();
+        effects.sort();
+        let mut matches = words.iter().filter(|(_, info)| {
+            let mut actual = info.effects.clone();
+            actual.sort();
+            info.params == params && info.results == results && actual == effects
+        });
+        let same_name = matches
+            .clone()
+            .find(|(entry, _)| entry.name.rsplit('/').next() == Some(symbol.name.as_str()));
+        match same_name.or_else(|| matches.next()) {
+            Some((entry, _)) => lines.push(format!(
+                "{} -> {} ({})",
+                symbol.name,
+                entry.name,
+                cid::to_hex(&entry.cid)
+            )),
+            None => lines.push(format!("{} -> no matching word", symbol.name)),
+        }
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::types::effect_mask;
+    use march5::word::{WordCanon, store_word};
+
+    #[test]
+    fn check_word_accepts_matching_and_rejects_drift() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let word = WordCanon {
+            root: [0x33u8; 32],
+            params: vec!["i64".to_string(), "i64".to_string()],
+            results: vec!["i64".to_string()],
+            effects: Vec::new(),
+            effect_mask: effect_mask::NONE,
+            guards: Vec::new(),
+            doc: None,
+        };
+        let word_cid = store_word(&conn, &word)?.cid;
+        put_name(&conn, "word", "demo.math/add", &word_cid)?;
+
+        let matching = parse_iface_spec("add(i64, i64) -> i64")?;
+        check_symbol_against_word(&conn, &matching, "demo.math/add")?;
+
+        let wrong_result = parse_iface_spec("add(i64, i64) -> f64")?;
+        let err = check_symbol_against_word(&conn, &wrong_result, "demo.math/add").unwrap_err();
+        assert!(err.to_string().contains("results (f64)"), "{err}");
+
+        let extra_effect = parse_iface_spec(&format!(
+            "add(i64, i64) -> i64 | {}",
+            cid::to_hex(&[0x44u8; 32])
+        ))?;
+        assert!(check_symbol_against_word(&conn, &extra_effect, "demo.math/add").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_names_a_word_with_the_symbol_signature() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let word = |root: u8, params: usize| WordCanon {
+            root: [root; 32],
+            params: vec!["i64".to_string(); params],
+            results: vec!["i64".to_string()],
+            effects: Vec::new(),
+            effect_mask: effect_mask::NONE,
+            guards: Vec::new(),
+            doc: None,
+        };
+        let sub = store_word(&conn, &word(0x51, 2))?.cid;
+        let add = store_word(&conn, &word(0x52, 2))?.cid;
+        let neg = store_word(&conn, &word(0x53, 1))?.cid;
+        put_name(&conn, "word", "demo.math/sub", &sub)?;
+        put_name(&conn, "word", "demo.more/add", &add)?;
+        put_name(&conn, "word", "demo.math/neg", &neg)?;
+
+        let iface = IfaceCanon {
+            names: vec![
+                parse_iface_spec("add(i64, i64) -> i64")?,
+                parse_iface_spec("twice(i64) -> i64")?,
+                parse_iface_spec("show(i64) -> text")?,
+            ],
+        };
+        let cid = iface::store_iface(&conn, &iface)?.cid;
+        put_name(&conn, "iface", "demo.math", &cid)?;
+
+        let lines = resolve_symbols(&conn, &iface::load_iface(&conn, &cid)?)?;
+        assert!(lines.contains(&format!("add -> demo.more/add ({})", cid::to_hex(&add))));
+        assert!(lines.contains(&format!("twice -> demo.math/neg ({})", cid::to_hex(&neg))));
+        assert!(lines.contains(&"show -> no matching word".to_string()));
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use anyhow::Result;
+use rusqlite::Connection;
+
+use march5::{cid, list_names_with_kind, open_store};
+
+pub(crate) fn cmd_list(
+    store: &Path,
+    scope: &str,
+    prefix: Option<&str>,
+    kind: Option<&str>,
+) -> Result<()> {
+    let conn = open_store(store)?;
+    let lines = kinded_lines(&conn, scope, prefix, kind)?;
+    if lines.is_empty() {
+        println!("no `{scope}` names registered");
+    }
+    for line in lines {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// `name -> cid (kind)` lines; names whose object is missing, or whose kind
+/// differs from the scope, are flagged.
+fn kinded_lines(
+    conn: &Connection,
+    scope: &str,
+    prefix: Option<&str>,
+    kind: Option<&str>,
+) -> Result<Vec<String>> {
+    Ok(list_names_with_kind(conn, scope, prefix, kind)?
+        .into_iter()
+        .map(|entry| {
+            let cid_hex = cid::to_hex(&entry.cid);
+            match entry.kind.as_deref() {
+                None => format!("{} -> {cid_hex} (missing object!)", entry.name),
+                Some(found) if found != scope => {
+                    format!("{} -> {cid_hex} ({found}, not {scope}!)", entry.name)
+                }
+                Some(found) => format!("{} -> {cid_hex} ({found})", entry.name),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::db;
+
+    #[test]
+    fn mismatched_kinds_are_flagged_or_filtered() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let word = cid::compute(b"word");
+        let prim = cid::compute(b"prim");
+        db::put_object(&conn, &word, "word", b"word")?;
+        db::put_object(&conn, &prim, "prim", b"prim")?;
+        db::put_name(&conn, "word", "demo/real", &word)?;
+        db::put_name(&conn, "word", "demo/stray", &prim)?;
+
+        assert_eq!(
+            kinded_lines(&conn, "word", None, None)?,
+            vec![
+                format!("demo/real -> {} (word)", cid::to_hex(&word)),
+                format!("demo/stray -> {} (prim, not word!)", cid::to_hex(&prim)),
+            ]
+        );
+        assert_eq!(
+            kinded_lines(&conn, "word", None, Some("word"))?,
+            vec![format!("demo/real -> {} (word)", cid::to_hex(&word))]
+        );
+        Ok(())
+    }
+}
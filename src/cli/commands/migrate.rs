@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use march5::db;
+
+pub(crate) fn cmd_migrate(store: &Path) -> Result<()> {
+    let outcome = db::migrate_store(store)?;
+    if outcome.from == outcome.to {
+        println!("schema already at version {}", outcome.to);
+    } else {
+        println!(
+            "migrated schema from version {} to {}",
+            outcome.from, outcome.to
+        );
+    }
+    if outcome.ifaces_reencoded > 0 {
+        println!(
+            "re-encoded {} interface(s); their names now point at the new CIDs",
+            outcome.ifaces_reencoded
+        );
+    }
+    if outcome.namespaces_reencoded > 0 {
+        println!(
+            "re-encoded {} namespace(s); their names now point at the new CIDs",
+            outcome.namespaces_reencoded
+        );
+    }
+    Ok(())
+}
@@ -1,55 +1,145 @@
+use std::io::Read;
 use std::path::Path;
+use std::time::{Duration, Instant};
 
 use anyhow::{Result, anyhow, bail};
 
 mod builder;
+mod bundle;
 mod catalog;
+mod catalog_export;
+mod cbor;
 mod effect;
 mod guard;
 mod iface;
+mod list;
+mod migrate;
 mod namespace;
 mod new;
 mod node;
 mod prim;
+mod schema;
 mod state;
+mod stats;
+mod types;
 mod util;
 mod word;
 
 pub(crate) use builder::cmd_builder;
-pub(crate) use catalog::cmd_catalog;
+pub(crate) use bundle::{cmd_export, cmd_import};
+pub(crate) use catalog::{cmd_catalog, cmd_catalog_export};
+pub(crate) use cbor::{cmd_cbor, cmd_show_encoded};
 pub(crate) use effect::cmd_effect;
 pub(crate) use guard::cmd_guard;
 pub(crate) use iface::cmd_iface;
+pub(crate) use list::cmd_list;
+pub(crate) use migrate::cmd_migrate;
 pub(crate) use namespace::cmd_namespace;
 pub(crate) use new::cmd_new;
 pub(crate) use node::cmd_node;
 pub(crate) use prim::cmd_prim;
+pub(crate) use schema::cmd_schema;
 pub(crate) use state::cmd_state;
+pub(crate) use stats::cmd_stats;
+pub(crate) use types::cmd_types;
 pub(crate) use word::cmd_word;
 
+use node::node_edges;
 pub(crate) use util::{list_scope, parse_cli_value, require_store_path, show_named_object};
+use util::{lookup_named_cid, store_report};
 
+use march5::exec;
+use march5::global_store;
 use march5::inet;
 use march5::yaml;
-use march5::{cid, get_name, open_store, put_name, run_word};
+use march5::{Value, get_name, open_store, put_name, run_word};
+use rusqlite::Connection;
 
 pub(crate) fn cmd_run(
     store: &Path,
     name: &str,
     args: &[String],
     args_yaml: Option<&Path>,
+    args_stdin: bool,
+    transactional: bool,
+    repeat: u32,
 ) -> Result<()> {
+    if repeat == 0 {
+        bail!("--repeat must be at least 1");
+    }
     let conn = open_store(store)?;
     let word_cid =
         get_name(&conn, "word", name)?.ok_or_else(|| anyhow!("word `{name}` not found"))?;
-    let arg_values = if let Some(path) = args_yaml {
+    let arg_values = if args_stdin {
+        read_yaml_args(std::io::stdin().lock())?
+    } else if let Some(path) = args_yaml {
         yaml::parse_values_from_file(path)?
     } else {
         args.iter()
             .map(|s| parse_cli_value(s))
             .collect::<Result<Vec<_>>>()?
     };
-    let outputs = run_word(&conn, &word_cid, &arg_values)?;
+    let run = || -> Result<()> {
+        let (outputs, elapsed) = run_repeated(&conn, &word_cid, &arg_values, repeat)?;
+        print_outputs(&outputs);
+        if repeat > 1 {
+            println!(
+                "ran {repeat} times in {elapsed:?} ({:?} per run, jit {})",
+                elapsed / repeat,
+                jit_status()
+            );
+        }
+        Ok(())
+    };
+    if transactional {
+        global_store::with_transaction(run)
+    } else {
+        run()
+    }
+}
+
+/// Parse a YAML argument sequence (as for `--args-yaml`) from `reader`.
+fn read_yaml_args(mut reader: impl Read) -> Result<Vec<Value>> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    yaml::parse_values_from_str(&input)
+}
+
+fn jit_status() -> &'static str {
+    if !exec::jit_enabled() {
+        "disabled"
+    } else if exec::jit_available() {
+        "enabled"
+    } else {
+        "unavailable"
+    }
+}
+
+/// Evaluate a word `repeat` times and return the last outputs with the total time.
+///
+/// Every `run_word` call starts from a fresh evaluation context, so node results
+/// and descriptor memos never carry over between iterations; global state does.
+pub(crate) fn run_repeated(
+    conn: &Connection,
+    word_cid: &[u8; 32],
+    args: &[Value],
+    repeat: u32,
+) -> Result<(Vec<Value>, Duration)> {
+    let start = Instant::now();
+    let mut outputs = Vec::new();
+    for _ in 0..repeat {
+        outputs = run_word(conn, word_cid, args)?;
+    }
+    Ok((outputs, start.elapsed()))
+}
+
+/// Run a word by CID and print its outputs (`()` for none, a tuple for several).
+pub(crate) fn run_and_print(conn: &Connection, word_cid: &[u8; 32], args: &[Value]) -> Result<()> {
+    print_outputs(&run_word(conn, word_cid, args)?);
+    Ok(())
+}
+
+fn print_outputs(outputs: &[Value]) {
     match outputs.len() {
         0 => println!("()"),
         1 => println!("{}", outputs[0]),
@@ -62,10 +152,9 @@ pub(crate) fn cmd_run(
             println!("({body})");
         }
     }
-    Ok(())
 }
 
-pub(crate) fn cmd_agent(store: &Path, command: super::AgentCommand) -> Result<()> {
+pub(crate) fn cmd_agent(store: &Path, command: super::AgentCommand, compact: bool) -> Result<()> {
     match command {
         super::AgentCommand::Add {
             name,
@@ -87,7 +176,10 @@ pub(crate) fn cmd_agent(store: &Path, command: super::AgentCommand) -> Result<()
             if let Some(n) = name {
                 put_name(&conn, "agent", &n, &out.cid)?;
             }
-            println!("stored agent `{kind}` with cid {}", cid::to_hex(&out.cid));
+            println!(
+                "{}",
+                store_report(&format!("agent `{kind}`"), &out.cid, out.inserted)
+            );
         }
         super::AgentCommand::List { prefix } => {
             let conn = open_store(store)?;
@@ -95,13 +187,60 @@ pub(crate) fn cmd_agent(store: &Path, command: super::AgentCommand) -> Result<()
         }
         super::AgentCommand::Show { name } => {
             let conn = open_store(store)?;
-            show_named_object(&conn, "agent", "agent", &name)?;
+            show_named_object(&conn, "agent", "agent", &name, compact)?;
+        }
+        super::AgentCommand::Dot { agents, wires } => {
+            let conn = open_store(store)?;
+            let net = build_agent_net(&conn, &agents, &wires)?;
+            print!("{}", inet::net_to_dot(&net));
         }
     }
     Ok(())
 }
 
-pub(crate) fn cmd_rule(store: &Path, command: super::RuleCommand) -> Result<()> {
+fn build_agent_net(conn: &Connection, agents: &[String], wires: &[String]) -> Result<inet::Net> {
+    let mut net = inet::Net::new();
+    for name in agents {
+        let agent_cid = lookup_named_cid(conn, "agent", name)?;
+        let info = inet::load_agent_info(conn, &agent_cid)?;
+        let ports: Vec<&str> = info.ports.iter().map(|s| s.as_str()).collect();
+        net.add_agent(&info.kind, &ports);
+    }
+    for spec in wires {
+        let Some((left, right)) = spec.split_once('=') else {
+            bail!("invalid wire `{spec}`; expected I.PORT=J.PORT");
+        };
+        let a = resolve_wire_end(&net, left.trim())?;
+        let b = resolve_wire_end(&net, right.trim())?;
+        net.connect(a, b)
+            .map_err(|err| anyhow!("cannot wire `{spec}`: {err}"))?;
+    }
+    Ok(net)
+}
+
+fn resolve_wire_end(net: &inet::Net, end: &str) -> Result<inet::PortRef> {
+    let Some((index, port)) = end.split_once('.') else {
+        bail!("invalid wire endpoint `{end}`; expected INDEX.PORT");
+    };
+    let index: usize = index
+        .parse()
+        .map_err(|_| anyhow!("invalid agent index in `{end}`"))?;
+    let agent = net
+        .agents
+        .get(index)
+        .ok_or_else(|| anyhow!("agent index {index} out of range"))?;
+    let port = agent
+        .port_names
+        .iter()
+        .position(|name| name == port)
+        .ok_or_else(|| anyhow!("agent `{}` has no port `{port}`", agent.kind))?;
+    Ok(inet::PortRef {
+        agent: inet::AgentId(index),
+        port,
+    })
+}
+
+pub(crate) fn cmd_rule(store: &Path, command: super::RuleCommand, compact: bool) -> Result<()> {
     match command {
         super::RuleCommand::Add {
             name,
@@ -115,13 +254,14 @@ pub(crate) fn cmd_rule(store: &Path, command: super::RuleCommand) -> Result<()>
                 lhs_b: &lhs_b,
                 body_syntax: &rewire,
             };
+            inet::validate_rule(&conn, &rule)?;
             let out = inet::store_rule(&conn, &rule)?;
             if let Some(n) = name {
                 put_name(&conn, "rule", &n, &out.cid)?;
             }
             println!(
-                "stored rule `({lhs_a} {lhs_b})` with cid {}",
-                cid::to_hex(&out.cid)
+                "{}",
+                store_report(&format!("rule `({lhs_a} {lhs_b})`"), &out.cid, out.inserted)
             );
         }
         super::RuleCommand::List { prefix } => {
@@ -130,8 +270,91 @@ pub(crate) fn cmd_rule(store: &Path, command: super::RuleCommand) -> Result<()>
         }
         super::RuleCommand::Show { name } => {
             let conn = open_store(store)?;
-            show_named_object(&conn, "rule", "rule", &name)?;
+            show_named_object(&conn, "rule", "rule", &name, compact)?;
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::prim::{self, PrimCanon};
+    use march5::types::{EffectDomain, effect_mask};
+    use march5::{GraphBuilder, TypeTag, cid, db, metrics};
+
+    #[test]
+    fn stdin_yaml_args_reach_the_word() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let pair = [TypeTag::I64, TypeTag::I64];
+        let one = [TypeTag::I64];
+        let spec = PrimCanon {
+            params: &pair,
+            results: &one,
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let sub = prim::store_prim(&conn, &spec)?.cid;
+        put_name(&conn, "prim", "core/sub_i64", &sub)?;
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&pair)?;
+        builder.apply_prim(sub)?;
+        let word = builder.finish_word(&pair, &one, None)?;
+
+        let args = read_yaml_args(std::io::Cursor::new("- !i64 10\n- 4\n"))?;
+        assert_eq!(args, vec![Value::I64(10), Value::I64(4)]);
+        assert_eq!(run_word(&conn, &word, &args)?, vec![Value::I64(6)]);
+        Ok(())
+    }
+
+    #[test]
+    fn repeat_runs_the_word_each_time() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let mut prims = Vec::new();
+        for (name, params, results) in [
+            (
+                "metric.incr",
+                vec![TypeTag::Ptr, TypeTag::I64],
+                vec![TypeTag::Unit],
+            ),
+            ("metric.read", vec![TypeTag::Ptr], vec![TypeTag::I64]),
+        ] {
+            let spec = PrimCanon {
+                params: &params,
+                results: &results,
+                effects: &[],
+                effect_mask: effect_mask::METRIC,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            };
+            let cid = prim::store_prim(&conn, &spec)?.cid;
+            put_name(&conn, "prim", name, &cid)?;
+            prims.push(cid);
+        }
+        // A key no other test touches, since metrics are process-global.
+        let key = [0x5A; 32];
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        builder.push_lit_i64(1)?;
+        builder.apply_prim(prims[0])?;
+        builder.drop()?;
+        builder.quote(key)?;
+        builder.apply_prim(prims[1])?;
+        let word = builder.finish_word(&[], &[TypeTag::I64], None)?;
+
+        let (outputs, _) = run_repeated(&conn, &word, &[], 3)?;
+        assert_eq!(
+            outputs,
+            vec![Value::Token(Some(EffectDomain::Metric)), Value::I64(3)]
+        );
+        assert_eq!(metrics::read(&cid::to_hex(&key)), 3);
+        Ok(())
+    }
+}
@@ -1,14 +1,18 @@
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
+use rusqlite::Connection;
 
-use super::util::{list_scope, parse_cid_list, parse_exports, show_named_object};
+use super::run_and_print;
+use super::util::{
+    list_scope, lookup_named_cid, parse_cid_list, parse_cli_value, parse_exports, show_named_object,
+};
 use crate::cli::NamespaceCommand;
 use march5::iface;
-use march5::namespace::{self, NamespaceCanon, NamespaceExport};
-use march5::{cid, open_store, put_name};
+use march5::namespace::{self, NamespaceCanon, NamespaceExport, NamespaceStoreOutcome};
+use march5::{cid, db, list_names_for_cid, open_store, put_name};
 
-pub(crate) fn cmd_namespace(store: &Path, command: NamespaceCommand) -> Result<()> {
+pub(crate) fn cmd_namespace(store: &Path, command: NamespaceCommand, compact: bool) -> Result<()> {
     match command {
         NamespaceCommand::Add {
             name,
@@ -20,30 +24,9 @@ pub(crate) fn cmd_namespace(store: &Path, command: NamespaceCommand) -> Result<(
             let conn = open_store(store)?;
             let imports = parse_cid_list(imports.iter().map(|s| s.as_str()))?;
             let export_pairs = parse_exports(&exports)?;
-            let iface_cid = if let Some(iface_hex) = iface {
-                cid::from_hex(&iface_hex)?
-            } else {
-                let iface_canon = iface::derive_from_exports(&conn, &export_pairs)?;
-                iface::store_iface(&conn, &iface_canon)?.cid
-            };
-            let exports = export_pairs
-                .iter()
-                .map(|(name, word_cid)| NamespaceExport {
-                    name: name.clone(),
-                    word: *word_cid,
-                })
-                .collect();
-            let ns = NamespaceCanon {
-                imports,
-                exports,
-                iface: iface_cid,
-            };
-            let outcome = namespace::store_namespace(&conn, &ns)?;
-            if !no_register {
-                if let Some(name) = &name {
-                    put_name(&conn, "namespace", name, &outcome.cid)?;
-                }
-            }
+            let iface_cid = iface.as_deref().map(cid::from_hex).transpose()?;
+            let register = if no_register { None } else { name.as_deref() };
+            let outcome = add_namespace(&conn, register, iface_cid, imports, &export_pairs)?;
             let cid_hex = cid::to_hex(&outcome.cid);
             if outcome.inserted {
                 println!("stored namespace with cid {cid_hex}");
@@ -51,7 +34,20 @@ pub(crate) fn cmd_namespace(store: &Path, command: NamespaceCommand) -> Result<(
                 println!("namespace already present with cid {cid_hex}");
             }
         }
-        NamespaceCommand::List { prefix } => {
+        NamespaceCommand::List {
+            prefix,
+            exports: true,
+        } => {
+            let conn = open_store(store)?;
+            let lines = namespace_export_lines(&conn, prefix.as_deref())?;
+            if lines.is_empty() {
+                println!("no namespaces registered");
+            }
+            for line in lines {
+                println!("{line}");
+            }
+        }
+        NamespaceCommand::List { prefix, .. } => {
             let conn = open_store(store)?;
             list_scope(
                 &conn,
@@ -62,8 +58,268 @@ pub(crate) fn cmd_namespace(store: &Path, command: NamespaceCommand) -> Result<(
         }
         NamespaceCommand::Show { name } => {
             let conn = open_store(store)?;
-            show_named_object(&conn, "namespace", "namespace", &name)?;
+            show_named_object(&conn, "namespace", "namespace", &name, compact)?;
+        }
+        NamespaceCommand::Resolve {
+            namespace,
+            symbol,
+            run,
+            args,
+        } => {
+            let conn = open_store(store)?;
+            let word_cid = resolve_export(&conn, &namespace, &symbol)?;
+            if run {
+                let arg_values = args
+                    .iter()
+                    .map(|s| parse_cli_value(s))
+                    .collect::<Result<Vec<_>>>()?;
+                run_and_print(&conn, &word_cid, &arg_values)?;
+            } else {
+                let names = list_names_for_cid(&conn, "word", &word_cid)?;
+                if names.is_empty() {
+                    println!("{}", cid::to_hex(&word_cid));
+                } else {
+                    println!("{} ({})", cid::to_hex(&word_cid), names.join(", "));
+                }
+            }
+        }
+        NamespaceCommand::Verify { name } => {
+            let conn = open_store(store)?;
+            let ns_cid = lookup_named_cid(&conn, "namespace", &name)?;
+            let problems = export_problems(&conn, &ns_cid)?;
+            for problem in &problems {
+                println!("{problem}");
+            }
+            if !problems.is_empty() {
+                bail!("namespace `{name}` has {} bad export(s)", problems.len());
+            }
+            println!("namespace `{name}`: all exports resolve to words");
         }
     }
     Ok(())
 }
+
+/// One line per export whose CID is missing from the store or names a non-word object.
+pub(crate) fn export_problems(conn: &Connection, ns_cid: &[u8; 32]) -> Result<Vec<String>> {
+    let ns = namespace::load_namespace(conn, ns_cid)?;
+    let mut problems = Vec::new();
+    for export in &ns.exports {
+        let word_hex = cid::to_hex(&export.word);
+        match db::load_object_cbor(conn, &export.word) {
+            Ok((kind, _)) if kind == "word" => {}
+            Ok((kind, _)) => problems.push(format!(
+                "{}: {word_hex} has kind `{kind}`, not `word`",
+                export.name
+            )),
+            Err(_) => problems.push(format!("{}: dangling export {word_hex}", export.name)),
+        }
+    }
+    Ok(problems)
+}
+
+/// Store a namespace, deriving (and storing) its interface from the exports when
+/// `iface` is omitted. With `register`, the namespace is named under the
+/// `namespace` scope and a derived interface under the `iface` scope.
+pub(crate) fn add_namespace(
+    conn: &Connection,
+    register: Option<&str>,
+    iface: Option<[u8; 32]>,
+    imports: Vec<[u8; 32]>,
+    export_pairs: &[(String, [u8; 32])],
+) -> Result<NamespaceStoreOutcome> {
+    let iface_cid = match iface {
+        Some(iface_cid) => iface_cid,
+        None => {
+            let iface_canon = iface::derive_from_exports(conn, export_pairs)?;
+            let derived = iface::store_iface(conn, &iface_canon)?.cid;
+            if let Some(name) = register {
+                put_name(conn, "iface", name, &derived)?;
+            }
+            derived
+        }
+    };
+    let exports = export_pairs
+        .iter()
+        .map(|(name, word_cid)| NamespaceExport {
+            name: name.clone(),
+            word: *word_cid,
+        })
+        .collect();
+    let ns = NamespaceCanon {
+        imports,
+        exports,
+        iface: iface_cid,
+    };
+    let outcome = namespace::store_namespace(conn, &ns)?;
+    if let Some(name) = register {
+        put_name(conn, "namespace", name, &outcome.cid)?;
+    }
+    Ok(outcome)
+}
+
+/// Render `name -> cid` lines for registered namespaces, each followed by its
+/// exports as indented `alias -> word` lines.
+pub(crate) fn namespace_export_lines(
+    conn: &Connection,
+    prefix: Option<&str>,
+) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for entry in db::list_names(conn, "namespace", prefix)? {
+        lines.push(format!("{} -> {}", entry.name, cid::to_hex(&entry.cid)));
+        let ns = namespace::load_namespace(conn, &entry.cid)?;
+        for row in namespace::export_rows(conn, &ns)? {
+            let word_hex = cid::to_hex(&row.word);
+            match &row.word_name {
+                Some(word_name) => {
+                    lines.push(format!("  {} -> {word_name} ({word_hex})", row.alias))
+                }
+                None => lines.push(format!("  {} -> {word_hex}", row.alias)),
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// Look up `symbol` among the exports of the namespace named (or CID-addressed) `namespace`.
+pub(crate) fn resolve_export(conn: &Connection, namespace: &str, symbol: &str) -> Result<[u8; 32]> {
+    let ns_cid = lookup_named_cid(conn, "namespace", namespace)?;
+    let ns = namespace::load_namespace(conn, &ns_cid)?;
+    ns.exports
+        .iter()
+        .find(|export| export.name == symbol)
+        .map(|export| export.word)
+        .ok_or_else(|| anyhow!("namespace `{namespace}` does not export `{symbol}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::get_name;
+    use march5::iface::{IfaceCanon, IfaceSymbol};
+    use march5::types::effect_mask;
+    use march5::word::{WordCanon, store_word};
+
+    #[test]
+    fn add_without_iface_derives_one_from_exports() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let io_effect = [0x55u8; 32];
+        let word = WordCanon {
+            root: [0x44u8; 32],
+            params: vec!["i64".to_string()],
+            results: vec!["unit".to_string()],
+            effects: vec![io_effect],
+            effect_mask: effect_mask::IO,
+            guards: Vec::new(),
+            doc: None,
+        };
+        let word_cid = store_word(&conn, &word)?.cid;
+        let exports = vec![("print".to_string(), word_cid)];
+
+        let outcome = add_namespace(&conn, Some("demo.io"), None, Vec::new(), &exports)?;
+        assert_eq!(get_name(&conn, "namespace", "demo.io")?, Some(outcome.cid));
+
+        let iface_cid = get_name(&conn, "iface", "demo.io")?.expect("derived iface registered");
+        let expected = IfaceCanon {
+            names: vec![IfaceSymbol {
+                name: "print".to_string(),
+                params: vec!["i64".to_string()],
+                results: vec!["unit".to_string()],
+                effects: vec![io_effect],
+            }],
+        };
+        let (kind, cbor) = db::load_object_cbor(&conn, &iface_cid)?;
+        assert_eq!(kind, "iface");
+        assert_eq!(cbor, iface::encode(&expected));
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_finds_the_exported_word() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let word_cid = [0x66u8; 32];
+        let exports = vec![("answer".to_string(), word_cid)];
+        let iface_cid = Some([0x77u8; 32]);
+        add_namespace(&conn, Some("demo.ns"), iface_cid, Vec::new(), &exports)?;
+
+        assert_eq!(resolve_export(&conn, "demo.ns", "answer")?, word_cid);
+        assert!(resolve_export(&conn, "demo.ns", "missing").is_err());
+        assert!(resolve_export(&conn, "demo.other", "answer").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn export_listing_expands_aliases() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let add_cid = [0x21u8; 32];
+        let sub_cid = [0x22u8; 32];
+        put_name(&conn, "word", "demo/add", &add_cid)?;
+        let exports = vec![
+            ("plus".to_string(), add_cid),
+            ("minus".to_string(), sub_cid),
+        ];
+        let outcome = add_namespace(
+            &conn,
+            Some("demo.math"),
+            Some([0x23u8; 32]),
+            Vec::new(),
+            &exports,
+        )?;
+
+        let lines = namespace_export_lines(&conn, None)?;
+        assert_eq!(
+            lines,
+            vec![
+                format!("demo.math -> {}", cid::to_hex(&outcome.cid)),
+                format!("  minus -> {}", cid::to_hex(&sub_cid)),
+                format!("  plus -> demo/add ({})", cid::to_hex(&add_cid)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn verify_flags_dangling_and_mistyped_exports() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let word = WordCanon {
+            root: [0x31u8; 32],
+            params: Vec::new(),
+            results: vec!["i64".to_string()],
+            effects: Vec::new(),
+            effect_mask: effect_mask::NONE,
+            guards: Vec::new(),
+            doc: None,
+        };
+        let word_cid = store_word(&conn, &word)?.cid;
+        let effect_cid = march5::effect::store_effect(
+            &conn,
+            &march5::EffectCanon {
+                name: "io",
+                doc: None,
+            },
+        )?
+        .cid;
+        let missing = [0x32u8; 32];
+        let exports = vec![
+            ("good".to_string(), word_cid),
+            ("gone".to_string(), missing),
+            ("odd".to_string(), effect_cid),
+        ];
+        let outcome = add_namespace(&conn, None, Some([0x33u8; 32]), Vec::new(), &exports)?;
+
+        assert_eq!(
+            export_problems(&conn, &outcome.cid)?,
+            vec![
+                format!("gone: dangling export {}", cid::to_hex(&missing)),
+                format!(
+                    "odd: {} has kind `effect`, not `word`",
+                    cid::to_hex(&effect_cid)
+                ),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1,27 +1,34 @@
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
+use rusqlite::Connection;
+use serde_cbor::Value as CborValue;
 
 use super::util::{parse_cid_list, parse_inputs};
 use crate::cli::NodeCommand;
-use march5::node::{self, NodeCanon, NodeKind, NodePayload};
-use march5::{cid, open_store};
+use march5::cid::{self, CidEncoding};
+use march5::node::{self, NodeCanon, NodeInfo, NodeInput, NodeKind, NodePayload};
+use march5::{TypeTag, open_store};
 
 pub(crate) fn cmd_node(store: &Path, command: NodeCommand) -> Result<()> {
     let conn = open_store(store)?;
     let outcome = match command {
+        NodeCommand::Show {
+            cid: cid_text,
+            base32,
+        } => {
+            let node_cid = cid::parse(&cid_text)?;
+            let encoding = if base32 {
+                CidEncoding::Base32
+            } else {
+                CidEncoding::Hex
+            };
+            print!("{}", describe_node(&conn, &node_cid, encoding)?);
+            return Ok(());
+        }
         NodeCommand::Lit { ty, value, effects } => {
             let effects = parse_cid_list(effects.iter().map(|s| s.as_str()))?;
-            let node = NodeCanon {
-                kind: NodeKind::Lit,
-                out: vec![ty],
-                inputs: Vec::new(),
-                vals: Vec::new(),
-                deps: Vec::new(),
-                effects,
-                payload: NodePayload::LitI64(value),
-            };
-            node::store_node(&conn, &node)?
+            node::store_node(&conn, &lit_node(&ty, value, effects)?)?
         }
         NodeCommand::Prim {
             ty,
@@ -103,3 +110,280 @@ pub(crate) fn cmd_node(store: &Path, command: NodeCommand) -> Result<()> {
     }
     Ok(())
 }
+
+/// Render a stored node as a human-readable multi-line summary.
+pub(crate) fn describe_node(
+    conn: &Connection,
+    node_cid: &[u8; 32],
+    enc: CidEncoding,
+) -> Result<String> {
+    let info = node::load_node_info(conn, node_cid)?;
+    let mut out = String::new();
+    out.push_str(&format!("cid: {}\n", enc.encode(node_cid)));
+    out.push_str(&format!("kind: {}\n", info.kind.name()));
+    out.push_str(&format!("out: [{}]\n", info.out.join(", ")));
+    out.push_str(&format!("inputs: [{}]\n", format_inputs(&info.inputs, enc)));
+    let effects = info
+        .effects
+        .iter()
+        .map(|cid| enc.encode(cid))
+        .collect::<Vec<_>>()
+        .join(", ");
+    out.push_str(&format!("effects: [{effects}]\n"));
+    out.push_str(&format!(
+        "payload: {}\n",
+        render_payload(info.kind, &info.payload, enc)?
+    ));
+    Ok(out)
+}
+
+/// Every node a node consumes: its inputs plus edges held in the payload
+/// (RETURN vals/deps, IF/GUARD continuations, DISPATCH targets).
+pub(crate) fn node_edges(info: &NodeInfo) -> Result<Vec<NodeInput>> {
+    let mut edges = info.inputs.clone();
+    match info.kind {
+        NodeKind::Return => {
+            let items = payload_array(&info.payload, 2)?;
+            edges.extend(payload_inputs(&items[0])?);
+            edges.extend(payload_inputs(&items[1])?);
+        }
+        NodeKind::If => {
+            for item in payload_array(&info.payload, 2)? {
+                edges.push(payload_input(item)?);
+            }
+        }
+        NodeKind::Guard => {
+            for item in &payload_array(&info.payload, 3)?[1..] {
+                edges.push(payload_input(item)?);
+            }
+        }
+        NodeKind::Dispatch => {
+            let CborValue::Array(cases) = &info.payload else {
+                bail!("DISPATCH payload must be an array");
+            };
+            for case in cases {
+                edges.push(payload_input(&payload_array(case, 4)?[1])?);
+            }
+        }
+        _ => {}
+    }
+    Ok(edges)
+}
+
+fn format_input(input: &NodeInput, enc: CidEncoding) -> String {
+    format!("{}:{}", enc.encode(&input.cid), input.port)
+}
+
+fn format_inputs(inputs: &[NodeInput], enc: CidEncoding) -> String {
+    inputs
+        .iter()
+        .map(|input| format_input(input, enc))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn render_payload(kind: NodeKind, payload: &CborValue, enc: CidEncoding) -> Result<String> {
+    let rendered = match kind {
+        NodeKind::Lit => match payload {
+            CborValue::Integer(value) => format!("i64 {value}"),
+            other => bail!("LIT payload must be an integer, found {other:?}"),
+        },
+        NodeKind::Prim => format!("prim {}", payload_cid(payload, enc)?),
+        NodeKind::Call => format!("word {}", payload_cid(payload, enc)?),
+        NodeKind::Arg => match payload {
+            CborValue::Integer(index) => format!("index {index}"),
+            other => bail!("ARG payload must be an integer, found {other:?}"),
+        },
+        NodeKind::LoadGlobal => format!("global {}", payload_cid(payload, enc)?),
+        NodeKind::Quote => format!("quote {}", payload_cid(payload, enc)?),
+        NodeKind::Return => {
+            let items = payload_array(payload, 2)?;
+            format!(
+                "vals [{}] deps [{}]",
+                format_inputs(&payload_inputs(&items[0])?, enc),
+                format_inputs(&payload_inputs(&items[1])?, enc)
+            )
+        }
+        NodeKind::Apply => {
+            let CborValue::Array(items) = payload else {
+                bail!("APPLY payload must be an array");
+            };
+            match items.as_slice() {
+                [qid] => format!("qid {}", payload_cid(qid, enc)?),
+                [qid, key] => format!(
+                    "qid {} type_key {}",
+                    payload_cid(qid, enc)?,
+                    payload_cid(key, enc)?
+                ),
+                _ => bail!("APPLY payload must have one or two entries"),
+            }
+        }
+        NodeKind::If => {
+            let items = payload_array(payload, 2)?;
+            format!(
+                "true {} false {}",
+                format_input(&payload_input(&items[0])?, enc),
+                format_input(&payload_input(&items[1])?, enc)
+            )
+        }
+        NodeKind::Guard => {
+            let items = payload_array(payload, 3)?;
+            format!(
+                "type_key {} match {} else {}",
+                payload_cid(&items[0], enc)?,
+                format_input(&payload_input(&items[1])?, enc),
+                format_input(&payload_input(&items[2])?, enc)
+            )
+        }
+        NodeKind::Dispatch => {
+            let CborValue::Array(cases) = payload else {
+                bail!("DISPATCH payload must be an array");
+            };
+            let mut parts = Vec::with_capacity(cases.len());
+            for case in cases {
+                let fields = payload_array(case, 4)?;
+                parts.push(format!(
+                    "-> {}",
+                    format_input(&payload_input(&fields[1])?, enc)
+                ));
+            }
+            format!("{} case(s) [{}]", cases.len(), parts.join(", "))
+        }
+        NodeKind::Pair | NodeKind::Unpair => match payload {
+            CborValue::Integer(arity) => format!("arity {arity}"),
+            _ => "(none)".to_string(),
+        },
+        NodeKind::Deopt => match payload {
+            CborValue::Array(items) => match items.first() {
+                Some(CborValue::Text(reason)) => format!("reason {reason:?}"),
+                _ => "(none)".to_string(),
+            },
+            _ => "(none)".to_string(),
+        },
+        NodeKind::Token => "(none)".to_string(),
+    };
+    Ok(rendered)
+}
+
+/// LIT node for `--ty`/`--value`; literal payloads are i64 only, so other types are refused.
+pub(crate) fn lit_node(ty: &str, value: i64, effects: Vec<[u8; 32]>) -> Result<NodeCanon> {
+    if ty != TypeTag::I64.as_atom() {
+        bail!("LIT value type `{ty}` not supported (literal payloads are i64)");
+    }
+    Ok(NodeCanon {
+        kind: NodeKind::Lit,
+        out: vec![ty.to_string()],
+        inputs: Vec::new(),
+        vals: Vec::new(),
+        deps: Vec::new(),
+        effects,
+        payload: NodePayload::LitI64(value),
+    })
+}
+
+fn payload_array(value: &CborValue, len: usize) -> Result<&[CborValue]> {
+    match value {
+        CborValue::Array(items) if items.len() == len => Ok(items),
+        other => bail!("expected {len}-element payload array, found {other:?}"),
+    }
+}
+
+fn payload_cid(value: &CborValue, enc: CidEncoding) -> Result<String> {
+    match value {
+        CborValue::Bytes(bytes) => Ok(enc.encode(&cid::from_slice(bytes)?)),
+        other => bail!("expected CID bytes in payload, found {other:?}"),
+    }
+}
+
+fn payload_input(value: &CborValue) -> Result<NodeInput> {
+    let items = payload_array(value, 2)?;
+    let CborValue::Bytes(bytes) = &items[0] else {
+        bail!("node input must start with CID bytes");
+    };
+    let CborValue::Integer(port) = items[1] else {
+        bail!("node input port must be an integer");
+    };
+    Ok(NodeInput {
+        cid: cid::from_slice(bytes)?,
+        port: u32::try_from(port).map_err(|_| anyhow!("node input port {port} out of range"))?,
+    })
+}
+
+fn payload_inputs(value: &CborValue) -> Result<Vec<NodeInput>> {
+    match value {
+        CborValue::Array(items) => items.iter().map(payload_input).collect(),
+        other => bail!("expected input list in payload, found {other:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::db;
+
+    #[test]
+    fn lit_rejects_non_i64_types() -> Result<()> {
+        let err = lit_node("text", 5, Vec::new()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("LIT value type `text` not supported"),
+            "{err}"
+        );
+        assert!(lit_node("f64", 5, Vec::new()).is_err());
+        assert_eq!(lit_node("i64", 5, Vec::new())?.out, vec!["i64".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn describe_prim_node_lists_kind_and_inputs() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let lit = NodeCanon {
+            kind: NodeKind::Lit,
+            out: vec!["i64".to_string()],
+            inputs: Vec::new(),
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::LitI64(7),
+        };
+        let lit_cid = node::store_node(&conn, &lit)?.cid;
+        let prim_cid = [0x11u8; 32];
+        let prim = NodeCanon {
+            kind: NodeKind::Prim,
+            out: vec!["i64".to_string()],
+            inputs: vec![
+                NodeInput {
+                    cid: lit_cid,
+                    port: 0,
+                },
+                NodeInput {
+                    cid: lit_cid,
+                    port: 0,
+                },
+            ],
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::Prim(prim_cid),
+        };
+        let node_cid = node::store_node(&conn, &prim)?.cid;
+
+        let rendered = describe_node(&conn, &node_cid, CidEncoding::Hex)?;
+        let lit_hex = cid::to_hex(&lit_cid);
+        assert!(rendered.contains("kind: PRIM"));
+        assert!(rendered.contains("out: [i64]"));
+        assert!(rendered.contains(&format!("inputs: [{lit_hex}:0, {lit_hex}:0]")));
+        assert!(rendered.contains(&format!("payload: prim {}", cid::to_hex(&prim_cid))));
+
+        let lit_rendered = describe_node(&conn, &lit_cid, CidEncoding::Hex)?;
+        assert!(lit_rendered.contains("kind: LIT"));
+        assert!(lit_rendered.contains("payload: i64 7"));
+
+        let short = describe_node(&conn, &node_cid, CidEncoding::Base32)?;
+        assert!(short.contains(&format!("cid: {}", cid::to_base32(&node_cid))));
+        assert!(short.contains(&format!("payload: prim {}", cid::to_base32(&prim_cid))));
+        Ok(())
+    }
+}
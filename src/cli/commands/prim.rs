@@ -1,11 +1,15 @@
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use rusqlite::Connection;
 
-use super::util::{parse_cid_list, parse_effect_mask_flags, parse_type_tags};
+use super::util::{
+    format_mask, format_types, list_scope, lookup_named_cid, parse_effect_mask_flags,
+    parse_type_tags, resolve_effects, store_report,
+};
 use crate::cli::PrimCommand;
 use march5::prim::{self, PrimCanon};
-use march5::types::effect_mask;
+use march5::types::{TypeTag, effect_mask};
 use march5::{cid, open_store, put_name};
 
 pub(crate) fn cmd_prim(store: &Path, command: PrimCommand) -> Result<()> {
@@ -16,12 +20,26 @@ pub(crate) fn cmd_prim(store: &Path, command: PrimCommand) -> Result<()> {
             results,
             effects,
             emask,
+            doc,
+            variadic,
+            builtin,
             no_register,
         } => {
             let conn = open_store(store)?;
             let param_tags = parse_type_tags(&params)?;
             let result_tags = parse_type_tags(&results)?;
-            let effect_cids = parse_cid_list(effects.iter().map(|s| s.as_str()))?;
+            let variadic_tag = variadic.as_deref().map(TypeTag::from_atom).transpose()?;
+            let builtin = match builtin {
+                Some(id) => Some(
+                    prim::BUILTINS
+                        .iter()
+                        .copied()
+                        .find(|known| *known == id)
+                        .ok_or_else(|| anyhow!("unknown built-in `{id}`"))?,
+                ),
+                None => prim::builtin_id(&name),
+            };
+            let effect_cids = resolve_effects(&conn, &effects)?;
             let mut effect_mask_value = parse_effect_mask_flags(&emask)?;
             if effect_mask_value == effect_mask::NONE && !effect_cids.is_empty() {
                 effect_mask_value = effect_mask::IO;
@@ -31,18 +49,208 @@ pub(crate) fn cmd_prim(store: &Path, command: PrimCommand) -> Result<()> {
                 results: &result_tags,
                 effects: effect_cids.as_slice(),
                 effect_mask: effect_mask_value,
+                doc: doc.as_deref(),
+                variadic: variadic_tag,
+                builtin,
             };
             let outcome = prim::store_prim(&conn, &spec)?;
             if !no_register {
                 put_name(&conn, "prim", &name, &outcome.cid)?;
             }
-            let cid_hex = cid::to_hex(&outcome.cid);
-            if outcome.inserted {
-                println!("stored prim `{name}` with cid {cid_hex}");
-            } else {
-                println!("prim `{name}` already present with cid {cid_hex}");
-            }
+            println!(
+                "{}",
+                store_report(&format!("prim `{name}`"), &outcome.cid, outcome.inserted)
+            );
+        }
+        PrimCommand::List { prefix } => {
+            let conn = open_store(store)?;
+            list_scope(&conn, "prim", prefix.as_deref(), "no prims registered")?;
+        }
+        PrimCommand::Show { name } => {
+            let conn = open_store(store)?;
+            println!("{}", describe_prim(&conn, &name)?);
         }
     }
     Ok(())
 }
+
+/// Render a primitive's signature, effect CIDs, and effect-mask domains.
+pub(crate) fn describe_prim(conn: &Connection, name: &str) -> Result<String> {
+    let prim_cid = lookup_named_cid(conn, "prim", name)?;
+    let info = prim::load_prim_info(conn, &prim_cid)?;
+    let mut lines = vec![
+        format!("prim {name}"),
+        format!("cid: {}", cid::to_hex(&prim_cid)),
+        format!("params: {}", format_types(&info.params)),
+        format!("results: {}", format_types(&info.results)),
+    ];
+    if let Some(tag) = info.variadic {
+        lines.push(format!("variadic: {}...", tag.as_atom()));
+    }
+    if let Some(builtin) = &info.builtin {
+        lines.push(format!("builtin: {builtin}"));
+    }
+    if let Some(doc) = &info.doc {
+        lines.push(format!("doc: {doc}"));
+    }
+    if info.effects.is_empty() {
+        lines.push("effects: none".to_string());
+    } else {
+        lines.push("effects:".to_string());
+        lines.extend(
+            info.effects
+                .iter()
+                .map(|effect| format!("  {}", cid::to_hex(effect))),
+        );
+    }
+    lines.push(format!("effect_mask: {}", format_mask(info.effect_mask)));
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::db;
+    use march5::types::TypeTag;
+
+    #[test]
+    fn storing_the_same_prim_twice_reports_dedup() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let params = [TypeTag::I64];
+        let spec = PrimCanon {
+            params: &params,
+            results: &params,
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let first = prim::store_prim(&conn, &spec)?;
+        let second = prim::store_prim(&conn, &spec)?;
+        assert_eq!(first.cid, second.cid);
+        let cid_hex = cid::to_hex(&first.cid);
+        assert_eq!(
+            store_report("prim `demo/id`", &first.cid, first.inserted),
+            format!("stored prim `demo/id` with cid {cid_hex} (new)")
+        );
+        assert_eq!(
+            store_report("prim `demo/id`", &second.cid, second.inserted),
+            format!("prim `demo/id` already present with cid {cid_hex} (dedup)")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn add_accepts_registered_effect_names() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("prims.march5.db");
+        march5::create_store(&path)?;
+        let conn = open_store(&path)?;
+        let io = march5::effect::store_effect(
+            &conn,
+            &march5::EffectCanon {
+                name: "demo/io",
+                doc: None,
+            },
+        )?
+        .cid;
+        put_name(&conn, "effect", "demo/io", &io)?;
+
+        cmd_prim(
+            &path,
+            PrimCommand::Add {
+                name: "demo/print".to_string(),
+                params: vec!["i64".to_string()],
+                results: Vec::new(),
+                effects: vec!["demo/io".to_string()],
+                emask: Vec::new(),
+                doc: None,
+                variadic: None,
+                builtin: None,
+                no_register: false,
+            },
+        )?;
+        let prim_cid = lookup_named_cid(&conn, "prim", "demo/print")?;
+        let info = prim::load_prim_info(&conn, &prim_cid)?;
+        assert_eq!(info.effects, vec![io]);
+        assert_eq!(info.effect_mask, effect_mask::IO);
+
+        let missing = PrimCommand::Add {
+            name: "demo/bad".to_string(),
+            params: Vec::new(),
+            results: Vec::new(),
+            effects: vec!["demo/nope".to_string()],
+            emask: Vec::new(),
+            doc: None,
+            variadic: None,
+            builtin: None,
+            no_register: false,
+        };
+        assert!(cmd_prim(&path, missing).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn add_records_the_builtin_named_by_the_prim() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("prims.march5.db");
+        march5::create_store(&path)?;
+        let add = |name: &str, builtin: Option<&str>| PrimCommand::Add {
+            name: name.to_string(),
+            params: vec!["i64".to_string()],
+            results: vec!["i64".to_string()],
+            effects: Vec::new(),
+            emask: Vec::new(),
+            doc: None,
+            variadic: None,
+            builtin: builtin.map(str::to_string),
+            no_register: false,
+        };
+        cmd_prim(&path, add("core/neg_i64", None))?;
+        cmd_prim(&path, add("core/abs_i64", None))?;
+        cmd_prim(&path, add("demo/magnitude", Some("abs_i64")))?;
+        assert!(cmd_prim(&path, add("demo/bad", Some("nope"))).is_err());
+
+        let conn = open_store(&path)?;
+        let neg = lookup_named_cid(&conn, "prim", "core/neg_i64")?;
+        let abs = lookup_named_cid(&conn, "prim", "core/abs_i64")?;
+        assert_ne!(neg, abs);
+        assert_eq!(lookup_named_cid(&conn, "prim", "demo/magnitude")?, abs);
+        assert!(describe_prim(&conn, "core/abs_i64")?.contains("builtin: abs_i64"));
+        Ok(())
+    }
+
+    #[test]
+    fn show_reports_state_write_domain() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let params = [TypeTag::Ptr, TypeTag::I64];
+        let spec = PrimCanon {
+            params: &params,
+            results: &[],
+            effects: &[],
+            effect_mask: effect_mask::STATE_WRITE,
+            doc: Some("store an i64 under a key"),
+            variadic: None,
+            builtin: None,
+        };
+        let prim_cid = prim::store_prim(&conn, &spec)?.cid;
+        put_name(&conn, "prim", "state.write_i64", &prim_cid)?;
+
+        let shown = describe_prim(&conn, "state.write_i64")?;
+        assert!(shown.contains("params: (ptr, i64)"), "{shown}");
+        assert!(shown.contains("results: ()"), "{shown}");
+        assert!(shown.contains("effects: none"), "{shown}");
+        assert!(shown.contains("doc: store an i64 under a key"), "{shown}");
+        assert!(
+            shown.contains(&format!(
+                "effect_mask: {:#x} [state]",
+                effect_mask::STATE_WRITE
+            )),
+            "{shown}"
+        );
+        Ok(())
+    }
+}
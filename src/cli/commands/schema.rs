@@ -0,0 +1,199 @@
+//! Static descriptions of each object kind's canonical CBOR layout.
+//!
+//! These mirror the `encode` functions in the library; keep them in step when
+//! an encoding changes.
+
+use anyhow::{Result, bail};
+use serde_json::{Value as JsonValue, json};
+
+use super::util::json_text;
+
+pub(crate) fn cmd_schema(kind: &str, compact: bool) -> Result<()> {
+    println!("{}", json_text(&object_schema(kind)?, compact)?);
+    Ok(())
+}
+
+/// Object kinds with a published layout.
+const KINDS: &[&str] = &["prim", "word", "node", "iface", "namespace", "effect"];
+
+fn field(name: &str, ty: &str, note: &str) -> JsonValue {
+    if note.is_empty() {
+        json!({ "name": name, "type": ty })
+    } else {
+        json!({ "name": name, "type": ty, "note": note })
+    }
+}
+
+fn tag_field(tag: u32) -> JsonValue {
+    json!({ "name": "tag", "type": "uint", "value": tag })
+}
+
+/// A trailing slot that is only encoded when present.
+fn optional_field(name: &str, ty: &str, note: &str) -> JsonValue {
+    let mut field = field(name, ty, note);
+    field["optional"] = json!(true);
+    field
+}
+
+fn array_schema(kind: &str, tag: u32, fields: Vec<JsonValue>) -> JsonValue {
+    let required = fields
+        .iter()
+        .filter(|f| f.get("optional").is_none())
+        .count();
+    let mut schema = json!({
+        "kind": kind,
+        "tag": tag,
+        "encoding": "array",
+        "length": required,
+        "fields": fields,
+    });
+    if required != fields.len() {
+        schema["max_length"] = json!(fields.len());
+    }
+    schema
+}
+
+/// JSON description of `kind`'s field order, field types and object tag.
+pub(crate) fn object_schema(kind: &str) -> Result<JsonValue> {
+    let input = "[bytes(32) cid, uint port]";
+    Ok(match kind {
+        "prim" => array_schema(
+            "prim",
+            0,
+            vec![
+                tag_field(0),
+                field("root", "bytes(32)", "reserved; always zero"),
+                field("params", "array<text>", "type atoms"),
+                field("results", "array<text>", "type atoms"),
+                field("effects", "array<bytes(32)>", "effect CIDs, sorted"),
+                field(
+                    "effect_mask",
+                    "uint",
+                    "bit flags: io=1, state.read=2, state.write=4, test=8, metric=16",
+                ),
+                optional_field(
+                    "doc",
+                    "text",
+                    "omitted when absent; null if a later slot is set",
+                ),
+                optional_field(
+                    "variadic",
+                    "text",
+                    "type atom of zero or more trailing arguments; null if only builtin is set",
+                ),
+                optional_field("builtin", "text", "runner built-in id, e.g. abs_i64"),
+            ],
+        ),
+        "word" => array_schema(
+            "word",
+            1,
+            vec![
+                tag_field(1),
+                field("root", "bytes(32)", "RETURN node CID"),
+                field("params", "array<text>", "type atoms"),
+                field("results", "array<text>", "type atoms"),
+                field("effects", "array<bytes(32)>", "effect CIDs, sorted"),
+                field("effect_mask", "uint", "bit flags, as for prim"),
+                field("guards", "array<bytes(32)>", "guard CIDs, sorted"),
+                optional_field("doc", "text", "omitted when absent"),
+            ],
+        ),
+        "node" => {
+            let mut schema = array_schema(
+                "node",
+                6,
+                vec![
+                    tag_field(6),
+                    field("kind", "uint", "node kind tag, see `payloads`"),
+                    field("inputs", &format!("array<{input}>"), "in consumption order"),
+                    field("out", "array<text>", "output type atoms; tokens first"),
+                    field("effects", "array<bytes(32)>", "effect CIDs, sorted"),
+                    field("payload", "any", "depends on kind"),
+                ],
+            );
+            schema["payloads"] = json!([
+                { "kind": 0, "name": "LIT", "payload": "int" },
+                { "kind": 1, "name": "PRIM", "payload": "bytes(32) prim CID" },
+                { "kind": 2, "name": "CALL", "payload": "bytes(32) word CID" },
+                { "kind": 3, "name": "ARG", "payload": "uint parameter index" },
+                { "kind": 4, "name": "LOAD_GLOBAL", "payload": "bytes(32) key" },
+                { "kind": 5, "name": "RETURN", "payload": format!("[vals: array<{input}>, deps: array<{input}> sorted]") },
+                { "kind": 6, "name": "PAIR", "payload": "uint arity, or [] for a binary pair" },
+                { "kind": 7, "name": "UNPAIR", "payload": "uint arity, or [] for a binary pair" },
+                { "kind": 8, "name": "QUOTE", "payload": "bytes(32) quoted CID" },
+                { "kind": 9, "name": "APPLY", "payload": "[bytes(32) qid] or [bytes(32) qid, bytes(32) type key]" },
+                { "kind": 10, "name": "IF", "payload": format!("[true: {input}, false: {input}]") },
+                { "kind": 11, "name": "TOKEN", "payload": "[]" },
+                { "kind": 12, "name": "GUARD", "payload": format!("[bytes(32) type key, match: {input}, else: {input}]") },
+                { "kind": 13, "name": "DEOPT", "payload": "[] or [text reason]" },
+                { "kind": 14, "name": "DISPATCH", "payload": format!("array<[array<bytes(32)> type keys, target: {input}, guard inputs: array<{input}>, array<bytes(32)> guard CIDs]>") },
+            ]);
+            schema
+        }
+        "iface" => array_schema(
+            "iface",
+            3,
+            vec![
+                tag_field(3),
+                field(
+                    "symbols",
+                    "array<[text name, array<text> params, array<text> results, array<bytes(32)> effect CIDs]>",
+                    "sorted by name; effect CIDs sorted",
+                ),
+            ],
+        ),
+        "namespace" => array_schema(
+            "namespace",
+            4,
+            vec![
+                tag_field(4),
+                field("iface", "bytes(32)", "interface CID"),
+                field(
+                    "imports",
+                    "array<bytes(32)>",
+                    "imported interface CIDs, sorted",
+                ),
+                field(
+                    "exports",
+                    "array<[text name, bytes(32) word CID]>",
+                    "sorted by name",
+                ),
+            ],
+        ),
+        "effect" => json!({
+            "kind": "effect",
+            "encoding": "map",
+            "fields": [
+                { "name": "kind", "type": "text", "value": "effect" },
+                field("name", "text", ""),
+                field("doc", "text", "optional; omitted from the map when absent"),
+            ],
+        }),
+        other => bail!(
+            "no schema for object kind `{other}` (known: {})",
+            KINDS.join(", ")
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prim_schema_is_a_tagged_six_element_array() -> Result<()> {
+        let schema = object_schema("prim")?;
+        assert_eq!(schema["encoding"], "array");
+        assert_eq!(schema["length"], 6);
+        assert_eq!(schema["max_length"], 9);
+        assert_eq!(schema["tag"], 0);
+        assert_eq!(schema["fields"][0]["value"], 0);
+        assert_eq!(schema["fields"][2]["name"], "params");
+
+        for kind in KINDS {
+            object_schema(kind)?;
+        }
+        assert!(object_schema("widget").is_err());
+        Ok(())
+    }
+}
@@ -1,10 +1,11 @@
 use std::path::Path;
 
 use anyhow::{Result, anyhow};
+use rusqlite::Connection;
 
 use super::util::{list_scope, require_store_path};
 use crate::cli::StateCommand;
-use march5::global_store::{self, store_snapshot};
+use march5::global_store::{self, GlobalStoreSnapshot, store_snapshot};
 use march5::{cid, get_name, open_store, put_name};
 
 pub(crate) fn cmd_state(store: Option<&Path>, command: StateCommand) -> Result<()> {
@@ -50,6 +51,113 @@ pub(crate) fn cmd_state(store: Option<&Path>, command: StateCommand) -> Result<(
             let conn = open_store(store_path)?;
             list_scope(&conn, "gstate", prefix.as_deref(), "no saved snapshots")?;
         }
+        StateCommand::Keys { prefix } => {
+            let lines = format_keys(&global_store::snapshot(), prefix.as_deref());
+            if lines.is_empty() {
+                println!("(no keys)");
+            }
+            for line in lines {
+                println!("{line}");
+            }
+        }
+        StateCommand::Diff { a, b } => {
+            let store_path = require_store_path(store)?;
+            let conn = open_store(store_path)?;
+            let lines = diff_snapshots(&load_named(&conn, &a)?, &load_named(&conn, &b)?);
+            if lines.is_empty() {
+                println!("snapshots `{a}` and `{b}` are identical");
+            }
+            for line in lines {
+                println!("{line}");
+            }
+        }
     }
     Ok(())
 }
+
+fn load_named(conn: &Connection, name: &str) -> Result<GlobalStoreSnapshot> {
+    let cid =
+        get_name(conn, "gstate", name)?.ok_or_else(|| anyhow!("snapshot `{name}` not found"))?;
+    global_store::load_snapshot(conn, &cid)
+}
+
+/// Key-ordered differences from `a` to `b`: `- key = old`, `+ key = new`, `~ key: old -> new`.
+fn diff_snapshots(a: &GlobalStoreSnapshot, b: &GlobalStoreSnapshot) -> Vec<String> {
+    let (a, b) = (a.entries(), b.entries());
+    let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter_map(|key| match (a.get(key), b.get(key)) {
+            (Some(old), None) => Some(format!("- {key} = {old}")),
+            (None, Some(new)) => Some(format!("+ {key} = {new}")),
+            (Some(old), Some(new)) if old != new => Some(format!("~ {key}: {old} -> {new}")),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Render `key : type = value` lines for keys matching `prefix`.
+fn format_keys(snapshot: &GlobalStoreSnapshot, prefix: Option<&str>) -> Vec<String> {
+    snapshot
+        .iter()
+        .filter(|(key, _)| prefix.is_none_or(|p| key.starts_with(p)))
+        .map(|(key, value)| format!("{key} : {} = {value}", value.type_name()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::Value;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn format_keys_filters_by_prefix() {
+        let mut entries = BTreeMap::new();
+        entries.insert("demo.counter".to_string(), Value::I64(3));
+        entries.insert("demo.label".to_string(), Value::Text("hi".to_string()));
+        entries.insert("other.flag".to_string(), Value::Unit);
+        let snapshot = GlobalStoreSnapshot::from_entries(entries);
+
+        assert_eq!(
+            format_keys(&snapshot, Some("demo.")),
+            vec![
+                "demo.counter : i64 = 3".to_string(),
+                "demo.label : text = \"hi\"".to_string(),
+            ]
+        );
+        assert_eq!(format_keys(&snapshot, None).len(), 3);
+        assert!(format_keys(&snapshot, Some("missing")).is_empty());
+    }
+
+    #[test]
+    fn diff_reports_the_changed_key() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        march5::db::install_schema(&conn)?;
+
+        let mut entries = BTreeMap::new();
+        entries.insert("demo.counter".to_string(), Value::I64(1));
+        entries.insert("demo.label".to_string(), Value::Text("hi".to_string()));
+        let before = GlobalStoreSnapshot::from_entries(entries.clone());
+        entries.insert("demo.counter".to_string(), Value::I64(2));
+        let after = GlobalStoreSnapshot::from_entries(entries);
+        for (name, snapshot) in [("before", &before), ("after", &after)] {
+            let outcome = store_snapshot(&conn, snapshot)?;
+            put_name(&conn, "gstate", name, &outcome.cid)?;
+        }
+
+        let lines = diff_snapshots(&load_named(&conn, "before")?, &load_named(&conn, "after")?);
+        assert_eq!(lines, vec!["~ demo.counter: 1 -> 2".to_string()]);
+
+        let empty = GlobalStoreSnapshot::default();
+        assert_eq!(
+            diff_snapshots(&empty, &before),
+            vec![
+                "+ demo.counter = 1".to_string(),
+                "+ demo.label = \"hi\"".to_string(),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use march5::{StoreStats, open_store, store_stats};
+
+pub(crate) fn cmd_stats(store: &Path) -> Result<()> {
+    let conn = open_store(store)?;
+    for line in stats_lines(&store_stats(&conn)?) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+fn stats_lines(stats: &StoreStats) -> Vec<String> {
+    let mut lines = Vec::new();
+    let objects: i64 = stats.objects_by_kind.iter().map(|(_, n)| n).sum();
+    lines.push(format!(
+        "objects: {objects} ({} distinct CIDs)",
+        stats.distinct_cids
+    ));
+    for (kind, count) in &stats.objects_by_kind {
+        lines.push(format!("  {kind}: {count}"));
+    }
+    let names: i64 = stats.names_by_scope.iter().map(|(_, n)| n).sum();
+    lines.push(format!(
+        "names: {names} (pointing at {} distinct CIDs)",
+        stats.named_cids
+    ));
+    for (scope, count) in &stats.names_by_scope {
+        lines.push(format!("  {scope}: {count}"));
+    }
+    lines
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+
+use super::util::EFFECT_MASK_FLAGS;
+use march5::TypeTag;
+
+pub(crate) fn cmd_types() -> Result<()> {
+    print!("{}", types_listing());
+    Ok(())
+}
+
+/// Type atoms accepted by `--param`/`--result`/`--ty`, then `--emask` domain names.
+pub(crate) fn types_listing() -> String {
+    let mut out = String::from("type atoms:\n");
+    for tag in TypeTag::all() {
+        out.push_str(&format!("  {}\n", tag.as_atom()));
+    }
+    out.push_str("effect domains:\n");
+    for (name, _) in EFFECT_MASK_FLAGS {
+        out.push_str(&format!("  {name}\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listing_covers_types_and_domains() {
+        let listing = types_listing();
+        let lines: Vec<&str> = listing.lines().map(str::trim).collect();
+        for expected in ["i64", "ptr", "token", "io", "state.write"] {
+            assert!(
+                lines.contains(&expected),
+                "missing `{expected}`:\n{listing}"
+            );
+        }
+    }
+}
@@ -2,14 +2,24 @@ use std::path::Path;
 
 use anyhow::{Result, anyhow, bail};
 use rusqlite::Connection;
-use serde::Deserialize;
-use serde_json;
+use serde::{Deserialize, Serialize};
 
+use crate::cli::OutputFormat;
 use march5::db;
 use march5::node::NodeInput;
-use march5::types::{EffectMask, effect_mask};
+use march5::types::{EffectMask, effect_domains, effect_mask, render_comma_list};
 use march5::{TypeTag, Value, cid, get_name, load_object_cbor};
 
+/// Describe a store outcome, making content-addressed dedup visible.
+pub(crate) fn store_report(what: &str, cid: &[u8; 32], inserted: bool) -> String {
+    let cid_hex = cid::to_hex(cid);
+    if inserted {
+        format!("stored {what} with cid {cid_hex} (new)")
+    } else {
+        format!("{what} already present with cid {cid_hex} (dedup)")
+    }
+}
+
 pub(crate) fn require_store_path(path: Option<&Path>) -> Result<&Path> {
     match path {
         Some(p) => Ok(p),
@@ -17,8 +27,13 @@ pub(crate) fn require_store_path(path: Option<&Path>) -> Result<&Path> {
     }
 }
 
+/// Parse type atoms given one per entry; an entry may also be a comma-separated list.
 pub(crate) fn parse_type_tags(entries: &[String]) -> Result<Vec<TypeTag>> {
-    entries.iter().map(|s| TypeTag::from_atom(s)).collect()
+    let mut tags = Vec::new();
+    for entry in entries {
+        tags.extend(TypeTag::parse_comma_list(entry)?);
+    }
+    Ok(tags)
 }
 
 pub(crate) fn parse_exports(entries: &[String]) -> Result<Vec<(String, [u8; 32])>> {
@@ -37,6 +52,14 @@ pub(crate) fn parse_exports(entries: &[String]) -> Result<Vec<(String, [u8; 32])
     Ok(out)
 }
 
+/// Resolve `--effect` values that may be hex CIDs or names in the `effect` scope.
+pub(crate) fn resolve_effects(conn: &Connection, entries: &[String]) -> Result<Vec<[u8; 32]>> {
+    entries
+        .iter()
+        .map(|entry| lookup_named_cid(conn, "effect", entry))
+        .collect()
+}
+
 pub(crate) fn parse_cid_list<'a, I>(entries: I) -> Result<Vec<[u8; 32]>>
 where
     I: IntoIterator<Item = &'a str>,
@@ -64,27 +87,62 @@ pub(crate) fn parse_inputs(entries: &[String]) -> Result<Vec<NodeInput>> {
     Ok(inputs)
 }
 
+/// Effect-mask domain names accepted by `--emask`; `_` or `-` may stand in for `.`.
+pub(crate) const EFFECT_MASK_FLAGS: &[(&str, EffectMask)] = &[
+    ("io", effect_mask::IO),
+    ("state", effect_mask::STATE_READ | effect_mask::STATE_WRITE),
+    ("state.read", effect_mask::STATE_READ),
+    ("state.write", effect_mask::STATE_WRITE),
+    ("test", effect_mask::TEST),
+    ("metric", effect_mask::METRIC),
+];
+
 pub(crate) fn parse_effect_mask_flags(entries: &[String]) -> Result<EffectMask> {
     let mut mask = effect_mask::NONE;
     for entry in entries {
-        let flag = entry.trim().to_ascii_lowercase();
+        let flag = entry.trim().to_ascii_lowercase().replace(['_', '-'], ".");
         if flag.is_empty() {
             continue;
         }
-        match flag.as_str() {
-            "io" => mask |= effect_mask::IO,
-            "state" => mask |= effect_mask::STATE_READ | effect_mask::STATE_WRITE,
-            "state.read" | "state_read" | "state-read" => mask |= effect_mask::STATE_READ,
-            "state.write" | "state_write" | "state-write" => mask |= effect_mask::STATE_WRITE,
-            "test" => mask |= effect_mask::TEST,
-            "metric" => mask |= effect_mask::METRIC,
-            other => bail!("unknown effect mask domain `{other}`"),
-        }
+        let Some((_, bits)) = EFFECT_MASK_FLAGS.iter().find(|(name, _)| *name == flag) else {
+            bail!("unknown effect mask domain `{}`", entry.trim());
+        };
+        mask |= bits;
     }
     Ok(mask)
 }
 
+/// Parse a `--arg` literal.
+///
+/// A known `tag:` prefix (`i64`, `f64`, `text`, `unit`, `quote`) forces the
+/// value type, mirroring the YAML tags; anything else falls back to guessing
+/// i64, then f64, then text.
 pub(crate) fn parse_cli_value(token: &str) -> Result<Value> {
+    if let Some((tag, rest)) = token.split_once(':') {
+        match tag {
+            "i64" => {
+                let number = rest
+                    .parse::<i64>()
+                    .map_err(|err| anyhow!("invalid i64 literal `{rest}`: {err}"))?;
+                return Ok(Value::I64(number));
+            }
+            "f64" => {
+                let number = rest
+                    .parse::<f64>()
+                    .map_err(|err| anyhow!("invalid f64 literal `{rest}`: {err}"))?;
+                return Ok(Value::F64(number));
+            }
+            "text" => return Ok(Value::Text(rest.to_string())),
+            "unit" => {
+                if !rest.is_empty() {
+                    bail!("unit literal takes no payload, found `{rest}`");
+                }
+                return Ok(Value::Unit);
+            }
+            "quote" => return Ok(Value::Quote(cid::from_hex(rest)?)),
+            _ => {}
+        }
+    }
     if token == "~" || token.eq_ignore_ascii_case("null") {
         return Ok(Value::Unit);
     }
@@ -103,19 +161,32 @@ pub(crate) fn list_scope(
     prefix: Option<&str>,
     empty_msg: &str,
 ) -> Result<()> {
-    let entries = db::list_names(conn, scope, prefix)?;
-    if entries.is_empty() {
+    let lines = scope_lines(conn, scope, prefix)?;
+    if lines.is_empty() {
         println!("{empty_msg}");
         return Ok(());
     }
 
-    for entry in entries {
-        println!("{} -> {}", entry.name, cid::to_hex(&entry.cid));
+    for line in lines {
+        println!("{line}");
     }
 
     Ok(())
 }
 
+/// Render `name -> cid` lines for every entry in `scope`.
+pub(crate) fn scope_lines(
+    conn: &Connection,
+    scope: &str,
+    prefix: Option<&str>,
+) -> Result<Vec<String>> {
+    let entries = db::list_names(conn, scope, prefix)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| format!("{} -> {}", entry.name, cid::to_hex(&entry.cid)))
+        .collect())
+}
+
 pub(crate) fn parse_iface_spec(spec: &str) -> Result<march5::iface::IfaceSymbol> {
     let mut parts = spec.splitn(2, '|');
     let sig_part = parts
@@ -138,15 +209,16 @@ pub(crate) fn parse_iface_spec(spec: &str) -> Result<march5::iface::IfaceSymbol>
         parse_cid_list(effect_tokens.iter().copied())?
     };
 
+    let atoms = |tags: Vec<TypeTag>| tags.iter().map(|tag| tag.as_atom().to_string()).collect();
     Ok(march5::iface::IfaceSymbol {
         name,
-        params,
-        results,
+        params: atoms(params),
+        results: atoms(results),
         effects,
     })
 }
 
-pub(crate) fn parse_signature(spec: &str) -> Result<(String, Vec<String>, Vec<String>)> {
+pub(crate) fn parse_signature(spec: &str) -> Result<(String, Vec<TypeTag>, Vec<TypeTag>)> {
     let spec = spec.trim();
     let open_paren = spec
         .find('(')
@@ -173,35 +245,45 @@ pub(crate) fn parse_signature(spec: &str) -> Result<(String, Vec<String>, Vec<St
     Ok((name.to_string(), params, results))
 }
 
-pub(crate) fn parse_type_list(spec: &str) -> Result<Vec<String>> {
-    let mut s = spec.trim();
-    if s.is_empty() {
-        return Ok(Vec::new());
-    }
+/// Parse `i64, f64` or `(i64, f64)`; blank input (or `()`) is an empty list.
+pub(crate) fn parse_type_list(spec: &str) -> Result<Vec<TypeTag>> {
+    let trimmed = spec.trim();
+    let list = match trimmed.strip_prefix('(') {
+        Some(inner) => inner
+            .strip_suffix(')')
+            .ok_or_else(|| anyhow!("unmatched parentheses in type list `{spec}`"))?,
+        None => trimmed,
+    };
+    TypeTag::parse_comma_list(list)
+}
 
-    if s.starts_with('(') {
-        if !s.ends_with(')') {
-            bail!("unmatched parentheses in type list `{spec}`");
-        }
-        s = &s[1..s.len() - 1];
-    }
+/// Render a type list as `(a, b)`.
+pub(crate) fn format_types(types: &[TypeTag]) -> String {
+    format!("({})", render_comma_list(types))
+}
 
-    let mut types = Vec::new();
-    for part in s.split(',') {
-        let ty = part.trim();
-        if ty.is_empty() {
-            continue;
-        }
-        types.push(ty.to_string());
-    }
-    Ok(types)
+/// Render a mask as hex followed by its domain names.
+pub(crate) fn format_mask(mask: EffectMask) -> String {
+    let domains = effect_domains(mask)
+        .iter()
+        .map(|domain| domain.as_str())
+        .collect::<Vec<_>>();
+    format!("{mask:#x} [{}]", domains.join(", "))
+}
+
+/// JSON text for `value`: indented by default, a single line under `--compact`.
+pub(crate) fn json_text<T: Serialize + ?Sized>(value: &T, compact: bool) -> Result<String> {
+    Ok(if compact {
+        serde_json::to_string(value)?
+    } else {
+        serde_json::to_string_pretty(value)?
+    })
 }
 
-pub(crate) fn cbor_to_pretty_json(bytes: &[u8]) -> Result<String> {
+pub(crate) fn cbor_to_json(bytes: &[u8], compact: bool) -> Result<String> {
     let mut deserializer = serde_cbor::Deserializer::from_slice(bytes);
     let value = serde_cbor::Value::deserialize(&mut deserializer)?;
-    let json = serde_json::to_string_pretty(&value)?;
-    Ok(json)
+    json_text(&value, compact)
 }
 
 pub(crate) fn show_named_object(
@@ -209,12 +291,76 @@ pub(crate) fn show_named_object(
     scope: &str,
     label: &str,
     name: &str,
+    compact: bool,
 ) -> Result<()> {
+    println!("{}", named_object_json(conn, scope, label, name, compact)?);
+    Ok(())
+}
+
+/// JSON for the object registered as `name` in `scope`.
+pub(crate) fn named_object_json(
+    conn: &Connection,
+    scope: &str,
+    label: &str,
+    name: &str,
+    compact: bool,
+) -> Result<String> {
     let cid = get_name(conn, scope, name)?.ok_or_else(|| anyhow!("{label} `{name}` not found"))?;
     let (_kind, cbor) = load_object_cbor(conn, &cid)?;
-    let json = cbor_to_pretty_json(&cbor)?;
-    println!("{json}");
-    Ok(())
+    cbor_to_json(&cbor, compact)
+}
+
+/// The object registered as `name` (or with CID `name`) in `scope`, rendered in `format`.
+pub(crate) fn render_named_object(
+    conn: &Connection,
+    scope: &str,
+    label: &str,
+    name: &str,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<String> {
+    if format == OutputFormat::Json {
+        return named_object_json(conn, scope, label, name, compact);
+    }
+    let cid = lookup_named_cid(conn, scope, name)?;
+    let (_kind, cbor) = load_object_cbor(conn, &cid)?;
+    Ok(match format {
+        OutputFormat::CborHex => cbor.iter().map(|b| format!("{b:02x}")).collect(),
+        _ => cbor_diagnostic(&serde_cbor::from_slice(&cbor)?),
+    })
+}
+
+/// RFC 8949 diagnostic notation for a decoded CBOR item.
+pub(crate) fn cbor_diagnostic(value: &serde_cbor::Value) -> String {
+    use serde_cbor::Value as CborValue;
+    match value {
+        CborValue::Null => "null".to_string(),
+        CborValue::Bool(b) => b.to_string(),
+        CborValue::Integer(n) => n.to_string(),
+        CborValue::Float(x) if x.is_nan() => "NaN".to_string(),
+        CborValue::Float(x) if x.is_infinite() => {
+            if *x > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+        }
+        CborValue::Float(x) => format!("{x:?}"),
+        CborValue::Bytes(bytes) => {
+            let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+            format!("h'{hex}'")
+        }
+        CborValue::Text(text) => serde_json::to_string(text).unwrap_or_default(),
+        CborValue::Array(items) => {
+            let items: Vec<String> = items.iter().map(cbor_diagnostic).collect();
+            format!("[{}]", items.join(", "))
+        }
+        CborValue::Map(entries) => {
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|(k, v)| format!("{}: {}", cbor_diagnostic(k), cbor_diagnostic(v)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        CborValue::Tag(tag, inner) => format!("{tag}({})", cbor_diagnostic(inner)),
+        other => format!("{other:?}"),
+    }
 }
 
 pub(crate) fn lookup_named_cid(conn: &Connection, scope: &str, name: &str) -> Result<[u8; 32]> {
@@ -222,7 +368,71 @@ pub(crate) fn lookup_named_cid(conn: &Connection, scope: &str, name: &str) -> Re
         return Ok(cid);
     }
     if name.len() == 64 && name.chars().all(|c| c.is_ascii_hexdigit()) {
-        return Ok(cid::from_hex(name)?);
+        return cid::from_hex(name);
+    }
+    if let Ok(cid) = cid::from_base32(name) {
+        return Ok(cid);
     }
     bail!("{scope} `{name}` not found in name index")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cli_value_honours_type_prefixes() -> Result<()> {
+        assert!(matches!(parse_cli_value("i64:42")?, Value::I64(42)));
+        assert!(matches!(parse_cli_value("f64:3.5")?, Value::F64(f) if f == 3.5));
+        assert!(matches!(parse_cli_value("unit:")?, Value::Unit));
+        match parse_cli_value("text:hello")? {
+            Value::Text(text) => assert_eq!(text, "hello"),
+            other => panic!("expected text, got {other:?}"),
+        }
+        let cid = [0x5au8; 32];
+        match parse_cli_value(&format!("quote:{}", cid::to_hex(&cid)))? {
+            Value::Quote(parsed) => assert_eq!(parsed, cid),
+            other => panic!("expected quote, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_cli_value_text_prefix_keeps_numbers_as_text() -> Result<()> {
+        match parse_cli_value("text:42")? {
+            Value::Text(text) => assert_eq!(text, "42"),
+            other => panic!("expected text, got {other:?}"),
+        }
+        assert!(matches!(parse_cli_value("42")?, Value::I64(42)));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_cli_value_rejects_malformed_prefixed_literals() {
+        assert!(parse_cli_value("i64:abc").is_err());
+        assert!(parse_cli_value("unit:1").is_err());
+        assert!(parse_cli_value("quote:zz").is_err());
+    }
+
+    #[test]
+    fn parse_cli_value_unknown_prefix_falls_back_to_text() -> Result<()> {
+        match parse_cli_value("demo:value")? {
+            Value::Text(text) => assert_eq!(text, "demo:value"),
+            other => panic!("expected text, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_type_list_validates_atoms() -> Result<()> {
+        assert_eq!(
+            parse_type_list("(i64, f64)")?,
+            vec![TypeTag::I64, TypeTag::F64]
+        );
+        assert!(parse_type_list("()")?.is_empty());
+        assert!(parse_type_list("i64,,f64").is_err());
+        assert!(parse_type_list("i128").is_err());
+        assert!(parse_type_list("(i64").is_err());
+        Ok(())
+    }
+}
@@ -1,14 +1,21 @@
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow, bail};
+use rusqlite::Connection;
 
-use super::util::{list_scope, parse_cid_list, parse_effect_mask_flags, show_named_object};
+use super::node_edges;
+use super::util::{
+    format_mask, format_types, json_text, list_scope, lookup_named_cid, parse_effect_mask_flags,
+    resolve_effects, store_report,
+};
 use crate::cli::WordCommand;
-use march5::types::effect_mask;
-use march5::word::{self, WordCanon};
-use march5::{cid, get_name, open_store, put_name};
+use march5::node::{NodeInfo, NodeInput, load_node_info};
+use march5::types::{effect_domains, effect_mask};
+use march5::word::{self, WordCanon, WordInfo};
+use march5::{cid, delete_name, get_name, load_object_cbor, open_store, put_name};
 
-pub(crate) fn cmd_word(store: &Path, command: WordCommand) -> Result<()> {
+pub(crate) fn cmd_word(store: &Path, command: WordCommand, compact: bool) -> Result<()> {
     match command {
         WordCommand::Add {
             name,
@@ -18,11 +25,12 @@ pub(crate) fn cmd_word(store: &Path, command: WordCommand) -> Result<()> {
             effects,
             emask,
             guards,
+            doc,
             no_register,
         } => {
             let conn = open_store(store)?;
             let root_cid = cid::from_hex(&root)?;
-            let effect_cids = parse_cid_list(effects.iter().map(|s| s.as_str()))?;
+            let effect_cids = resolve_effects(&conn, &effects)?;
             let mut effect_mask_value = parse_effect_mask_flags(&emask)?;
             if effect_mask_value == effect_mask::NONE && !effect_cids.is_empty() {
                 effect_mask_value = effect_mask::IO;
@@ -44,19 +52,13 @@ pub(crate) fn cmd_word(store: &Path, command: WordCommand) -> Result<()> {
                 effects: effect_cids,
                 effect_mask: effect_mask_value,
                 guards: guard_cids,
+                doc,
             };
             let outcome = word::store_word(&conn, &word)?;
-            if !no_register {
-                if let Some(name) = &name {
-                    put_name(&conn, "word", name, &outcome.cid)?;
-                }
-            }
-            let cid_hex = cid::to_hex(&outcome.cid);
-            if outcome.inserted {
-                println!("stored word with cid {cid_hex}");
-            } else {
-                println!("word already present with cid {cid_hex}");
+            if !no_register && let Some(name) = &name {
+                put_name(&conn, "word", name, &outcome.cid)?;
             }
+            println!("{}", store_report("word", &outcome.cid, outcome.inserted));
         }
         WordCommand::List { prefix } => {
             let conn = open_store(store)?;
@@ -64,8 +66,354 @@ pub(crate) fn cmd_word(store: &Path, command: WordCommand) -> Result<()> {
         }
         WordCommand::Show { name } => {
             let conn = open_store(store)?;
-            show_named_object(&conn, "word", "word", &name)?;
+            println!("{}", word_show_json(&conn, &name, compact)?);
+        }
+        WordCommand::Diff { left, right } => {
+            let conn = open_store(store)?;
+            let left_info = word::load_word_info(&conn, &lookup_named_cid(&conn, "word", &left)?)?;
+            let right_info =
+                word::load_word_info(&conn, &lookup_named_cid(&conn, "word", &right)?)?;
+            let differences = diff_words(&left_info, &right_info);
+            if differences.is_empty() {
+                println!("words `{left}` and `{right}` have identical contracts");
+            } else {
+                for line in &differences {
+                    println!("{line}");
+                }
+                std::process::exit(1);
+            }
+        }
+        WordCommand::Graph { name, dot } => {
+            let conn = open_store(store)?;
+            let word_cid = lookup_named_cid(&conn, "word", &name)?;
+            let nodes = word_graph(&conn, &word_cid)?;
+            if dot {
+                print!("{}", graph_to_dot(&nodes));
+            } else {
+                for (node_cid, info, edges) in &nodes {
+                    println!("{}", graph_line(node_cid, info, edges));
+                }
+            }
+        }
+        WordCommand::Lint { name } => {
+            let conn = open_store(store)?;
+            let info = word::load_word_info(&conn, &lookup_named_cid(&conn, "word", &name)?)?;
+            let issues = word::effect_issues(&info);
+            if issues.is_empty() {
+                println!("word `{name}`: no issues");
+            } else {
+                for issue in &issues {
+                    println!("warning: word `{name}`: {issue}");
+                }
+                std::process::exit(1);
+            }
+        }
+        WordCommand::Rename { old, new } => {
+            let conn = open_store(store)?;
+            let cid = rename_word(&conn, &old, &new)?;
+            println!("renamed word `{old}` -> `{new}` ({})", cid::to_hex(&cid));
+        }
+        WordCommand::Alias { existing, name } => {
+            let conn = open_store(store)?;
+            let cid = alias_word(&conn, &existing, &name)?;
+            println!(
+                "aliased word `{name}` -> `{existing}` ({})",
+                cid::to_hex(&cid)
+            );
         }
     }
     Ok(())
 }
+
+/// JSON for a word's contract, with the effect mask also spelled out as domains
+/// and the canonical object kept under `canonical`.
+pub(crate) fn word_show_json(conn: &Connection, name: &str, compact: bool) -> Result<String> {
+    let word_cid =
+        get_name(conn, "word", name)?.ok_or_else(|| anyhow!("word `{name}` not found"))?;
+    let info = word::load_word_info(conn, &word_cid)?;
+    let (_kind, cbor) = load_object_cbor(conn, &word_cid)?;
+    let canonical: serde_cbor::Value = serde_cbor::from_slice(&cbor)?;
+    let hex_list = |cids: &[[u8; 32]]| cids.iter().map(cid::to_hex).collect::<Vec<_>>();
+    let atoms = |tags: &[march5::TypeTag]| tags.iter().map(|t| t.as_atom()).collect::<Vec<_>>();
+    let value = serde_json::json!({
+        "cid": cid::to_hex(&word_cid),
+        "root": cid::to_hex(&info.root),
+        "params": atoms(&info.params),
+        "results": atoms(&info.results),
+        "effects": hex_list(&info.effects),
+        "effect_mask": info.effect_mask,
+        "effect_domains": effect_domains(info.effect_mask)
+            .iter()
+            .map(|domain| domain.as_str())
+            .collect::<Vec<_>>(),
+        "guards": hex_list(&info.guards),
+        "doc": info.doc,
+        "canonical": canonical,
+    });
+    json_text(&value, compact)
+}
+
+type GraphNode = ([u8; 32], NodeInfo, Vec<NodeInput>);
+
+/// Nodes reachable from the word's root, root first, each listed once with the edges it consumes.
+pub(crate) fn word_graph(conn: &Connection, word_cid: &[u8; 32]) -> Result<Vec<GraphNode>> {
+    let info = word::load_word_info(conn, word_cid)?;
+    let mut seen = HashSet::new();
+    let mut pending = vec![info.root];
+    let mut nodes = Vec::new();
+    while let Some(node_cid) = pending.pop() {
+        if !seen.insert(node_cid) {
+            continue;
+        }
+        let node = load_node_info(conn, &node_cid)?;
+        let edges = node_edges(&node)?;
+        pending.extend(edges.iter().rev().map(|edge| edge.cid));
+        nodes.push((node_cid, node, edges));
+    }
+    Ok(nodes)
+}
+
+fn graph_line(node_cid: &[u8; 32], info: &NodeInfo, edges: &[NodeInput]) -> String {
+    let consumed = edges
+        .iter()
+        .map(|edge| format!("{}:{}", cid::to_hex(&edge.cid), edge.port))
+        .collect::<Vec<_>>();
+    format!(
+        "{} {} [{}] <- [{}]",
+        cid::to_hex(node_cid),
+        info.kind.name(),
+        info.out.join(", "),
+        consumed.join(", ")
+    )
+}
+
+fn graph_to_dot(nodes: &[GraphNode]) -> String {
+    let ids: HashMap<[u8; 32], usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(idx, (node_cid, _, _))| (*node_cid, idx))
+        .collect();
+    let mut out = String::from("digraph word {\n");
+    for (idx, (node_cid, info, _)) in nodes.iter().enumerate() {
+        out.push_str(&format!(
+            "  n{idx} [label=\"{}\\n{}\\n{}\"];\n",
+            info.kind.name(),
+            info.out.join(", "),
+            &cid::to_hex(node_cid)[..12]
+        ));
+    }
+    for (idx, (_, _, edges)) in nodes.iter().enumerate() {
+        for edge in edges {
+            out.push_str(&format!(
+                "  n{} -> n{idx} [label=\"{}\"];\n",
+                ids[&edge.cid], edge.port
+            ));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Point `new` at the word registered as `old`, then drop `old`.
+pub(crate) fn rename_word(conn: &Connection, old: &str, new: &str) -> Result<[u8; 32]> {
+    let tx = conn.unchecked_transaction()?;
+    let cid = alias_word(&tx, old, new)?;
+    if old != new {
+        delete_name(&tx, "word", old)?;
+    }
+    tx.commit()?;
+    Ok(cid)
+}
+
+/// Register `name` for the word registered as `existing`; both names then resolve to one CID.
+pub(crate) fn alias_word(conn: &Connection, existing: &str, name: &str) -> Result<[u8; 32]> {
+    let cid =
+        get_name(conn, "word", existing)?.ok_or_else(|| anyhow!("word `{existing}` not found"))?;
+    match get_name(conn, "word", name)? {
+        Some(bound) if bound != cid => bail!(
+            "word name `{name}` already bound to {}",
+            cid::to_hex(&bound)
+        ),
+        _ => put_name(conn, "word", name, &cid)?,
+    }
+    Ok(cid)
+}
+
+/// Describe every observable contract difference between two words.
+pub(crate) fn diff_words(left: &WordInfo, right: &WordInfo) -> Vec<String> {
+    let mut out = Vec::new();
+    if left.params != right.params {
+        out.push(format!(
+            "params: {} -> {}",
+            format_types(&left.params),
+            format_types(&right.params)
+        ));
+    }
+    if left.results != right.results {
+        out.push(format!(
+            "results: {} -> {}",
+            format_types(&left.results),
+            format_types(&right.results)
+        ));
+    }
+    if left.effect_mask != right.effect_mask {
+        out.push(format!(
+            "effect_mask: {} -> {}",
+            format_mask(left.effect_mask),
+            format_mask(right.effect_mask)
+        ));
+    }
+    diff_cid_sets("effects", &left.effects, &right.effects, &mut out);
+    diff_cid_sets("guards", &left.guards, &right.guards, &mut out);
+    out
+}
+
+fn diff_cid_sets(label: &str, left: &[[u8; 32]], right: &[[u8; 32]], out: &mut Vec<String>) {
+    for removed in left.iter().filter(|cid| !right.contains(cid)) {
+        out.push(format!("{label}: - {}", cid::to_hex(removed)));
+    }
+    for added in right.iter().filter(|cid| !left.contains(cid)) {
+        out.push(format!("{label}: + {}", cid::to_hex(added)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use march5::TypeTag;
+    use march5::types::EffectMask;
+
+    fn info(results: Vec<TypeTag>, effects: Vec<[u8; 32]>, mask: EffectMask) -> WordInfo {
+        WordInfo {
+            root: [0u8; 32],
+            params: vec![TypeTag::I64],
+            results,
+            effects,
+            effect_mask: mask,
+            guards: Vec::new(),
+            doc: None,
+        }
+    }
+
+    #[test]
+    fn diff_reports_effect_and_signature_changes() {
+        let pure = info(vec![TypeTag::I64], Vec::new(), effect_mask::NONE);
+        let io_effect = [0x22u8; 32];
+        let effectful = info(
+            vec![TypeTag::I64, TypeTag::IoToken],
+            vec![io_effect],
+            effect_mask::IO,
+        );
+
+        let diff = diff_words(&pure, &effectful);
+        assert_eq!(
+            diff,
+            vec![
+                "results: (i64) -> (i64, io.token)".to_string(),
+                "effect_mask: 0x0 [] -> 0x1 [io]".to_string(),
+                format!("effects: + {}", cid::to_hex(&io_effect)),
+            ]
+        );
+        assert!(diff_words(&pure, &pure).is_empty());
+    }
+
+    #[test]
+    fn show_lists_effect_domains_next_to_mask() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        march5::db::install_schema(&conn)?;
+        let mask = effect_mask::IO | effect_mask::STATE_READ;
+        let canon = WordCanon {
+            root: [0x11u8; 32],
+            params: Vec::new(),
+            results: vec!["i64".to_string()],
+            effects: Vec::new(),
+            effect_mask: mask,
+            guards: Vec::new(),
+            doc: Some("reads state, prints".to_string()),
+        };
+        let word_cid = word::store_word(&conn, &canon)?.cid;
+        put_name(&conn, "word", "demo/effectful", &word_cid)?;
+
+        let shown: serde_json::Value =
+            serde_json::from_str(&word_show_json(&conn, "demo/effectful", false)?)?;
+        assert_eq!(shown["effect_mask"], serde_json::json!(mask));
+        assert_eq!(shown["effect_domains"], serde_json::json!(["io", "state"]));
+        assert_eq!(shown["cid"], serde_json::json!(cid::to_hex(&word_cid)));
+        assert_eq!(shown["doc"], serde_json::json!("reads state, prints"));
+        let raw: serde_json::Value =
+            serde_json::from_str(&crate::cli::commands::util::named_object_json(
+                &conn,
+                "word",
+                "word",
+                "demo/effectful",
+                false,
+            )?)?;
+        assert_eq!(shown["canonical"], raw);
+        Ok(())
+    }
+
+    #[test]
+    fn graph_walks_from_return_to_args() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        march5::db::install_schema(&conn)?;
+        let params = [TypeTag::I64, TypeTag::I64];
+        let add = march5::prim::store_prim(
+            &conn,
+            &march5::PrimCanon {
+                params: &params,
+                results: &[TypeTag::I64],
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            },
+        )?
+        .cid;
+        let mut builder = march5::GraphBuilder::new(&conn);
+        builder.begin_word(&params)?;
+        let prim_node = builder.apply_prim(add)?;
+        let word_cid = builder.finish_word(&params, &[TypeTag::I64], Some("demo/add"))?;
+
+        let nodes = word_graph(&conn, &word_cid)?;
+        let kinds: Vec<&str> = nodes.iter().map(|(_, info, _)| info.kind.name()).collect();
+        assert_eq!(kinds, vec!["RETURN", "PRIM", "ARG", "ARG"]);
+        let (_, prim_info, prim_edges) = &nodes[1];
+        assert_eq!(nodes[1].0, prim_node);
+        assert_eq!(prim_edges.len(), 2);
+        let line = graph_line(&nodes[1].0, prim_info, prim_edges);
+        assert!(
+            line.contains(&format!("{}:0", cid::to_hex(&nodes[2].0))),
+            "{line}"
+        );
+
+        let dot = graph_to_dot(&nodes);
+        assert!(dot.starts_with("digraph word {"));
+        assert!(dot.contains("n1 -> n0"));
+        assert!(dot.contains("n2 -> n1") && dot.contains("n3 -> n1"));
+        Ok(())
+    }
+
+    #[test]
+    fn rename_moves_and_alias_shares_the_mapping() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        march5::db::install_schema(&conn)?;
+        let word_cid = [0x42u8; 32];
+        let other_cid = [0x43u8; 32];
+        put_name(&conn, "word", "demo/old", &word_cid)?;
+        put_name(&conn, "word", "demo/taken", &other_cid)?;
+
+        assert_eq!(rename_word(&conn, "demo/old", "demo/new")?, word_cid);
+        assert_eq!(get_name(&conn, "word", "demo/old")?, None);
+        assert_eq!(get_name(&conn, "word", "demo/new")?, Some(word_cid));
+
+        assert_eq!(alias_word(&conn, "demo/new", "demo/alias")?, word_cid);
+        assert_eq!(get_name(&conn, "word", "demo/new")?, Some(word_cid));
+        assert_eq!(get_name(&conn, "word", "demo/alias")?, Some(word_cid));
+
+        assert!(rename_word(&conn, "demo/new", "demo/taken").is_err());
+        assert_eq!(get_name(&conn, "word", "demo/new")?, Some(word_cid));
+        assert!(alias_word(&conn, "demo/missing", "demo/other").is_err());
+        Ok(())
+    }
+}
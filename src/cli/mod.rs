@@ -2,8 +2,8 @@ mod commands;
 
 use std::path::PathBuf;
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Result, anyhow, bail};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "march5", version, about = "March α₅ CLI tooling")]
@@ -12,10 +12,28 @@ struct Cli {
     #[arg(short = 'd', long = "db", global = true, value_name = "PATH")]
     store: Option<PathBuf>,
 
+    /// Output encoding for `show` commands (`json` keeps each command's usual output)
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Json)]
+    format: OutputFormat,
+
+    /// Emit JSON on a single line instead of indented
+    #[arg(long, global = true)]
+    compact: bool,
+
     #[command(subcommand)]
     command: Command,
 }
 
+/// How `show` commands render the object they resolve.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    Json,
+    /// Raw canonical bytes as hex
+    CborHex,
+    /// RFC 8949 diagnostic notation
+    CborDiag,
+}
+
 #[derive(Subcommand)]
 enum Command {
     /// Create a new March database initialised with schema and PRAGMAs
@@ -68,19 +86,71 @@ enum Command {
     /// Execute a word and print its result
     Run {
         name: String,
-        /// Supply repeated --arg <literal> values for parameters
+        /// Supply repeated --arg <literal> values (optionally typed: i64:, f64:, text:, unit:, quote:)
         #[arg(long = "arg")]
         args: Vec<String>,
         /// Provide arguments via YAML sequence (tags like !i64, !text, !tuple)
         #[arg(long = "args-yaml", value_name = "PATH")]
         args_yaml: Option<PathBuf>,
+        /// Read the YAML argument sequence from standard input
+        #[arg(long = "args-stdin", conflicts_with = "args_yaml")]
+        args_stdin: bool,
+        /// Discard global state changes if the word fails
+        #[arg(long = "transactional")]
+        transactional: bool,
+        /// Evaluate the word N times, print the last outputs and the timing
+        #[arg(long = "repeat", value_name = "N", default_value_t = 1)]
+        repeat: u32,
+        /// Use the plain Rust fallbacks instead of the native add/sub routines
+        #[arg(long = "no-jit")]
+        no_jit: bool,
     },
     /// Apply a YAML catalog of effects/prims/words/snapshots
+    #[command(args_conflicts_with_subcommands = true)]
     Catalog {
-        file: PathBuf,
+        #[command(subcommand)]
+        command: Option<CatalogCommand>,
+        #[arg(required = true)]
+        file: Option<PathBuf>,
         #[arg(long = "dry-run")]
         dry_run: bool,
+        /// Fail instead of rebinding a name that already points at a different CID
+        #[arg(long = "no-clobber")]
+        no_clobber: bool,
+        /// Keep running and re-apply the file whenever its modification time changes
+        #[arg(long = "watch")]
+        watch: bool,
+    },
+    /// Export every object and name into a portable CBOR bundle
+    Export { file: PathBuf },
+    /// Import a bundle produced by `export` (idempotent; CIDs are re-verified)
+    Import { file: PathBuf },
+    /// Summarise object counts per kind, names per scope and distinct CIDs
+    Stats,
+    /// List the names in a scope with the kind of object each points at
+    List {
+        /// Name scope (e.g. word, prim, guard)
+        scope: String,
+        #[arg(long = "prefix")]
+        prefix: Option<String>,
+        /// Only keep names whose object has this kind
+        #[arg(long = "kind")]
+        kind: Option<String>,
+    },
+    /// Dump a stored object's raw CBOR structure
+    Cbor {
+        /// Object CID (hex or base32)
+        cid: String,
     },
+    /// Describe the canonical CBOR layout of an object kind as JSON
+    Schema {
+        /// prim, word, node, iface, namespace or effect
+        kind: String,
+    },
+    /// List the type atoms and effect-mask domains the CLI accepts
+    Types,
+    /// Check the store's schema version and upgrade it to the current one
+    Migrate,
     /// Manage inet agents (ports-based node kinds)
     Agent {
         #[command(subcommand)]
@@ -102,7 +172,20 @@ enum EffectCommand {
         /// Optional documentation string
         #[arg(long)]
         doc: Option<String>,
+        /// Also record `name` under the `effect` scope of name_index
+        #[arg(long)]
+        register: bool,
+        /// Register `name` for an existing effect object instead of creating one
+        #[arg(long, value_name = "HEX", conflicts_with_all = ["doc", "register"])]
+        cid: Option<String>,
     },
+    /// List registered effect names
+    List {
+        #[arg(long = "prefix")]
+        prefix: Option<String>,
+    },
+    /// Show canonical JSON for an effect
+    Show { name: String },
 }
 
 #[derive(Subcommand)]
@@ -117,16 +200,32 @@ enum PrimCommand {
         /// Repeat per result type
         #[arg(long = "result", value_name = "TYPE")]
         results: Vec<String>,
-        /// Declared effect CIDs
-        #[arg(long = "effect", value_name = "CID")]
+        /// Declared effects, as hex CIDs or registered effect names
+        #[arg(long = "effect", value_name = "CID|NAME")]
         effects: Vec<String>,
         /// Explicit effect mask domains (e.g. io, state.write, test)
         #[arg(long = "emask", value_name = "DOMAIN")]
         emask: Vec<String>,
+        /// Optional description stored with the primitive
+        #[arg(long = "doc")]
+        doc: Option<String>,
+        /// Accept zero or more trailing arguments of this type
+        #[arg(long = "variadic", value_name = "TYPE")]
+        variadic: Option<String>,
+        /// Runner built-in this prim implements (defaults to the one named by NAME)
+        #[arg(long = "builtin", value_name = "ID")]
+        builtin: Option<String>,
         /// Skip name_index registration
         #[arg(long = "no-register")]
         no_register: bool,
     },
+    /// List registered primitive names
+    List {
+        #[arg(long = "prefix")]
+        prefix: Option<String>,
+    },
+    /// Show a primitive's signature, effects, and effect mask
+    Show { name: String },
 }
 
 #[derive(Subcommand)]
@@ -142,6 +241,19 @@ enum IfaceCommand {
         /// Skip name_index registration
         #[arg(long = "no-register")]
         no_register: bool,
+        /// Require an export to match a stored word's signature (`symbol=word-name`)
+        #[arg(long = "check-word", value_name = "SYM=WORD")]
+        check_words: Vec<String>,
+    },
+    /// Union two interfaces into a new one; shared names must agree
+    Merge {
+        /// First interface (name or CID)
+        a: String,
+        /// Second interface (name or CID)
+        b: String,
+        /// Register the merged interface under this name
+        #[arg(long = "name", value_name = "NAME")]
+        name: String,
     },
     /// List registered interface names
     List {
@@ -149,7 +261,18 @@ enum IfaceCommand {
         prefix: Option<String>,
     },
     /// Show canonical JSON for an interface
-    Show { name: String },
+    Show {
+        name: String,
+        /// Also name a registered word whose signature matches each symbol
+        #[arg(long = "resolve")]
+        resolve: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CatalogCommand {
+    /// Print a stored word as a catalog `!word` entry (pure, stack-expressible words only)
+    Export { word: String },
 }
 
 #[derive(Subcommand)]
@@ -176,9 +299,25 @@ enum NamespaceCommand {
     List {
         #[arg(long = "prefix")]
         prefix: Option<String>,
+        /// Also print each namespace's `alias -> word` exports
+        #[arg(long = "exports")]
+        exports: bool,
     },
     /// Show canonical JSON for a namespace by name
     Show { name: String },
+    /// Resolve an exported symbol to its word CID (and registered word names)
+    Resolve {
+        namespace: String,
+        symbol: String,
+        /// Run the resolved word instead of only printing it
+        #[arg(long = "run")]
+        run: bool,
+        /// Arguments for `--run` (same literal syntax as `run --arg`)
+        #[arg(long = "arg", requires = "run")]
+        args: Vec<String>,
+    },
+    /// Check that every export of a namespace resolves to a stored word
+    Verify { name: String },
 }
 
 #[derive(Subcommand)]
@@ -203,12 +342,25 @@ enum StateCommand {
         #[arg(long = "prefix")]
         prefix: Option<String>,
     },
+    /// List live keys in the in-memory global store with their value types
+    Keys {
+        #[arg(long = "prefix")]
+        prefix: Option<String>,
+    },
+    /// Compare two saved snapshots: keys only in A (-), only in B (+), changed (~)
+    Diff {
+        /// First snapshot name
+        a: String,
+        /// Second snapshot name
+        b: String,
+    },
 }
 
 #[derive(Subcommand)]
 enum NodeCommand {
     /// Insert a literal node (currently supports i64 literals)
     Lit {
+        /// Output type; only `i64` literals are supported
         #[arg(long = "ty")]
         ty: String,
         #[arg(long = "value")]
@@ -256,6 +408,13 @@ enum NodeCommand {
         #[arg(long = "effect")]
         effects: Vec<String>,
     },
+    /// Decode a stored node by CID (hex or base32)
+    Show {
+        cid: String,
+        /// Print CIDs in base32 instead of hex
+        #[arg(long)]
+        base32: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -282,6 +441,14 @@ enum AgentCommand {
         #[arg(long = "prefix")]
         prefix: Option<String>,
     },
+    /// Instantiate stored agents into a net and print it as Graphviz DOT
+    Dot {
+        /// Agent names or CIDs; each occurrence creates one instance (indexed from 0)
+        agents: Vec<String>,
+        /// Wire two ports by instance index and port name (e.g. 0.principal=1.principal)
+        #[arg(long = "wire", value_name = "I.PORT=J.PORT")]
+        wires: Vec<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -323,8 +490,8 @@ enum WordCommand {
         params: Vec<String>,
         #[arg(long = "result", value_name = "TYPE")]
         results: Vec<String>,
-        /// Declared effect CIDs
-        #[arg(long = "effect", value_name = "CID")]
+        /// Declared effects, as hex CIDs or registered effect names
+        #[arg(long = "effect", value_name = "CID|NAME")]
         effects: Vec<String>,
         /// Explicit effect mask domains (e.g. io, state.write, test)
         #[arg(long = "emask", value_name = "DOMAIN")]
@@ -332,6 +499,9 @@ enum WordCommand {
         /// Attach guard CIDs or names (repeatable)
         #[arg(long = "guard", value_name = "CID_OR_NAME")]
         guards: Vec<String>,
+        /// Optional description stored with the word
+        #[arg(long = "doc")]
+        doc: Option<String>,
         #[arg(long = "no-register")]
         no_register: bool,
     },
@@ -342,6 +512,26 @@ enum WordCommand {
         #[arg(long = "prefix")]
         prefix: Option<String>,
     },
+    /// Compare two words' signatures, effects, and guards (exit 1 when they differ)
+    Diff {
+        /// First word (name or hex CID)
+        left: String,
+        /// Second word (name or hex CID)
+        right: String,
+    },
+    /// Move a word's name to a new one (the object itself is untouched)
+    Rename { old: String, new: String },
+    /// Register an additional name for an existing word
+    Alias { existing: String, name: String },
+    /// Print the node DAG reachable from a word's root
+    Graph {
+        name: String,
+        /// Emit Graphviz DOT instead of one line per node
+        #[arg(long = "dot")]
+        dot: bool,
+    },
+    /// Warn when a word's declared effects and effect mask disagree (exit 1 on findings)
+    Lint { name: String },
 }
 
 #[derive(Subcommand)]
@@ -371,16 +561,65 @@ enum GuardCommand {
         #[arg(long = "prefix")]
         prefix: Option<String>,
     },
+    /// Evaluate a guard against literal arguments and print 1 (pass) or 0 (fail)
+    Run {
+        name: String,
+        /// Supply repeated --arg <literal> values (optionally typed: i64:, f64:, text:, unit:, quote:)
+        #[arg(long = "arg")]
+        args: Vec<String>,
+    },
+}
+
+/// Scope and name (or CID) targeted by a `show` subcommand.
+fn show_target(command: &Command) -> Option<(&'static str, &str)> {
+    match command {
+        Command::Effect {
+            command: EffectCommand::Show { name },
+        } => Some(("effect", name)),
+        Command::Prim {
+            command: PrimCommand::Show { name },
+        } => Some(("prim", name)),
+        Command::Iface {
+            command: IfaceCommand::Show { name, .. },
+        } => Some(("iface", name)),
+        Command::Namespace {
+            command: NamespaceCommand::Show { name },
+        } => Some(("namespace", name)),
+        Command::Node {
+            command: NodeCommand::Show { cid, .. },
+        } => Some(("node", cid)),
+        Command::Word {
+            command: WordCommand::Show { name },
+        } => Some(("word", name)),
+        Command::Guard {
+            command: GuardCommand::Show { name },
+        } => Some(("guard", name)),
+        Command::Agent {
+            command: AgentCommand::Show { name },
+        } => Some(("agent", name)),
+        Command::Rule {
+            command: RuleCommand::Show { name },
+        } => Some(("rule", name)),
+        _ => None,
+    }
 }
 
 pub(crate) fn run() -> Result<()> {
     let cli = Cli::parse();
 
+    if cli.format != OutputFormat::Json {
+        let Some((scope, name)) = show_target(&cli.command) else {
+            bail!("--format only applies to `show` commands");
+        };
+        let store_path = commands::require_store_path(cli.store.as_deref())?;
+        return commands::cmd_show_encoded(store_path, scope, name, cli.format, cli.compact);
+    }
+
     match cli.command {
         Command::New { name } => commands::cmd_new(&name),
         Command::Effect { command } => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
-            commands::cmd_effect(store_path, command)
+            commands::cmd_effect(store_path, command, cli.compact)
         }
         Command::Prim { command } => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
@@ -388,11 +627,11 @@ pub(crate) fn run() -> Result<()> {
         }
         Command::Iface { command } => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
-            commands::cmd_iface(store_path, command)
+            commands::cmd_iface(store_path, command, cli.compact)
         }
         Command::Namespace { command } => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
-            commands::cmd_namespace(store_path, command)
+            commands::cmd_namespace(store_path, command, cli.compact)
         }
         Command::Node { command } => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
@@ -400,11 +639,11 @@ pub(crate) fn run() -> Result<()> {
         }
         Command::Word { command } => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
-            commands::cmd_word(store_path, command)
+            commands::cmd_word(store_path, command, cli.compact)
         }
         Command::Guard { command } => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
-            commands::cmd_guard(store_path, command)
+            commands::cmd_guard(store_path, command, cli.compact)
         }
         Command::Builder => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
@@ -415,20 +654,79 @@ pub(crate) fn run() -> Result<()> {
             name,
             args,
             args_yaml,
+            args_stdin,
+            transactional,
+            repeat,
+            no_jit,
+        } => {
+            let store_path = commands::require_store_path(cli.store.as_deref())?;
+            if no_jit {
+                march5::exec::set_jit_enabled(false);
+            }
+            commands::cmd_run(
+                store_path,
+                &name,
+                &args,
+                args_yaml.as_deref(),
+                args_stdin,
+                transactional,
+                repeat,
+            )
+        }
+        Command::Catalog {
+            command: Some(CatalogCommand::Export { word }),
+            ..
+        } => {
+            let store_path = commands::require_store_path(cli.store.as_deref())?;
+            commands::cmd_catalog_export(store_path, &word)
+        }
+        Command::Catalog {
+            command: None,
+            file,
+            dry_run,
+            no_clobber,
+            watch,
+        } => {
+            let file = file.ok_or_else(|| anyhow!("catalog requires a YAML file"))?;
+            commands::cmd_catalog(cli.store.as_deref(), &file, dry_run, no_clobber, watch)
+        }
+        Command::Export { file } => {
+            let store_path = commands::require_store_path(cli.store.as_deref())?;
+            commands::cmd_export(store_path, &file)
+        }
+        Command::Import { file } => {
+            let store_path = commands::require_store_path(cli.store.as_deref())?;
+            commands::cmd_import(store_path, &file)
+        }
+        Command::Stats => {
+            let store_path = commands::require_store_path(cli.store.as_deref())?;
+            commands::cmd_stats(store_path)
+        }
+        Command::List {
+            scope,
+            prefix,
+            kind,
         } => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
-            commands::cmd_run(store_path, &name, &args, args_yaml.as_deref())
+            commands::cmd_list(store_path, &scope, prefix.as_deref(), kind.as_deref())
+        }
+        Command::Cbor { cid } => {
+            let store_path = commands::require_store_path(cli.store.as_deref())?;
+            commands::cmd_cbor(store_path, &cid)
         }
-        Command::Catalog { file, dry_run } => {
-            commands::cmd_catalog(cli.store.as_deref(), &file, dry_run)
+        Command::Schema { kind } => commands::cmd_schema(&kind, cli.compact),
+        Command::Types => commands::cmd_types(),
+        Command::Migrate => {
+            let store_path = commands::require_store_path(cli.store.as_deref())?;
+            commands::cmd_migrate(store_path)
         }
         Command::Agent { command } => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
-            commands::cmd_agent(store_path, command)
+            commands::cmd_agent(store_path, command, cli.compact)
         }
         Command::Rule { command } => {
             let store_path = commands::require_store_path(cli.store.as_deref())?;
-            commands::cmd_rule(store_path, command)
+            commands::cmd_rule(store_path, command, cli.compact)
         }
     }
 }
@@ -1,10 +1,12 @@
 //! SQLite-backed persistence helpers for March content-addressed objects.
 
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
-use rusqlite::{Connection, DatabaseName, OpenFlags, params};
+use rusqlite::{Connection, DatabaseName, OpenFlags, OptionalExtension, Statement, params};
 
 /// Derive a database file path, appending `.march5.db` when no extension is supplied.
 pub fn derive_db_path(name: &str) -> PathBuf {
@@ -17,15 +19,18 @@ pub fn derive_db_path(name: &str) -> PathBuf {
 
 /// Ensure directories required to create `path` exist.
 pub fn ensure_parent_dirs(path: &Path) -> Result<()> {
-    if let Some(parent) = path.parent() {
-        if !parent.as_os_str().is_empty() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("failed to create directory {}", parent.display()))?;
-        }
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory {}", parent.display()))?;
     }
     Ok(())
 }
 
+/// Schema version written by this build; bump when the on-disk layout changes.
+pub const SCHEMA_VERSION: u32 = 3;
+
 /// Create a new March store on disk and initialise schema/PRAGMA settings.
 pub fn create_store(path: &Path) -> Result<Connection> {
     ensure_parent_dirs(path)?;
@@ -41,11 +46,21 @@ pub fn create_store(path: &Path) -> Result<Connection> {
 
     configure_pragmas(&conn)?;
     install_schema(&conn)?;
+    set_schema_version(&conn, SCHEMA_VERSION)?;
     Ok(conn)
 }
 
 /// Open an existing March store, applying PRAGMA preferences.
+///
+/// Fails with an upgrade hint when the store's schema version differs from
+/// [`SCHEMA_VERSION`].
 pub fn open_store(path: &Path) -> Result<Connection> {
+    let conn = open_store_unchecked(path)?;
+    check_schema_version(&conn)?;
+    Ok(conn)
+}
+
+fn open_store_unchecked(path: &Path) -> Result<Connection> {
     if !path.exists() {
         bail!("database not found at {}", path.display());
     }
@@ -57,13 +72,171 @@ pub fn open_store(path: &Path) -> Result<Connection> {
     Ok(conn)
 }
 
+/// Read the recorded schema version; stores created before versioning report `0`.
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM schema_meta WHERE key = 'version'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match value {
+        Some(text) => text
+            .parse()
+            .with_context(|| format!("invalid schema version `{text}` in schema_meta")),
+        None => Ok(0),
+    }
+}
+
+fn set_schema_version(conn: &Connection, version: u32) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO schema_meta (key, value) VALUES ('version', ?1)",
+        params![version.to_string()],
+    )?;
+    Ok(())
+}
+
+/// Ensure the store matches the schema version this build understands.
+pub fn check_schema_version(conn: &Connection) -> Result<()> {
+    let version = schema_version(conn)?;
+    if version < SCHEMA_VERSION {
+        bail!(
+            "store uses schema version {version} but this build expects {SCHEMA_VERSION}; \
+             run `march5 --db <path> migrate` to upgrade it"
+        );
+    }
+    if version > SCHEMA_VERSION {
+        bail!(
+            "store uses schema version {version}, newer than this build supports \
+             ({SCHEMA_VERSION}); upgrade march5 to open it"
+        );
+    }
+    Ok(())
+}
+
+/// Version change applied by [`migrate_store`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MigrationOutcome {
+    pub from: u32,
+    pub to: u32,
+    /// Namespaces rewritten from the pre-version-2 export encoding or to
+    /// reference re-encoded interfaces.
+    pub namespaces_reencoded: usize,
+    /// Interfaces rewritten from the pre-version-3 symbol encoding.
+    pub ifaces_reencoded: usize,
+}
+
+/// Upgrade a store to [`SCHEMA_VERSION`], refusing stores written by newer builds.
+pub fn migrate_store(path: &Path) -> Result<MigrationOutcome> {
+    let conn = open_store_unchecked(path)?;
+    let from = schema_version(&conn)?;
+    if from > SCHEMA_VERSION {
+        bail!(
+            "store uses schema version {from}, newer than this build supports ({SCHEMA_VERSION})"
+        );
+    }
+    // Version 0 stores share the version 1 table layout; only the marker is missing.
+    // Version 2 encodes namespace exports as `[name, word]` pairs; version 3
+    // wraps each interface symbol in its own array.
+    let mut namespaces_reencoded = 0;
+    let mut ifaces_reencoded = 0;
+    if from < SCHEMA_VERSION {
+        let tx = conn.unchecked_transaction()?;
+        let iface_cids = reencode_legacy_ifaces(&tx)?;
+        ifaces_reencoded = iface_cids.len();
+        namespaces_reencoded = reencode_legacy_namespaces(&tx, &iface_cids)?;
+        set_schema_version(&tx, SCHEMA_VERSION)?;
+        tx.commit()?;
+    }
+    Ok(MigrationOutcome {
+        from,
+        to: SCHEMA_VERSION,
+        namespaces_reencoded,
+        ifaces_reencoded,
+    })
+}
+
+/// Every stored object of `kind` as `(cid, cbor)`.
+fn objects_of_kind(conn: &Connection, kind: &str) -> Result<Vec<([u8; 32], Vec<u8>)>> {
+    let mut stmt = conn.prepare("SELECT cid, cbor FROM object WHERE kind = ?1")?;
+    let mut rows = stmt.query(params![kind])?;
+    let mut objects = Vec::new();
+    while let Some(row) = rows.next()? {
+        let blob: Vec<u8> = row.get(0)?;
+        objects.push((crate::cid::from_slice(&blob)?, row.get(1)?));
+    }
+    Ok(objects)
+}
+
+/// Point every name bound to `old` at `new`, then drop the `old` object.
+fn replace_object(conn: &Connection, old: &[u8; 32], new: &[u8; 32]) -> Result<()> {
+    conn.execute(
+        "UPDATE name_index SET cid = ?1 WHERE cid = ?2",
+        params![&new[..], &old[..]],
+    )?;
+    conn.execute("DELETE FROM object WHERE cid = ?1", params![&old[..]])?;
+    Ok(())
+}
+
+/// Re-store interfaces written with the legacy symbol encoding and return
+/// the old-to-new CID mapping.
+fn reencode_legacy_ifaces(conn: &Connection) -> Result<HashMap<[u8; 32], [u8; 32]>> {
+    let mut remapped = HashMap::new();
+    for (old, cbor) in objects_of_kind(conn, "iface")? {
+        let Some(iface) = crate::iface::decode_legacy(&cbor)
+            .with_context(|| format!("failed to migrate iface {}", crate::cid::to_hex(&old)))?
+        else {
+            continue;
+        };
+        let new = crate::iface::store_iface(conn, &iface)?.cid;
+        replace_object(conn, &old, &new)?;
+        remapped.insert(old, new);
+    }
+    Ok(remapped)
+}
+
+/// Re-store namespaces written with a legacy export encoding or referencing
+/// an interface in `iface_cids`, point their names at the new CIDs and drop
+/// the old objects.
+fn reencode_legacy_namespaces(
+    conn: &Connection,
+    iface_cids: &HashMap<[u8; 32], [u8; 32]>,
+) -> Result<usize> {
+    let mut count = 0;
+    for (old, cbor) in objects_of_kind(conn, "namespace")? {
+        let context = || format!("failed to migrate namespace {}", crate::cid::to_hex(&old));
+        let (mut ns, mut changed) =
+            match crate::namespace::decode_legacy(&cbor).with_context(context)? {
+                Some(ns) => (ns, true),
+                None => (
+                    crate::namespace::decode(&cbor).with_context(context)?,
+                    false,
+                ),
+            };
+        for cid in std::iter::once(&mut ns.iface).chain(ns.imports.iter_mut()) {
+            if let Some(new) = iface_cids.get(cid) {
+                *cid = *new;
+                changed = true;
+            }
+        }
+        if !changed {
+            continue;
+        }
+        let new = crate::namespace::store_namespace(conn, &ns)?.cid;
+        replace_object(conn, &old, &new)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
 /// Apply recommended PRAGMA settings for the March store.
 pub fn configure_pragmas(conn: &Connection) -> Result<()> {
-    conn.pragma_update(Some(DatabaseName::Main), "journal_mode", &"WAL")?;
-    conn.pragma_update(Some(DatabaseName::Main), "synchronous", &"NORMAL")?;
-    conn.pragma_update(Some(DatabaseName::Main), "temp_store", &"MEMORY")?;
-    conn.pragma_update(Some(DatabaseName::Main), "mmap_size", &268_435_456i64)?;
-    conn.pragma_update(Some(DatabaseName::Main), "cache_size", &-262_144i64)?;
+    conn.pragma_update(Some(DatabaseName::Main), "journal_mode", "WAL")?;
+    conn.pragma_update(Some(DatabaseName::Main), "synchronous", "NORMAL")?;
+    conn.pragma_update(Some(DatabaseName::Main), "temp_store", "MEMORY")?;
+    conn.pragma_update(Some(DatabaseName::Main), "mmap_size", 268_435_456i64)?;
+    conn.pragma_update(Some(DatabaseName::Main), "cache_size", -262_144i64)?;
     Ok(())
 }
 
@@ -92,14 +265,54 @@ CREATE TABLE IF NOT EXISTS code_cache (
   PRIMARY KEY (subgraph_cid, arch, abi, flags)
 );
 
+CREATE TABLE IF NOT EXISTS schema_meta (
+  key   TEXT PRIMARY KEY,
+  value TEXT NOT NULL
+);
+
 CREATE INDEX IF NOT EXISTS object_kind_idx ON object(kind);
 CREATE INDEX IF NOT EXISTS name_scope_cid_idx ON name_index(scope, cid);
 "#;
 
     conn.execute_batch(SCHEMA)?;
+    conn.execute(
+        "INSERT OR IGNORE INTO schema_meta (key, value) VALUES ('store_id', ?1)",
+        params![fresh_store_id()],
+    )?;
     Ok(())
 }
 
+/// Identity recorded for this store when its schema was installed.
+///
+/// Copies of a database file share it, so callers that must tell copies apart
+/// should pair it with the connection's path.
+pub fn store_id(conn: &Connection) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT value FROM schema_meta WHERE key = 'store_id'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+fn fresh_store_id() -> String {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    let seed = format!(
+        "{}:{nanos}:{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    );
+    crate::cid::to_hex(&crate::cid::compute(seed.as_bytes()))[..32].to_string()
+}
+
 /// Insert an object if missing; returns `true` when inserted.
 pub fn put_object(conn: &Connection, cid: &[u8; 32], kind: &str, cbor: &[u8]) -> Result<bool> {
     let rows = conn.execute(
@@ -109,6 +322,24 @@ pub fn put_object(conn: &Connection, cid: &[u8; 32], kind: &str, cbor: &[u8]) ->
     Ok(rows > 0)
 }
 
+/// Like [`put_object`], but refuses CBOR that is not in canonical form.
+///
+/// Use this for bytes that did not come from the crate's own encoders.
+pub fn put_object_canonical(
+    conn: &Connection,
+    cid: &[u8; 32],
+    kind: &str,
+    cbor: &[u8],
+) -> Result<bool> {
+    crate::cbor::check_canonical(cbor).with_context(|| {
+        format!(
+            "{kind} object {} is not canonical CBOR",
+            crate::cid::to_hex(cid)
+        )
+    })?;
+    put_object(conn, cid, kind, cbor)
+}
+
 /// Register or update a name → CID mapping under `scope`.
 pub fn put_name(conn: &Connection, scope: &str, name: &str, cid: &[u8; 32]) -> Result<()> {
     conn.execute(
@@ -118,41 +349,110 @@ pub fn put_name(conn: &Connection, scope: &str, name: &str, cid: &[u8; 32]) -> R
     Ok(())
 }
 
+/// Remove a name mapping; returns false when `scope/name` was not registered.
+pub fn delete_name(conn: &Connection, scope: &str, name: &str) -> Result<bool> {
+    let rows = conn.execute(
+        "DELETE FROM name_index WHERE scope = ?1 AND name = ?2",
+        params![scope, name],
+    )?;
+    Ok(rows > 0)
+}
+
 /// Return all names registered for a given CID within `scope` (sorted ascending).
 pub fn list_names_for_cid(conn: &Connection, scope: &str, cid: &[u8; 32]) -> Result<Vec<String>> {
-    let mut stmt =
-        conn.prepare("SELECT name FROM name_index WHERE scope = ?1 AND cid = ?2 ORDER BY name")?;
-    let mut rows = stmt.query(params![scope, &cid[..]])?;
-    let mut names = Vec::new();
-    while let Some(row) = rows.next()? {
-        names.push(row.get(0)?);
-    }
-    Ok(names)
+    ObjectLoader::new(conn).names_for_cid(scope, cid)
 }
 
 /// Lookup a CID by scope/name in `name_index`.
 pub fn get_name(conn: &Connection, scope: &str, name: &str) -> Result<Option<[u8; 32]>> {
-    let mut stmt = conn.prepare("SELECT cid FROM name_index WHERE scope = ?1 AND name = ?2")?;
-    let mut rows = stmt.query(params![scope, name])?;
-    if let Some(row) = rows.next()? {
-        let blob: Vec<u8> = row.get(0)?;
-        let cid = crate::cid::from_slice(&blob)?;
-        Ok(Some(cid))
-    } else {
-        Ok(None)
-    }
+    ObjectLoader::new(conn).get_name(scope, name)
 }
 
 /// Load the raw CBOR payload (and kind) for a given CID.
 pub fn load_object_cbor(conn: &Connection, cid: &[u8; 32]) -> Result<(String, Vec<u8>)> {
-    let mut stmt = conn.prepare("SELECT kind, cbor FROM object WHERE cid = ?1")?;
-    let mut rows = stmt.query(params![&cid[..]])?;
-    if let Some(row) = rows.next()? {
-        let kind: String = row.get(0)?;
-        let cbor: Vec<u8> = row.get(1)?;
-        Ok((kind, cbor))
-    } else {
-        bail!("object `{}` not found", crate::cid::to_hex(cid));
+    ObjectLoader::new(conn).load(cid)
+}
+
+/// Reusable `object`/`name_index` lookups backed by prepared statements.
+///
+/// Each statement is prepared on first use and kept for the loader's lifetime, so
+/// hot loops (like the interpreter) skip re-parsing SQL on every access.
+pub struct ObjectLoader<'conn> {
+    conn: &'conn Connection,
+    object: RefCell<Option<Statement<'conn>>>,
+    name: RefCell<Option<Statement<'conn>>>,
+    names_for_cid: RefCell<Option<Statement<'conn>>>,
+}
+
+impl<'conn> ObjectLoader<'conn> {
+    pub fn new(conn: &'conn Connection) -> Self {
+        ObjectLoader {
+            conn,
+            object: RefCell::new(None),
+            name: RefCell::new(None),
+            names_for_cid: RefCell::new(None),
+        }
+    }
+
+    /// Load the raw CBOR payload (and kind) for a given CID.
+    pub fn load(&self, cid: &[u8; 32]) -> Result<(String, Vec<u8>)> {
+        let found = self.with_statement(
+            &self.object,
+            "SELECT kind, cbor FROM object WHERE cid = ?1",
+            |stmt| {
+                Ok(stmt
+                    .query_row(params![&cid[..]], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .optional()?)
+            },
+        )?;
+        match found {
+            Some(row) => Ok(row),
+            None => bail!("object `{}` not found", crate::cid::to_hex(cid)),
+        }
+    }
+
+    /// Lookup a CID by scope/name in `name_index`.
+    pub fn get_name(&self, scope: &str, name: &str) -> Result<Option<[u8; 32]>> {
+        let blob: Option<Vec<u8>> = self.with_statement(
+            &self.name,
+            "SELECT cid FROM name_index WHERE scope = ?1 AND name = ?2",
+            |stmt| {
+                Ok(stmt
+                    .query_row(params![scope, name], |row| row.get(0))
+                    .optional()?)
+            },
+        )?;
+        blob.map(|bytes| crate::cid::from_slice(&bytes)).transpose()
+    }
+
+    /// Names registered for `cid` within `scope`, sorted.
+    pub fn names_for_cid(&self, scope: &str, cid: &[u8; 32]) -> Result<Vec<String>> {
+        self.with_statement(
+            &self.names_for_cid,
+            "SELECT name FROM name_index WHERE scope = ?1 AND cid = ?2 ORDER BY name",
+            |stmt| {
+                let mut rows = stmt.query(params![scope, &cid[..]])?;
+                let mut names = Vec::new();
+                while let Some(row) = rows.next()? {
+                    names.push(row.get(0)?);
+                }
+                Ok(names)
+            },
+        )
+    }
+
+    fn with_statement<T>(
+        &self,
+        slot: &RefCell<Option<Statement<'conn>>>,
+        sql: &str,
+        f: impl FnOnce(&mut Statement<'conn>) -> Result<T>,
+    ) -> Result<T> {
+        let mut slot = slot.borrow_mut();
+        let stmt = match slot.as_mut() {
+            Some(stmt) => stmt,
+            None => slot.insert(self.conn.prepare(sql)?),
+        };
+        f(stmt)
     }
 }
 
@@ -193,6 +493,43 @@ pub fn count_objects_of_kind(conn: &Connection, kind: &str) -> Result<i64> {
     Ok(count)
 }
 
+/// Aggregate counts over the `object` and `name_index` tables.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoreStats {
+    /// `(kind, count)` pairs ordered by kind.
+    pub objects_by_kind: Vec<(String, i64)>,
+    /// `(scope, count)` pairs ordered by scope.
+    pub names_by_scope: Vec<(String, i64)>,
+    /// Number of distinct CIDs stored in `object`.
+    pub distinct_cids: i64,
+    /// Number of distinct CIDs referenced from `name_index`.
+    pub named_cids: i64,
+}
+
+/// Summarise the store: objects per kind, names per scope and distinct CIDs.
+pub fn store_stats(conn: &Connection) -> Result<StoreStats> {
+    let grouped = |sql: &str| -> Result<Vec<(String, i64)>> {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    };
+    let objects_by_kind = grouped("SELECT kind, COUNT(*) FROM object GROUP BY kind ORDER BY kind")?;
+    let names_by_scope =
+        grouped("SELECT scope, COUNT(*) FROM name_index GROUP BY scope ORDER BY scope")?;
+    let distinct_cids = conn.query_row("SELECT COUNT(DISTINCT cid) FROM object", [], |row| {
+        row.get(0)
+    })?;
+    let named_cids = conn.query_row("SELECT COUNT(DISTINCT cid) FROM name_index", [], |row| {
+        row.get(0)
+    })?;
+    Ok(StoreStats {
+        objects_by_kind,
+        names_by_scope,
+        distinct_cids,
+        named_cids,
+    })
+}
+
 /// Entry returned when listing names scoped within the object store.
 pub struct NameEntry {
     pub name: String,
@@ -200,6 +537,8 @@ pub struct NameEntry {
 }
 
 /// List names registered in `name_index` by scope, optionally filtering by prefix.
+///
+/// Entries are ordered by name, so repeated listings of the same store are identical.
 pub fn list_names(conn: &Connection, scope: &str, prefix: Option<&str>) -> Result<Vec<NameEntry>> {
     let mut entries = Vec::new();
     if let Some(prefix) = prefix {
@@ -228,3 +567,361 @@ pub fn list_names(conn: &Connection, scope: &str, prefix: Option<&str>) -> Resul
     }
     Ok(entries)
 }
+
+/// Name entry together with the kind of the object it points at.
+pub struct KindedNameEntry {
+    pub name: String,
+    pub cid: [u8; 32],
+    /// `None` when the CID has no object in the store.
+    pub kind: Option<String>,
+}
+
+/// Like [`list_names`], but joined to `object` so each entry carries the actual
+/// object kind; with `kind`, only names pointing at objects of that kind remain.
+///
+/// Scopes and kinds are normally equal, so this mostly serves as a consistency
+/// check (e.g. after importing a bundle).
+pub fn list_names_with_kind(
+    conn: &Connection,
+    scope: &str,
+    prefix: Option<&str>,
+    kind: Option<&str>,
+) -> Result<Vec<KindedNameEntry>> {
+    let pattern = format!("{}%", prefix.unwrap_or(""));
+    let mut stmt = conn.prepare(
+        "SELECT n.name, n.cid, o.kind FROM name_index n \
+         LEFT JOIN object o ON o.cid = n.cid \
+         WHERE n.scope = ?1 AND n.name LIKE ?2 AND (?3 IS NULL OR o.kind = ?3) \
+         ORDER BY n.name",
+    )?;
+    let mut rows = stmt.query(params![scope, pattern, kind])?;
+    let mut entries = Vec::new();
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(0)?;
+        let blob: Vec<u8> = row.get(1)?;
+        let cid = crate::cid::from_slice(&blob)?;
+        entries.push(KindedNameEntry {
+            name,
+            cid,
+            kind: row.get(2)?,
+        });
+    }
+    Ok(entries)
+}
+
+/// Raw `object` row used for bulk export.
+pub struct ObjectRow {
+    pub cid: [u8; 32],
+    pub kind: String,
+    pub cbor: Vec<u8>,
+}
+
+/// Return every row in `object`, ordered by CID (byte-wise), independent of insertion order.
+pub fn list_all_objects(conn: &Connection) -> Result<Vec<ObjectRow>> {
+    let mut stmt = conn.prepare("SELECT cid, kind, cbor FROM object ORDER BY cid")?;
+    let mut rows = stmt.query([])?;
+    let mut objects = Vec::new();
+    while let Some(row) = rows.next()? {
+        let blob: Vec<u8> = row.get(0)?;
+        let cid = crate::cid::from_slice(&blob)?;
+        let kind: String = row.get(1)?;
+        let cbor: Vec<u8> = row.get(2)?;
+        objects.push(ObjectRow { cid, kind, cbor });
+    }
+    Ok(objects)
+}
+
+/// `name_index` row including its scope.
+pub struct ScopedNameEntry {
+    pub scope: String,
+    pub name: String,
+    pub cid: [u8; 32],
+}
+
+/// Return every `name_index` row, ordered by scope then name.
+pub fn list_all_names(conn: &Connection) -> Result<Vec<ScopedNameEntry>> {
+    let mut stmt = conn.prepare("SELECT scope, name, cid FROM name_index ORDER BY scope, name")?;
+    let mut rows = stmt.query([])?;
+    let mut entries = Vec::new();
+    while let Some(row) = rows.next()? {
+        let scope: String = row.get(0)?;
+        let name: String = row.get(1)?;
+        let blob: Vec<u8> = row.get(2)?;
+        let cid = crate::cid::from_slice(&blob)?;
+        entries.push(ScopedNameEntry { scope, name, cid });
+    }
+    Ok(entries)
+}
+
+/// Find names containing `needle` (matched literally), optionally restricted to one scope.
+pub fn search_names(
+    conn: &Connection,
+    needle: &str,
+    scope: Option<&str>,
+) -> Result<Vec<ScopedNameEntry>> {
+    let pattern = format!("%{}%", escape_like(needle));
+    let mut stmt = conn.prepare(
+        "SELECT scope, name, cid FROM name_index \
+         WHERE (?1 IS NULL OR scope = ?1) AND name LIKE ?2 ESCAPE '\\' \
+         ORDER BY scope, name",
+    )?;
+    let mut rows = stmt.query(params![scope, pattern])?;
+    let mut entries = Vec::new();
+    while let Some(row) = rows.next()? {
+        let scope: String = row.get(0)?;
+        let name: String = row.get(1)?;
+        let blob: Vec<u8> = row.get(2)?;
+        let cid = crate::cid::from_slice(&blob)?;
+        entries.push(ScopedNameEntry { scope, name, cid });
+    }
+    Ok(entries)
+}
+
+fn escape_like(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for ch in input.chars() {
+        if matches!(ch, '%' | '_' | '\\') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_loader_matches_free_functions() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        install_schema(&conn)?;
+        let cid = crate::cid::compute(b"payload");
+        put_object(&conn, &cid, "blob", b"payload")?;
+        put_name(&conn, "blob", "demo/payload", &cid)?;
+        put_name(&conn, "blob", "demo/alias", &cid)?;
+
+        let loader = ObjectLoader::new(&conn);
+        for _ in 0..2 {
+            assert_eq!(loader.load(&cid)?, load_object_cbor(&conn, &cid)?);
+            assert_eq!(
+                loader.get_name("blob", "demo/payload")?,
+                get_name(&conn, "blob", "demo/payload")?
+            );
+            assert_eq!(
+                loader.names_for_cid("blob", &cid)?,
+                vec!["demo/alias".to_string(), "demo/payload".to_string()]
+            );
+        }
+        assert_eq!(
+            loader.load(&cid)?,
+            ("blob".to_string(), b"payload".to_vec())
+        );
+        assert_eq!(loader.get_name("blob", "missing")?, None);
+        assert!(loader.load(&[0u8; 32]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn kinded_listing_filters_on_the_object_kind() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        install_schema(&conn)?;
+        let word = crate::cid::compute(b"word");
+        let prim = crate::cid::compute(b"prim");
+        put_object(&conn, &word, "word", b"word")?;
+        put_object(&conn, &prim, "prim", b"prim")?;
+        put_name(&conn, "word", "demo/real", &word)?;
+        put_name(&conn, "word", "demo/stray", &prim)?;
+        put_name(&conn, "word", "demo/dangling", &[0x42; 32])?;
+
+        let all = list_names_with_kind(&conn, "word", Some("demo/"), None)?;
+        let kinds: Vec<_> = all
+            .iter()
+            .map(|e| (e.name.as_str(), e.kind.as_deref()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("demo/dangling", None),
+                ("demo/real", Some("word")),
+                ("demo/stray", Some("prim")),
+            ]
+        );
+        let words = list_names_with_kind(&conn, "word", None, Some("word"))?;
+        assert_eq!(words.len(), 1);
+        assert_eq!((words[0].name.as_str(), words[0].cid), ("demo/real", word));
+        Ok(())
+    }
+
+    #[test]
+    fn store_stats_counts_kinds_scopes_and_shared_cids() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        install_schema(&conn)?;
+        let first = crate::cid::compute(b"first");
+        let second = crate::cid::compute(b"second");
+        let word = crate::cid::compute(b"word");
+        put_object(&conn, &first, "prim", b"first")?;
+        put_object(&conn, &second, "prim", b"second")?;
+        put_object(&conn, &word, "word", b"word")?;
+        assert!(!put_object(&conn, &first, "prim", b"first")?);
+        put_name(&conn, "prim", "a/add", &first)?;
+        put_name(&conn, "prim", "b/add", &first)?;
+        put_name(&conn, "word", "demo/main", &word)?;
+
+        let stats = store_stats(&conn)?;
+        assert_eq!(
+            stats.objects_by_kind,
+            vec![("prim".to_string(), 2), ("word".to_string(), 1)]
+        );
+        assert_eq!(
+            stats.names_by_scope,
+            vec![("prim".to_string(), 2), ("word".to_string(), 1)]
+        );
+        assert_eq!(stats.distinct_cids, 3);
+        assert_eq!(stats.named_cids, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn listings_are_ordered_regardless_of_insertion_order() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        install_schema(&conn)?;
+        let payloads: [&[u8]; 4] = [b"delta", b"alpha", b"charlie", b"bravo"];
+        for payload in payloads {
+            put_object(&conn, &crate::cid::compute(payload), "blob", payload)?;
+        }
+        for (name, payload) in [("zeta", b"alpha"), ("beta", b"bravo"), ("mu", b"delta")] {
+            put_name(&conn, "blob", name, &crate::cid::compute(payload))?;
+        }
+
+        let cids: Vec<[u8; 32]> = list_all_objects(&conn)?.iter().map(|row| row.cid).collect();
+        let mut expected: Vec<[u8; 32]> = payloads.iter().map(|p| crate::cid::compute(p)).collect();
+        expected.sort();
+        assert_eq!(cids, expected);
+        let rows = list_all_objects(&conn)?;
+        assert!(
+            rows.iter()
+                .all(|row| crate::cid::compute(&row.cbor) == row.cid)
+        );
+
+        let names: Vec<String> = list_names(&conn, "blob", None)?
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(names, vec!["beta", "mu", "zeta"]);
+        Ok(())
+    }
+
+    #[test]
+    fn create_store_records_schema_version() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("versioned.march5.db");
+        let conn = create_store(&path)?;
+        assert_eq!(schema_version(&conn)?, SCHEMA_VERSION);
+        drop(conn);
+
+        let reopened = open_store(&path)?;
+        assert_eq!(schema_version(&reopened)?, SCHEMA_VERSION);
+        let outcome = migrate_store(&path)?;
+        assert_eq!(outcome.from, SCHEMA_VERSION);
+        assert_eq!(outcome.to, SCHEMA_VERSION);
+        Ok(())
+    }
+
+    #[test]
+    fn unversioned_store_requires_migration() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("legacy.march5.db");
+        let conn = create_store(&path)?;
+        conn.execute("DELETE FROM schema_meta", [])?;
+        drop(conn);
+
+        let err = open_store(&path).unwrap_err();
+        assert!(err.to_string().contains("migrate"));
+        let outcome = migrate_store(&path)?;
+        assert_eq!(outcome.from, 0);
+        assert_eq!(schema_version(&open_store(&path)?)?, SCHEMA_VERSION);
+
+        set_schema_version(&open_store(&path)?, SCHEMA_VERSION + 1)?;
+        assert!(open_store(&path).is_err());
+        assert!(migrate_store(&path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn migration_reencodes_legacy_namespaces_and_ifaces() -> Result<()> {
+        use crate::iface::{self, IfaceCanon, IfaceSymbol};
+        use crate::namespace::{self, NamespaceCanon, NamespaceExport};
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("namespaces.march5.db");
+        let conn = create_store(&path)?;
+        let run = IfaceCanon {
+            names: vec![IfaceSymbol {
+                name: "run".into(),
+                params: Vec::new(),
+                results: vec!["i64".into()],
+                effects: Vec::new(),
+            }],
+        };
+        let legacy_iface = iface::tests::legacy_encode(&run);
+        let old_iface = crate::cid::compute(&legacy_iface);
+        put_object(&conn, &old_iface, "iface", &legacy_iface)?;
+        put_name(&conn, "iface", "run", &old_iface)?;
+        let mut ns = NamespaceCanon {
+            imports: Vec::new(),
+            exports: vec![NamespaceExport {
+                name: "run".into(),
+                word: [0x11; 32],
+            }],
+            iface: old_iface,
+        };
+        let legacy = namespace::tests::legacy_encode(&ns);
+        let old = crate::cid::compute(&legacy);
+        put_object(&conn, &old, "namespace", &legacy)?;
+        put_name(&conn, "namespace", "demo", &old)?;
+        let mut early = NamespaceCanon {
+            imports: vec![old_iface],
+            ..ns.clone()
+        };
+        let map_form = namespace::tests::legacy_map_encode(&early);
+        let map_cid = crate::cid::compute(&map_form);
+        put_object(&conn, &map_cid, "namespace", &map_form)?;
+        put_name(&conn, "namespace", "early", &map_cid)?;
+        set_schema_version(&conn, 1)?;
+        drop(conn);
+
+        let outcome = migrate_store(&path)?;
+        assert_eq!(
+            (
+                outcome.from,
+                outcome.ifaces_reencoded,
+                outcome.namespaces_reencoded
+            ),
+            (1, 1, 2)
+        );
+        let conn = open_store(&path)?;
+        let new_iface = get_name(&conn, "iface", "run")?.expect("name kept");
+        assert_eq!(new_iface, iface::store_iface(&conn, &run)?.cid);
+        assert_eq!(iface::load_iface(&conn, &new_iface)?.names[0].name, "run");
+        assert!(load_object_cbor(&conn, &old_iface).is_err());
+        // Namespaces now reference the re-encoded interface.
+        ns.iface = new_iface;
+        early.iface = new_iface;
+        early.imports = vec![new_iface];
+        let new = get_name(&conn, "namespace", "demo")?.expect("name kept");
+        assert_eq!(new, namespace::store_namespace(&conn, &ns)?.cid);
+        assert_eq!(
+            namespace::load_namespace(&conn, &new)?.exports[0].name,
+            "run"
+        );
+        assert!(load_object_cbor(&conn, &old).is_err());
+        let early_cid = get_name(&conn, "namespace", "early")?.expect("name kept");
+        assert_eq!(early_cid, namespace::store_namespace(&conn, &early)?.cid);
+        assert_eq!(
+            namespace::load_namespace(&conn, &early_cid)?.imports,
+            early.imports
+        );
+        Ok(())
+    }
+}
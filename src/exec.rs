@@ -1,6 +1,7 @@
 #![allow(unsafe_op_in_unsafe_fn)]
 
 use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::{Result, anyhow};
 use libc::{
@@ -19,17 +20,33 @@ const SUB_BYTES: &[u8] = &[0x48, 0x89, 0xf8, 0x48, 0x29, 0xf0, 0xc3];
 
 static ADD_PTR: OnceCell<BinFn> = OnceCell::new();
 static SUB_PTR: OnceCell<BinFn> = OnceCell::new();
+static JIT_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// True when the native add/sub routines could be mapped executable.
+pub fn jit_available() -> bool {
+    compiled_add().is_ok() && compiled_sub().is_ok()
+}
+
+/// Whether the interpreter may call the native routines (process-global, default on).
+pub fn jit_enabled() -> bool {
+    JIT_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Force the interpreter onto (or back off) the plain Rust fallbacks.
+pub fn set_jit_enabled(enabled: bool) {
+    JIT_ENABLED.store(enabled, Ordering::Relaxed);
+}
 
 pub fn compiled_add() -> Result<BinFn> {
     ADD_PTR
         .get_or_try_init(|| unsafe { load_exec_binary(ADD_BYTES) })
-        .map(|f| *f)
+        .copied()
 }
 
 pub fn compiled_sub() -> Result<BinFn> {
     SUB_PTR
         .get_or_try_init(|| unsafe { load_exec_binary(SUB_BYTES) })
-        .map(|f| *f)
+        .copied()
 }
 
 unsafe fn load_exec_binary(bytes: &[u8]) -> Result<BinFn> {
@@ -38,7 +55,7 @@ unsafe fn load_exec_binary(bytes: &[u8]) -> Result<BinFn> {
         return Err(anyhow!("sysconf(_SC_PAGESIZE) returned 0"));
     }
     let len = bytes.len();
-    let alloc_len = ((len + page_size - 1) / page_size) * page_size;
+    let alloc_len = len.div_ceil(page_size) * page_size;
     let ptr = mmap(
         std::ptr::null_mut(),
         alloc_len,
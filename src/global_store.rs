@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
 use anyhow::{Result, anyhow, bail};
 use once_cell::sync::Lazy;
@@ -7,33 +8,92 @@ use rusqlite::Connection;
 use serde_cbor::Value as CborValue;
 use std::convert::TryFrom;
 
-use crate::cbor::{push_array, push_bytes, push_f64, push_i64, push_text, push_u32};
+use crate::cbor::{
+    push_array, push_bytes, push_f64, push_i64, push_null, push_text, push_u32, push_u64,
+};
 use crate::interp::Value;
+use crate::types::EffectDomain;
 use crate::{cid, db};
 
 /// Global process-local store holding immutable values keyed by namespace-qualified names.
 #[derive(Clone, Debug)]
 pub struct GlobalStore {
     entries: BTreeMap<String, Value>,
+    /// Expiry deadlines for entries written with a TTL.
+    expiries: BTreeMap<String, Instant>,
 }
 
 impl GlobalStore {
     pub fn new() -> Self {
         Self {
             entries: BTreeMap::new(),
+            expiries: BTreeMap::new(),
         }
     }
 
+    /// Current value for `key`; expired entries read as absent.
     pub fn get(&self, key: &str) -> Option<Value> {
+        if self.is_expired(key, Instant::now()) {
+            return None;
+        }
         self.entries.get(key).cloned()
     }
 
     pub fn put(&mut self, key: impl Into<String>, value: Value) -> Option<Value> {
-        self.entries.insert(key.into(), value)
+        let key = key.into();
+        self.expiries.remove(&key);
+        self.entries.insert(key, value)
+    }
+
+    /// Insert `value` so that it expires `ttl` from now.
+    ///
+    /// A `ttl` too large to represent as a deadline never expires.
+    pub fn put_with_ttl(
+        &mut self,
+        key: impl Into<String>,
+        value: Value,
+        ttl: Duration,
+    ) -> Option<Value> {
+        let key = key.into();
+        match Instant::now().checked_add(ttl) {
+            Some(deadline) => self.expiries.insert(key.clone(), deadline),
+            None => self.expiries.remove(&key),
+        };
+        self.entries.insert(key, value)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.expiries.remove(key);
+        self.entries.remove(key)
     }
 
     pub fn replace(&mut self, snapshot: GlobalStoreSnapshot) {
         self.entries = snapshot.entries;
+        self.expiries = snapshot.expiries;
+    }
+
+    fn is_expired(&self, key: &str, now: Instant) -> bool {
+        self.expiries
+            .get(key)
+            .is_some_and(|deadline| *deadline <= now)
+    }
+
+    /// Copy of the entries that have not expired, with their deadlines.
+    fn live(&self) -> GlobalStoreSnapshot {
+        let now = Instant::now();
+        let entries = self
+            .entries
+            .iter()
+            .filter(|(key, _)| !self.is_expired(key, now))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let expiries = self
+            .expiries
+            .iter()
+            .filter(|(_, deadline)| **deadline > now)
+            .map(|(key, deadline)| (key.clone(), *deadline))
+            .collect();
+        GlobalStoreSnapshot { entries, expiries }
     }
 }
 
@@ -44,9 +104,13 @@ impl Default for GlobalStore {
 }
 
 /// Immutable snapshot of the global store.
+///
+/// Only live entries are captured. Their expiry deadlines travel with the
+/// snapshot so [`restore`] keeps them, but are not persisted.
 #[derive(Clone, Debug, Default)]
 pub struct GlobalStoreSnapshot {
     entries: BTreeMap<String, Value>,
+    expiries: BTreeMap<String, Instant>,
 }
 
 impl GlobalStoreSnapshot {
@@ -67,7 +131,10 @@ impl GlobalStoreSnapshot {
     }
 
     pub fn from_entries(entries: BTreeMap<String, Value>) -> Self {
-        Self { entries }
+        Self {
+            entries,
+            expiries: BTreeMap::new(),
+        }
     }
 
     pub fn entries(&self) -> &BTreeMap<String, Value> {
@@ -77,15 +144,31 @@ impl GlobalStoreSnapshot {
 
 static STORE: Lazy<RwLock<GlobalStore>> = Lazy::new(|| RwLock::new(GlobalStore::new()));
 
+/// Serialises unit tests that touch the process-global store.
+#[cfg(test)]
+pub(crate) fn test_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 /// Reset the global store to an empty map.
 pub fn reset() {
     let mut guard = STORE.write().expect("global store poisoned");
     *guard = GlobalStore::new();
 }
 
-/// Retrieve the current value for a key, if any.
+/// Retrieve the current value for a key, if any; an expired entry is dropped on the way.
 pub fn read(key: &str) -> Option<Value> {
-    let guard = STORE.read().expect("global store poisoned");
+    {
+        let guard = STORE.read().expect("global store poisoned");
+        if !guard.is_expired(key, Instant::now()) {
+            return guard.get(key);
+        }
+    }
+    let mut guard = STORE.write().expect("global store poisoned");
+    if guard.is_expired(key, Instant::now()) {
+        guard.remove(key);
+    }
     guard.get(key)
 }
 
@@ -95,12 +178,34 @@ pub fn write(key: impl Into<String>, value: Value) -> Option<Value> {
     guard.put(key, value)
 }
 
+/// Insert or update a value that expires once `ttl` has elapsed.
+pub fn write_with_ttl(key: impl Into<String>, value: Value, ttl: Duration) -> Option<Value> {
+    let mut guard = STORE.write().expect("global store poisoned");
+    guard.put_with_ttl(key, value, ttl)
+}
+
+/// Remove the value stored under `key`, returning it if it was present.
+pub fn remove(key: &str) -> Option<Value> {
+    let mut guard = STORE.write().expect("global store poisoned");
+    guard.remove(key)
+}
+
+/// Atomically replace the value for `key` with `new` if it currently equals `expected`.
+///
+/// `expected == None` means the key must be absent. Returns whether the swap happened.
+pub fn compare_and_swap(key: &str, expected: Option<Value>, new: Value) -> bool {
+    let mut guard = STORE.write().expect("global store poisoned");
+    if guard.get(key) != expected {
+        return false;
+    }
+    guard.put(key, new);
+    true
+}
+
 /// Acquire a snapshot of the current global store.
 pub fn snapshot() -> GlobalStoreSnapshot {
     let guard = STORE.read().expect("global store poisoned");
-    GlobalStoreSnapshot {
-        entries: guard.entries.clone(),
-    }
+    guard.live()
 }
 
 /// Replace the in-memory store with a provided snapshot.
@@ -109,6 +214,28 @@ pub fn restore(snapshot: GlobalStoreSnapshot) {
     guard.replace(snapshot);
 }
 
+/// Start a transaction: capture the current store so it can be restored with [`rollback_to`].
+pub fn begin_transaction() -> GlobalStoreSnapshot {
+    snapshot()
+}
+
+/// Abandon a transaction, discarding every change made since `begin_transaction`.
+pub fn rollback_to(snapshot: GlobalStoreSnapshot) {
+    restore(snapshot);
+}
+
+/// Run `body`, rolling the store back to its prior contents if it returns `Err`.
+///
+/// Not isolated: other threads may observe (or lose) writes made during `body`.
+pub fn with_transaction<T>(body: impl FnOnce() -> Result<T>) -> Result<T> {
+    let saved = begin_transaction();
+    let result = body();
+    if result.is_err() {
+        rollback_to(saved);
+    }
+    result
+}
+
 /// Canonically encode the snapshot for persistence.
 pub fn encode_snapshot(snapshot: &GlobalStoreSnapshot) -> Result<Vec<u8>> {
     let mut buf = Vec::new();
@@ -193,10 +320,33 @@ fn encode_store_value(buf: &mut Vec<u8>, value: &Value) -> Result<()> {
             push_text(buf, s);
             Ok(())
         }
-        other => bail!(
-            "unsupported value type in global store snapshot: {:?}",
-            other
-        ),
+        Value::Ptr(ptr) => {
+            push_array(buf, 2);
+            push_text(buf, "ptr");
+            push_u64(buf, *ptr);
+            Ok(())
+        }
+        Value::U64(n) => {
+            push_array(buf, 2);
+            push_text(buf, "u64");
+            push_u64(buf, *n);
+            Ok(())
+        }
+        Value::Bytes(bytes) => {
+            push_array(buf, 2);
+            push_text(buf, "bytes");
+            push_bytes(buf, bytes);
+            Ok(())
+        }
+        Value::Token(domain) => {
+            push_array(buf, 2);
+            push_text(buf, "token");
+            match domain {
+                Some(domain) => push_text(buf, domain.as_str()),
+                None => push_null(buf),
+            }
+            Ok(())
+        }
     }
 }
 
@@ -228,7 +378,7 @@ fn decode_snapshot(value: &CborValue) -> Result<GlobalStoreSnapshot> {
                     other => bail!("global store entry must be [key, value], found {other:?}"),
                 }
             }
-            Ok(GlobalStoreSnapshot { entries: map })
+            Ok(GlobalStoreSnapshot::from_entries(map))
         }
         other => bail!("invalid global store snapshot object {other:?}"),
     }
@@ -309,6 +459,53 @@ fn decode_store_value(value: &CborValue) -> Result<Value> {
                         other => bail!("text payload must be UTF-8 string, found {other:?}"),
                     }
                 }
+                "ptr" => {
+                    if items.len() != 2 {
+                        bail!("ptr value must include payload");
+                    }
+                    match &items[1] {
+                        CborValue::Integer(n) => {
+                            let value = u64::try_from(*n)
+                                .map_err(|_| anyhow!("ptr payload out of range"))?;
+                            Ok(Value::Ptr(value))
+                        }
+                        other => bail!("ptr payload must be integer, found {other:?}"),
+                    }
+                }
+                "u64" => {
+                    if items.len() != 2 {
+                        bail!("u64 value must include payload");
+                    }
+                    match &items[1] {
+                        CborValue::Integer(n) => {
+                            let value = u64::try_from(*n)
+                                .map_err(|_| anyhow!("u64 payload out of range"))?;
+                            Ok(Value::U64(value))
+                        }
+                        other => bail!("u64 payload must be integer, found {other:?}"),
+                    }
+                }
+                "bytes" => {
+                    if items.len() != 2 {
+                        bail!("bytes value must include payload");
+                    }
+                    match &items[1] {
+                        CborValue::Bytes(bytes) => Ok(Value::Bytes(bytes.clone())),
+                        other => bail!("bytes payload must be a byte string, found {other:?}"),
+                    }
+                }
+                "token" => {
+                    if items.len() != 2 {
+                        bail!("token value must include payload");
+                    }
+                    match &items[1] {
+                        CborValue::Null => Ok(Value::Token(None)),
+                        CborValue::Text(s) => Ok(Value::Token(Some(EffectDomain::from_name(s)?))),
+                        other => {
+                            bail!("token payload must be domain text or null, found {other:?}")
+                        }
+                    }
+                }
                 other => bail!("unsupported global store value type `{other}`"),
             }
         }
@@ -324,6 +521,7 @@ mod tests {
 
     #[test]
     fn snapshot_roundtrip() -> Result<()> {
+        let _lock = test_lock();
         reset();
         write("demo/item", Value::I64(123));
         write("demo/float", Value::F64(1.5));
@@ -354,10 +552,50 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn snapshot_roundtrip_ptr_and_tokens() -> Result<()> {
+        let mut entries = BTreeMap::new();
+        entries.insert("demo/ptr".to_string(), Value::Ptr(u64::MAX));
+        entries.insert("demo/u64".to_string(), Value::U64(u64::MAX - 1));
+        entries.insert(
+            "demo/bytes".to_string(),
+            Value::Bytes(vec![0xde, 0xad, 0x00]),
+        );
+        entries.insert("demo/empty_bytes".to_string(), Value::Bytes(Vec::new()));
+        entries.insert("demo/token".to_string(), Value::Token(None));
+        entries.insert(
+            "demo/io_token".to_string(),
+            Value::Token(Some(EffectDomain::Io)),
+        );
+        entries.insert(
+            "demo/state_token".to_string(),
+            Value::Token(Some(EffectDomain::State)),
+        );
+        let snapshot = GlobalStoreSnapshot::from_entries(entries.clone());
+        let cbor = encode_snapshot(&snapshot)?;
+        let value: CborValue = serde_cbor::from_slice(&cbor)?;
+        let decoded = decode_snapshot(&value)?;
+        assert_eq!(decoded.entries(), &entries);
+        Ok(())
+    }
+
+    #[test]
+    fn compare_and_swap_checks_expected_value() {
+        let _lock = test_lock();
+        let key = "demo.cas/counter";
+        assert!(compare_and_swap(key, None, Value::I64(1)));
+        assert!(!compare_and_swap(key, None, Value::I64(5)));
+        assert!(!compare_and_swap(key, Some(Value::I64(0)), Value::I64(5)));
+        assert_eq!(read(key), Some(Value::I64(1)));
+        assert!(compare_and_swap(key, Some(Value::I64(1)), Value::I64(2)));
+        assert_eq!(read(key), Some(Value::I64(2)));
+    }
+
     #[test]
     fn store_and_load_snapshot_from_db() -> Result<()> {
         let conn = Connection::open_in_memory()?;
         db::install_schema(&conn)?;
+        let _lock = test_lock();
         reset();
         write("demo.item", Value::I64(7));
         write("demo.float", Value::F64(2.5));
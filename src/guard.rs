@@ -4,7 +4,9 @@ use serde::Deserialize;
 use serde_bytes::ByteBuf;
 
 use crate::cbor::{push_array, push_bytes, push_text};
-use crate::types::{EffectMask, TypeTag, effect_mask};
+use crate::node::{self, NodeCanon, NodeInput, NodeKind, NodePayload};
+use crate::types::{EffectMask, TypeTag, effect_mask, render_comma_list};
+use crate::word::{self, WordCanon};
 use crate::{cid, db};
 
 #[derive(Clone, Debug)]
@@ -107,6 +109,129 @@ pub fn load_guard_info(conn: &Connection, cid_bytes: &[u8; 32]) -> Result<GuardI
     })
 }
 
+/// Guard passing when both `a` and `b` pass; `b` only runs once `a` has passed.
+pub fn compose_and(conn: &Connection, a: &[u8; 32], b: &[u8; 32]) -> Result<[u8; 32]> {
+    compose(conn, a, b, true)
+}
+
+/// Guard passing when `a` or `b` passes; `b` only runs once `a` has failed.
+pub fn compose_or(conn: &Connection, a: &[u8; 32], b: &[u8; 32]) -> Result<[u8; 32]> {
+    compose(conn, a, b, false)
+}
+
+/// Store a guard that CALLs both sub-guards on its own arguments and combines
+/// them through short-circuiting IF nodes, returning 1 or 0.
+fn compose(conn: &Connection, a: &[u8; 32], b: &[u8; 32], and: bool) -> Result<[u8; 32]> {
+    let left = load_guard_info(conn, a)?;
+    let right = load_guard_info(conn, b)?;
+    for (cid_bytes, info) in [(a, &left), (b, &right)] {
+        if !info.effects.is_empty() || info.effect_mask != effect_mask::NONE {
+            bail!("guard {} is not pure", cid::to_hex(cid_bytes));
+        }
+        if info.results != [TypeTag::I64] {
+            bail!("guard {} must return a single i64", cid::to_hex(cid_bytes));
+        }
+    }
+    if left.params != right.params {
+        bail!(
+            "cannot compose guards with different params: ({}) vs ({})",
+            render_comma_list(&left.params),
+            render_comma_list(&right.params)
+        );
+    }
+
+    let i64_atom = TypeTag::I64.as_atom().to_string();
+    let params: Vec<String> = left
+        .params
+        .iter()
+        .map(|t| t.as_atom().to_string())
+        .collect();
+    let store = |kind, out: String, inputs, payload| -> Result<NodeInput> {
+        let node = NodeCanon {
+            kind,
+            out: vec![out],
+            inputs,
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload,
+        };
+        Ok(NodeInput {
+            cid: node::store_node(conn, &node)?.cid,
+            port: 0,
+        })
+    };
+    let mut args = Vec::with_capacity(params.len());
+    for (idx, atom) in params.iter().enumerate() {
+        let arg = NodePayload::Arg(idx as u32);
+        args.push(store(NodeKind::Arg, atom.clone(), Vec::new(), arg)?);
+    }
+    // Guards are CALLed through a word wrapping their root, as attached guards are.
+    let call = |info: &GuardInfo| -> Result<NodeInput> {
+        let wrapper = WordCanon {
+            root: info.root,
+            params: params.clone(),
+            results: vec![i64_atom.clone()],
+            effects: Vec::new(),
+            effect_mask: effect_mask::NONE,
+            guards: Vec::new(),
+            doc: None,
+        };
+        let word_cid = word::store_word(conn, &wrapper)?.cid;
+        store(
+            NodeKind::Call,
+            i64_atom.clone(),
+            args.clone(),
+            NodePayload::Word(word_cid),
+        )
+    };
+    let first = call(&left)?;
+    let second = call(&right)?;
+    let lit = |value| {
+        store(
+            NodeKind::Lit,
+            i64_atom.clone(),
+            Vec::new(),
+            NodePayload::LitI64(value),
+        )
+    };
+    let (one, zero) = (lit(1)?, lit(0)?);
+    let branch = |cond: NodeInput, true_cont, false_cont| {
+        let payload = NodePayload::If {
+            true_cont,
+            false_cont,
+        };
+        store(NodeKind::If, i64_atom.clone(), vec![cond], payload)
+    };
+    let second_passed = branch(second, one, zero)?;
+    let result = if and {
+        branch(first, second_passed, zero)?
+    } else {
+        branch(first, one, second_passed)?
+    };
+    let root = node::store_node(
+        conn,
+        &NodeCanon {
+            kind: NodeKind::Return,
+            out: vec![i64_atom.clone()],
+            inputs: Vec::new(),
+            vals: vec![result],
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::Return,
+        },
+    )?
+    .cid;
+    let guard = GuardCanon {
+        root,
+        params,
+        results: vec![i64_atom],
+        effects: Vec::new(),
+        effect_mask: effect_mask::NONE,
+    };
+    Ok(store_guard(conn, &guard)?.cid)
+}
+
 fn bytebuf_to_array(buf: &ByteBuf) -> Result<[u8; 32]> {
     let slice = buf.as_slice();
     if slice.len() != 32 {
@@ -126,3 +251,69 @@ struct GuardRecord(
     Vec<ByteBuf>,
     #[serde(default)] Option<u32>,
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::GraphBuilder;
+    use crate::interp::{Value, run_guard};
+    use crate::prim::{PrimCanon, store_prim};
+
+    #[test]
+    fn composed_guards_combine_their_verdicts() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let pair = [TypeTag::I64, TypeTag::I64];
+        let one = [TypeTag::I64];
+        // Same-signature prims share a CID unless their docs differ.
+        let comparison = |name: &str| -> Result<[u8; 32]> {
+            let spec = PrimCanon {
+                params: &pair,
+                results: &one,
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: Some(name),
+                variadic: None,
+                builtin: None,
+            };
+            let cid = store_prim(&conn, &spec)?.cid;
+            db::put_name(&conn, "prim", &format!("core/{name}"), &cid)?;
+            Ok(cid)
+        };
+        let gt = comparison("gt_i64")?;
+        let lt = comparison("lt_i64")?;
+        let threshold = |prim: [u8; 32], bound: i64, name: &str| -> Result<[u8; 32]> {
+            let mut builder = GraphBuilder::new(&conn);
+            builder.begin_guard(&one)?;
+            builder.push_lit_i64(bound)?;
+            builder.apply_prim(prim)?;
+            builder.finish_guard(&one, &one, Some(name))
+        };
+        let positive = threshold(gt, 0, "demo/positive")?;
+        let small = threshold(lt, 10, "demo/small")?;
+
+        let negative = threshold(lt, 0, "demo/negative")?;
+
+        let both = compose_and(&conn, &positive, &small)?;
+        let either = compose_or(&conn, &positive, &negative)?;
+        for (n, expect_and, expect_or) in [
+            (-5, false, true),
+            (0, false, false),
+            (3, true, true),
+            (10, false, true),
+            (42, false, true),
+        ] {
+            let args = [Value::I64(n)];
+            assert_eq!(run_guard(&conn, &both, &args)?, expect_and, "and({n})");
+            assert_eq!(run_guard(&conn, &either, &args)?, expect_or, "or({n})");
+        }
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_guard(&pair)?;
+        builder.apply_prim(gt)?;
+        let binary = builder.finish_guard(&pair, &one, None)?;
+        let err = compose_and(&conn, &positive, &binary).unwrap_err();
+        assert!(err.to_string().contains("different params"), "{err}");
+        Ok(())
+    }
+}
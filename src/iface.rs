@@ -1,7 +1,10 @@
 //! Canonical encoding for interface descriptors (import/export surfaces).
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use rusqlite::Connection;
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+use serde_cbor::Value;
 
 use crate::cbor::{push_array, push_bytes, push_text};
 use crate::{cid, db};
@@ -36,6 +39,130 @@ pub fn encode(iface: &IfaceCanon) -> Vec<u8> {
     buf
 }
 
+/// Decode an interface previously produced by [`encode`].
+pub fn decode(cbor: &[u8]) -> Result<IfaceCanon> {
+    let IfaceRecord(tag, symbols) = serde_cbor::from_slice(cbor)?;
+    if tag != 3 {
+        bail!("object tag mismatch while loading iface: {tag}");
+    }
+    let names = symbols
+        .into_iter()
+        .map(|SymbolRecord(name, params, results, effects)| {
+            Ok(IfaceSymbol {
+                name,
+                params,
+                results,
+                effects: effects
+                    .iter()
+                    .map(|buf| cid::from_slice(buf))
+                    .collect::<Result<Vec<_>>>()?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(IfaceCanon { names })
+}
+
+#[derive(Deserialize)]
+struct IfaceRecord(u64, Vec<SymbolRecord>);
+
+#[derive(Deserialize)]
+struct SymbolRecord(String, Vec<String>, Vec<String>, Vec<ByteBuf>);
+
+/// Decode an interface in the pre-schema-3 encoding, where the symbol array's
+/// length counted symbols but each symbol's four fields were written as bare
+/// items. Returns `None` when `cbor` already uses the current encoding.
+pub(crate) fn decode_legacy(cbor: &[u8]) -> Result<Option<IfaceCanon>> {
+    if decode(cbor).is_ok() {
+        return Ok(None);
+    }
+    let mut items = serde_cbor::Deserializer::from_slice(cbor).into_iter::<Value>();
+    let head = match items.next().transpose()? {
+        Some(Value::Array(head)) => head,
+        _ => bail!("iface object is not an array"),
+    };
+    let [Value::Integer(3), Value::Array(first)] = &head[..] else {
+        bail!("iface object does not match the legacy layout");
+    };
+    let mut flat = first.clone();
+    for item in items {
+        flat.push(item?);
+    }
+    if flat.len() != first.len() * 4 {
+        bail!(
+            "legacy iface has {} symbol items, expected {}",
+            flat.len(),
+            first.len() * 4
+        );
+    }
+    let texts = |value: &Value| match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Text(text) => Ok(text.clone()),
+                _ => bail!("legacy iface type atom is not text"),
+            })
+            .collect::<Result<Vec<_>>>(),
+        _ => bail!("legacy iface type list is not an array"),
+    };
+    let names = flat
+        .chunks(4)
+        .map(|fields| {
+            let [Value::Text(name), params, results, Value::Array(effects)] = fields else {
+                bail!("legacy iface symbol does not match name, params, results, effects");
+            };
+            Ok(IfaceSymbol {
+                name: name.clone(),
+                params: texts(params)?,
+                results: texts(results)?,
+                effects: effects
+                    .iter()
+                    .map(|item| match item {
+                        Value::Bytes(buf) => cid::from_slice(buf),
+                        _ => bail!("legacy iface effect is not a CID"),
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(IfaceCanon { names }))
+}
+
+/// Load and decode the interface stored under `cid`.
+pub fn load_iface(conn: &Connection, cid: &[u8; 32]) -> Result<IfaceCanon> {
+    let (kind, cbor) = db::load_object_cbor(conn, cid)?;
+    if kind != "iface" {
+        bail!("object {} is a {kind}, not an iface", cid::to_hex(cid));
+    }
+    decode(&cbor)
+}
+
+/// Union the symbols of two interfaces.
+///
+/// A name exported by both sides must carry the same signature and effects.
+pub fn merge(a: &IfaceCanon, b: &IfaceCanon) -> Result<IfaceCanon> {
+    let mut names = a.names.clone();
+    for symbol in &b.names {
+        match names.iter().find(|existing| existing.name == symbol.name) {
+            Some(existing) if same_signature(existing, symbol) => {}
+            Some(_) => bail!(
+                "cannot merge interfaces: `{}` is exported with different signatures",
+                symbol.name
+            ),
+            None => names.push(symbol.clone()),
+        }
+    }
+    names.sort_by(|x, y| x.name.cmp(&y.name));
+    Ok(IfaceCanon { names })
+}
+
+fn same_signature(a: &IfaceSymbol, b: &IfaceSymbol) -> bool {
+    let mut a_effects = a.effects.clone();
+    a_effects.sort();
+    let mut b_effects = b.effects.clone();
+    b_effects.sort();
+    a.params == b.params && a.results == b.results && a_effects == b_effects
+}
+
 /// Persist an interface object to the store.
 pub fn store_iface(conn: &Connection, iface: &IfaceCanon) -> Result<IfaceStoreOutcome> {
     let cbor = encode(iface);
@@ -78,6 +205,7 @@ fn encode_names(buf: &mut Vec<u8>, names: &[IfaceSymbol]) {
 
     push_array(buf, sorted.len() as u64);
     for symbol in sorted {
+        push_array(buf, 4);
         push_text(buf, &symbol.name);
 
         push_array(buf, symbol.params.len() as u64);
@@ -100,7 +228,7 @@ fn encode_names(buf: &mut Vec<u8>, names: &[IfaceSymbol]) {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::db;
     use crate::types::{TypeTag, effect_mask};
@@ -136,6 +264,45 @@ mod tests {
         assert!(hello_pos < world_pos);
     }
 
+    #[test]
+    fn decode_legacy_reads_inline_symbol_fields() -> Result<()> {
+        let iface = IfaceCanon {
+            names: vec![
+                symbol("add", &["i64", "i64"], &["i64"]),
+                IfaceSymbol {
+                    effects: vec![[0x11; 32]],
+                    ..symbol("print", &["text"], &["unit"])
+                },
+            ],
+        };
+        assert!(decode_legacy(&encode(&iface))?.is_none());
+        let decoded = decode_legacy(&legacy_encode(&iface))?.expect("legacy encoding");
+        assert_eq!(encode(&decoded), encode(&iface));
+        Ok(())
+    }
+
+    /// The pre-schema-3 encoding: each symbol's fields written inline.
+    pub(crate) fn legacy_encode(iface: &IfaceCanon) -> Vec<u8> {
+        let mut buf = Vec::new();
+        push_array(&mut buf, 2);
+        crate::cbor::push_u32(&mut buf, 3);
+        push_array(&mut buf, iface.names.len() as u64);
+        for symbol in &iface.names {
+            push_text(&mut buf, &symbol.name);
+            for list in [&symbol.params, &symbol.results] {
+                push_array(&mut buf, list.len() as u64);
+                for atom in list {
+                    push_text(&mut buf, atom);
+                }
+            }
+            push_array(&mut buf, symbol.effects.len() as u64);
+            for effect in &symbol.effects {
+                push_bytes(&mut buf, effect);
+            }
+        }
+        buf
+    }
+
     #[test]
     fn derive_interface_from_words() -> Result<()> {
         let conn = Connection::open_in_memory()?;
@@ -148,6 +315,7 @@ mod tests {
             effects: effects.clone(),
             effect_mask: effect_mask::IO,
             guards: Vec::new(),
+            doc: None,
         };
         let outcome = store_word(&conn, &word)?;
         let iface = derive_from_exports(&conn, &[("add".into(), outcome.cid)])?;
@@ -156,4 +324,42 @@ mod tests {
         assert_eq!(iface.names[0].effects, effects);
         Ok(())
     }
+
+    fn symbol(name: &str, params: &[&str], results: &[&str]) -> IfaceSymbol {
+        IfaceSymbol {
+            name: name.to_string(),
+            params: params.iter().map(|p| p.to_string()).collect(),
+            results: results.iter().map(|r| r.to_string()).collect(),
+            effects: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn merge_unions_disjoint_and_rejects_conflicts() -> Result<()> {
+        let a = IfaceCanon {
+            names: vec![
+                symbol("sub", &["i64", "i64"], &["i64"]),
+                symbol("add", &["i64", "i64"], &["i64"]),
+            ],
+        };
+        let b = IfaceCanon {
+            names: vec![
+                symbol("neg", &["i64"], &["i64"]),
+                symbol("add", &["i64", "i64"], &["i64"]),
+            ],
+        };
+        let merged = merge(&a, &b)?;
+        let names: Vec<_> = merged.names.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["add", "neg", "sub"]);
+
+        let decoded = decode(&encode(&merged))?;
+        assert_eq!(encode(&decoded), encode(&merged));
+
+        let conflicting = IfaceCanon {
+            names: vec![symbol("add", &["f64", "f64"], &["f64"])],
+        };
+        let err = merge(&a, &conflicting).unwrap_err();
+        assert!(err.to_string().contains("`add`"), "{err}");
+        Ok(())
+    }
 }
@@ -1,7 +1,10 @@
+use std::fmt::Write as _;
+
 use anyhow::{Result, bail};
 use rusqlite::Connection;
+use serde::Deserialize;
 
-use crate::cbor::{push_array, push_map, push_text};
+use crate::cbor::{push_array, push_header, push_map, push_text, push_u32};
 use crate::{cid, db};
 
 #[derive(Clone, Debug)]
@@ -77,13 +80,140 @@ pub fn store_agent(conn: &Connection, agent: &AgentCanon) -> Result<AgentStoreOu
     Ok(AgentStoreOutcome { cid, inserted })
 }
 
+/// Name-index scope mapping an active pair to its rule CID.
+pub const RULE_INDEX_SCOPE: &str = "rule.lhs";
+
+/// Index key for the pair: `"<len(lhs_a)>:lhs_a lhs_b"`. The length prefix
+/// keeps kinds that contain spaces from colliding (`"a b" + "c"` vs `"a" + "b c"`).
+fn rule_index_key(lhs_a: &str, lhs_b: &str) -> String {
+    format!("{}:{lhs_a} {lhs_b}", lhs_a.len())
+}
+
+/// Persist a rule and index it by its `(lhs_a, lhs_b)` pair.
 pub fn store_rule(conn: &Connection, rule: &RuleCanon) -> Result<RuleStoreOutcome> {
     let cbor = encode_rule(rule);
     let cid = cid::compute(&cbor);
     let inserted = db::put_object(conn, &cid, "rule", &cbor)?;
+    db::put_name(
+        conn,
+        RULE_INDEX_SCOPE,
+        &rule_index_key(rule.lhs_a, rule.lhs_b),
+        &cid,
+    )?;
     Ok(RuleStoreOutcome { cid, inserted })
 }
 
+/// Decoded agent descriptor as stored in the object table.
+#[derive(Clone, Debug)]
+pub struct AgentInfo {
+    /// Agent kind name (the `name` field of the canonical map).
+    pub kind: String,
+    /// Port names in declaration order; index 0 is principal.
+    pub ports: Vec<String>,
+    pub doc: Option<String>,
+}
+
+/// Load an agent descriptor by CID.
+pub fn load_agent_info(conn: &Connection, cid_bytes: &[u8; 32]) -> Result<AgentInfo> {
+    let cbor = db::load_cbor_for_kind(conn, cid_bytes, "agent")?;
+    let record: AgentRecord = serde_cbor::from_slice(&cbor)?;
+    if record.kind != "agent" {
+        bail!("object kind mismatch while loading agent: {}", record.kind);
+    }
+    Ok(AgentInfo {
+        kind: record.name,
+        ports: record.ports,
+        doc: record.doc,
+    })
+}
+
+#[derive(Deserialize)]
+struct AgentRecord {
+    kind: String,
+    name: String,
+    ports: Vec<String>,
+    #[serde(default)]
+    doc: Option<String>,
+}
+
+/// Resolve an agent kind to its descriptor.
+///
+/// The kind is first looked up as a name in the `agent` scope of `name_index`;
+/// failing that, stored agents are scanned for a matching kind. A name bound to
+/// an agent of another kind is an error.
+pub fn find_agent_by_kind(conn: &Connection, kind: &str) -> Result<Option<AgentInfo>> {
+    if let Some(cid) = db::get_name(conn, "agent", kind)? {
+        let info = load_agent_info(conn, &cid)?;
+        if info.kind != kind {
+            bail!(
+                "agent name `{kind}` is bound to {}, an agent of kind `{}`",
+                cid::to_hex(&cid),
+                info.kind
+            );
+        }
+        return Ok(Some(info));
+    }
+    for cbor in db::load_all_cbor_for_kind(conn, "agent")? {
+        let record: AgentRecord = serde_cbor::from_slice(&cbor)?;
+        if record.name == kind {
+            return Ok(Some(AgentInfo {
+                kind: record.name,
+                ports: record.ports,
+                doc: record.doc,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Check that every `(A port)` / `(B port)` reference in a rule body names a
+/// declared port on the corresponding LHS agent. Ports on agents introduced by
+/// `new` aliases are not checked.
+pub fn validate_rule(conn: &Connection, rule: &RuleCanon) -> Result<()> {
+    let agent_a = find_agent_by_kind(conn, rule.lhs_a)?
+        .ok_or_else(|| anyhow::anyhow!("rule references unknown agent `{}`", rule.lhs_a))?;
+    let agent_b = find_agent_by_kind(conn, rule.lhs_b)?
+        .ok_or_else(|| anyhow::anyhow!("rule references unknown agent `{}`", rule.lhs_b))?;
+    if rule.body_syntax.trim() == "(pair-unpair)" {
+        if agent_a.ports.len() < 3 || agent_b.ports.len() < 3 {
+            bail!("builtin (pair-unpair) requires agents with at least three ports");
+        }
+        return Ok(());
+    }
+    let forms = parse_sexpr_sequence(rule.body_syntax)?;
+    for form in &forms {
+        check_port_refs(form, &agent_a, &agent_b)?;
+    }
+    Ok(())
+}
+
+fn check_port_refs(form: &SExpr, agent_a: &AgentInfo, agent_b: &AgentInfo) -> Result<()> {
+    let SExpr::List(items) = form else {
+        return Ok(());
+    };
+    if let [SExpr::Sym(sym), SExpr::Sym(port)] = items.as_slice() {
+        let agent = match sym.as_str() {
+            "A" => Some(agent_a),
+            "B" => Some(agent_b),
+            _ => None,
+        };
+        if let Some(agent) = agent
+            && !agent.ports.iter().any(|p| p == port)
+        {
+            bail!(
+                "rule references unknown port `{port}` on {sym} (`{}` has ports: {})",
+                agent.kind,
+                agent.ports.join(", ")
+            );
+        }
+        return Ok(());
+    }
+    for item in items {
+        check_port_refs(item, agent_a, agent_b)?;
+    }
+    Ok(())
+}
+
 /// Minimal net representation (placeholder). Future: ports, wires, active pairs.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct AgentId(pub usize);
@@ -115,6 +245,12 @@ pub struct Net {
     pub entry: Option<AgentId>,
 }
 
+impl Default for Net {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Net {
     pub fn new() -> Self {
         Self {
@@ -175,18 +311,26 @@ impl Net {
     }
 
     pub fn find_active_pair(&self) -> Option<(PortRef, PortRef)> {
-        for w in &self.wires {
-            if let Some((a, b)) = w.0 {
-                if a.port == 0 && b.port == 0 {
-                    let aa = &self.agents[a.agent.0];
-                    let bb = &self.agents[b.agent.0];
-                    if !aa.deleted && !bb.deleted {
-                        return Some((a, b));
-                    }
-                }
+        self.active_pairs().next()
+    }
+
+    /// Number of live principal-principal wires (diagnostics).
+    pub fn active_pair_count(&self) -> usize {
+        self.active_pairs().count()
+    }
+
+    fn active_pairs(&self) -> impl Iterator<Item = (PortRef, PortRef)> + '_ {
+        self.wires.iter().filter_map(|w| match w.0 {
+            Some((a, b))
+                if a.port == 0
+                    && b.port == 0
+                    && !self.agents[a.agent.0].deleted
+                    && !self.agents[b.agent.0].deleted =>
+            {
+                Some((a, b))
             }
-        }
-        None
+            _ => None,
+        })
     }
 }
 
@@ -259,6 +403,240 @@ pub fn reduce_step(net: &mut Net) -> Result<bool> {
     Ok(true)
 }
 
+/// Result of persisting a net snapshot.
+pub struct NetStoreOutcome {
+    pub cid: [u8; 32],
+    pub inserted: bool,
+}
+
+/// Encode a net into canonical CBOR.
+///
+/// Layout: `[9, [[kind, [port...]]...], [[a, a_port, b, b_port]...], [entry?]]`.
+/// Deleted agents and dead wires are compacted away, so live agents are
+/// renumbered in their original order; port wiring is implied by the wire list.
+pub fn encode_net(net: &Net) -> Vec<u8> {
+    let mut remap = vec![None; net.agents.len()];
+    let mut live = 0usize;
+    for (idx, agent) in net.agents.iter().enumerate() {
+        if !agent.deleted {
+            remap[idx] = Some(live);
+            live += 1;
+        }
+    }
+    let wires: Vec<(usize, usize, usize, usize)> = net
+        .wires
+        .iter()
+        .filter_map(|wire| {
+            let (x, y) = wire.0?;
+            Some((remap[x.agent.0]?, x.port, remap[y.agent.0]?, y.port))
+        })
+        .collect();
+
+    let mut buf = Vec::new();
+    push_array(&mut buf, 4);
+    push_u32(&mut buf, 9); // object tag for "net"
+    push_array(&mut buf, live as u64);
+    for agent in net.agents.iter().filter(|agent| !agent.deleted) {
+        push_array(&mut buf, 2);
+        push_text(&mut buf, &agent.kind);
+        push_array(&mut buf, agent.port_names.len() as u64);
+        for name in &agent.port_names {
+            push_text(&mut buf, name);
+        }
+    }
+    push_array(&mut buf, wires.len() as u64);
+    for (a, a_port, b, b_port) in wires {
+        push_array(&mut buf, 4);
+        push_header(&mut buf, 0, a as u64);
+        push_header(&mut buf, 0, a_port as u64);
+        push_header(&mut buf, 0, b as u64);
+        push_header(&mut buf, 0, b_port as u64);
+    }
+    match net.entry.and_then(|entry| remap[entry.0]) {
+        Some(entry) => {
+            push_array(&mut buf, 1);
+            push_header(&mut buf, 0, entry as u64);
+        }
+        None => push_array(&mut buf, 0),
+    }
+    buf
+}
+
+/// Decode a net previously produced by [`encode_net`].
+pub fn decode_net(bytes: &[u8]) -> Result<Net> {
+    let NetRecord(tag, agents, wires, entry) = serde_cbor::from_slice(bytes)?;
+    if tag != 9 {
+        bail!("object tag mismatch while loading net: {tag}");
+    }
+    let mut net = Net::new();
+    for NetAgentRecord(kind, ports) in &agents {
+        let ports: Vec<&str> = ports.iter().map(|s| s.as_str()).collect();
+        net.add_agent(kind, &ports);
+    }
+    let port_ref = |net: &Net, agent: usize, port: usize| -> Result<PortRef> {
+        match net.agents.get(agent) {
+            Some(a) if port < a.ports.len() => Ok(PortRef {
+                agent: AgentId(agent),
+                port,
+            }),
+            _ => bail!("net wire references missing port {agent}.{port}"),
+        }
+    };
+    for (a, a_port, b, b_port) in wires {
+        let x = port_ref(&net, a, a_port)?;
+        let y = port_ref(&net, b, b_port)?;
+        net.connect(x, y)?;
+    }
+    match entry.as_slice() {
+        [] => {}
+        [idx] if *idx < net.agents.len() => net.entry = Some(AgentId(*idx)),
+        _ => bail!("invalid net entry {entry:?}"),
+    }
+    Ok(net)
+}
+
+/// Persist a net snapshot in the object store.
+pub fn store_net(conn: &Connection, net: &Net) -> Result<NetStoreOutcome> {
+    let cbor = encode_net(net);
+    let cid = cid::compute(&cbor);
+    let inserted = db::put_object(conn, &cid, "net", &cbor)?;
+    Ok(NetStoreOutcome { cid, inserted })
+}
+
+/// Load a net snapshot from the object store.
+pub fn load_net(conn: &Connection, cid_bytes: &[u8; 32]) -> Result<Net> {
+    let cbor = db::load_cbor_for_kind(conn, cid_bytes, "net")?;
+    decode_net(&cbor)
+}
+
+#[derive(Deserialize)]
+struct NetRecord(
+    u64,
+    Vec<NetAgentRecord>,
+    Vec<(usize, usize, usize, usize)>,
+    Vec<usize>,
+);
+
+#[derive(Deserialize)]
+struct NetAgentRecord(String, Vec<String>);
+
+/// Render a net as a Graphviz DOT graph.
+///
+/// Each live agent becomes a record node listing its kind and ports; each live
+/// wire becomes an edge between port anchors, with principal-principal (active)
+/// edges drawn bold red. Free ports dangle to anonymous point nodes.
+pub fn net_to_dot(net: &Net) -> String {
+    let mut out = String::new();
+    out.push_str("graph net {\n");
+    out.push_str("  node [shape=record];\n");
+    for (idx, agent) in net.agents.iter().enumerate() {
+        if agent.deleted {
+            continue;
+        }
+        let ports = agent
+            .port_names
+            .iter()
+            .enumerate()
+            .map(|(port, name)| format!("<p{port}>{}", escape_dot_record(name)))
+            .collect::<Vec<_>>()
+            .join("|");
+        let extra = if net.entry == Some(AgentId(idx)) {
+            ", peripheries=2"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            out,
+            "  a{idx} [label=\"{{{}|{{{ports}}}}}\"{extra}];",
+            escape_dot_record(&agent.kind)
+        );
+    }
+    for wire in &net.wires {
+        let Some((x, y)) = wire.0 else {
+            continue;
+        };
+        if net.agents[x.agent.0].deleted || net.agents[y.agent.0].deleted {
+            continue;
+        }
+        let style = if x.port == 0 && y.port == 0 {
+            " [color=red, style=bold]"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            out,
+            "  a{}:p{} -- a{}:p{}{style};",
+            x.agent.0, x.port, y.agent.0, y.port
+        );
+    }
+    let mut free = 0usize;
+    for (idx, agent) in net.agents.iter().enumerate() {
+        if agent.deleted {
+            continue;
+        }
+        for (port, wire) in agent.ports.iter().enumerate() {
+            if wire.is_none() {
+                let _ = writeln!(out, "  free{free} [shape=point];");
+                let _ = writeln!(out, "  a{idx}:p{port} -- free{free};");
+                free += 1;
+            }
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn escape_dot_record(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Decode a stored rule's `(lhs_a, lhs_b, rewire)`; `None` when fields are missing.
+fn decode_rule(cbor: &[u8]) -> Result<Option<(String, String, String)>> {
+    // Decode minimal fields: kind, lhs (array of two), rewire
+    let value: serde_cbor::Value = serde_cbor::from_slice(cbor)?;
+    let map = match value {
+        serde_cbor::Value::Map(m) => m,
+        _ => return Ok(None),
+    };
+    let mut lhs_a = None;
+    let mut lhs_b = None;
+    let mut rewire = None;
+    for (k, v) in map {
+        match k {
+            serde_cbor::Value::Text(ref s) if s == "lhs" => match v {
+                serde_cbor::Value::Array(items) if items.len() == 2 => {
+                    lhs_a = items.first().and_then(|x| match x {
+                        serde_cbor::Value::Text(s) => Some(s.clone()),
+                        _ => None,
+                    });
+                    lhs_b = items.get(1).and_then(|x| match x {
+                        serde_cbor::Value::Text(s) => Some(s.clone()),
+                        _ => None,
+                    });
+                }
+                _ => {}
+            },
+            serde_cbor::Value::Text(ref s) if s == "rewire" => {
+                if let serde_cbor::Value::Text(body) = v {
+                    rewire = Some(body.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(match (lhs_a, lhs_b, rewire) {
+        (Some(a), Some(b), Some(body)) => Some((a, b, body)),
+        _ => None,
+    })
+}
+
 /// In-memory rule table loaded from the object store.
 pub struct Reducer {
     /// Map from (lhs_a, lhs_b) -> body_syntax
@@ -266,46 +644,75 @@ pub struct Reducer {
 }
 
 impl Reducer {
+    /// Load and decode every stored rule.
     pub fn new(conn: &Connection) -> Result<Self> {
-        let mut rules = std::collections::HashMap::new();
+        let mut reducer = Self::lazy();
         for cbor in db::load_all_cbor_for_kind(conn, "rule")? {
-            // Decode minimal fields: kind, lhs (array of two), rewire
-            let value: serde_cbor::Value = serde_cbor::from_slice(&cbor)?;
-            let map = match value {
-                serde_cbor::Value::Map(m) => m,
-                _ => continue,
-            };
-            let mut lhs_a = None;
-            let mut lhs_b = None;
-            let mut rewire = None;
-            for (k, v) in map {
-                match k {
-                    serde_cbor::Value::Text(ref s) if s == "lhs" => match v {
-                        serde_cbor::Value::Array(items) if items.len() == 2 => {
-                            lhs_a = items.get(0).and_then(|x| match x {
-                                serde_cbor::Value::Text(s) => Some(s.clone()),
-                                _ => None,
-                            });
-                            lhs_b = items.get(1).and_then(|x| match x {
-                                serde_cbor::Value::Text(s) => Some(s.clone()),
-                                _ => None,
-                            });
-                        }
-                        _ => {}
-                    },
-                    serde_cbor::Value::Text(ref s) if s == "rewire" => {
-                        if let serde_cbor::Value::Text(body) = v {
-                            rewire = Some(body.clone());
-                        }
-                    }
-                    _ => {}
-                }
+            if let Some((a, b, body)) = decode_rule(&cbor)? {
+                reducer.rules.insert((a, b), body);
+            }
+        }
+        Ok(reducer)
+    }
+
+    /// Start with an empty table; rules are pulled in per active pair with
+    /// [`load_rules_for_pair`](Self::load_rules_for_pair) or
+    /// [`step_lazy`](Self::step_lazy).
+    pub fn lazy() -> Self {
+        Self {
+            rules: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Number of rules currently decoded into the table.
+    pub fn loaded_rules(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Decode the rule stored under `cid` and add (or replace) it in the table.
+    pub fn reload_rule(&mut self, conn: &Connection, cid_bytes: &[u8; 32]) -> Result<()> {
+        let (kind, cbor) = db::load_object_cbor(conn, cid_bytes)?;
+        if kind != "rule" {
+            bail!("object {} is not a rule", cid::to_hex(cid_bytes));
+        }
+        let Some((a, b, body)) = decode_rule(&cbor)? else {
+            bail!("rule {} is malformed", cid::to_hex(cid_bytes));
+        };
+        self.rules.insert((a, b), body);
+        Ok(())
+    }
+
+    /// Load the rules indexed for the active pair `(kind_a, kind_b)`, in
+    /// either orientation, without decoding any others.
+    ///
+    /// Only rules written through [`store_rule`] are indexed. Returns whether a
+    /// rule for the pair is now in the table.
+    pub fn load_rules_for_pair(
+        &mut self,
+        conn: &Connection,
+        kind_a: &str,
+        kind_b: &str,
+    ) -> Result<bool> {
+        for (a, b) in [(kind_a, kind_b), (kind_b, kind_a)] {
+            if self.rules.contains_key(&(a.to_string(), b.to_string())) {
+                return Ok(true);
             }
-            if let (Some(a), Some(b), Some(body)) = (lhs_a, lhs_b, rewire) {
-                rules.insert((a, b), body);
+            if let Some(rule_cid) = db::get_name(conn, RULE_INDEX_SCOPE, &rule_index_key(a, b))? {
+                self.reload_rule(conn, &rule_cid)?;
+                return Ok(true);
             }
         }
-        Ok(Self { rules })
+        Ok(false)
+    }
+
+    /// Like [`step`](Self::step), first loading the rule for the next active pair.
+    pub fn step_lazy(&mut self, conn: &Connection, net: &mut Net) -> Result<bool> {
+        if let Some((a, b)) = net.find_active_pair() {
+            let kind_a = net.agents[a.agent.0].kind.clone();
+            let kind_b = net.agents[b.agent.0].kind.clone();
+            self.load_rules_for_pair(conn, &kind_a, &kind_b)?;
+        }
+        self.step(net)
     }
 
     /// Apply one rule step if possible. Returns true if a rule was applied.
@@ -313,16 +720,59 @@ impl Reducer {
         let Some((a, b)) = net.find_active_pair() else {
             return Ok(false);
         };
+        match self.match_rule(net, a, b) {
+            Some((body, lhs_a, lhs_b)) => {
+                let body = body.to_string();
+                self.apply_rewire(&body, net, lhs_a, lhs_b)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Rewrite active pairs until none has a rule.
+    ///
+    /// Every active pair is considered, so pairs without a rule are skipped
+    /// rather than ending the reduction. Returns the number of steps taken;
+    /// errors when `max_steps` is exhausted while a reducible pair remains
+    /// (e.g. a non-terminating rule set).
+    pub fn reduce_to_normal_form(&self, net: &mut Net, max_steps: usize) -> Result<usize> {
+        let mut steps = 0;
+        while let Some((body, lhs_a, lhs_b)) = self.next_redex(net) {
+            if steps == max_steps {
+                bail!("net did not reach normal form within {max_steps} steps");
+            }
+            self.apply_rewire(&body, net, lhs_a, lhs_b)?;
+            steps += 1;
+        }
+        Ok(steps)
+    }
+
+    /// The first active pair with a rule, as returned by [`match_rule`](Self::match_rule).
+    fn next_redex(&self, net: &Net) -> Option<(String, PortRef, PortRef)> {
+        net.active_pairs().find_map(|(a, b)| {
+            self.match_rule(net, a, b)
+                .map(|(body, lhs_a, lhs_b)| (body.to_string(), lhs_a, lhs_b))
+        })
+    }
+
+    /// Look up the rule for an active pair, returning its body and the ports
+    /// bound to `A`/`B` respectively.
+    ///
+    /// Wire endpoint order is incidental (it depends on which side was passed
+    /// to `connect` first), so a rule stored once as `(lhs_a, lhs_b)` matches
+    /// either orientation. The exact order is tried first, then the swapped
+    /// one; in both cases `A` is bound to the agent whose kind is the stored
+    /// `lhs_a` and `B` to the `lhs_b` agent.
+    fn match_rule(&self, net: &Net, a: PortRef, b: PortRef) -> Option<(&str, PortRef, PortRef)> {
         let kind_a = net.agents[a.agent.0].kind.clone();
         let kind_b = net.agents[b.agent.0].kind.clone();
-        // Prefer exact (a,b), then symmetric (b,a)
         if let Some(body) = self.rules.get(&(kind_a.clone(), kind_b.clone())) {
-            return self.apply_rewire(body, net, a, b);
+            return Some((body, a, b));
         }
-        if let Some(body) = self.rules.get(&(kind_b.clone(), kind_a.clone())) {
-            return self.apply_rewire(body, net, b, a);
+        if let Some(body) = self.rules.get(&(kind_b, kind_a)) {
+            return Some((body, b, a));
         }
-        Ok(false)
+        None
     }
 
     fn apply_rewire(
@@ -410,67 +860,62 @@ impl Reducer {
             })
         }
         for form in &forms {
-            if let SExpr::List(items) = form {
-                if !items.is_empty() {
-                    if let SExpr::Sym(op) = &items[0] {
-                        if op == "connect" && items.len() == 3 {
-                            let (s1, p1) = match &items[1] {
-                                SExpr::List(v) if v.len() == 2 => match (&v[0], &v[1]) {
-                                    (SExpr::Sym(a), SExpr::Sym(b)) => (a.as_str(), b.as_str()),
-                                    _ => continue,
-                                },
-                                _ => continue,
-                            };
-                            let (s2, p2) = match &items[2] {
-                                SExpr::List(v) if v.len() == 2 => match (&v[0], &v[1]) {
-                                    (SExpr::Sym(a), SExpr::Sym(b)) => (a.as_str(), b.as_str()),
-                                    _ => continue,
-                                },
-                                _ => continue,
-                            };
-                            let pr1 = resolve_alias(net, &aliases, lhs_a, lhs_b, s1, p1)?;
-                            let pr2 = resolve_alias(net, &aliases, lhs_a, lhs_b, s2, p2)?;
-                            connects.push((pr1, pr2));
-                        } else if op == "disconnect" {
-                            for arg in items.iter().skip(1) {
-                                if let SExpr::List(v) = arg {
-                                    if v.len() == 2 {
-                                        if let (SExpr::Sym(a), SExpr::Sym(p)) = (&v[0], &v[1]) {
-                                            if let Ok(pr) =
-                                                resolve_alias(net, &aliases, lhs_a, lhs_b, a, p)
-                                            {
-                                                to_disconnect.push(pr);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        } else if op == "new" && items.len() >= 3 {
-                            // (new KIND alias (port port ...))
-                            let kind = if let SExpr::Sym(k) = &items[1] {
-                                k
-                            } else {
-                                continue;
-                            };
-                            let alias = if let SExpr::Sym(a) = &items[2] {
-                                a
-                            } else {
-                                continue;
-                            };
-                            let mut plist: Vec<&str> = Vec::new();
-                            if items.len() >= 4 {
-                                if let SExpr::List(l) = &items[3] {
-                                    for it in l {
-                                        if let SExpr::Sym(s) = it {
-                                            plist.push(s)
-                                        }
-                                    }
-                                }
+            if let SExpr::List(items) = form
+                && !items.is_empty()
+                && let SExpr::Sym(op) = &items[0]
+            {
+                if op == "connect" && items.len() == 3 {
+                    let (s1, p1) = match &items[1] {
+                        SExpr::List(v) if v.len() == 2 => match (&v[0], &v[1]) {
+                            (SExpr::Sym(a), SExpr::Sym(b)) => (a.as_str(), b.as_str()),
+                            _ => continue,
+                        },
+                        _ => continue,
+                    };
+                    let (s2, p2) = match &items[2] {
+                        SExpr::List(v) if v.len() == 2 => match (&v[0], &v[1]) {
+                            (SExpr::Sym(a), SExpr::Sym(b)) => (a.as_str(), b.as_str()),
+                            _ => continue,
+                        },
+                        _ => continue,
+                    };
+                    let pr1 = resolve_alias(net, &aliases, lhs_a, lhs_b, s1, p1)?;
+                    let pr2 = resolve_alias(net, &aliases, lhs_a, lhs_b, s2, p2)?;
+                    connects.push((pr1, pr2));
+                } else if op == "disconnect" {
+                    for arg in items.iter().skip(1) {
+                        if let SExpr::List(v) = arg
+                            && v.len() == 2
+                            && let (SExpr::Sym(a), SExpr::Sym(p)) = (&v[0], &v[1])
+                            && let Ok(pr) = resolve_alias(net, &aliases, lhs_a, lhs_b, a, p)
+                        {
+                            to_disconnect.push(pr);
+                        }
+                    }
+                } else if op == "new" && items.len() >= 3 {
+                    // (new KIND alias (port port ...))
+                    let kind = if let SExpr::Sym(k) = &items[1] {
+                        k
+                    } else {
+                        continue;
+                    };
+                    let alias = if let SExpr::Sym(a) = &items[2] {
+                        a
+                    } else {
+                        continue;
+                    };
+                    let mut plist: Vec<&str> = Vec::new();
+                    if items.len() >= 4
+                        && let SExpr::List(l) = &items[3]
+                    {
+                        for it in l {
+                            if let SExpr::Sym(s) = it {
+                                plist.push(s)
                             }
-                            let id = net.add_agent(kind, &plist);
-                            aliases.insert(alias.clone(), id);
                         }
                     }
+                    let id = net.add_agent(kind, &plist);
+                    aliases.insert(alias.clone(), id);
                 }
             }
         }
@@ -491,28 +936,26 @@ impl Reducer {
         }
         // Handle delete if present
         for form in &forms {
-            if let SExpr::List(items) = form {
-                if items.len() >= 2 {
-                    if let SExpr::Sym(op) = &items[0] {
-                        if op == "delete" {
-                            for sym in items.iter().skip(1) {
-                                if let SExpr::Sym(s) = sym {
-                                    let ag = if s == "A" {
-                                        lhs_a.agent
-                                    } else if s == "B" {
-                                        lhs_b.agent
-                                    } else {
-                                        continue;
-                                    };
-                                    // Disconnect all ports
-                                    let nports = net.agents[ag.0].ports.len();
-                                    for i in 0..nports {
-                                        net.disconnect(PortRef { agent: ag, port: i });
-                                    }
-                                    net.agents[ag.0].deleted = true;
-                                }
-                            }
+            if let SExpr::List(items) = form
+                && items.len() >= 2
+                && let SExpr::Sym(op) = &items[0]
+                && op == "delete"
+            {
+                for sym in items.iter().skip(1) {
+                    if let SExpr::Sym(s) = sym {
+                        let ag = if s == "A" {
+                            lhs_a.agent
+                        } else if s == "B" {
+                            lhs_b.agent
+                        } else {
+                            continue;
+                        };
+                        // Disconnect all ports
+                        let nports = net.agents[ag.0].ports.len();
+                        for i in 0..nports {
+                            net.disconnect(PortRef { agent: ag, port: i });
                         }
+                        net.agents[ag.0].deleted = true;
                     }
                 }
             }
@@ -536,18 +979,16 @@ fn parse_sexpr_sequence(input: &str) -> Result<Vec<SExpr>> {
         forms.push(parse_one(&mut tokens)?);
     }
     // Unwrap (seq ...) if present
-    if forms.len() == 1 {
-        if let SExpr::List(items) = &forms[0] {
-            if let Some(SExpr::Sym(head)) = items.get(0) {
-                if head == "seq" {
-                    let mut seq = Vec::new();
-                    for it in items.iter().skip(1) {
-                        seq.push(it.clone());
-                    }
-                    return Ok(seq);
-                }
-            }
+    if forms.len() == 1
+        && let SExpr::List(items) = &forms[0]
+        && let Some(SExpr::Sym(head)) = items.first()
+        && head == "seq"
+    {
+        let mut seq = Vec::new();
+        for it in items.iter().skip(1) {
+            seq.push(it.clone());
         }
+        return Ok(seq);
     }
     Ok(forms)
 }
@@ -622,6 +1063,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn find_agent_by_kind_rejects_names_bound_to_other_kinds() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        let pair = store_agent(
+            &conn,
+            &AgentCanon {
+                name: "pair",
+                ports: &["principal", "head", "tail"],
+                doc: None,
+            },
+        )?
+        .cid;
+        db::put_name(&conn, "agent", "pair", &pair)?;
+        assert_eq!(
+            find_agent_by_kind(&conn, "pair")?.map(|info| info.kind),
+            Some("pair".to_string())
+        );
+
+        db::put_name(&conn, "agent", "unpair", &pair)?;
+        let err = find_agent_by_kind(&conn, "unpair").unwrap_err();
+        assert!(err.to_string().contains("kind `pair`"), "{err}");
+        Ok(())
+    }
+
     #[test]
     fn encode_store_rule() -> Result<()> {
         let rule = RuleCanon {
@@ -708,6 +1174,50 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn lazy_reducer_decodes_only_the_matching_rule() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        for idx in 0..200 {
+            let kind = format!("k{idx}");
+            store_rule(
+                &conn,
+                &RuleCanon {
+                    lhs_a: &kind,
+                    lhs_b: "eraser",
+                    body_syntax: "(seq (delete A B))",
+                },
+            )?;
+        }
+
+        let mut reducer = Reducer::lazy();
+        // Stored as (k42 eraser); the lookup accepts either orientation.
+        assert!(reducer.load_rules_for_pair(&conn, "eraser", "k42")?);
+        assert!(!reducer.load_rules_for_pair(&conn, "k42", "k43")?);
+        assert_eq!(reducer.loaded_rules(), 1);
+
+        store_rule(
+            &conn,
+            &RuleCanon {
+                lhs_a: "a b",
+                lhs_b: "c",
+                body_syntax: "(seq (delete A B))",
+            },
+        )?;
+        let mut spaced = Reducer::lazy();
+        assert!(!spaced.load_rules_for_pair(&conn, "a", "b c")?);
+        assert!(spaced.load_rules_for_pair(&conn, "a b", "c")?);
+
+        let mut net = Net::new();
+        let x = net.add_agent("k7", &["p"]);
+        let e = net.add_agent("eraser", &["p"]);
+        net.connect(PortRef { agent: x, port: 0 }, PortRef { agent: e, port: 0 })?;
+        assert!(reducer.step_lazy(&conn, &mut net)?);
+        assert_eq!(reducer.loaded_rules(), 2);
+        assert!(net.agents[x.0].deleted && net.agents[e.0].deleted);
+        Ok(())
+    }
+
     #[test]
     fn reducer_applies_stored_pair_unpair_rule() -> Result<()> {
         let conn = Connection::open_in_memory()?;
@@ -785,6 +1295,266 @@ mod tests {
         Ok(())
     }
 
+    fn pr(agent: AgentId, port: usize) -> PortRef {
+        PortRef { agent, port }
+    }
+
+    fn other_agent(net: &Net, agent: AgentId) -> Option<AgentId> {
+        let end = pr(agent, 1);
+        net.agents[agent.0].ports[1]
+            .and_then(|w| net.other_end(w, end))
+            .map(|p| p.agent)
+    }
+
+    #[test]
+    fn reducer_reaches_normal_form() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        let rule = RuleCanon {
+            lhs_a: "pair",
+            lhs_b: "unpair",
+            body_syntax: "(seq (connect (A head) (B left)) (connect (A tail) (B right)) (delete A B))",
+        };
+        store_rule(&conn, &rule)?;
+        let reducer = Reducer::new(&conn)?;
+
+        // Two independent pair/unpair redexes; endpoints hang off aux ports so the
+        // rewired wires are not themselves active pairs.
+        let mut net = Net::new();
+        let mut endpoints = Vec::new();
+        for _ in 0..2 {
+            let a = net.add_agent("A", &["principal", "p"]);
+            let b = net.add_agent("B", &["principal", "p"]);
+            let c = net.add_agent("C", &["principal", "p"]);
+            let d = net.add_agent("D", &["principal", "p"]);
+            let pair = net.add_agent("pair", &["principal", "head", "tail"]);
+            let unpair = net.add_agent("unpair", &["principal", "left", "right"]);
+            net.connect(pr(pair, 0), pr(unpair, 0))?;
+            net.connect(pr(pair, 1), pr(a, 1))?;
+            net.connect(pr(pair, 2), pr(c, 1))?;
+            net.connect(pr(unpair, 1), pr(b, 1))?;
+            net.connect(pr(unpair, 2), pr(d, 1))?;
+            endpoints.push((a, b, c, d));
+        }
+        assert_eq!(net.active_pair_count(), 2);
+
+        let steps = reducer.reduce_to_normal_form(&mut net, 16)?;
+        assert_eq!(steps, 2);
+        assert_eq!(net.active_pair_count(), 0);
+        for (a, b, c, d) in endpoints {
+            assert_eq!(other_agent(&net, a), Some(b));
+            assert_eq!(other_agent(&net, c), Some(d));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reducer_normal_form_respects_step_cap() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        // A rule that rebuilds its own redex never terminates.
+        let rule = RuleCanon {
+            lhs_a: "loop",
+            lhs_b: "loop",
+            body_syntax: "(seq (delete A B) (new loop x (principal)) (new loop y (principal)) (connect (x principal) (y principal)))",
+        };
+        store_rule(&conn, &rule)?;
+        let reducer = Reducer::new(&conn)?;
+
+        let mut net = Net::new();
+        let x = net.add_agent("loop", &["principal"]);
+        let y = net.add_agent("loop", &["principal"]);
+        net.connect(pr(x, 0), pr(y, 0))?;
+
+        assert!(reducer.reduce_to_normal_form(&mut net, 5).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn reducer_normal_form_skips_pairs_without_rules() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        let rule = RuleCanon {
+            lhs_a: "pair",
+            lhs_b: "unpair",
+            body_syntax: "(pair-unpair)",
+        };
+        store_rule(&conn, &rule)?;
+        let reducer = Reducer::new(&conn)?;
+
+        // The stuck pair is wired first, so it is the first active pair found.
+        let mut net = Net::new();
+        let x = net.add_agent("stuck", &["principal"]);
+        let y = net.add_agent("stuck", &["principal"]);
+        net.connect(pr(x, 0), pr(y, 0))?;
+        let pair = net.add_agent("pair", &["principal", "head", "tail"]);
+        let unpair = net.add_agent("unpair", &["principal", "left", "right"]);
+        net.connect(pr(pair, 0), pr(unpair, 0))?;
+        assert_eq!(net.active_pair_count(), 2);
+
+        assert_eq!(reducer.reduce_to_normal_form(&mut net, 4)?, 1);
+        assert_eq!(net.active_pair_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn net_to_dot_renders_agents_and_principal_edge() -> Result<()> {
+        let mut net = Net::new();
+        let a = net.add_agent("A", &["p"]);
+        let pair = net.add_agent("pair", &["principal", "head", "tail"]);
+        let unpair = net.add_agent("unpair", &["principal", "left", "right"]);
+        net.connect(pr(pair, 0), pr(unpair, 0))?;
+        net.connect(pr(pair, 1), pr(a, 0))?;
+
+        let dot = net_to_dot(&net);
+        assert!(dot.starts_with("graph net {"));
+        assert!(dot.contains("a1 [label=\"{pair|{<p0>principal|<p1>head|<p2>tail}}\"];"));
+        assert!(dot.contains("a2 [label=\"{unpair|{<p0>principal|<p1>left|<p2>right}}\"];"));
+        assert!(dot.contains("a1:p0 -- a2:p0 [color=red, style=bold];"));
+        assert!(dot.contains("a1:p1 -- a0:p0;"));
+        // pair.tail, unpair.left and unpair.right are free.
+        assert_eq!(dot.matches("[shape=point]").count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn load_agent_info_roundtrip() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        let agent = AgentCanon {
+            name: "pair",
+            ports: &["principal", "head", "tail"],
+            doc: Some("cons cell"),
+        };
+        let cid = store_agent(&conn, &agent)?.cid;
+        let info = load_agent_info(&conn, &cid)?;
+        assert_eq!(info.kind, "pair");
+        assert_eq!(info.ports, vec!["principal", "head", "tail"]);
+        assert_eq!(info.doc.as_deref(), Some("cons cell"));
+        Ok(())
+    }
+
+    #[test]
+    fn validate_rule_checks_port_names() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        let pair = AgentCanon {
+            name: "pair",
+            ports: &["principal", "head", "tail"],
+            doc: None,
+        };
+        let pair_cid = store_agent(&conn, &pair)?.cid;
+        crate::db::put_name(&conn, "agent", "pair", &pair_cid)?;
+        // Registered under a different name; found by scanning kinds.
+        let unpair = AgentCanon {
+            name: "unpair",
+            ports: &["principal", "left", "right"],
+            doc: None,
+        };
+        let unpair_cid = store_agent(&conn, &unpair)?.cid;
+        crate::db::put_name(&conn, "agent", "core/unpair", &unpair_cid)?;
+
+        let good = RuleCanon {
+            lhs_a: "pair",
+            lhs_b: "unpair",
+            body_syntax: "(seq (new dup d (principal out)) (connect (A head) (d out)) (connect (A tail) (B right)) (delete A B))",
+        };
+        validate_rule(&conn, &good)?;
+
+        let bad = RuleCanon {
+            lhs_a: "pair",
+            lhs_b: "unpair",
+            body_syntax: "(seq (connect (A head) (B middle)) (delete A B))",
+        };
+        let err = validate_rule(&conn, &bad).unwrap_err().to_string();
+        assert!(err.contains("unknown port `middle` on B"), "{err}");
+
+        let unknown = RuleCanon {
+            lhs_a: "pair",
+            lhs_b: "ghost",
+            body_syntax: "(delete A B)",
+        };
+        assert!(validate_rule(&conn, &unknown).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn net_store_load_roundtrip() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+
+        let mut net = Net::new();
+        let gone = net.add_agent("gone", &["principal"]);
+        let a = net.add_agent("A", &["principal", "p"]);
+        let pair = net.add_agent("pair", &["principal", "head", "tail"]);
+        let unpair = net.add_agent("unpair", &["principal", "left", "right"]);
+        net.connect(pr(pair, 0), pr(unpair, 0))?;
+        net.connect(pr(pair, 1), pr(a, 1))?;
+        net.agents[gone.0].deleted = true;
+        net.entry = Some(pair);
+
+        let out = store_net(&conn, &net)?;
+        assert!(out.inserted);
+        let loaded = load_net(&conn, &out.cid)?;
+
+        // The deleted agent is compacted away; the rest keep their order.
+        let kinds: Vec<&str> = loaded.agents.iter().map(|a| a.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["A", "pair", "unpair"]);
+        assert_eq!(
+            loaded.agents[1].port_names,
+            vec!["principal", "head", "tail"]
+        );
+        assert_eq!(loaded.entry, Some(AgentId(1)));
+        assert_eq!(loaded.active_pair_count(), 1);
+        let head = pr(AgentId(1), 1);
+        let wire = loaded.agents[1].ports[1].expect("head stays wired");
+        assert_eq!(loaded.other_end(wire, head), Some(pr(AgentId(0), 1)));
+        assert!(loaded.agents[1].ports[2].is_none());
+
+        // Re-encoding the reloaded net is stable.
+        assert_eq!(encode_net(&loaded), encode_net(&net));
+        Ok(())
+    }
+
+    #[test]
+    fn reducer_binds_stored_order_when_pair_is_swapped() -> Result<()> {
+        for body in [
+            "(seq (connect (A head) (B left)) (connect (A tail) (B right)) (delete A B))",
+            "(pair-unpair)",
+        ] {
+            let conn = Connection::open_in_memory()?;
+            crate::db::install_schema(&conn)?;
+            let rule = RuleCanon {
+                lhs_a: "pair",
+                lhs_b: "unpair",
+                body_syntax: body,
+            };
+            store_rule(&conn, &rule)?;
+            let reducer = Reducer::new(&conn)?;
+
+            let mut net = Net::new();
+            let a = net.add_agent("A", &["principal", "p"]);
+            let b = net.add_agent("B", &["principal", "p"]);
+            let c = net.add_agent("C", &["principal", "p"]);
+            let d = net.add_agent("D", &["principal", "p"]);
+            let pair = net.add_agent("pair", &["principal", "head", "tail"]);
+            let unpair = net.add_agent("unpair", &["principal", "left", "right"]);
+            // unpair is the first endpoint of the active wire.
+            net.connect(pr(unpair, 0), pr(pair, 0))?;
+            assert_eq!(net.find_active_pair(), Some((pr(unpair, 0), pr(pair, 0))));
+            net.connect(pr(pair, 1), pr(a, 1))?;
+            net.connect(pr(pair, 2), pr(c, 1))?;
+            net.connect(pr(unpair, 1), pr(b, 1))?;
+            net.connect(pr(unpair, 2), pr(d, 1))?;
+
+            assert_eq!(reducer.reduce_to_normal_form(&mut net, 4)?, 1, "{body}");
+            assert_eq!(other_agent(&net, a), Some(b), "{body}");
+            assert_eq!(other_agent(&net, c), Some(d), "{body}");
+            assert!(net.agents[pair.0].deleted && net.agents[unpair.0].deleted);
+        }
+        Ok(())
+    }
+
     #[test]
     fn dsl_disconnect_then_connect() -> Result<()> {
         let conn = Connection::open_in_memory()?;
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
 use std::str;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use anyhow::{Result, anyhow, bail};
 use rusqlite::Connection;
@@ -9,13 +12,16 @@ use serde_bytes::ByteBuf;
 use serde_cbor::Value as CborValue;
 use std::fmt;
 
-use crate::exec::{compiled_add, compiled_sub};
+use crate::cid;
+use crate::db::ObjectLoader;
+use crate::exec::{compiled_add, compiled_sub, jit_enabled};
 use crate::global_store;
 use crate::guard;
-use crate::prim::load_prim_info;
+use crate::metrics;
+use crate::prim::{self, PrimInfo, load_prim_info};
 use crate::types::{self, EffectDomain, EffectMask, TypeTag, effect_mask};
-use crate::word::load_word_info;
-use crate::{cid, list_names_for_cid, load_object_cbor};
+use crate::word::{WordInfo, load_word_info};
+use once_cell::sync::Lazy;
 use smallvec::SmallVec;
 
 /// Evaluates a word and returns its single `i64` result.
@@ -23,11 +29,13 @@ use smallvec::SmallVec;
 pub fn run_word_i64(conn: &Connection, word_cid: &[u8; 32], args: &[i64]) -> Result<i64> {
     let info = load_word_info(conn, word_cid)?;
     if info.params.len() != args.len() {
-        bail!(
-            "argument mismatch: word expects {} params, got {}",
-            info.params.len(),
-            args.len()
-        );
+        return Err(EvalError::ArgMismatch {
+            what: "word".to_string(),
+            expected: info.params.len(),
+            actual: args.len(),
+            variadic: false,
+        }
+        .into());
     }
     for (idx, expected) in info.params.iter().enumerate() {
         if *expected != TypeTag::I64 {
@@ -39,7 +47,8 @@ pub fn run_word_i64(conn: &Connection, word_cid: &[u8; 32], args: &[i64]) -> Res
         }
     }
     let arg_values: Vec<Value> = args.iter().copied().map(Value::I64).collect();
-    let mut results = run_word_with_info(conn, &info, &arg_values)?;
+    let mut ctx = EvalCtx::new(conn, Budget::unlimited());
+    let mut results = run_word_with_info(&mut ctx, &info, &arg_values)?;
     let token_domains = wrap_token_domains(&word_token_domains(&info));
     validate_output_tokens(&results, &token_domains)?;
     if !token_domains.is_empty() {
@@ -56,12 +65,460 @@ pub fn run_word_i64(conn: &Connection, word_cid: &[u8; 32], args: &[i64]) -> Res
 
 /// Evaluate a word and return its result values.
 pub fn run_word(conn: &Connection, word_cid: &[u8; 32], args: &[Value]) -> Result<Vec<Value>> {
+    run_word_budgeted(conn, word_cid, args, &mut Budget::unlimited())
+}
+
+/// Evaluate a word, spending one unit of `budget` per node evaluation (nested calls included).
+///
+/// Fails once the budget runs out; `budget.remaining` reflects what was left either way.
+pub fn run_word_budgeted(
+    conn: &Connection,
+    word_cid: &[u8; 32],
+    args: &[Value],
+    budget: &mut Budget,
+) -> Result<Vec<Value>> {
+    let mut ctx = EvalCtx::new(conn, *budget);
+    let result = run_word_in(&mut ctx, word_cid, args);
+    *budget = ctx.budget;
+    result
+}
+
+/// Host function backing a primitive that the built-in runner does not know.
+pub type NativePrim = fn(&[Value]) -> Result<Value>;
+
+/// Embedder-supplied primitives, looked up by registered name before the built-ins.
+#[derive(Clone, Debug)]
+pub struct PrimRegistry {
+    prims: HashMap<String, NativePrim>,
+    /// Distinct for every set of registrations, so cached results stay with the
+    /// registry that produced them.
+    id: u64,
+}
+
+impl Default for PrimRegistry {
+    fn default() -> Self {
+        PrimRegistry {
+            prims: HashMap::new(),
+            id: next_registry_id(),
+        }
+    }
+}
+
+fn next_registry_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+impl PrimRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `func` under `name`; either a full `namespace/symbol` or a bare symbol.
+    pub fn register(&mut self, name: impl Into<String>, func: NativePrim) -> &mut Self {
+        self.prims.insert(name.into(), func);
+        self.id = next_registry_id();
+        self
+    }
+
+    /// Resolve the first of `names` with a registration, trying full names before bare symbols.
+    fn resolve(&self, names: &[String]) -> Option<NativePrim> {
+        if self.prims.is_empty() {
+            return None;
+        }
+        names
+            .iter()
+            .find_map(|name| self.prims.get(name))
+            .or_else(|| {
+                names
+                    .iter()
+                    .find_map(|name| self.prims.get(name.rsplit('/').next().unwrap_or(name)))
+            })
+            .copied()
+    }
+}
+
+/// Evaluate a word, consulting `prims` before the built-in primitives.
+pub fn run_word_with_prims(
+    conn: &Connection,
+    word_cid: &[u8; 32],
+    args: &[Value],
+    prims: &PrimRegistry,
+) -> Result<Vec<Value>> {
+    let mut ctx = EvalCtx::new(conn, Budget::unlimited());
+    ctx.natives = Some(prims);
+    run_word_in(&mut ctx, word_cid, args)
+}
+
+/// Most results the process-global pure-word cache keeps before evicting.
+pub const RESULT_CACHE_CAPACITY: usize = 1024;
+
+/// Memoised results of pure words, keyed by store, registry, word CID and arguments.
+static RESULT_CACHE: Lazy<Mutex<ResultCache>> =
+    Lazy::new(|| Mutex::new(ResultCache::new(RESULT_CACHE_CAPACITY)));
+
+/// Like [`run_word`], but pure words (NONE mask, no effect CIDs) reuse earlier results.
+///
+/// Effectful words, and runs that read a global through LOAD_GLOBAL, always run.
+/// The cache is process-global. A word's CID does not pin its behaviour on its
+/// own (legacy prims dispatch by the names a store binds to them), so entries are
+/// scoped to the store and prim registry they were computed with; call
+/// [`clear_result_cache`] after rebinding prim names in a store.
+pub fn run_word_cached(
+    conn: &Connection,
+    word_cid: &[u8; 32],
+    args: &[Value],
+) -> Result<Vec<Value>> {
+    cached_run(conn, word_cid, args, None)
+}
+
+/// [`run_word_cached`] with embedder primitives, which are assumed to be pure when
+/// the word's contract says so.
+pub fn run_word_cached_with_prims(
+    conn: &Connection,
+    word_cid: &[u8; 32],
+    args: &[Value],
+    prims: &PrimRegistry,
+) -> Result<Vec<Value>> {
+    cached_run(conn, word_cid, args, Some(prims))
+}
+
+/// Forget every memoised pure-word result.
+pub fn clear_result_cache() {
+    result_cache().clear();
+}
+
+fn result_cache() -> std::sync::MutexGuard<'static, ResultCache> {
+    RESULT_CACHE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn cached_run(
+    conn: &Connection,
+    word_cid: &[u8; 32],
+    args: &[Value],
+    prims: Option<&PrimRegistry>,
+) -> Result<Vec<Value>> {
     let info = load_word_info(conn, word_cid)?;
-    run_word_with_info(conn, &info, args)
+    let pure = info.effect_mask == effect_mask::NONE && info.effects.is_empty();
+    let key = CacheKey {
+        store: format!(
+            "{}#{}",
+            conn.path().unwrap_or_default(),
+            crate::db::store_id(conn)?.unwrap_or_default()
+        ),
+        registry: prims.map(|prims| prims.id),
+        word: *word_cid,
+        args: args.to_vec(),
+    };
+    if pure && let Some(results) = result_cache().get(&key) {
+        return Ok(results);
+    }
+    let mut ctx = EvalCtx::new(conn, Budget::unlimited());
+    ctx.natives = prims;
+    let results = run_word_in(&mut ctx, word_cid, args)?;
+    if pure && !ctx.read_globals {
+        result_cache().insert(key, results.clone());
+    }
+    Ok(results)
+}
+
+/// Cache key comparing floats by bit pattern, so every key equals itself (NaN included).
+#[derive(Clone, Debug)]
+struct CacheKey {
+    /// Store path and id, since prim dispatch can depend on the store's names.
+    store: String,
+    /// [`PrimRegistry::id`] of the embedder prims, if any.
+    registry: Option<u64>,
+    word: [u8; 32],
+    args: Vec<Value>,
+}
+
+impl PartialEq for CacheKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.store == other.store
+            && self.registry == other.registry
+            && self.word == other.word
+            && self.args.len() == other.args.len()
+            && self
+                .args
+                .iter()
+                .zip(&other.args)
+                .all(|(a, b)| same_value(a, b))
+    }
+}
+
+impl Eq for CacheKey {}
+
+impl Hash for CacheKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.store.hash(state);
+        self.registry.hash(state);
+        self.word.hash(state);
+        self.args.len().hash(state);
+        for arg in &self.args {
+            hash_value(arg, state);
+        }
+    }
+}
+
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::F64(x), Value::F64(y)) => x.to_bits() == y.to_bits(),
+        (Value::Tuple(xs), Value::Tuple(ys)) => {
+            xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| same_value(x, y))
+        }
+        _ => a == b,
+    }
+}
+
+fn hash_value<H: Hasher>(value: &Value, state: &mut H) {
+    value.type_name().hash(state);
+    match value {
+        Value::I64(n) => n.hash(state),
+        Value::U64(n) | Value::Ptr(n) => n.hash(state),
+        Value::F64(x) => x.to_bits().hash(state),
+        Value::Text(text) => text.hash(state),
+        Value::Bytes(bytes) => bytes.hash(state),
+        Value::Unit => {}
+        Value::Tuple(items) => {
+            items.len().hash(state);
+            for item in items {
+                hash_value(item, state);
+            }
+        }
+        Value::Quote(cid) => cid.hash(state),
+        Value::Token(domain) => domain.hash(state),
+    }
+}
+
+/// Memoised pure-word results, evicting the least recently used entry when full.
+#[derive(Debug)]
+struct ResultCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<CacheKey, (u64, Vec<Value>)>,
+}
+
+impl ResultCache {
+    fn new(capacity: usize) -> Self {
+        ResultCache {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<Vec<Value>> {
+        self.tick += 1;
+        let (used, results) = self.entries.get_mut(key)?;
+        *used = self.tick;
+        Some(results.clone())
+    }
+
+    fn insert(&mut self, key: CacheKey, results: Vec<Value>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if self.entries.len() >= self.capacity
+            && !self.entries.contains_key(&key)
+            && let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(key, _)| key.clone())
+        {
+            self.entries.remove(&oldest);
+        }
+        self.entries.insert(key, (self.tick, results));
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Evaluate a guard quotation against the leading `args`; true when it returns non-zero.
+pub fn run_guard(conn: &Connection, guard_cid: &[u8; 32], args: &[Value]) -> Result<bool> {
+    run_guard_in(
+        &mut EvalCtx::new(conn, Budget::unlimited()),
+        guard_cid,
+        args,
+    )
+}
+
+/// Check that `values` begins with one token per domain, in the order given.
+///
+/// Uses the same rules the interpreter applies to effectful node outputs, so
+/// external graph builders can validate token wiring without evaluating.
+pub fn check_token_sequence(values: &[Value], domains: &[EffectDomain]) -> Result<()> {
+    validate_output_tokens(values, &wrap_token_domains(domains))
+}
+
+/// Execution fuel for sandboxed evaluation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub remaining: u64,
+}
+
+impl Budget {
+    pub fn new(remaining: u64) -> Self {
+        Budget { remaining }
+    }
+
+    pub fn unlimited() -> Self {
+        Budget {
+            remaining: u64::MAX,
+        }
+    }
+
+    fn spend(&mut self) -> Result<()> {
+        if self.remaining == 0 {
+            return Err(EvalError::DepthExceeded.into());
+        }
+        self.remaining -= 1;
+        Ok(())
+    }
+}
+
+/// Evaluation failures callers may want to tell apart; recover one from the
+/// returned `anyhow::Error` with `downcast_ref::<EvalError>()`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EvalError {
+    /// A word or primitive was called with the wrong number of arguments; `variadic`
+    /// callees accept `expected` or more.
+    ArgMismatch {
+        what: String,
+        expected: usize,
+        actual: usize,
+        variadic: bool,
+    },
+    /// A word argument or result has a type other than the declared one.
+    TypeMismatch {
+        what: String,
+        expected: TypeTag,
+        actual: TypeTag,
+    },
+    /// Execution left a specialised or guarded path; `reason` says why when known.
+    Deopt { reason: Option<String> },
+    /// A node consumed an input that was never produced.
+    Underflow(String),
+    /// A primitive the runner has no implementation for.
+    UnknownPrim(String),
+    /// A primitive with neither a builtin id nor a registered name to dispatch on.
+    UnnamedPrim([u8; 32]),
+    /// Effect tokens were missing or belonged to the wrong domain.
+    TokenMismatch(String),
+    /// Evaluation (nested calls included) outran its budget.
+    DepthExceeded,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::ArgMismatch {
+                what,
+                expected,
+                actual,
+                variadic,
+            } => write!(
+                f,
+                "argument mismatch: {what} expects {}{expected} params, got {actual}",
+                if *variadic { "at least " } else { "" }
+            ),
+            EvalError::TypeMismatch {
+                what,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{what} type mismatch: expected {expected:?}, got {actual:?}"
+            ),
+            EvalError::Deopt { reason: None } => f.write_str(DEOPT_MESSAGE),
+            EvalError::Deopt {
+                reason: Some(reason),
+            } => write!(f, "{DEOPT_MESSAGE}: {reason}"),
+            EvalError::Underflow(message) | EvalError::TokenMismatch(message) => {
+                f.write_str(message)
+            }
+            EvalError::UnknownPrim(name) => {
+                write!(f, "primitive `{name}` not supported in runner")
+            }
+            EvalError::UnnamedPrim(prim_cid) => write!(
+                f,
+                "primitive {} not registered with a name (runner needs a symbolic name)",
+                cid::to_hex(prim_cid)
+            ),
+            EvalError::DepthExceeded => f.write_str("execution budget exhausted"),
+        }
+    }
 }
 
-fn run_guard(conn: &Connection, guard_cid: &[u8; 32], args: &[Value]) -> Result<bool> {
-    let info = guard::load_guard_info(conn, guard_cid)?;
+impl std::error::Error for EvalError {}
+
+fn deopt(reason: impl Into<String>) -> anyhow::Error {
+    EvalError::Deopt {
+        reason: Some(reason.into()),
+    }
+    .into()
+}
+
+/// State shared by one top-level evaluation, including nested calls.
+///
+/// Object lookups reuse one prepared-statement loader, and decoded word/prim descriptors
+/// are memoised here; node outputs are cached per word body.
+struct EvalCtx<'conn> {
+    conn: &'conn Connection,
+    loader: ObjectLoader<'conn>,
+    budget: Budget,
+    words: HashMap<[u8; 32], WordInfo>,
+    prims: HashMap<[u8; 32], PrimInfo>,
+    natives: Option<&'conn PrimRegistry>,
+    /// Set once a LOAD_GLOBAL runs; such results depend on the global store.
+    read_globals: bool,
+}
+
+impl<'conn> EvalCtx<'conn> {
+    fn new(conn: &'conn Connection, budget: Budget) -> Self {
+        EvalCtx {
+            conn,
+            loader: ObjectLoader::new(conn),
+            budget,
+            words: HashMap::new(),
+            prims: HashMap::new(),
+            natives: None,
+            read_globals: false,
+        }
+    }
+
+    fn word_info(&mut self, cid: &[u8; 32]) -> Result<WordInfo> {
+        if let Some(info) = self.words.get(cid) {
+            return Ok(info.clone());
+        }
+        let info = load_word_info(self.conn, cid)?;
+        self.words.insert(*cid, info.clone());
+        Ok(info)
+    }
+
+    fn prim_info(&mut self, cid: &[u8; 32]) -> Result<PrimInfo> {
+        if let Some(info) = self.prims.get(cid) {
+            return Ok(info.clone());
+        }
+        let info = load_prim_info(self.conn, cid)?;
+        self.prims.insert(*cid, info.clone());
+        Ok(info)
+    }
+}
+
+fn run_word_in(ctx: &mut EvalCtx<'_>, word_cid: &[u8; 32], args: &[Value]) -> Result<Vec<Value>> {
+    let info = ctx.word_info(word_cid)?;
+    run_word_with_info(ctx, &info, args)
+}
+
+fn run_guard_in(ctx: &mut EvalCtx<'_>, guard_cid: &[u8; 32], args: &[Value]) -> Result<bool> {
+    let info = guard::load_guard_info(ctx.conn, guard_cid)?;
     if info.params.len() > args.len() {
         bail!(
             "guard {} expects at least {} argument(s), provided {}",
@@ -102,8 +559,9 @@ fn run_guard(conn: &Connection, guard_cid: &[u8; 32], args: &[Value]) -> Result<
         effects: info.effects.clone(),
         effect_mask: info.effect_mask,
         guards: Vec::new(),
+        doc: None,
     };
-    let outputs = eval_return(conn, &info.root, &mut cache, guard_args, &word_like)?;
+    let outputs = eval_return(ctx, &info.root, &mut cache, guard_args, &word_like)?;
     if outputs.len() != 1 {
         bail!(
             "guard {} returned {} value(s)",
@@ -150,27 +608,28 @@ fn consume_token_inputs(inputs: &mut Vec<Value>, expected: &[Option<EffectDomain
     for domain in expected.iter().rev() {
         let value = inputs
             .pop()
-            .ok_or_else(|| anyhow!("effectful node missing token input"))?;
+            .ok_or_else(|| token_error("effectful node missing token input".to_string()))?;
         match (value, domain) {
             (Value::Token(Some(actual)), Some(expected_domain)) if actual == *expected_domain => {}
             (Value::Token(Some(actual)), Some(expected_domain)) => {
-                bail!(
+                return Err(token_error(format!(
                     "effectful node received token for domain {:?}, expected {:?}",
-                    actual,
-                    *expected_domain
-                );
+                    actual, *expected_domain
+                )));
             }
             (Value::Token(_), None) => {}
             (Value::Token(None), Some(expected_domain)) => {
-                bail!(
+                return Err(token_error(format!(
                     "effectful node received generic token, expected {:?}",
                     *expected_domain
-                );
+                )));
+            }
+            (other, _) => {
+                return Err(token_error(format!(
+                    "effectful node missing token input, got {:?}",
+                    other.type_tag()
+                )));
             }
-            (other, _) => bail!(
-                "effectful node missing token input, got {:?}",
-                other.type_tag()
-            ),
         }
     }
     Ok(())
@@ -178,79 +637,103 @@ fn consume_token_inputs(inputs: &mut Vec<Value>, expected: &[Option<EffectDomain
 
 fn validate_output_tokens(outputs: &[Value], expected: &[Option<EffectDomain>]) -> Result<()> {
     if outputs.len() < expected.len() {
-        bail!(
+        return Err(token_error(format!(
             "effectful node expected {} token output(s), found {}",
             expected.len(),
             outputs.len()
-        );
+        )));
     }
     for (idx, expected_domain) in expected.iter().enumerate() {
         match (outputs.get(idx), expected_domain) {
             (Some(Value::Token(Some(actual))), Some(expected)) if *actual == *expected => {}
             (Some(Value::Token(Some(actual))), Some(expected)) => {
-                bail!(
+                return Err(token_error(format!(
                     "node returned token for domain {:?}, expected {:?}",
-                    *actual,
-                    *expected
-                );
+                    *actual, *expected
+                )));
             }
             (Some(Value::Token(Some(_))), None) => {}
             (Some(Value::Token(None)), Some(expected)) => {
-                bail!("node returned generic token, expected {:?}", *expected);
+                return Err(token_error(format!(
+                    "node returned generic token, expected {:?}",
+                    *expected
+                )));
             }
             (Some(Value::Token(None)), None) => {}
             (Some(other), _) => {
-                bail!(
+                return Err(token_error(format!(
                     "effectful node missing token output at index {} (got {:?})",
                     idx,
                     other.type_tag()
-                );
+                )));
             }
             (None, _) => {
-                bail!("effectful node missing token output at index {}", idx);
+                return Err(token_error(format!(
+                    "effectful node missing token output at index {}",
+                    idx
+                )));
             }
         }
     }
     Ok(())
 }
 
+fn token_error(message: String) -> anyhow::Error {
+    EvalError::TokenMismatch(message).into()
+}
+
 fn run_word_with_info(
-    conn: &Connection,
+    ctx: &mut EvalCtx<'_>,
     info: &crate::word::WordInfo,
     args: &[Value],
 ) -> Result<Vec<Value>> {
     if info.params.len() != args.len() {
-        bail!(
-            "argument mismatch: word expects {} params, got {}",
-            info.params.len(),
-            args.len()
-        );
+        return Err(EvalError::ArgMismatch {
+            what: "word".to_string(),
+            expected: info.params.len(),
+            actual: args.len(),
+            variadic: false,
+        }
+        .into());
     }
     for (idx, (expected, actual)) in info.params.iter().zip(args.iter()).enumerate() {
         let actual_tag = actual.type_tag();
         if *expected != actual_tag {
-            bail!(
-                "argument {idx} type mismatch: expected {:?}, got {:?}",
-                expected,
-                actual_tag
-            );
+            return Err(EvalError::TypeMismatch {
+                what: format!("argument {idx}"),
+                expected: *expected,
+                actual: actual_tag,
+            }
+            .into());
         }
     }
     let mut cache: HashMap<[u8; 32], Vec<Value>> = HashMap::new();
-    let outputs = match eval_return(conn, &info.root, &mut cache, args, info) {
+    let outputs = match eval_return(ctx, &info.root, &mut cache, args, info) {
         Ok(values) => values,
-        Err(err) if is_deopt_error(&err) && !info.guards.is_empty() => {
+        Err(err)
+            if !info.guards.is_empty()
+                && matches!(
+                    err.downcast_ref::<EvalError>(),
+                    Some(EvalError::Deopt { reason: None })
+                ) =>
+        {
+            // Attached guards lower to reasonless DEOPTs so the guard does not leak
+            // into the word's CID; re-run them to name the one that failed.
+            for guard_cid in &info.guards {
+                if matches!(run_guard_in(ctx, guard_cid, args), Ok(false)) {
+                    return Err(deopt(format!("guard {} failed", cid::to_hex(guard_cid))));
+                }
+            }
             let guard_list = info
                 .guards
                 .iter()
                 .map(cid::to_hex)
                 .collect::<Vec<_>>()
                 .join(", ");
-            bail!(
-                "guard deopt triggered while executing word {} (guards: {})",
-                cid::to_hex(&info.root),
-                guard_list
-            );
+            return Err(deopt(format!(
+                "guard failed while executing word {} (guards: {guard_list})",
+                cid::to_hex(&info.root)
+            )));
         }
         Err(err) => return Err(err),
     };
@@ -271,12 +754,12 @@ fn run_word_with_info(
         .enumerate()
     {
         if actual.type_tag() != *expected {
-            bail!(
-                "result {} type mismatch: expected {:?}, got {:?}",
-                idx,
-                expected,
-                actual.type_tag()
-            );
+            return Err(EvalError::TypeMismatch {
+                what: format!("result {idx}"),
+                expected: *expected,
+                actual: actual.type_tag(),
+            }
+            .into());
         }
     }
     Ok(outputs)
@@ -315,9 +798,11 @@ struct DispatchCaseRecord {
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     I64(i64),
+    U64(u64),
     F64(f64),
     Ptr(u64),
     Text(String),
+    Bytes(Vec<u8>),
     Unit,
     Tuple(Vec<Value>),
     Quote([u8; 32]),
@@ -325,12 +810,30 @@ pub enum Value {
 }
 
 impl Value {
+    /// Short name of the value's variant, matching the global store type tags.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::I64(_) => "i64",
+            Value::U64(_) => "u64",
+            Value::F64(_) => "f64",
+            Value::Ptr(_) => "ptr",
+            Value::Text(_) => "text",
+            Value::Bytes(_) => "bytes",
+            Value::Unit => "unit",
+            Value::Tuple(_) => "tuple",
+            Value::Quote(_) => "quote",
+            Value::Token(_) => "token",
+        }
+    }
+
     fn type_tag(&self) -> TypeTag {
         match self {
             Value::I64(_) => TypeTag::I64,
+            Value::U64(_) => TypeTag::U64,
             Value::F64(_) => TypeTag::F64,
             Value::Ptr(_) => TypeTag::Ptr,
             Value::Text(_) => TypeTag::Text,
+            Value::Bytes(_) => TypeTag::Bytes,
             Value::Unit => TypeTag::Unit,
             Value::Tuple(_) => TypeTag::Ptr,
             Value::Quote(_) => TypeTag::Ptr,
@@ -346,9 +849,17 @@ impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Value::I64(n) => write!(f, "{n}"),
+            Value::U64(n) => write!(f, "{n}u64"),
             Value::F64(x) => write!(f, "{x}"),
             Value::Ptr(ptr) => write!(f, "0x{ptr:016x}"),
             Value::Text(s) => write!(f, "\"{}\"", s.escape_default()),
+            Value::Bytes(bytes) => {
+                write!(f, "<bytes:")?;
+                for byte in bytes {
+                    write!(f, "{byte:02x}")?;
+                }
+                write!(f, ">")
+            }
             Value::Unit => write!(f, "()"),
             Value::Tuple(values) => {
                 let body = values
@@ -367,22 +878,88 @@ impl fmt::Display for Value {
     }
 }
 
+/// A node on the evaluation work stack; `record` is set once its inputs are scheduled.
+struct PendingNode {
+    cid: [u8; 32],
+    record: Option<NodeRecord>,
+}
+
+/// Evaluate `node_cid` and everything it depends on, memoising outputs in `cache`.
+///
+/// Data inputs are resolved post-order on an explicit work stack, so arbitrarily
+/// deep chains do not grow the native stack. Lazy continuations (IF/GUARD/DISPATCH
+/// branches) re-enter here, so only control nesting costs native frames.
 fn eval_node(
-    conn: &Connection,
+    ctx: &mut EvalCtx<'_>,
     node_cid: &[u8; 32],
     cache: &mut HashMap<[u8; 32], Vec<Value>>,
     args: &[Value],
 ) -> Result<Vec<Value>> {
+    ctx.budget.spend()?;
     if let Some(values) = cache.get(node_cid) {
         return Ok(values.clone());
     }
 
-    let (_, cbor) = load_object_cbor(conn, node_cid)?;
-    let NodeRecord(tag, kind_tag, inputs_raw, out_types, _effects_raw, payload_val) =
-        serde_cbor::from_slice(&cbor)?;
-    if tag != 6 {
+    let mut work = vec![PendingNode {
+        cid: *node_cid,
+        record: None,
+    }];
+    while let Some(top) = work.len().checked_sub(1) {
+        let current = work[top].cid;
+        if cache.contains_key(&current) {
+            work.pop();
+            continue;
+        }
+        match work[top].record.take() {
+            None => {
+                let record = load_node_record(ctx, &current)?;
+                let mut deps = Vec::with_capacity(record.2.len());
+                for input in &record.2 {
+                    // One unit per input edge, as if each input were evaluated on its own.
+                    ctx.budget.spend()?;
+                    let dep = input.cid_array()?;
+                    if !cache.contains_key(&dep) {
+                        deps.push(dep);
+                    }
+                }
+                work[top].record = Some(record);
+                // Reversed so the first input is popped (and evaluated) first.
+                work.extend(
+                    deps.into_iter()
+                        .rev()
+                        .map(|cid| PendingNode { cid, record: None }),
+                );
+            }
+            Some(record) => {
+                work.pop();
+                let values = compute_node(ctx, record, cache, args)?;
+                cache.insert(current, values);
+            }
+        }
+    }
+    cache
+        .get(node_cid)
+        .cloned()
+        .ok_or_else(|| anyhow!("node {} was not evaluated", cid::to_hex(node_cid)))
+}
+
+fn load_node_record(ctx: &mut EvalCtx<'_>, node_cid: &[u8; 32]) -> Result<NodeRecord> {
+    let (_, cbor) = ctx.loader.load(node_cid)?;
+    let record: NodeRecord = serde_cbor::from_slice(&cbor)?;
+    if record.0 != 6 {
         bail!("object {} is not a node", cid::to_hex(node_cid));
     }
+    Ok(record)
+}
+
+/// Compute one node whose data inputs are already in `cache`.
+fn compute_node(
+    ctx: &mut EvalCtx<'_>,
+    record: NodeRecord,
+    cache: &mut HashMap<[u8; 32], Vec<Value>>,
+    args: &[Value],
+) -> Result<Vec<Value>> {
+    let NodeRecord(_, kind_tag, inputs_raw, out_types, _effects_raw, payload_val) = record;
 
     let out_tags: Vec<TypeTag> = out_types
         .iter()
@@ -394,7 +971,10 @@ fn eval_node(
         .map(|tag| tag.token_domain())
         .collect();
 
-    let mut inputs = eval_inputs(conn, &inputs_raw, cache, args)?;
+    let mut inputs = inputs_raw
+        .iter()
+        .map(|input| cached_input(cache, input))
+        .collect::<Result<Vec<_>>>()?;
 
     let values = match kind_tag {
         0 => {
@@ -405,14 +985,14 @@ fn eval_node(
             let prim_cid = cbor_to_bytes32(&payload_val, "PRIM payload")?;
             consume_token_inputs(&mut inputs, &token_domains)?;
             let mut outputs = token_values(&token_domains);
-            outputs.push(eval_primitive(conn, &prim_cid, inputs)?);
+            outputs.push(eval_primitive(ctx, &prim_cid, inputs)?);
             validate_output_tokens(&outputs, &token_domains)?;
             outputs
         }
         2 => {
             let word_cid = cbor_to_bytes32(&payload_val, "CALL payload")?;
             consume_token_inputs(&mut inputs, &token_domains)?;
-            let outputs = run_word(conn, &word_cid, &inputs)?;
+            let outputs = run_word_in(ctx, &word_cid, &inputs)?;
             validate_output_tokens(&outputs, &token_domains)?;
             outputs
         }
@@ -421,14 +1001,32 @@ fn eval_node(
             let value = args
                 .get(index)
                 .cloned()
-                .ok_or_else(|| anyhow!("argument {index} not supplied"))?;
+                .ok_or_else(|| EvalError::Underflow(format!("argument {index} not supplied")))?;
+            vec![value]
+        }
+        4 => {
+            let key = cid::to_hex(&cbor_to_bytes32(&payload_val, "LOAD_GLOBAL payload")?);
+            let expected = *out_tags
+                .first()
+                .ok_or_else(|| anyhow!("LOAD_GLOBAL node declares no output"))?;
+            ctx.read_globals = true;
+            let value =
+                global_store::read(&key).ok_or_else(|| anyhow!("global `{key}` not found"))?;
+            if value.type_tag() != expected {
+                return Err(EvalError::TypeMismatch {
+                    what: format!("global `{key}`"),
+                    expected,
+                    actual: value.type_tag(),
+                }
+                .into());
+            }
             vec![value]
         }
-        4 => bail!("LOAD_GLOBAL not supported by runner (yet)"),
         5 => bail!("RETURN node should be handled at word entry"),
         6 => {
-            if inputs.len() != 2 {
-                bail!("PAIR node expects two inputs, found {}", inputs.len());
+            let arity = tuple_arity(&payload_val, "PAIR payload")?.unwrap_or(2);
+            if inputs.len() != arity {
+                bail!("PAIR node expects {arity} inputs, found {}", inputs.len());
             }
             vec![Value::Tuple(inputs)]
         }
@@ -436,8 +1034,15 @@ fn eval_node(
             if inputs.len() != 1 {
                 bail!("UNPAIR node expects one input, found {}", inputs.len());
             }
+            let arity = tuple_arity(&payload_val, "UNPAIR payload")?;
             match inputs.pop().unwrap() {
-                Value::Tuple(values) => values,
+                Value::Tuple(values) => match arity {
+                    Some(n) if values.len() != n => bail!(
+                        "UNPAIR expected {n}-element tuple, got {} element(s)",
+                        values.len()
+                    ),
+                    _ => values,
+                },
                 other => bail!("UNPAIR expected tuple input, got {:?}", other.type_tag()),
             }
         }
@@ -448,7 +1053,7 @@ fn eval_node(
         9 => {
             let (qid, type_key) = cbor_to_apply_payload(&payload_val)?;
             consume_token_inputs(&mut inputs, &token_domains)?;
-            eval_apply(conn, &qid, type_key, &mut inputs, &token_domains)?
+            eval_apply(ctx, &qid, type_key, &mut inputs, &token_domains)?
         }
         10 => {
             if inputs_raw.len() != 1 {
@@ -457,10 +1062,9 @@ fn eval_node(
                     inputs_raw.len()
                 );
             }
-            let cond_value = inputs
-                .drain(..1)
-                .next()
-                .ok_or_else(|| anyhow!("IF missing evaluated condition"))?;
+            let cond_value = inputs.drain(..1).next().ok_or_else(|| {
+                EvalError::Underflow("IF missing evaluated condition".to_string())
+            })?;
             let cond_truth = match cond_value {
                 Value::I64(n) => n != 0,
                 _ => bail!("IF condition must be i64 (0/!=0)"),
@@ -474,7 +1078,7 @@ fn eval_node(
             } else {
                 &branches[1]
             };
-            let result = eval_input(conn, branch, cache, args)?;
+            let result = eval_input(ctx, branch, cache, args)?;
             vec![result]
         }
         11 => token_values(&token_domains),
@@ -484,10 +1088,28 @@ fn eval_node(
             let input_value = inputs
                 .drain(..1)
                 .next()
-                .ok_or_else(|| anyhow!("GUARD missing evaluated input"))?;
-            let matches = input_value.type_tag() == expected_tag;
+                .ok_or_else(|| EvalError::Underflow("GUARD missing evaluated input".to_string()))?;
+            let actual_tag = input_value.type_tag();
+            let matches = actual_tag == expected_tag;
             let branch = if matches { match_input } else { else_input };
-            vec![eval_input(conn, &branch, cache, args)?]
+            match eval_input(ctx, &branch, cache, args) {
+                // Only a DEOPT directly on the else path is this guard's failure; deeper
+                // guards have already described theirs.
+                Err(err)
+                    if !matches
+                        && is_deopt_error(&err)
+                        && load_node_record(ctx, &branch.cid_array()?)?.1 == 13 =>
+                {
+                    let actual = actual_tag.as_atom();
+                    return Err(match err.downcast_ref::<EvalError>() {
+                        Some(EvalError::Deopt {
+                            reason: Some(reason),
+                        }) => deopt(format!("{reason}, got {actual}")),
+                        _ => deopt(format!("expected {}, got {actual}", expected_tag.as_atom())),
+                    });
+                }
+                value => vec![value?],
+            }
         }
         14 => {
             let cases = cbor_to_dispatch_payload(&payload_val)?;
@@ -509,7 +1131,7 @@ fn eval_node(
                 }
                 if !case.guard_inputs.is_empty() {
                     for guard_input in &case.guard_inputs {
-                        match eval_input(conn, guard_input, cache, args) {
+                        match eval_input(ctx, guard_input, cache, args) {
                             Ok(Value::I64(n)) if n != 0 => {}
                             Ok(Value::I64(_)) => continue 'cases,
                             Ok(other) => {
@@ -522,36 +1144,40 @@ fn eval_node(
                 } else {
                     // Legacy dispatch nodes without lowered guard inputs.
                     for gid in &case.guard_cids {
-                        if !run_guard(conn, gid, &inputs)? {
+                        if !run_guard_in(ctx, gid, &inputs)? {
                             continue 'cases;
                         }
                     }
                 }
-                match eval_input(conn, &case.target, cache, args) {
+                match eval_input(ctx, &case.target, cache, args) {
                     Ok(Value::Tuple(values)) => return Ok(values),
                     Ok(other) => return Ok(vec![other]),
                     Err(err) if is_deopt_error(&err) => continue,
                     Err(err) => return Err(err),
                 }
             }
-            bail!("deopt triggered")
+            return Err(EvalError::Deopt { reason: None }.into());
         }
-        13 => bail!("deopt triggered"),
+        13 => match &payload_val {
+            CborValue::Array(items) => match items.first() {
+                Some(CborValue::Text(reason)) => return Err(deopt(reason.clone())),
+                _ => return Err(EvalError::Deopt { reason: None }.into()),
+            },
+            other => bail!("DEOPT payload must be array, found {other:?}"),
+        },
         other => bail!("unsupported node kind tag `{other}` in runner"),
     };
-
-    cache.insert(*node_cid, values.clone());
     Ok(values)
 }
 
 fn eval_return(
-    conn: &Connection,
+    ctx: &mut EvalCtx<'_>,
     root: &[u8; 32],
     cache: &mut HashMap<[u8; 32], Vec<Value>>,
     args: &[Value],
     info: &crate::word::WordInfo,
 ) -> Result<Vec<Value>> {
-    let (_, cbor) = load_object_cbor(conn, root)?;
+    let (_, cbor) = ctx.loader.load(root)?;
     let NodeRecord(tag, kind_tag, _inputs_raw, _out_types, _effects_raw, payload_val) =
         serde_cbor::from_slice(&cbor)?;
     if tag != 6 {
@@ -560,7 +1186,7 @@ fn eval_return(
 
     if kind_tag != 5 {
         // Legacy root without RETURN.
-        let values = eval_node(conn, root, cache, args)?;
+        let values = eval_node(ctx, root, cache, args)?;
         return Ok(values);
     }
 
@@ -576,44 +1202,41 @@ fn eval_return(
     }
 
     for dep in &deps_raw {
-        let _ = eval_input(conn, dep, cache, args)?;
+        let _ = eval_input(ctx, dep, cache, args)?;
     }
 
     let mut outputs = Vec::with_capacity(vals_raw.len());
     for input in &vals_raw {
-        let value = eval_input(conn, input, cache, args)?;
+        let value = eval_input(ctx, input, cache, args)?;
         outputs.push(value);
     }
     validate_output_tokens(&outputs, &token_domains)?;
     Ok(outputs)
 }
 
-fn eval_inputs(
-    conn: &Connection,
-    records: &[NodeInputRecord],
-    cache: &mut HashMap<[u8; 32], Vec<Value>>,
-    args: &[Value],
-) -> Result<Vec<Value>> {
-    let mut values = Vec::with_capacity(records.len());
-    for input in records {
-        values.push(eval_input(conn, input, cache, args)?);
-    }
-    Ok(values)
-}
-
 fn eval_input(
-    conn: &Connection,
+    ctx: &mut EvalCtx<'_>,
     record: &NodeInputRecord,
     cache: &mut HashMap<[u8; 32], Vec<Value>>,
     args: &[Value],
 ) -> Result<Value> {
+    eval_node(ctx, &record.cid_array()?, cache, args)?;
+    cached_input(cache, record)
+}
+
+fn cached_input(cache: &HashMap<[u8; 32], Vec<Value>>, record: &NodeInputRecord) -> Result<Value> {
     let input_cid = record.cid_array()?;
-    let outputs = eval_node(conn, &input_cid, cache, args)?;
+    let outputs = cache
+        .get(&input_cid)
+        .ok_or_else(|| anyhow!("node {} has not been evaluated", cid::to_hex(&input_cid)))?;
     let port = record.port() as usize;
-    outputs
-        .get(port)
-        .cloned()
-        .ok_or_else(|| anyhow!("node {} missing port {port}", cid::to_hex(&input_cid)))
+    outputs.get(port).cloned().ok_or_else(|| {
+        EvalError::Underflow(format!(
+            "node {} missing port {port}",
+            cid::to_hex(&input_cid)
+        ))
+        .into()
+    })
 }
 
 fn cbor_to_i64(value: &CborValue, context: &str) -> Result<i64> {
@@ -634,6 +1257,14 @@ fn cbor_to_u32(value: &CborValue, context: &str) -> Result<u32> {
     Ok(n as u32)
 }
 
+/// PAIR/UNPAIR arity; `None` for the legacy empty payload.
+fn tuple_arity(value: &CborValue, context: &str) -> Result<Option<usize>> {
+    match value {
+        CborValue::Array(items) if items.is_empty() => Ok(None),
+        other => Ok(Some(cbor_to_u32(other, context)? as usize)),
+    }
+}
+
 fn cbor_to_bytes32(value: &CborValue, context: &str) -> Result<[u8; 32]> {
     match value {
         CborValue::Bytes(bytes) => {
@@ -715,14 +1346,25 @@ fn cbor_to_return_payload(
 }
 
 fn eval_apply(
-    conn: &Connection,
+    ctx: &mut EvalCtx<'_>,
     qid: &[u8; 32],
-    _type_key: Option<[u8; 32]>,
+    type_key: Option<[u8; 32]>,
     inputs: &mut Vec<Value>,
     token_domains: &[Option<EffectDomain>],
 ) -> Result<Vec<Value>> {
+    // A type key marks a specialised application: it only holds for the value
+    // type it was compiled against, so anything else deopts back to the caller.
+    if let Some(key) = type_key {
+        let expected = decode_guard_type_key(&key)?;
+        let actual = inputs.last().map(Value::type_tag);
+        if actual != Some(expected) {
+            return Err(deopt(format!(
+                "APPLY specialised for {expected:?}, got {actual:?}"
+            )));
+        }
+    }
     let args = std::mem::take(inputs);
-    let outputs = run_word(conn, qid, &args)?;
+    let outputs = run_word_in(ctx, qid, &args)?;
     validate_output_tokens(&outputs, token_domains)?;
     Ok(outputs)
 }
@@ -760,6 +1402,16 @@ fn quote_key(value: &Value) -> Result<String> {
     }
 }
 
+fn tuple_items(value: &Value) -> Result<&[Value]> {
+    match value {
+        Value::Tuple(items) => Ok(items),
+        other => bail!(
+            "tuple primitive expects tuple value, got {:?}",
+            other.type_tag()
+        ),
+    }
+}
+
 fn decode_guard_type_key(bytes: &[u8]) -> Result<TypeTag> {
     let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
     let slice = &bytes[..end];
@@ -767,8 +1419,11 @@ fn decode_guard_type_key(bytes: &[u8]) -> Result<TypeTag> {
     TypeTag::from_atom(atom)
 }
 
+/// Prefix of every deopt error; reasons follow after a colon.
+const DEOPT_MESSAGE: &str = "deopt triggered";
+
 fn is_deopt_error(err: &anyhow::Error) -> bool {
-    err.to_string().contains("deopt triggered")
+    matches!(err.downcast_ref(), Some(EvalError::Deopt { .. }))
 }
 
 fn cbor_to_dispatch_payload(value: &CborValue) -> Result<Vec<DispatchCaseRecord>> {
@@ -837,26 +1492,62 @@ fn cbor_to_dispatch_payload(value: &CborValue) -> Result<Vec<DispatchCaseRecord>
     }
 }
 
-fn eval_primitive(conn: &Connection, prim_cid: &[u8; 32], inputs: Vec<Value>) -> Result<Value> {
-    let info = load_prim_info(conn, prim_cid)?;
-    let names: Vec<String> = list_names_for_cid(conn, "prim", prim_cid)?
+/// Evaluate a pure primitive on constant arguments outside of any word (used for folding).
+pub(crate) fn eval_pure_prim(
+    conn: &Connection,
+    prim_cid: &[u8; 32],
+    args: Vec<Value>,
+) -> Result<Value> {
+    let mut ctx = EvalCtx::new(conn, Budget::unlimited());
+    let info = ctx.prim_info(prim_cid)?;
+    if !info.effects.is_empty() || info.effect_mask != effect_mask::NONE {
+        bail!("primitive {} is not pure", cid::to_hex(prim_cid));
+    }
+    eval_primitive(&mut ctx, prim_cid, args)
+}
+
+fn eval_primitive(ctx: &mut EvalCtx<'_>, prim_cid: &[u8; 32], inputs: Vec<Value>) -> Result<Value> {
+    let info = ctx.prim_info(prim_cid)?;
+    let fixed = info.params.len();
+    if inputs.len() < fixed || (info.variadic.is_none() && inputs.len() != fixed) {
+        return Err(EvalError::ArgMismatch {
+            what: format!("primitive {}", cid::to_hex(prim_cid)),
+            expected: fixed,
+            actual: inputs.len(),
+            variadic: info.variadic.is_some(),
+        }
+        .into());
+    }
+    let names: Vec<String> = ctx
+        .loader
+        .names_for_cid("prim", prim_cid)?
         .into_iter()
         .collect();
-    let mut preferred_base: Option<String> = None;
-    const PRIMITIVE_PRIORITY: &[&str] = &[
-        "eq_i64", "gt_i64", "ge_i64", "lt_i64", "le_i64", "and", "or", "not", "add_i64", "sub_i64",
-    ];
-    for target in PRIMITIVE_PRIORITY {
-        if names
-            .iter()
-            .any(|candidate| candidate.rsplit('/').next().unwrap_or(candidate) == *target)
-        {
-            preferred_base = Some((*target).to_string());
-            break;
-        }
+    if let Some(native) = ctx.natives.and_then(|natives| natives.resolve(&names)) {
+        return native(&inputs);
     }
-    let full_name = names.first().cloned();
-    let base = preferred_base.as_deref().or_else(|| {
+    // Prims carrying a built-in id dispatch on it; older ones fall back to their names.
+    let (full_name, base) = match info.builtin.as_deref() {
+        Some(id) => (Some(id.to_string()), Some(id)),
+        None => {
+            let mut bound: Vec<&str> = names.iter().filter_map(|n| prim::builtin_id(n)).collect();
+            bound.sort_unstable();
+            bound.dedup();
+            if bound.len() > 1 {
+                bail!(
+                    "primitive {} is bound to built-ins {}; register each with its own `--builtin` so they get distinct CIDs",
+                    cid::to_hex(prim_cid),
+                    bound.join(", ")
+                );
+            }
+            let base = bound.first().copied();
+            (
+                base.map(str::to_string).or_else(|| names.first().cloned()),
+                base,
+            )
+        }
+    };
+    let base = base.or_else(|| {
         full_name
             .as_deref()
             .map(|n| n.rsplit('/').next().unwrap_or(n))
@@ -871,8 +1562,8 @@ fn eval_primitive(conn: &Connection, prim_cid: &[u8; 32], inputs: Vec<Value>) ->
             let lhs = value_to_i64(&inputs[0])?;
             let rhs = value_to_i64(&inputs[1])?;
             let result = match compiled_add() {
-                Ok(func) => unsafe { func(lhs, rhs) },
-                Err(_) => lhs + rhs,
+                Ok(func) if jit_enabled() => unsafe { func(lhs, rhs) },
+                _ => lhs + rhs,
             };
             Ok(Value::I64(result))
         }
@@ -884,11 +1575,72 @@ fn eval_primitive(conn: &Connection, prim_cid: &[u8; 32], inputs: Vec<Value>) ->
             let lhs = value_to_i64(&inputs[0])?;
             let rhs = value_to_i64(&inputs[1])?;
             let result = match compiled_sub() {
-                Ok(func) => unsafe { func(lhs, rhs) },
-                Err(_) => lhs - rhs,
+                Ok(func) if jit_enabled() => unsafe { func(lhs, rhs) },
+                _ => lhs - rhs,
             };
             Ok(Value::I64(result))
         }
+        Some("neg_i64") => {
+            require_sig(&info, &[TypeTag::I64], &[TypeTag::I64])?;
+            if inputs.len() != 1 {
+                bail!("neg_i64 expects 1 argument, got {}", inputs.len());
+            }
+            let value = value_to_i64(&inputs[0])?;
+            let result = value
+                .checked_neg()
+                .ok_or_else(|| anyhow!("neg_i64 overflow negating {value}"))?;
+            Ok(Value::I64(result))
+        }
+        Some("abs_i64") => {
+            require_sig(&info, &[TypeTag::I64], &[TypeTag::I64])?;
+            if inputs.len() != 1 {
+                bail!("abs_i64 expects 1 argument, got {}", inputs.len());
+            }
+            let value = value_to_i64(&inputs[0])?;
+            let result = value
+                .checked_abs()
+                .ok_or_else(|| anyhow!("abs_i64 overflow on {value}"))?;
+            Ok(Value::I64(result))
+        }
+        Some("sum_i64") => {
+            require_sig(&info, &[], &[TypeTag::I64])?;
+            if info.variadic != Some(TypeTag::I64) {
+                bail!("sum_i64 must take a variadic i64 tail");
+            }
+            let mut total = 0i64;
+            for input in &inputs {
+                total = total
+                    .checked_add(value_to_i64(input)?)
+                    .ok_or_else(|| anyhow!("sum_i64 overflow"))?;
+            }
+            Ok(Value::I64(total))
+        }
+        Some("tuple_len") => {
+            require_sig(&info, &[TypeTag::Ptr], &[TypeTag::I64])?;
+            if inputs.len() != 1 {
+                bail!("tuple_len expects 1 argument, got {}", inputs.len());
+            }
+            let items = tuple_items(&inputs[0])?;
+            Ok(Value::I64(i64::try_from(items.len())?))
+        }
+        Some("tuple_get") => {
+            require_sig(&info, &[TypeTag::Ptr, TypeTag::I64], &[TypeTag::Ptr])?;
+            if inputs.len() != 2 {
+                bail!("tuple_get expects 2 arguments, got {}", inputs.len());
+            }
+            let items = tuple_items(&inputs[0])?;
+            let index = value_to_i64(&inputs[1])?;
+            usize::try_from(index)
+                .ok()
+                .and_then(|idx| items.get(idx))
+                .cloned()
+                .ok_or_else(|| {
+                    anyhow!(
+                        "tuple_get index {index} out of range for tuple of {} element(s)",
+                        items.len()
+                    )
+                })
+        }
         Some("eq_i64") => {
             require_sig(&info, &[TypeTag::I64, TypeTag::I64], &[TypeTag::I64])?;
             if inputs.len() != 2 {
@@ -1022,6 +1774,28 @@ fn eval_primitive(conn: &Connection, prim_cid: &[u8; 32], inputs: Vec<Value>) ->
                     None => bail!("state entry `{key}` not found"),
                 }
             }
+            Some("state.read_i64_array") => {
+                require_sig(&info, &[TypeTag::Ptr], &[TypeTag::Ptr])?;
+                if inputs.len() != 1 {
+                    bail!(
+                        "state.read_i64_array expects 1 argument, got {}",
+                        inputs.len()
+                    );
+                }
+                let key = quote_key(&inputs[0])?;
+                match global_store::read(&key) {
+                    Some(Value::Tuple(items))
+                        if items.iter().all(|v| matches!(v, Value::I64(_))) =>
+                    {
+                        Ok(Value::Tuple(items))
+                    }
+                    Some(other) => bail!(
+                        "state entry `{key}` does not hold an i64 array (found {})",
+                        other.type_name()
+                    ),
+                    None => bail!("state entry `{key}` not found"),
+                }
+            }
             Some("state.write_i64") => {
                 require_sig(&info, &[TypeTag::Ptr, TypeTag::I64], &[TypeTag::Unit])?;
                 if inputs.len() != 2 {
@@ -1058,6 +1832,29 @@ fn eval_primitive(conn: &Connection, prim_cid: &[u8; 32], inputs: Vec<Value>) ->
                 global_store::write(key, value);
                 Ok(Value::Unit)
             }
+            Some("state.write_i64_array") => {
+                require_sig(&info, &[TypeTag::Ptr, TypeTag::Ptr], &[TypeTag::Unit])?;
+                if inputs.len() != 2 {
+                    bail!(
+                        "state.write_i64_array expects 2 arguments, got {}",
+                        inputs.len()
+                    );
+                }
+                let key = quote_key(&inputs[0])?;
+                let items = tuple_items(&inputs[1])?;
+                if let Some((idx, other)) = items
+                    .iter()
+                    .enumerate()
+                    .find(|(_, v)| !matches!(v, Value::I64(_)))
+                {
+                    bail!(
+                        "state.write_i64_array element {idx} is {}, expected i64",
+                        other.type_name()
+                    );
+                }
+                global_store::write(key, Value::Tuple(items.to_vec()));
+                Ok(Value::Unit)
+            }
             Some("state.write_text") => {
                 require_sig(&info, &[TypeTag::Ptr, TypeTag::Text], &[TypeTag::Unit])?;
                 if inputs.len() != 2 {
@@ -1074,135 +1871,1328 @@ fn eval_primitive(conn: &Connection, prim_cid: &[u8; 32], inputs: Vec<Value>) ->
                 global_store::write(key, value);
                 Ok(Value::Unit)
             }
-            Some(other) => bail!("primitive `{other}` not supported in runner"),
-            None => bail!(
-                "primitive {} not registered with a name (runner needs a symbolic name)",
-                cid::to_hex(prim_cid)
-            ),
+            Some("state.write_ttl_i64") => {
+                require_sig(
+                    &info,
+                    &[TypeTag::Ptr, TypeTag::I64, TypeTag::I64],
+                    &[TypeTag::Unit],
+                )?;
+                if inputs.len() != 3 {
+                    bail!(
+                        "state.write_ttl_i64 expects 3 arguments, got {}",
+                        inputs.len()
+                    );
+                }
+                let key = quote_key(&inputs[0])?;
+                let value = value_to_i64(&inputs[1])?;
+                let ttl = u64::try_from(value_to_i64(&inputs[2])?)
+                    .map_err(|_| anyhow!("state.write_ttl_i64 ttl must not be negative"))?;
+                global_store::write_with_ttl(
+                    key,
+                    Value::I64(value),
+                    std::time::Duration::from_secs(ttl),
+                );
+                Ok(Value::Unit)
+            }
+            Some("state.delete") => {
+                require_sig(&info, &[TypeTag::Ptr], &[TypeTag::Unit])?;
+                if inputs.len() != 1 {
+                    bail!("state.delete expects 1 argument, got {}", inputs.len());
+                }
+                let key = quote_key(&inputs[0])?;
+                global_store::remove(&key);
+                Ok(Value::Unit)
+            }
+            Some("state.cas_i64") => {
+                require_sig(
+                    &info,
+                    &[TypeTag::Ptr, TypeTag::I64, TypeTag::I64],
+                    &[TypeTag::I64],
+                )?;
+                if inputs.len() != 3 {
+                    bail!("state.cas_i64 expects 3 arguments, got {}", inputs.len());
+                }
+                let key = quote_key(&inputs[0])?;
+                let expected = value_to_i64(&inputs[1])?;
+                let new = value_to_i64(&inputs[2])?;
+                let swapped = global_store::compare_and_swap(
+                    &key,
+                    Some(Value::I64(expected)),
+                    Value::I64(new),
+                );
+                Ok(Value::I64(if swapped { 1 } else { 0 }))
+            }
+            Some("metric.incr") => {
+                require_sig(&info, &[TypeTag::Ptr, TypeTag::I64], &[TypeTag::Unit])?;
+                if inputs.len() != 2 {
+                    bail!("metric.incr expects 2 arguments, got {}", inputs.len());
+                }
+                let key = quote_key(&inputs[0])?;
+                metrics::incr(key, value_to_i64(&inputs[1])?);
+                Ok(Value::Unit)
+            }
+            Some("metric.read") => {
+                require_sig(&info, &[TypeTag::Ptr], &[TypeTag::I64])?;
+                if inputs.len() != 1 {
+                    bail!("metric.read expects 1 argument, got {}", inputs.len());
+                }
+                let key = quote_key(&inputs[0])?;
+                Ok(Value::I64(metrics::read(&key)))
+            }
+            Some(other) => Err(EvalError::UnknownPrim(other.to_string()).into()),
+            None => Err(EvalError::UnnamedPrim(*prim_cid).into()),
         },
     }
-}
+}
+
+fn require_sig(
+    info: &crate::prim::PrimInfo,
+    params: &[TypeTag],
+    results: &[TypeTag],
+) -> Result<()> {
+    if info.params != params || info.results != results {
+        bail!(
+            "primitive signature mismatch: params {:?} -> {:?}, expected {:?} -> {:?}",
+            info.params,
+            info.results,
+            params,
+            results
+        );
+    }
+    Ok(())
+}
+
+fn bytebuf_to_array(buf: &ByteBuf) -> Result<[u8; 32]> {
+    let slice = buf.as_slice();
+    if slice.len() != 32 {
+        bail!("expected 32-byte CID, found {} bytes", slice.len());
+    }
+    let mut arr = [0u8; 32];
+    arr.copy_from_slice(slice);
+    Ok(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{DispatchSpec, GraphBuilder};
+    use crate::db;
+    use crate::global_store;
+    use crate::node::{NodeCanon, NodeInput, NodeKind, NodePayload};
+    use crate::prim::{self, PrimCanon};
+    use crate::types::{EffectDomain, TypeTag, effect_mask};
+
+    #[test]
+    fn check_token_sequence_enforces_domain_order() -> Result<()> {
+        let io = Value::Token(Some(EffectDomain::Io));
+        let state = Value::Token(Some(EffectDomain::State));
+        let domains = [EffectDomain::Io, EffectDomain::State];
+
+        check_token_sequence(&[io.clone(), state.clone(), Value::I64(1)], &domains)?;
+
+        let swapped = check_token_sequence(&[state.clone(), io.clone()], &domains).unwrap_err();
+        assert!(swapped.to_string().contains("expected Io"), "{swapped}");
+
+        let missing = check_token_sequence(std::slice::from_ref(&io), &domains).unwrap_err();
+        assert!(
+            missing.to_string().contains("expected 2 token output(s)"),
+            "{missing}"
+        );
+        assert!(check_token_sequence(&[io, Value::I64(1)], &domains).is_err());
+        Ok(())
+    }
+
+    fn guard_key(tag: TypeTag) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        let atom = tag.as_atom().as_bytes();
+        bytes[..atom.len()].copy_from_slice(atom);
+        bytes
+    }
+
+    #[test]
+    fn apply_type_key_deopts_on_mismatch() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[TypeTag::I64])?;
+        let identity = builder.finish_word(&[TypeTag::I64], &[TypeTag::I64], Some("demo/id"))?;
+
+        let apply_with = |key: TypeTag, name: &str| -> Result<[u8; 32]> {
+            let mut builder = GraphBuilder::new(&conn);
+            builder.begin_word(&[])?;
+            builder.push_lit_i64(4)?;
+            builder.apply_quotation(
+                identity,
+                &[TypeTag::I64],
+                &[TypeTag::I64],
+                &[],
+                effect_mask::NONE,
+                Some(guard_key(key)),
+            )?;
+            builder.finish_word(&[], &[TypeTag::I64], Some(name))
+        };
+
+        let matching = apply_with(TypeTag::I64, "demo/apply_i64")?;
+        assert_eq!(run_word(&conn, &matching, &[])?, vec![Value::I64(4)]);
+
+        let mismatched = apply_with(TypeTag::F64, "demo/apply_f64")?;
+        let err = run_word(&conn, &mismatched, &[]).unwrap_err();
+        assert!(is_deopt_error(&err), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn dispatch_runs_first_candidate_whose_guard_passes() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let params = [TypeTag::I64, TypeTag::I64];
+        let results = [TypeTag::I64];
+        let gt = PrimCanon {
+            params: &params,
+            results: &results,
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let gt_cid = prim::store_prim(&conn, &gt)?.cid;
+        db::put_name(&conn, "prim", "core/gt_i64", &gt_cid)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        // x > 0
+        builder.begin_guard(&[TypeTag::I64])?;
+        builder.push_lit_i64(0)?;
+        builder.apply_prim(gt_cid)?;
+        let positive = builder.finish_guard(&[TypeTag::I64], &results, Some("demo/positive"))?;
+        // -10 > x
+        builder.begin_guard(&[TypeTag::I64])?;
+        builder.push_lit_i64(-10)?;
+        builder.swap()?;
+        builder.apply_prim(gt_cid)?;
+        let very_negative =
+            builder.finish_guard(&[TypeTag::I64], &results, Some("demo/very_negative"))?;
+
+        let mut candidates = Vec::new();
+        for (guard, value, name) in [
+            (positive, 1, "demo/on_positive"),
+            (very_negative, 2, "demo/on_very_negative"),
+        ] {
+            builder.begin_word(&[TypeTag::I64])?;
+            builder.drop()?;
+            builder.push_lit_i64(value)?;
+            builder.attach_guard(guard);
+            let word = builder.finish_word(&[TypeTag::I64], &results, Some(name))?;
+            candidates.push((word, crate::word::load_word_info(&conn, &word)?));
+        }
+        let specs: Vec<DispatchSpec<'_>> = candidates
+            .iter()
+            .map(|(word, info)| DispatchSpec {
+                word: *word,
+                params: &info.params,
+                results: &info.results,
+                guards: &info.guards,
+                effect_mask: info.effect_mask,
+            })
+            .collect();
+
+        builder.begin_word(&[TypeTag::I64])?;
+        builder.apply_dispatch(&specs)?;
+        let select = builder.finish_word(&[TypeTag::I64], &results, Some("demo/select"))?;
+
+        assert_eq!(
+            run_word(&conn, &select, &[Value::I64(5)])?,
+            vec![Value::I64(1)]
+        );
+        assert_eq!(
+            run_word(&conn, &select, &[Value::I64(-20)])?,
+            vec![Value::I64(2)]
+        );
+        let err = run_word(&conn, &select, &[Value::I64(-5)]).unwrap_err();
+        assert!(is_deopt_error(&err), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn run_word_supports_multi_result_literals() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.push_lit_i64(1)?;
+        builder.push_lit_i64(2)?;
+        let word_cid =
+            builder.finish_word(&[], &[TypeTag::I64, TypeTag::I64], Some("demo/multi"))?;
+
+        let outputs = run_word(&conn, &word_cid, &[])?;
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0], Value::I64(1));
+        assert_eq!(outputs[1], Value::I64(2));
+        Ok(())
+    }
+
+    #[test]
+    fn unary_i64_primitives_evaluate() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let unary = [TypeTag::I64];
+        let mut prim_ids = HashMap::new();
+        for name in ["neg_i64", "abs_i64"] {
+            let prim = PrimCanon {
+                params: &unary,
+                results: &unary,
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: prim::builtin_id(name),
+            };
+            let cid = prim::store_prim(&conn, &prim)?.cid;
+            db::put_name(&conn, "prim", &format!("core/{name}"), &cid)?;
+            prim_ids.insert(name, cid);
+        }
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&unary)?;
+        builder.apply_prim(prim_ids["neg_i64"])?;
+        let neg_word = builder.finish_word(&unary, &unary, Some("demo/negate"))?;
+        assert_eq!(
+            run_word(&conn, &neg_word, &[Value::I64(7)])?,
+            vec![Value::I64(-7)]
+        );
+        assert!(run_word(&conn, &neg_word, &[Value::I64(i64::MIN)]).is_err());
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.push_lit_i64(-42)?;
+        builder.apply_prim(prim_ids["abs_i64"])?;
+        let abs_word = builder.finish_word(&[], &unary, Some("demo/abs_literal"))?;
+        assert_eq!(run_word(&conn, &abs_word, &[])?, vec![Value::I64(42)]);
+
+        assert_ne!(prim_ids["neg_i64"], prim_ids["abs_i64"]);
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&unary)?;
+        builder.apply_prim(prim_ids["abs_i64"])?;
+        let abs_word = builder.finish_word(&unary, &unary, Some("demo/abs"))?;
+        assert_eq!(
+            run_word(&conn, &abs_word, &[Value::I64(5)])?,
+            vec![Value::I64(5)]
+        );
+        assert_eq!(
+            run_word(&conn, &neg_word, &[Value::I64(5)])?,
+            vec![Value::I64(-5)]
+        );
+
+        // The built-in id, not the names, picks the operation.
+        db::put_name(&conn, "prim", "core/abs_i64", &prim_ids["neg_i64"])?;
+        assert_eq!(
+            run_word(&conn, &neg_word, &[Value::I64(5)])?,
+            vec![Value::I64(-5)]
+        );
+
+        // Prims stored without an id fall back to their names, which must agree.
+        let legacy = prim::store_prim(
+            &conn,
+            &PrimCanon {
+                params: &unary,
+                results: &unary,
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            },
+        )?
+        .cid;
+        db::put_name(&conn, "prim", "legacy/neg_i64", &legacy)?;
+        builder.begin_word(&unary)?;
+        builder.apply_prim(legacy)?;
+        let legacy_word = builder.finish_word(&unary, &unary, None)?;
+        assert_eq!(
+            run_word(&conn, &legacy_word, &[Value::I64(5)])?,
+            vec![Value::I64(-5)]
+        );
+        db::put_name(&conn, "prim", "legacy/abs_i64", &legacy)?;
+        let err = run_word(&conn, &legacy_word, &[Value::I64(5)]).unwrap_err();
+        assert!(err.to_string().contains("abs_i64, neg_i64"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn variadic_sum_accepts_any_number_of_trailing_args() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let result = [TypeTag::I64];
+        let sum = prim::store_prim(
+            &conn,
+            &PrimCanon {
+                params: &[],
+                results: &result,
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: Some(TypeTag::I64),
+                builtin: None,
+            },
+        )?
+        .cid;
+        db::put_name(&conn, "prim", "core/sum_i64", &sum)?;
+
+        for (count, expected) in [(0usize, 0i64), (1, 1), (3, 6)] {
+            let params = vec![TypeTag::I64; count];
+            let mut builder = GraphBuilder::new(&conn);
+            builder.begin_word(&params)?;
+            builder.apply_prim_variadic(sum, count)?;
+            let word = builder.finish_word(&params, &result, None)?;
+            let args: Vec<Value> = (1..=count as i64).map(Value::I64).collect();
+            assert_eq!(run_word(&conn, &word, &args)?, vec![Value::I64(expected)]);
+        }
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[TypeTag::F64])?;
+        assert!(builder.apply_prim_variadic(sum, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn registered_native_prims_run_before_builtins() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let unary = [TypeTag::I64];
+        let double = prim::store_prim(
+            &conn,
+            &PrimCanon {
+                params: &unary,
+                results: &unary,
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            },
+        )?
+        .cid;
+        db::put_name(&conn, "prim", "ext/double_i64", &double)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&unary)?;
+        builder.apply_prim(double)?;
+        let word = builder.finish_word(&unary, &unary, Some("ext/double"))?;
+
+        assert!(run_word(&conn, &word, &[Value::I64(21)]).is_err());
+        let mut natives = PrimRegistry::new();
+        natives.register("double_i64", |args| {
+            Ok(Value::I64(value_to_i64(&args[0])? * 2))
+        });
+        assert_eq!(
+            run_word_with_prims(&conn, &word, &[Value::I64(21)], &natives)?,
+            vec![Value::I64(42)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cached_runs_skip_pure_words_but_not_effectful_ones() -> Result<()> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static PURE_RUNS: AtomicUsize = AtomicUsize::new(0);
+        static IO_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let unary = [TypeTag::I64];
+        let word_over = |name: &str, mask: EffectMask| -> Result<[u8; 32]> {
+            let prim_cid = prim::store_prim(
+                &conn,
+                &PrimCanon {
+                    params: &unary,
+                    results: &unary,
+                    effects: &[],
+                    effect_mask: mask,
+                    doc: Some(name),
+                    variadic: None,
+                    builtin: None,
+                },
+            )?
+            .cid;
+            db::put_name(&conn, "prim", &format!("ext/{name}"), &prim_cid)?;
+            let mut builder = GraphBuilder::new(&conn);
+            builder.begin_word(&unary)?;
+            builder.apply_prim(prim_cid)?;
+            builder.finish_word(&unary, &unary, None)
+        };
+        let pure = word_over("pure_bump", effect_mask::NONE)?;
+        let effectful = word_over("io_bump", effect_mask::IO)?;
+
+        let mut natives = PrimRegistry::new();
+        natives.register("pure_bump", |args| {
+            PURE_RUNS.fetch_add(1, Ordering::SeqCst);
+            Ok(Value::I64(value_to_i64(&args[0])? + 1))
+        });
+        natives.register("io_bump", |args| {
+            IO_RUNS.fetch_add(1, Ordering::SeqCst);
+            Ok(Value::I64(value_to_i64(&args[0])? + 1))
+        });
+
+        let _lock = result_cache_test_lock();
+        let args = [Value::I64(41)];
+        let first = run_word_cached_with_prims(&conn, &pure, &args, &natives)?;
+        let second = run_word_cached_with_prims(&conn, &pure, &args, &natives)?;
+        assert_eq!(first, vec![Value::I64(42)]);
+        assert_eq!(second, first);
+        assert_eq!(PURE_RUNS.load(Ordering::SeqCst), 1);
+        run_word_cached_with_prims(&conn, &pure, &[Value::I64(1)], &natives)?;
+        assert_eq!(PURE_RUNS.load(Ordering::SeqCst), 2);
+
+        run_word_cached_with_prims(&conn, &effectful, &args, &natives)?;
+        run_word_cached_with_prims(&conn, &effectful, &args, &natives)?;
+        assert_eq!(IO_RUNS.load(Ordering::SeqCst), 2);
+
+        clear_result_cache();
+        run_word_cached_with_prims(&conn, &pure, &args, &natives)?;
+        assert_eq!(PURE_RUNS.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn result_caches_do_not_leak_between_stores() -> Result<()> {
+        let pair = [TypeTag::I64, TypeTag::I64];
+        let one = [TypeTag::I64];
+        let store_with = |prim_name: &str| -> Result<(Connection, [u8; 32])> {
+            let conn = Connection::open_in_memory()?;
+            db::install_schema(&conn)?;
+            let prim_cid = prim::store_prim(
+                &conn,
+                &PrimCanon {
+                    params: &pair,
+                    results: &one,
+                    effects: &[],
+                    effect_mask: effect_mask::NONE,
+                    doc: None,
+                    variadic: None,
+                    builtin: None,
+                },
+            )?
+            .cid;
+            db::put_name(&conn, "prim", prim_name, &prim_cid)?;
+            let mut builder = GraphBuilder::new(&conn);
+            builder.begin_word(&pair)?;
+            builder.apply_prim(prim_cid)?;
+            let word = builder.finish_word(&pair, &one, None)?;
+            Ok((conn, word))
+        };
+        let (add_conn, add_word) = store_with("core/add_i64")?;
+        let (sub_conn, sub_word) = store_with("core/sub_i64")?;
+        assert_eq!(add_word, sub_word, "same graph, same CID");
+
+        let _lock = result_cache_test_lock();
+        let args = [Value::I64(5), Value::I64(3)];
+        assert_eq!(
+            run_word_cached(&add_conn, &add_word, &args)?,
+            vec![Value::I64(8)]
+        );
+        assert_eq!(
+            run_word_cached(&sub_conn, &sub_word, &args)?,
+            vec![Value::I64(2)]
+        );
+        assert_eq!(
+            run_word_cached(&add_conn, &add_word, &args)?,
+            vec![Value::I64(8)]
+        );
+
+        // Registries are part of the key too: same store, same word, other natives.
+        let mut doubled = PrimRegistry::new();
+        doubled.register("add_i64", |args| {
+            Ok(Value::I64(
+                2 * (value_to_i64(&args[0])? + value_to_i64(&args[1])?),
+            ))
+        });
+        assert_eq!(
+            run_word_cached_with_prims(&add_conn, &add_word, &args, &doubled)?,
+            vec![Value::I64(16)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cached_runs_reread_globals() -> Result<()> {
+        let _lock = global_store::test_lock();
+        global_store::reset();
+
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let key = [0x6C; 32];
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.push_load_global(key, TypeTag::I64)?;
+        let word = builder.finish_word(&[], &[TypeTag::I64], None)?;
+
+        let _cache_lock = result_cache_test_lock();
+        global_store::write(cid::to_hex(&key), Value::I64(1));
+        assert_eq!(run_word_cached(&conn, &word, &[])?, vec![Value::I64(1)]);
+        global_store::write(cid::to_hex(&key), Value::I64(2));
+        assert_eq!(run_word_cached(&conn, &word, &[])?, vec![Value::I64(2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn result_cache_evicts_the_least_recently_used_entry() {
+        let key = |n: i64| CacheKey {
+            store: String::new(),
+            registry: None,
+            word: [0; 32],
+            args: vec![Value::I64(n), Value::F64(f64::NAN)],
+        };
+        let mut cache = ResultCache::new(2);
+        cache.insert(key(1), vec![Value::I64(1)]);
+        cache.insert(key(2), vec![Value::I64(2)]);
+        assert_eq!(cache.get(&key(1)), Some(vec![Value::I64(1)]));
+        cache.insert(key(3), vec![Value::I64(3)]);
+        assert_eq!(cache.get(&key(2)), None);
+        assert!(cache.get(&key(1)).is_some() && cache.get(&key(3)).is_some());
+
+        let mut disabled = ResultCache::new(0);
+        disabled.insert(key(1), vec![Value::I64(1)]);
+        assert_eq!(disabled.get(&key(1)), None);
+    }
+
+    /// Serialises tests that count hits in the process-global result cache.
+    fn result_cache_test_lock() -> std::sync::MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    #[test]
+    fn add_i64_falls_back_when_jit_is_disabled() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let pair = [TypeTag::I64, TypeTag::I64];
+        let one = [TypeTag::I64];
+        let add = prim::store_prim(
+            &conn,
+            &PrimCanon {
+                params: &pair,
+                results: &one,
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            },
+        )?
+        .cid;
+        db::put_name(&conn, "prim", "core/add_i64", &add)?;
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&pair)?;
+        builder.apply_prim(add)?;
+        let word = builder.finish_word(&pair, &one, None)?;
+
+        crate::exec::set_jit_enabled(false);
+        let result = run_word(&conn, &word, &[Value::I64(40), Value::I64(2)]);
+        crate::exec::set_jit_enabled(true);
+        assert_eq!(result?, vec![Value::I64(42)]);
+        Ok(())
+    }
+
+    #[test]
+    fn type_guard_deopt_reports_expected_and_actual_types() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let tuple_get = prim::store_prim(
+            &conn,
+            &PrimCanon {
+                params: &[TypeTag::Ptr, TypeTag::I64],
+                results: &[TypeTag::Ptr],
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            },
+        )?
+        .cid;
+        db::put_name(&conn, "prim", "core/tuple_get", &tuple_get)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[TypeTag::Ptr])?;
+        builder.push_lit_i64(0)?;
+        builder.apply_prim(tuple_get)?;
+        builder.guard_type(TypeTag::I64)?;
+        let first = builder.finish_word(&[TypeTag::Ptr], &[TypeTag::I64], None)?;
+
+        let ints = Value::Tuple(vec![Value::I64(5)]);
+        assert_eq!(run_word(&conn, &first, &[ints])?, vec![Value::I64(5)]);
+
+        let floats = Value::Tuple(vec![Value::F64(0.5)]);
+        let err = run_word(&conn, &first, &[floats]).unwrap_err();
+        assert_eq!(err.to_string(), "deopt triggered: expected i64, got f64");
+        Ok(())
+    }
+
+    #[test]
+    fn budget_caps_node_evaluations() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let params = [TypeTag::I64, TypeTag::I64];
+        let results = [TypeTag::I64];
+        let add = PrimCanon {
+            params: &params,
+            results: &results,
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let add_cid = prim::store_prim(&conn, &add)?.cid;
+        db::put_name(&conn, "prim", "core/add_i64", &add_cid)?;
+
+        // Eight independent (lit + lit) sums folded together: wide, but only a few levels deep.
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        for n in 0..8 {
+            builder.push_lit_i64(n)?;
+            builder.push_lit_i64(n * 10)?;
+            builder.apply_prim(add_cid)?;
+        }
+        for _ in 0..7 {
+            builder.apply_prim(add_cid)?;
+        }
+        let word_cid = builder.finish_word(&[], &results, Some("demo/fan"))?;
+
+        let mut tight = Budget::new(10);
+        let err = run_word_budgeted(&conn, &word_cid, &[], &mut tight).unwrap_err();
+        assert!(err.to_string().contains("budget exhausted"), "{err}");
+        assert_eq!(tight.remaining, 0);
+
+        let mut generous = Budget::new(1_000);
+        let outputs = run_word_budgeted(&conn, &word_cid, &[], &mut generous)?;
+        assert_eq!(outputs, vec![Value::I64(308)]);
+        assert!(generous.remaining < 1_000);
+        Ok(())
+    }
+
+    #[test]
+    fn repeated_calls_reuse_cached_descriptors() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let params = [TypeTag::I64, TypeTag::I64];
+        let unary = [TypeTag::I64];
+        let add = PrimCanon {
+            params: &params,
+            results: &unary,
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let add_cid = prim::store_prim(&conn, &add)?.cid;
+        db::put_name(&conn, "prim", "core/add_i64", &add_cid)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&unary)?;
+        builder.push_lit_i64(1)?;
+        builder.apply_prim(add_cid)?;
+        let inc = builder.finish_word(&unary, &unary, Some("demo/inc"))?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&unary)?;
+        for _ in 0..5 {
+            builder.apply_word(inc)?;
+        }
+        let add_five = builder.finish_word(&unary, &unary, Some("demo/add_five"))?;
+
+        assert_eq!(
+            run_word(&conn, &add_five, &[Value::I64(10)])?,
+            vec![Value::I64(15)]
+        );
+
+        let mut ctx = EvalCtx::new(&conn, Budget::unlimited());
+        let outputs = run_word_in(&mut ctx, &add_five, &[Value::I64(-3)])?;
+        assert_eq!(outputs, vec![Value::I64(2)]);
+        assert_eq!(ctx.words.len(), 2);
+        assert_eq!(ctx.prims.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn deep_chains_evaluate_without_native_recursion() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let params = [TypeTag::I64, TypeTag::I64];
+        let results = [TypeTag::I64];
+        let add = PrimCanon {
+            params: &params,
+            results: &results,
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let add_cid = prim::store_prim(&conn, &add)?.cid;
+        db::put_name(&conn, "prim", "core/add_i64", &add_cid)?;
+
+        const DEPTH: i64 = 50_000;
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.push_lit_i64(0)?;
+        for _ in 0..DEPTH {
+            builder.push_lit_i64(1)?;
+            builder.apply_prim(add_cid)?;
+        }
+        let word_cid = builder.finish_word(&[], &results, Some("demo/deep"))?;
+
+        assert_eq!(run_word(&conn, &word_cid, &[])?, vec![Value::I64(DEPTH)]);
+        Ok(())
+    }
+
+    #[test]
+    fn run_word_with_multiple_tokens() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        // Create a primitive that claims IO + State effects so we emit both tokens.
+        let params = [TypeTag::I64, TypeTag::I64];
+        let results = [TypeTag::I64];
+        let prim = PrimCanon {
+            params: &params,
+            results: &results,
+            effects: &[],
+            effect_mask: effect_mask::IO | effect_mask::STATE_READ,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let prim_outcome = prim::store_prim(&conn, &prim)?;
+        db::put_name(&conn, "prim", "add_i64", &prim_outcome.cid)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&params)?;
+        builder.apply_prim(prim_outcome.cid)?;
+        let word_cid = builder.finish_word(&params, &results, Some("demo/add_multi"))?;
+
+        let outputs = run_word(&conn, &word_cid, &[Value::I64(1), Value::I64(2)])?;
+
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(outputs[0], Value::Token(Some(EffectDomain::Io)));
+        assert_eq!(outputs[1], Value::Token(Some(EffectDomain::State)));
+        assert_eq!(outputs[2], Value::I64(3));
+        Ok(())
+    }
+
+    #[test]
+    fn state_read_write_roundtrip() -> Result<()> {
+        let _lock = global_store::test_lock();
+        global_store::reset();
+
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let key = [0xAA; 32];
+
+        let read_params = [TypeTag::Ptr];
+        let read_results = [TypeTag::I64];
+        let read_prim = PrimCanon {
+            params: &read_params,
+            results: &read_results,
+            effects: &[],
+            effect_mask: effect_mask::STATE_READ,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let read_outcome = prim::store_prim(&conn, &read_prim)?;
+        db::put_name(&conn, "prim", "state.read_i64", &read_outcome.cid)?;
+
+        let write_params = [TypeTag::Ptr, TypeTag::I64];
+        let write_results = [TypeTag::Unit];
+        let write_prim = PrimCanon {
+            params: &write_params,
+            results: &write_results,
+            effects: &[],
+            effect_mask: effect_mask::STATE_WRITE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let write_outcome = prim::store_prim(&conn, &write_prim)?;
+        db::put_name(&conn, "prim", "state.write_i64", &write_outcome.cid)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        builder.push_lit_i64(99)?;
+        builder.apply_prim(write_outcome.cid)?;
+        builder.drop()?;
+        builder.quote(key)?;
+        builder.apply_prim(read_outcome.cid)?;
+        let word_cid = builder.finish_word(&[], &[TypeTag::I64], Some("state/test"))?;
+
+        let outputs = run_word(&conn, &word_cid, &[])?;
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0], Value::Token(Some(EffectDomain::State)));
+        assert_eq!(outputs[1], Value::I64(99));
+
+        let stored = global_store::read(&cid::to_hex(&key)).expect("value persisted");
+        assert_eq!(stored, Value::I64(99));
+        Ok(())
+    }
+
+    #[test]
+    fn state_i64_arrays_round_trip_and_reject_mixed_tuples() -> Result<()> {
+        let _lock = global_store::test_lock();
+        global_store::reset();
+
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let key = [0xA7; 32];
+        let mut prim_ids = HashMap::new();
+        for (name, params, results, mask) in [
+            (
+                "state.write_i64_array",
+                vec![TypeTag::Ptr, TypeTag::Ptr],
+                vec![TypeTag::Unit],
+                effect_mask::STATE_WRITE,
+            ),
+            (
+                "state.read_i64_array",
+                vec![TypeTag::Ptr],
+                vec![TypeTag::Ptr],
+                effect_mask::STATE_READ,
+            ),
+            (
+                "state.write_ptr",
+                vec![TypeTag::Ptr, TypeTag::Ptr],
+                vec![TypeTag::Unit],
+                effect_mask::STATE_WRITE,
+            ),
+            (
+                "state.read_ptr",
+                vec![TypeTag::Ptr],
+                vec![TypeTag::Ptr],
+                effect_mask::STATE_READ,
+            ),
+        ] {
+            let prim = PrimCanon {
+                params: &params,
+                results: &results,
+                effects: &[],
+                effect_mask: mask,
+                doc: None,
+                variadic: None,
+                builtin: prim::builtin_id(name),
+            };
+            let cid = prim::store_prim(&conn, &prim)?.cid;
+            db::put_name(&conn, "prim", name, &cid)?;
+            prim_ids.insert(name, cid);
+        }
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        for n in [3, 1, 4, 1] {
+            builder.push_lit_i64(n)?;
+        }
+        builder.tuple(4)?;
+        builder.apply_prim(prim_ids["state.write_i64_array"])?;
+        builder.drop()?;
+        builder.quote(key)?;
+        builder.apply_prim(prim_ids["state.read_i64_array"])?;
+        let round_trip = builder.finish_word(&[], &[TypeTag::Ptr], Some("state/array"))?;
+
+        let outputs = run_word(&conn, &round_trip, &[])?;
+        let expected = Value::Tuple([3, 1, 4, 1].map(Value::I64).to_vec());
+        assert_eq!(outputs.last(), Some(&expected));
+        assert_eq!(global_store::read(&cid::to_hex(&key)), Some(expected));
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        builder.push_lit_i64(7)?;
+        builder.quote([0x01; 32])?;
+        builder.tuple(2)?;
+        builder.apply_prim(prim_ids["state.write_i64_array"])?;
+        let mixed = builder.finish_word(&[], &[TypeTag::Unit], Some("state/mixed"))?;
+
+        let err = run_word(&conn, &mixed, &[]).unwrap_err();
+        assert!(err.to_string().contains("element 1 is quote"), "{err}");
+        assert_eq!(
+            global_store::read(&cid::to_hex(&key)),
+            Some(Value::Tuple([3, 1, 4, 1].map(Value::I64).to_vec()))
+        );
+
+        assert_ne!(
+            prim_ids["state.write_ptr"],
+            prim_ids["state.write_i64_array"]
+        );
+        assert_ne!(prim_ids["state.read_ptr"], prim_ids["state.read_i64_array"]);
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        builder.quote([0x01; 32])?;
+        builder.apply_prim(prim_ids["state.write_ptr"])?;
+        builder.drop()?;
+        builder.quote(key)?;
+        builder.apply_prim(prim_ids["state.read_ptr"])?;
+        let ptr_round_trip = builder.finish_word(&[], &[TypeTag::Ptr], Some("state/ptr"))?;
+        let outputs = run_word(&conn, &ptr_round_trip, &[])?;
+        assert_eq!(outputs.last(), Some(&Value::Quote([0x01; 32])));
+
+        // The array identity comes from the built-in id, so a doc (or another
+        // name) neither creates nor removes it.
+        let params = [TypeTag::Ptr, TypeTag::Ptr];
+        let documented = prim::store_prim(
+            &conn,
+            &PrimCanon {
+                params: &params,
+                results: &[TypeTag::Unit],
+                effects: &[],
+                effect_mask: effect_mask::STATE_WRITE,
+                doc: Some("bulk write"),
+                variadic: None,
+                builtin: Some("state.write_i64_array"),
+            },
+        )?
+        .cid;
+        db::put_name(&conn, "prim", "demo/bulk_write", &documented)?;
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        builder.push_lit_i64(7)?;
+        builder.quote([0x01; 32])?;
+        builder.tuple(2)?;
+        builder.apply_prim(documented)?;
+        let bulk = builder.finish_word(&[], &[TypeTag::Unit], Some("state/bulk"))?;
+        let err = run_word(&conn, &bulk, &[]).unwrap_err();
+        assert!(err.to_string().contains("element 1 is quote"), "{err}");
+        Ok(())
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::builder::GraphBuilder;
-    use crate::db;
-    use crate::global_store;
-    use crate::node::{NodeCanon, NodeInput, NodeKind, NodePayload};
-    use crate::prim::{self, PrimCanon};
-    use crate::types::{EffectDomain, TypeTag, effect_mask};
+    #[test]
+    fn state_delete_removes_the_entry() -> Result<()> {
+        let _lock = global_store::test_lock();
+        global_store::reset();
 
-    fn guard_key(tag: TypeTag) -> [u8; 32] {
-        let mut bytes = [0u8; 32];
-        let atom = tag.as_atom().as_bytes();
-        bytes[..atom.len()].copy_from_slice(atom);
-        bytes
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let key = [0xDE; 32];
+        let mut prim_ids = HashMap::new();
+        for (name, params, results, mask) in [
+            (
+                "state.write_i64",
+                vec![TypeTag::Ptr, TypeTag::I64],
+                vec![TypeTag::Unit],
+                effect_mask::STATE_WRITE,
+            ),
+            (
+                "state.delete",
+                vec![TypeTag::Ptr],
+                vec![TypeTag::Unit],
+                effect_mask::STATE_WRITE,
+            ),
+            (
+                "state.read_i64",
+                vec![TypeTag::Ptr],
+                vec![TypeTag::I64],
+                effect_mask::STATE_READ,
+            ),
+        ] {
+            let prim = PrimCanon {
+                params: &params,
+                results: &results,
+                effects: &[],
+                effect_mask: mask,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            };
+            let cid = prim::store_prim(&conn, &prim)?.cid;
+            db::put_name(&conn, "prim", name, &cid)?;
+            prim_ids.insert(name, cid);
+        }
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        builder.push_lit_i64(5)?;
+        builder.apply_prim(prim_ids["state.write_i64"])?;
+        builder.drop()?;
+        builder.quote(key)?;
+        builder.apply_prim(prim_ids["state.delete"])?;
+        let delete_word = builder.finish_word(&[], &[TypeTag::Unit], Some("state/delete"))?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        builder.apply_prim(prim_ids["state.read_i64"])?;
+        let read_word = builder.finish_word(&[], &[TypeTag::I64], Some("state/read"))?;
+
+        global_store::write("other", Value::I64(1));
+        let outputs = run_word(&conn, &delete_word, &[])?;
+        assert_eq!(outputs.last(), Some(&Value::Unit));
+        assert_eq!(global_store::read(&cid::to_hex(&key)), None);
+
+        let err = run_word(&conn, &read_word, &[]).unwrap_err();
+        assert!(err.to_string().contains("not found"), "{err}");
+
+        let snapshot = global_store::snapshot();
+        assert!(!snapshot.entries().contains_key(&cid::to_hex(&key)));
+        assert_eq!(snapshot.len(), 1);
+        Ok(())
     }
 
     #[test]
-    fn run_word_supports_multi_result_literals() -> Result<()> {
+    fn metric_incr_accumulates_and_threads_its_token() -> Result<()> {
+        let _lock = metrics::test_lock();
+        metrics::reset();
+
         let conn = Connection::open_in_memory()?;
         db::install_schema(&conn)?;
 
+        let key = [0x3C; 32];
+        let mut prim_ids = HashMap::new();
+        for (name, params, results) in [
+            (
+                "metric.incr",
+                vec![TypeTag::Ptr, TypeTag::I64],
+                vec![TypeTag::Unit],
+            ),
+            ("metric.read", vec![TypeTag::Ptr], vec![TypeTag::I64]),
+        ] {
+            let prim = PrimCanon {
+                params: &params,
+                results: &results,
+                effects: &[],
+                effect_mask: effect_mask::METRIC,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            };
+            let cid = prim::store_prim(&conn, &prim)?.cid;
+            db::put_name(&conn, "prim", name, &cid)?;
+            prim_ids.insert(name, cid);
+        }
+
         let mut builder = GraphBuilder::new(&conn);
         builder.begin_word(&[])?;
-        builder.push_lit_i64(1)?;
-        builder.push_lit_i64(2)?;
-        let word_cid =
-            builder.finish_word(&[], &[TypeTag::I64, TypeTag::I64], Some("demo/multi"))?;
+        for _ in 0..2 {
+            builder.quote(key)?;
+            builder.push_lit_i64(1)?;
+            builder.apply_prim(prim_ids["metric.incr"])?;
+            builder.drop()?;
+        }
+        builder.quote(key)?;
+        builder.apply_prim(prim_ids["metric.read"])?;
+        let word_cid = builder.finish_word(&[], &[TypeTag::I64], Some("metric/count"))?;
 
         let outputs = run_word(&conn, &word_cid, &[])?;
-        assert_eq!(outputs.len(), 2);
-        assert_eq!(outputs[0], Value::I64(1));
-        assert_eq!(outputs[1], Value::I64(2));
+        assert_eq!(
+            outputs,
+            vec![Value::Token(Some(EffectDomain::Metric)), Value::I64(2)]
+        );
+        assert_eq!(metrics::snapshot().get(&cid::to_hex(&key)), Some(&2));
+        assert!(global_store::read(&cid::to_hex(&key)).is_none());
         Ok(())
     }
 
     #[test]
-    fn run_word_with_multiple_tokens() -> Result<()> {
+    fn ttl_writes_expire() -> Result<()> {
+        let _lock = global_store::test_lock();
+        global_store::reset();
+
         let conn = Connection::open_in_memory()?;
         db::install_schema(&conn)?;
 
-        // Create a primitive that claims IO + State effects so we emit both tokens.
-        let params = [TypeTag::I64, TypeTag::I64];
-        let results = [TypeTag::I64];
-        let prim = PrimCanon {
-            params: &params,
-            results: &results,
-            effects: &[],
-            effect_mask: effect_mask::IO | effect_mask::STATE_READ,
+        let key = [0x7E; 32];
+        let mut prim_ids = HashMap::new();
+        for (name, params, results, mask) in [
+            (
+                "state.write_ttl_i64",
+                vec![TypeTag::Ptr, TypeTag::I64, TypeTag::I64],
+                vec![TypeTag::Unit],
+                effect_mask::STATE_WRITE,
+            ),
+            (
+                "state.read_i64",
+                vec![TypeTag::Ptr],
+                vec![TypeTag::I64],
+                effect_mask::STATE_READ,
+            ),
+        ] {
+            let prim = PrimCanon {
+                params: &params,
+                results: &results,
+                effects: &[],
+                effect_mask: mask,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            };
+            let cid = prim::store_prim(&conn, &prim)?.cid;
+            db::put_name(&conn, "prim", name, &cid)?;
+            prim_ids.insert(name, cid);
+        }
+
+        let write_with_ttl = |ttl: i64| -> Result<[u8; 32]> {
+            let mut builder = GraphBuilder::new(&conn);
+            builder.begin_word(&[])?;
+            builder.quote(key)?;
+            builder.push_lit_i64(7)?;
+            builder.push_lit_i64(ttl)?;
+            builder.apply_prim(prim_ids["state.write_ttl_i64"])?;
+            builder.drop()?;
+            builder.quote(key)?;
+            builder.apply_prim(prim_ids["state.read_i64"])?;
+            builder.finish_word(&[], &[TypeTag::I64], None)
         };
-        let prim_outcome = prim::store_prim(&conn, &prim)?;
-        db::put_name(&conn, "prim", "add_i64", &prim_outcome.cid)?;
 
         let mut builder = GraphBuilder::new(&conn);
-        builder.begin_word(&params)?;
-        builder.apply_prim(prim_outcome.cid)?;
-        let word_cid = builder.finish_word(&params, &results, Some("demo/add_multi"))?;
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        builder.apply_prim(prim_ids["state.read_i64"])?;
+        let read_word = builder.finish_word(&[], &[TypeTag::I64], Some("state/read"))?;
+
+        let outputs = run_word(&conn, &write_with_ttl(3600)?, &[])?;
+        assert_eq!(outputs.last(), Some(&Value::I64(7)));
+        assert_eq!(
+            run_word(&conn, &read_word, &[])?.last(),
+            Some(&Value::I64(7))
+        );
 
-        let outputs = run_word(&conn, &word_cid, &[Value::I64(1), Value::I64(2)])?;
+        let err = run_word(&conn, &write_with_ttl(0)?, &[]).unwrap_err();
+        assert!(err.to_string().contains("not found"), "{err}");
+        assert!(global_store::snapshot().is_empty());
+        let err = run_word(&conn, &read_word, &[]).unwrap_err();
+        assert!(err.to_string().contains("not found"), "{err}");
 
-        assert_eq!(outputs.len(), 3);
-        assert_eq!(outputs[0], Value::Token(Some(EffectDomain::Io)));
-        assert_eq!(outputs[1], Value::Token(Some(EffectDomain::State)));
-        assert_eq!(outputs[2], Value::I64(3));
+        let outputs = run_word(&conn, &write_with_ttl(i64::MAX)?, &[])?;
+        assert_eq!(outputs.last(), Some(&Value::I64(7)));
+        assert_eq!(global_store::snapshot().len(), 1);
         Ok(())
     }
 
     #[test]
-    fn state_read_write_roundtrip() -> Result<()> {
+    fn failed_transactional_run_discards_state_writes() -> Result<()> {
+        let _lock = global_store::test_lock();
         global_store::reset();
 
         let conn = Connection::open_in_memory()?;
         db::install_schema(&conn)?;
 
-        let key = [0xAA; 32];
-
+        let written = [0x71; 32];
+        let missing = [0x72; 32];
+        let write_params = [TypeTag::Ptr, TypeTag::I64];
+        let unit = [TypeTag::Unit];
+        let write_prim = PrimCanon {
+            params: &write_params,
+            results: &unit,
+            effects: &[],
+            effect_mask: effect_mask::STATE_WRITE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        let write_cid = prim::store_prim(&conn, &write_prim)?.cid;
+        db::put_name(&conn, "prim", "state.write_i64", &write_cid)?;
         let read_params = [TypeTag::Ptr];
-        let read_results = [TypeTag::I64];
+        let i64_result = [TypeTag::I64];
         let read_prim = PrimCanon {
             params: &read_params,
-            results: &read_results,
+            results: &i64_result,
             effects: &[],
             effect_mask: effect_mask::STATE_READ,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
-        let read_outcome = prim::store_prim(&conn, &read_prim)?;
-        db::put_name(&conn, "prim", "state.read_i64", &read_outcome.cid)?;
+        let read_cid = prim::store_prim(&conn, &read_prim)?.cid;
+        db::put_name(&conn, "prim", "state.read_i64", &read_cid)?;
 
-        let write_params = [TypeTag::Ptr, TypeTag::I64];
-        let write_results = [TypeTag::Unit];
-        let write_prim = PrimCanon {
-            params: &write_params,
-            results: &write_results,
+        // Overwrite one key, then fail reading a key that was never written.
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(written)?;
+        builder.push_lit_i64(2)?;
+        builder.apply_prim(write_cid)?;
+        builder.drop()?;
+        builder.quote(missing)?;
+        builder.apply_prim(read_cid)?;
+        let word_cid = builder.finish_word(&[], &i64_result, Some("state/half_done"))?;
+
+        global_store::write(cid::to_hex(&written), Value::I64(1));
+        let before = global_store::snapshot();
+        let err = global_store::with_transaction(|| run_word(&conn, &word_cid, &[])).unwrap_err();
+        assert!(err.to_string().contains("not found"), "{err}");
+        assert_eq!(global_store::snapshot().entries(), before.entries());
+
+        // Without the transaction the partial write sticks.
+        assert!(run_word(&conn, &word_cid, &[]).is_err());
+        assert_eq!(
+            global_store::read(&cid::to_hex(&written)),
+            Some(Value::I64(2))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn state_cas_i64_swaps_only_on_match() -> Result<()> {
+        let _lock = global_store::test_lock();
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let key = [0xC5; 32];
+        let params = [TypeTag::Ptr, TypeTag::I64, TypeTag::I64];
+        let results = [TypeTag::I64];
+        let cas_prim = PrimCanon {
+            params: &params,
+            results: &results,
             effects: &[],
-            effect_mask: effect_mask::STATE_WRITE,
+            effect_mask: effect_mask::STATE_READ | effect_mask::STATE_WRITE,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
-        let write_outcome = prim::store_prim(&conn, &write_prim)?;
-        db::put_name(&conn, "prim", "state.write_i64", &write_outcome.cid)?;
+        let cas_outcome = prim::store_prim(&conn, &cas_prim)?;
+        db::put_name(&conn, "prim", "state.cas_i64", &cas_outcome.cid)?;
 
         let mut builder = GraphBuilder::new(&conn);
-        builder.begin_word(&[])?;
-        builder.quote(key)?;
-        builder.push_lit_i64(99)?;
-        builder.apply_prim(write_outcome.cid)?;
-        builder.drop()?;
+        builder.begin_word(&[TypeTag::I64, TypeTag::I64])?;
         builder.quote(key)?;
-        builder.apply_prim(read_outcome.cid)?;
-        let word_cid = builder.finish_word(&[], &[TypeTag::I64], Some("state/test"))?;
+        builder.rot()?;
+        builder.rot()?;
+        builder.apply_prim(cas_outcome.cid)?;
+        let word_cid = builder.finish_word(
+            &[TypeTag::I64, TypeTag::I64],
+            &[TypeTag::I64],
+            Some("state/cas"),
+        )?;
 
-        let outputs = run_word(&conn, &word_cid, &[])?;
-        assert_eq!(outputs.len(), 2);
-        assert_eq!(outputs[0], Value::Token(Some(EffectDomain::State)));
-        assert_eq!(outputs[1], Value::I64(99));
+        global_store::write(cid::to_hex(&key), Value::I64(10));
+        let outputs = run_word(&conn, &word_cid, &[Value::I64(10), Value::I64(11)])?;
+        assert_eq!(outputs.last(), Some(&Value::I64(1)));
+        assert_eq!(global_store::read(&cid::to_hex(&key)), Some(Value::I64(11)));
 
-        let stored = global_store::read(&cid::to_hex(&key)).expect("value persisted");
-        assert_eq!(stored, Value::I64(99));
+        let outputs = run_word(&conn, &word_cid, &[Value::I64(10), Value::I64(12)])?;
+        assert_eq!(outputs.last(), Some(&Value::I64(0)));
+        assert_eq!(global_store::read(&cid::to_hex(&key)), Some(Value::I64(11)));
         Ok(())
     }
 
     #[test]
     fn state_read_write_f64_roundtrip() -> Result<()> {
+        let _lock = global_store::test_lock();
         global_store::reset();
 
         let conn = Connection::open_in_memory()?;
@@ -1217,6 +3207,9 @@ mod tests {
             results: &read_results,
             effects: &[],
             effect_mask: effect_mask::STATE_READ,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let read_outcome = prim::store_prim(&conn, &read_prim)?;
         db::put_name(&conn, "prim", "state.read_f64", &read_outcome.cid)?;
@@ -1228,6 +3221,9 @@ mod tests {
             results: &write_results,
             effects: &[],
             effect_mask: effect_mask::STATE_WRITE,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let write_outcome = prim::store_prim(&conn, &write_prim)?;
         db::put_name(&conn, "prim", "state.write_f64", &write_outcome.cid)?;
@@ -1254,6 +3250,7 @@ mod tests {
 
     #[test]
     fn state_read_write_ptr_tuple() -> Result<()> {
+        let _lock = global_store::test_lock();
         global_store::reset();
 
         let conn = Connection::open_in_memory()?;
@@ -1268,6 +3265,9 @@ mod tests {
             results: &read_results,
             effects: &[],
             effect_mask: effect_mask::STATE_READ,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let read_outcome = prim::store_prim(&conn, &read_prim)?;
         db::put_name(&conn, "prim", "state.read_ptr", &read_outcome.cid)?;
@@ -1279,6 +3279,9 @@ mod tests {
             results: &write_results,
             effects: &[],
             effect_mask: effect_mask::STATE_WRITE,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let write_outcome = prim::store_prim(&conn, &write_prim)?;
         db::put_name(&conn, "prim", "state.write_ptr", &write_outcome.cid)?;
@@ -1308,8 +3311,97 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn tuple_primitives_inspect_state_tuple() -> Result<()> {
+        let _lock = global_store::test_lock();
+        global_store::reset();
+
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+
+        let key = [0xCD; 32];
+        let middle = Value::Tuple(vec![Value::I64(2), Value::Text("two".to_string())]);
+        global_store::write(
+            cid::to_hex(&key),
+            Value::Tuple(vec![Value::I64(1), middle.clone(), Value::I64(3)]),
+        );
+
+        let mut prim_ids = HashMap::new();
+        let prims = [
+            (
+                "state.read_ptr",
+                &[TypeTag::Ptr][..],
+                &[TypeTag::Ptr][..],
+                effect_mask::STATE_READ,
+            ),
+            (
+                "core/tuple_len",
+                &[TypeTag::Ptr][..],
+                &[TypeTag::I64][..],
+                effect_mask::NONE,
+            ),
+            (
+                "core/tuple_get",
+                &[TypeTag::Ptr, TypeTag::I64][..],
+                &[TypeTag::Ptr][..],
+                effect_mask::NONE,
+            ),
+        ];
+        for (name, params, results, mask) in prims {
+            let prim = PrimCanon {
+                params,
+                results,
+                effects: &[],
+                effect_mask: mask,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            };
+            let cid = prim::store_prim(&conn, &prim)?.cid;
+            db::put_name(&conn, "prim", name, &cid)?;
+            prim_ids.insert(name, cid);
+        }
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        builder.apply_prim(prim_ids["state.read_ptr"])?;
+        builder.apply_prim(prim_ids["core/tuple_len"])?;
+        let len_word = builder.finish_word(&[], &[TypeTag::I64], Some("demo/len"))?;
+        assert_eq!(
+            run_word(&conn, &len_word, &[])?,
+            vec![Value::Token(Some(EffectDomain::State)), Value::I64(3)]
+        );
+
+        let build_get = |index: i64, name: &str| -> Result<[u8; 32]> {
+            let mut builder = GraphBuilder::new(&conn);
+            builder.begin_word(&[])?;
+            builder.quote(key)?;
+            builder.apply_prim(prim_ids["state.read_ptr"])?;
+            builder.push_lit_i64(index)?;
+            builder.apply_prim(prim_ids["core/tuple_get"])?;
+            builder.finish_word(&[], &[TypeTag::Ptr], Some(name))
+        };
+        let get_word = build_get(1, "demo/middle")?;
+        assert_eq!(
+            run_word(&conn, &get_word, &[])?,
+            vec![Value::Token(Some(EffectDomain::State)), middle]
+        );
+        let out_of_range = build_get(3, "demo/out_of_range")?;
+        assert!(run_word(&conn, &out_of_range, &[]).is_err());
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.quote(key)?;
+        builder.apply_prim(prim_ids["core/tuple_len"])?;
+        let not_tuple = builder.finish_word(&[], &[TypeTag::I64], Some("demo/len_quote"))?;
+        assert!(run_word(&conn, &not_tuple, &[]).is_err());
+        Ok(())
+    }
+
     #[test]
     fn state_read_write_text() -> Result<()> {
+        let _lock = global_store::test_lock();
         global_store::reset();
 
         let conn = Connection::open_in_memory()?;
@@ -1324,6 +3416,9 @@ mod tests {
             results: &read_results,
             effects: &[],
             effect_mask: effect_mask::STATE_READ,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let read_outcome = prim::store_prim(&conn, &read_prim)?;
         db::put_name(&conn, "prim", "state.read_text", &read_outcome.cid)?;
@@ -1335,6 +3430,9 @@ mod tests {
             results: &write_results,
             effects: &[],
             effect_mask: effect_mask::STATE_WRITE,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let write_outcome = prim::store_prim(&conn, &write_prim)?;
         db::put_name(&conn, "prim", "state.write_text", &write_outcome.cid)?;
@@ -1458,6 +3556,9 @@ mod tests {
                 results,
                 effects: &[],
                 effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: Some(tag),
             };
             let outcome = prim::store_prim(&conn, &prim)?;
             db::put_name(&conn, "prim", name, &outcome.cid)?;
@@ -1607,6 +3708,7 @@ mod tests {
             effects: Vec::new(),
             effect_mask: effect_mask::NONE,
             guards: Vec::new(),
+            doc: None,
         };
         let word_cid = crate::word::store_word(&conn, &word)?.cid;
 
@@ -1698,6 +3800,7 @@ mod tests {
             effects: Vec::new(),
             effect_mask: effect_mask::NONE,
             guards: Vec::new(),
+            doc: None,
         };
         let word_cid = crate::word::store_word(&conn, &word)?.cid;
 
@@ -1740,7 +3843,7 @@ mod tests {
             vals: Vec::new(),
             deps: Vec::new(),
             effects: Vec::new(),
-            payload: NodePayload::Deopt,
+            payload: NodePayload::Deopt(None),
         };
         let deopt_cid = crate::node::store_node(&conn, &deopt_node)?.cid;
 
@@ -1789,11 +3892,116 @@ mod tests {
             effects: Vec::new(),
             effect_mask: effect_mask::NONE,
             guards: Vec::new(),
+            doc: None,
         };
         let word_cid = crate::word::store_word(&conn, &word)?.cid;
 
         let err = run_word(&conn, &word_cid, &[]).unwrap_err();
-        assert!(err.to_string().contains("deopt"));
+        assert!(
+            matches!(err.downcast_ref(), Some(EvalError::Deopt { .. })),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deopts_and_arity_errors_downcast_to_eval_error() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let deopt_cid = crate::node::store_node(
+            &conn,
+            &NodeCanon {
+                kind: NodeKind::Deopt,
+                out: vec![TypeTag::I64.as_atom().to_string()],
+                inputs: Vec::new(),
+                vals: Vec::new(),
+                deps: Vec::new(),
+                effects: Vec::new(),
+                payload: NodePayload::Deopt(Some("cold path".to_string())),
+            },
+        )?
+        .cid;
+        let return_cid = crate::node::store_node(
+            &conn,
+            &NodeCanon {
+                kind: NodeKind::Return,
+                out: vec![TypeTag::I64.as_atom().to_string()],
+                inputs: Vec::new(),
+                vals: vec![NodeInput {
+                    cid: deopt_cid,
+                    port: 0,
+                }],
+                deps: Vec::new(),
+                effects: Vec::new(),
+                payload: NodePayload::Return,
+            },
+        )?
+        .cid;
+        let word = crate::word::store_word(
+            &conn,
+            &crate::word::WordCanon {
+                root: return_cid,
+                params: Vec::new(),
+                results: vec![TypeTag::I64.as_atom().to_string()],
+                effects: Vec::new(),
+                effect_mask: effect_mask::NONE,
+                guards: Vec::new(),
+                doc: None,
+            },
+        )?
+        .cid;
+
+        let err = run_word(&conn, &word, &[]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<EvalError>(),
+            Some(&EvalError::Deopt {
+                reason: Some("cold path".to_string())
+            })
+        );
+        let err = run_word(&conn, &word, &[Value::I64(1)]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<EvalError>(),
+            Some(&EvalError::ArgMismatch {
+                what: "word".to_string(),
+                expected: 0,
+                actual: 1,
+                variadic: false,
+            })
+        );
+
+        let pair = [TypeTag::I64, TypeTag::I64];
+        let unnamed = prim::store_prim(
+            &conn,
+            &PrimCanon {
+                params: &pair,
+                results: &pair[..1],
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            },
+        )?
+        .cid;
+        let err = eval_pure_prim(&conn, &unnamed, vec![Value::I64(1)]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<EvalError>(),
+            Some(&EvalError::ArgMismatch {
+                what: format!("primitive {}", cid::to_hex(&unnamed)),
+                expected: 2,
+                actual: 1,
+                variadic: false,
+            })
+        );
+        let err = eval_pure_prim(&conn, &unnamed, vec![Value::I64(1), Value::I64(2)]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<EvalError>(),
+            Some(&EvalError::UnnamedPrim(unnamed))
+        );
+        assert!(
+            err.to_string().contains("not registered with a name"),
+            "{err}"
+        );
         Ok(())
     }
 
@@ -1879,6 +4087,7 @@ mod tests {
             effects: Vec::new(),
             effect_mask: effect_mask::NONE,
             guards: Vec::new(),
+            doc: None,
         };
         let word_cid = crate::word::store_word(&conn, &word)?.cid;
 
@@ -1887,30 +4096,3 @@ mod tests {
         Ok(())
     }
 }
-
-fn require_sig(
-    info: &crate::prim::PrimInfo,
-    params: &[TypeTag],
-    results: &[TypeTag],
-) -> Result<()> {
-    if info.params != params || info.results != results {
-        bail!(
-            "primitive signature mismatch: params {:?} -> {:?}, expected {:?} -> {:?}",
-            info.params,
-            info.results,
-            params,
-            results
-        );
-    }
-    Ok(())
-}
-
-fn bytebuf_to_array(buf: &ByteBuf) -> Result<[u8; 32]> {
-    let slice = buf.as_slice();
-    if slice.len() != 32 {
-        bail!("expected 32-byte CID, found {} bytes", slice.len());
-    }
-    let mut arr = [0u8; 32];
-    arr.copy_from_slice(slice);
-    Ok(arr)
-}
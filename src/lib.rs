@@ -1,6 +1,7 @@
 //! Core March α₅ library primitives shared by the CLI and, eventually, the Forth surface.
 
 pub mod builder;
+pub mod bundle;
 pub mod cbor;
 pub mod cid;
 pub mod db;
@@ -11,6 +12,7 @@ pub mod guard;
 pub mod iface;
 pub mod inet;
 pub mod interp;
+pub mod metrics;
 pub mod namespace;
 pub mod node;
 pub mod prim;
@@ -22,9 +24,12 @@ pub type Result<T> = anyhow::Result<T>;
 
 pub use builder::{DispatchSpec, GraphBuilder};
 pub use db::{
-    NameEntry, count_objects_of_kind, create_store, derive_db_path, ensure_parent_dirs, get_name,
-    list_names, list_names_for_cid, load_all_cbor_for_kind, load_cbor_for_kind, load_object_cbor,
-    open_store, put_name,
+    KindedNameEntry, MigrationOutcome, NameEntry, ObjectLoader, ObjectRow, SCHEMA_VERSION,
+    ScopedNameEntry, StoreStats, check_schema_version, count_objects_of_kind, create_store,
+    delete_name, derive_db_path, ensure_parent_dirs, get_name, list_all_names, list_all_objects,
+    list_names, list_names_for_cid, list_names_with_kind, load_all_cbor_for_kind,
+    load_cbor_for_kind, load_object_cbor, open_store, put_name, schema_version, search_names,
+    store_stats,
 };
 pub use effect::{EffectCanon, EffectStoreOutcome};
 pub use global_store::{
@@ -33,7 +38,11 @@ pub use global_store::{
 pub use guard::{GuardCanon, GuardInfo, GuardStoreOutcome};
 pub use iface::{IfaceCanon, IfaceStoreOutcome, IfaceSymbol};
 pub use inet::{AgentCanon as InetAgentCanon, Net as InetNet, RuleCanon as InetRuleCanon};
-pub use interp::{Value, run_word, run_word_i64};
+pub use interp::{
+    Budget, EvalError, NativePrim, PrimRegistry, Value, check_token_sequence, clear_result_cache,
+    run_guard, run_word, run_word_budgeted, run_word_cached, run_word_cached_with_prims,
+    run_word_i64, run_word_with_prims,
+};
 pub use namespace::{NamespaceCanon, NamespaceExport, NamespaceStoreOutcome};
 pub use node::{NodeCanon, NodeInput, NodeKind, NodePayload, NodeStoreOutcome};
 pub use prim::{PrimCanon, PrimInfo, PrimStoreOutcome};
@@ -0,0 +1,42 @@
+//! Process-local counters behind the `metric` effect domain.
+//!
+//! Kept apart from [`crate::global_store`] so metric traffic never shows up in
+//! state snapshots or transactions.
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+
+static METRICS: Lazy<RwLock<BTreeMap<String, i64>>> = Lazy::new(|| RwLock::new(BTreeMap::new()));
+
+/// Serialises unit tests that touch the process-global counters.
+#[cfg(test)]
+pub(crate) fn test_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Add `delta` to the counter for `key` (starting from zero), returning the new total.
+pub fn incr(key: impl Into<String>, delta: i64) -> i64 {
+    let mut guard = METRICS.write().expect("metrics poisoned");
+    let counter = guard.entry(key.into()).or_insert(0);
+    *counter = counter.wrapping_add(delta);
+    *counter
+}
+
+/// Current value of the counter for `key`; counters never touched read as zero.
+pub fn read(key: &str) -> i64 {
+    let guard = METRICS.read().expect("metrics poisoned");
+    guard.get(key).copied().unwrap_or(0)
+}
+
+/// Copy of every counter, ordered by key.
+pub fn snapshot() -> BTreeMap<String, i64> {
+    METRICS.read().expect("metrics poisoned").clone()
+}
+
+/// Drop every counter.
+pub fn reset() {
+    METRICS.write().expect("metrics poisoned").clear();
+}
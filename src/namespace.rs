@@ -1,7 +1,10 @@
 //! Canonical encoding for namespace descriptors tying interfaces to words.
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use rusqlite::Connection;
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+use serde_cbor::Value;
 
 use crate::cbor::{push_array, push_bytes, push_text};
 use crate::{cid, db};
@@ -20,6 +23,15 @@ pub struct NamespaceExport {
     pub word: [u8; 32],
 }
 
+/// An export as listings show it: alias, word CID, and the first name
+/// registered for that word.
+#[derive(Clone, Debug)]
+pub struct ExportRow {
+    pub alias: String,
+    pub word: [u8; 32],
+    pub word_name: Option<String>,
+}
+
 /// Result of persisting a namespace object.
 pub struct NamespaceStoreOutcome {
     pub cid: [u8; 32],
@@ -59,13 +71,164 @@ fn encode_exports(buf: &mut Vec<u8>, exports: &[NamespaceExport]) {
     sorted.sort_by(|a, b| a.name.cmp(&b.name));
     push_array(buf, sorted.len() as u64);
     for export in sorted {
+        push_array(buf, 2);
         push_text(buf, &export.name);
         push_bytes(buf, &export.word);
     }
 }
 
+/// Decode a stored namespace object.
+pub fn load_namespace(conn: &Connection, ns_cid: &[u8; 32]) -> Result<NamespaceCanon> {
+    decode(&db::load_cbor_for_kind(conn, ns_cid, "namespace")?)
+}
+
+/// Exports of `ns` with each word's registered name looked up.
+pub fn export_rows(conn: &Connection, ns: &NamespaceCanon) -> Result<Vec<ExportRow>> {
+    ns.exports
+        .iter()
+        .map(|export| {
+            Ok(ExportRow {
+                alias: export.name.clone(),
+                word: export.word,
+                word_name: db::list_names_for_cid(conn, "word", &export.word)?
+                    .into_iter()
+                    .next(),
+            })
+        })
+        .collect()
+}
+
+/// Decode a namespace previously produced by [`encode`].
+pub fn decode(cbor: &[u8]) -> Result<NamespaceCanon> {
+    let NamespaceRecord(tag, iface_buf, imports_raw, exports_raw) = serde_cbor::from_slice(cbor)?;
+    if tag != 4 {
+        bail!("object tag mismatch while loading namespace: {tag}");
+    }
+    let imports = imports_raw
+        .iter()
+        .map(|buf| cid::from_slice(buf.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+    let exports = exports_raw
+        .into_iter()
+        .map(|(name, word_buf)| {
+            Ok(NamespaceExport {
+                name,
+                word: cid::from_slice(word_buf.as_ref())?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(NamespaceCanon {
+        imports,
+        exports,
+        iface: cid::from_slice(iface_buf.as_ref())?,
+    })
+}
+
+#[derive(Deserialize)]
+struct NamespaceRecord(u64, ByteBuf, Vec<ByteBuf>, Vec<(String, ByteBuf)>);
+
+/// Early map form: `{kind, imports, exports: [{name, word}], iface}`.
+#[derive(Deserialize)]
+struct NamespaceMapRecord {
+    kind: String,
+    imports: Vec<ByteBuf>,
+    exports: Vec<NamespaceMapExport>,
+    iface: ByteBuf,
+}
+
+#[derive(Deserialize)]
+struct NamespaceMapExport {
+    name: String,
+    word: ByteBuf,
+}
+
+/// Decode a namespace in a pre-schema-2 encoding: either the early map form,
+/// or the array form whose export array's length counted entries while each
+/// entry was written as two bare items (name, then word CID). Returns `None`
+/// when `cbor` already uses the current encoding.
+pub(crate) fn decode_legacy(cbor: &[u8]) -> Result<Option<NamespaceCanon>> {
+    if serde_cbor::from_slice::<NamespaceRecord>(cbor).is_ok() {
+        return Ok(None);
+    }
+    if let Ok(record) = serde_cbor::from_slice::<NamespaceMapRecord>(cbor) {
+        if record.kind != "namespace" {
+            bail!(
+                "object kind mismatch while migrating namespace: {}",
+                record.kind
+            );
+        }
+        let imports = record
+            .imports
+            .iter()
+            .map(|buf| cid::from_slice(buf))
+            .collect::<Result<Vec<_>>>()?;
+        let exports = record
+            .exports
+            .into_iter()
+            .map(|export| {
+                Ok(NamespaceExport {
+                    name: export.name,
+                    word: cid::from_slice(&export.word)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        return Ok(Some(NamespaceCanon {
+            imports,
+            exports,
+            iface: cid::from_slice(&record.iface)?,
+        }));
+    }
+    let mut items = serde_cbor::Deserializer::from_slice(cbor).into_iter::<Value>();
+    let head = match items.next().transpose()? {
+        Some(Value::Array(head)) => head,
+        _ => bail!("namespace object is neither a map nor an array"),
+    };
+    let [
+        Value::Integer(4),
+        Value::Bytes(iface),
+        Value::Array(imports),
+        Value::Array(first),
+    ] = &head[..]
+    else {
+        bail!("namespace object does not match the legacy layout");
+    };
+    let mut flat = first.clone();
+    for item in items {
+        flat.push(item?);
+    }
+    if flat.len() != first.len() * 2 {
+        bail!(
+            "legacy namespace has {} export items, expected {}",
+            flat.len(),
+            first.len() * 2
+        );
+    }
+    let imports = imports
+        .iter()
+        .map(|item| match item {
+            Value::Bytes(buf) => cid::from_slice(buf),
+            _ => bail!("legacy namespace import is not a CID"),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let exports = flat
+        .chunks(2)
+        .map(|pair| match pair {
+            [Value::Text(name), Value::Bytes(word)] => Ok(NamespaceExport {
+                name: name.clone(),
+                word: cid::from_slice(word)?,
+            }),
+            _ => bail!("legacy namespace export is not a name followed by a CID"),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(NamespaceCanon {
+        imports,
+        exports,
+        iface: cid::from_slice(iface)?,
+    }))
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     #[test]
@@ -104,4 +267,98 @@ mod tests {
             .unwrap();
         assert!(alpha_pos < zeta_pos);
     }
+
+    #[test]
+    fn load_namespace_roundtrips_exports() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        db::install_schema(&conn)?;
+        let ns = NamespaceCanon {
+            imports: vec![[0x03; 32]],
+            exports: vec![
+                NamespaceExport {
+                    name: "beta".into(),
+                    word: [0x22; 32],
+                },
+                NamespaceExport {
+                    name: "alpha".into(),
+                    word: [0x11; 32],
+                },
+            ],
+            iface: [0xEE; 32],
+        };
+        let ns_cid = store_namespace(&conn, &ns)?.cid;
+        let loaded = load_namespace(&conn, &ns_cid)?;
+        assert_eq!(loaded.iface, ns.iface);
+        assert_eq!(loaded.imports, ns.imports);
+        let exports: Vec<_> = loaded
+            .exports
+            .iter()
+            .map(|export| (export.name.as_str(), export.word))
+            .collect();
+        assert_eq!(exports, vec![("alpha", [0x11; 32]), ("beta", [0x22; 32])]);
+        Ok(())
+    }
+
+    #[test]
+    fn decode_legacy_reads_unpaired_exports() -> Result<()> {
+        let ns = NamespaceCanon {
+            imports: vec![[0x03; 32]],
+            exports: vec![
+                NamespaceExport {
+                    name: "alpha".into(),
+                    word: [0x11; 32],
+                },
+                NamespaceExport {
+                    name: "beta".into(),
+                    word: [0x22; 32],
+                },
+            ],
+            iface: [0xEE; 32],
+        };
+        assert!(decode_legacy(&encode(&ns))?.is_none());
+        let legacy = legacy_encode(&ns);
+        let decoded = decode_legacy(&legacy)?.expect("legacy encoding");
+        assert_eq!(encode(&decoded), encode(&ns));
+        let decoded = decode_legacy(&legacy_map_encode(&ns))?.expect("map encoding");
+        assert_eq!(encode(&decoded), encode(&ns));
+        Ok(())
+    }
+
+    /// The early map form: `{kind, imports, exports: [{name, word}], iface}`.
+    pub(crate) fn legacy_map_encode(ns: &NamespaceCanon) -> Vec<u8> {
+        use crate::cbor::push_map;
+        let mut buf = Vec::new();
+        push_map(&mut buf, 4);
+        push_text(&mut buf, "kind");
+        push_text(&mut buf, "namespace");
+        push_text(&mut buf, "imports");
+        encode_cid_list(&mut buf, &ns.imports);
+        push_text(&mut buf, "exports");
+        push_array(&mut buf, ns.exports.len() as u64);
+        for export in &ns.exports {
+            push_map(&mut buf, 2);
+            push_text(&mut buf, "name");
+            push_text(&mut buf, &export.name);
+            push_text(&mut buf, "word");
+            push_bytes(&mut buf, &export.word);
+        }
+        push_text(&mut buf, "iface");
+        push_bytes(&mut buf, &ns.iface);
+        buf
+    }
+
+    /// The pre-schema-2 encoding: two bare items per export.
+    pub(crate) fn legacy_encode(ns: &NamespaceCanon) -> Vec<u8> {
+        let mut buf = Vec::new();
+        push_array(&mut buf, 4);
+        crate::cbor::push_u32(&mut buf, 4);
+        push_bytes(&mut buf, &ns.iface);
+        encode_cid_list(&mut buf, &ns.imports);
+        push_array(&mut buf, ns.exports.len() as u64);
+        for export in &ns.exports {
+            push_text(&mut buf, &export.name);
+            push_bytes(&mut buf, &export.word);
+        }
+        buf
+    }
 }
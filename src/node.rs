@@ -2,8 +2,11 @@
 
 use std::cmp::Ordering;
 
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow, bail};
 use rusqlite::Connection;
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+use serde_cbor::Value as CborValue;
 
 use crate::cbor::{push_array, push_bytes, push_i64, push_text, push_u32};
 use crate::{cid, db};
@@ -41,10 +44,13 @@ pub enum NodePayload {
         match_cont: NodeInput,
         else_cont: NodeInput,
     },
-    Deopt,
+    /// Optional diagnostic; `None` (or empty) keeps the legacy empty payload.
+    Deopt(Option<String>),
     Dispatch {
         cases: Vec<DispatchCase>,
     },
+    /// Element count for PAIR/UNPAIR nodes building or splitting N-ary tuples.
+    Arity(u32),
     Empty,
 }
 
@@ -94,9 +100,29 @@ pub struct NodeStoreOutcome {
     pub inserted: bool,
 }
 
+/// Put a node's unordered edge sets into canonical order.
+///
+/// Regular `inputs` are positional for every kind (PRIM/CALL arguments, PAIR
+/// elements, IF/GUARD operands, ...) and RETURN `vals` are the ordered results,
+/// so those are left alone. RETURN `deps` only sequence effects and are sorted
+/// by `(cid, port)` with duplicates removed; `effects` are sorted by CID.
+pub fn canonicalize(node: &NodeCanon) -> NodeCanon {
+    let mut canon = node.clone();
+    canon.deps.sort_by(|a, b| match a.cid.cmp(&b.cid) {
+        Ordering::Equal => a.port.cmp(&b.port),
+        other => other,
+    });
+    canon
+        .deps
+        .dedup_by(|a, b| a.cid == b.cid && a.port == b.port);
+    canon.effects.sort();
+    canon
+}
+
 /// Encode a node into canonical CBOR.
 pub fn encode(node: &NodeCanon) -> Result<Vec<u8>> {
     validate_node(node)?;
+    let node = &canonicalize(node);
 
     let mut buf = Vec::new();
     push_array(&mut buf, 6);
@@ -111,6 +137,9 @@ pub fn encode(node: &NodeCanon) -> Result<Vec<u8>> {
 }
 
 /// Persist a node in the object store.
+///
+/// Encoding goes through [`canonicalize`], so nodes differing only in the
+/// order of their deps or effects share a CID.
 pub fn store_node(conn: &Connection, node: &NodeCanon) -> Result<NodeStoreOutcome> {
     let cbor = encode(node)?;
     let cid = cid::compute(&cbor);
@@ -118,6 +147,197 @@ pub fn store_node(conn: &Connection, node: &NodeCanon) -> Result<NodeStoreOutcom
     Ok(NodeStoreOutcome { cid, inserted })
 }
 
+impl NodeKind {
+    /// Upper-case mnemonic used in diagnostics and inspection output.
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeKind::Lit => "LIT",
+            NodeKind::Prim => "PRIM",
+            NodeKind::Call => "CALL",
+            NodeKind::Arg => "ARG",
+            NodeKind::LoadGlobal => "LOAD_GLOBAL",
+            NodeKind::Return => "RETURN",
+            NodeKind::Pair => "PAIR",
+            NodeKind::Unpair => "UNPAIR",
+            NodeKind::Quote => "QUOTE",
+            NodeKind::Apply => "APPLY",
+            NodeKind::If => "IF",
+            NodeKind::Token => "TOKEN",
+            NodeKind::Guard => "GUARD",
+            NodeKind::Deopt => "DEOPT",
+            NodeKind::Dispatch => "DISPATCH",
+        }
+    }
+
+    /// Map a canonical kind tag back to its node kind.
+    pub fn from_tag(tag: u64) -> Option<NodeKind> {
+        let kind = match tag {
+            0 => NodeKind::Lit,
+            1 => NodeKind::Prim,
+            2 => NodeKind::Call,
+            3 => NodeKind::Arg,
+            4 => NodeKind::LoadGlobal,
+            5 => NodeKind::Return,
+            6 => NodeKind::Pair,
+            7 => NodeKind::Unpair,
+            8 => NodeKind::Quote,
+            9 => NodeKind::Apply,
+            10 => NodeKind::If,
+            11 => NodeKind::Token,
+            12 => NodeKind::Guard,
+            13 => NodeKind::Deopt,
+            14 => NodeKind::Dispatch,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+/// Decoded view of a stored node for inspection tooling.
+#[derive(Clone, Debug)]
+pub struct NodeInfo {
+    pub kind: NodeKind,
+    pub out: Vec<String>,
+    pub inputs: Vec<NodeInput>,
+    pub effects: Vec<[u8; 32]>,
+    /// Kind-specific payload, left as raw CBOR.
+    pub payload: CborValue,
+}
+
+/// Load and structurally decode a node object.
+pub fn load_node_info(conn: &Connection, cid_bytes: &[u8; 32]) -> Result<NodeInfo> {
+    let cbor = db::load_cbor_for_kind(conn, cid_bytes, "node")?;
+    let NodeRecord(tag, kind_tag, inputs_raw, out, effects_raw, payload) =
+        serde_cbor::from_slice(&cbor)?;
+    if tag != 6 {
+        bail!("object tag mismatch while loading node: {tag}");
+    }
+    let kind =
+        NodeKind::from_tag(kind_tag).ok_or_else(|| anyhow!("unknown node kind tag {kind_tag}"))?;
+    let inputs = inputs_raw
+        .into_iter()
+        .map(|NodeInputRecord(cid_buf, port)| {
+            Ok(NodeInput {
+                cid: cid::from_slice(cid_buf.as_ref())?,
+                port,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let effects = effects_raw
+        .iter()
+        .map(|bytes| cid::from_slice(bytes.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(NodeInfo {
+        kind,
+        out,
+        inputs,
+        effects,
+        payload,
+    })
+}
+
+/// Rebuild an encodable node from its decoded view, for graph rewrites.
+///
+/// DISPATCH payloads are not supported.
+pub fn canon_from_info(info: &NodeInfo) -> Result<NodeCanon> {
+    let mut canon = NodeCanon {
+        kind: info.kind,
+        out: info.out.clone(),
+        inputs: info.inputs.clone(),
+        vals: Vec::new(),
+        deps: Vec::new(),
+        effects: info.effects.clone(),
+        payload: NodePayload::Empty,
+    };
+    let name = info.kind.name();
+    canon.payload = match (info.kind, &info.payload) {
+        (NodeKind::Lit, CborValue::Integer(value)) => NodePayload::LitI64(i64::try_from(*value)?),
+        (NodeKind::Prim, CborValue::Bytes(bytes)) => NodePayload::Prim(cid::from_slice(bytes)?),
+        (NodeKind::Call, CborValue::Bytes(bytes)) => NodePayload::Word(cid::from_slice(bytes)?),
+        (NodeKind::Arg, CborValue::Integer(index)) => NodePayload::Arg(u32::try_from(*index)?),
+        (NodeKind::LoadGlobal, CborValue::Bytes(bytes)) => {
+            NodePayload::Global(cid::from_slice(bytes)?)
+        }
+        (NodeKind::Quote, CborValue::Bytes(bytes)) => NodePayload::Quote(cid::from_slice(bytes)?),
+        (NodeKind::Return, CborValue::Array(parts)) if parts.len() == 2 => {
+            canon.vals = edges_from_cbor(&parts[0])?;
+            canon.deps = edges_from_cbor(&parts[1])?;
+            NodePayload::Return
+        }
+        (NodeKind::Pair | NodeKind::Unpair, CborValue::Integer(arity)) => {
+            NodePayload::Arity(u32::try_from(*arity)?)
+        }
+        (NodeKind::Pair | NodeKind::Unpair, CborValue::Array(parts)) if parts.is_empty() => {
+            NodePayload::Empty
+        }
+        (NodeKind::Token, _) => NodePayload::Token,
+        (NodeKind::Deopt, CborValue::Array(parts)) => match parts.as_slice() {
+            [] => NodePayload::Deopt(None),
+            [CborValue::Text(reason)] => NodePayload::Deopt(Some(reason.clone())),
+            _ => bail!("malformed DEOPT payload"),
+        },
+        (NodeKind::Apply, CborValue::Array(parts)) => match parts.as_slice() {
+            [CborValue::Bytes(qid)] => NodePayload::Apply {
+                qid: cid::from_slice(qid)?,
+                type_key: None,
+            },
+            [CborValue::Bytes(qid), CborValue::Bytes(key)] => NodePayload::Apply {
+                qid: cid::from_slice(qid)?,
+                type_key: Some(cid::from_slice(key)?),
+            },
+            _ => bail!("malformed APPLY payload"),
+        },
+        (NodeKind::If, CborValue::Array(parts)) if parts.len() == 2 => NodePayload::If {
+            true_cont: edge_from_cbor(&parts[0])?,
+            false_cont: edge_from_cbor(&parts[1])?,
+        },
+        (NodeKind::Guard, CborValue::Array(parts)) if parts.len() == 3 => match &parts[0] {
+            CborValue::Bytes(key) => NodePayload::Guard {
+                type_key: cid::from_slice(key)?,
+                match_cont: edge_from_cbor(&parts[1])?,
+                else_cont: edge_from_cbor(&parts[2])?,
+            },
+            _ => bail!("malformed GUARD payload"),
+        },
+        (NodeKind::Dispatch, _) => bail!("DISPATCH nodes cannot be rebuilt"),
+        _ => bail!("malformed {name} payload"),
+    };
+    Ok(canon)
+}
+
+fn edge_from_cbor(value: &CborValue) -> Result<NodeInput> {
+    match value {
+        CborValue::Array(pair) => match pair.as_slice() {
+            [CborValue::Bytes(bytes), CborValue::Integer(port)] => Ok(NodeInput {
+                cid: cid::from_slice(bytes)?,
+                port: u32::try_from(*port)?,
+            }),
+            _ => bail!("malformed node edge"),
+        },
+        _ => bail!("malformed node edge"),
+    }
+}
+
+fn edges_from_cbor(value: &CborValue) -> Result<Vec<NodeInput>> {
+    match value {
+        CborValue::Array(items) => items.iter().map(edge_from_cbor).collect(),
+        _ => bail!("malformed node edge list"),
+    }
+}
+
+#[derive(Deserialize)]
+struct NodeRecord(
+    u64,
+    u64,
+    Vec<NodeInputRecord>,
+    Vec<String>,
+    Vec<ByteBuf>,
+    CborValue,
+);
+
+#[derive(Deserialize)]
+struct NodeInputRecord(ByteBuf, u32);
+
 fn node_kind_tag(kind: NodeKind) -> u8 {
     match kind {
         NodeKind::Lit => 0,
@@ -158,16 +378,6 @@ fn encode_input_list(buf: &mut Vec<u8>, inputs: &[NodeInput]) {
     }
 }
 
-fn encode_input_list_sorted(buf: &mut Vec<u8>, inputs: &[NodeInput]) {
-    let mut sorted = inputs.to_vec();
-    sorted.sort_by(|a, b| match a.cid.cmp(&b.cid) {
-        Ordering::Equal => a.port.cmp(&b.port),
-        other => other,
-    });
-    sorted.dedup_by(|a, b| a.cid == b.cid && a.port == b.port);
-    encode_input_list(buf, &sorted);
-}
-
 fn encode_outputs(buf: &mut Vec<u8>, outs: &[String]) {
     push_array(buf, outs.len() as u64);
     for out in outs {
@@ -176,11 +386,9 @@ fn encode_outputs(buf: &mut Vec<u8>, outs: &[String]) {
 }
 
 fn encode_effects(buf: &mut Vec<u8>, effects: &[[u8; 32]]) {
-    let mut sorted = effects.to_vec();
-    sorted.sort();
-    push_array(buf, sorted.len() as u64);
-    for effect in sorted {
-        push_bytes(buf, &effect);
+    push_array(buf, effects.len() as u64);
+    for effect in effects {
+        push_bytes(buf, effect);
     }
 }
 
@@ -189,52 +397,70 @@ fn encode_payload(buf: &mut Vec<u8>, node: &NodeCanon) -> Result<()> {
         NodeKind::Return => {
             push_array(buf, 2);
             encode_input_list(buf, &node.vals);
-            encode_input_list_sorted(buf, &node.deps);
-            return Ok(());
+            encode_input_list(buf, &node.deps);
+            Ok(())
         }
         NodeKind::Lit => match node.payload {
             NodePayload::LitI64(value) => {
                 push_i64(buf, value);
-                return Ok(());
+                Ok(())
             }
             _ => bail!("LIT node requires literal payload"),
         },
         NodeKind::Prim => match node.payload {
             NodePayload::Prim(cid) => {
                 push_bytes(buf, &cid);
-                return Ok(());
+                Ok(())
             }
             _ => bail!("PRIM node requires prim payload"),
         },
         NodeKind::Call => match node.payload {
             NodePayload::Word(cid) => {
                 push_bytes(buf, &cid);
-                return Ok(());
+                Ok(())
             }
             _ => bail!("CALL node requires word payload"),
         },
         NodeKind::Arg => match node.payload {
             NodePayload::Arg(index) => {
                 push_u32(buf, index);
-                return Ok(());
+                Ok(())
             }
             _ => bail!("ARG node requires index payload"),
         },
         NodeKind::LoadGlobal => match node.payload {
             NodePayload::Global(cid) => {
                 push_bytes(buf, &cid);
-                return Ok(());
+                Ok(())
             }
             _ => bail!("LOAD_GLOBAL node requires global payload"),
         },
-        NodeKind::Pair | NodeKind::Unpair | NodeKind::Token | NodeKind::Deopt => {
+        NodeKind::Pair | NodeKind::Unpair => {
+            // Binary pairs keep the empty payload so their CIDs are unchanged.
+            match node.payload {
+                NodePayload::Arity(arity) => push_u32(buf, arity),
+                _ => push_array(buf, 0),
+            }
+            Ok(())
+        }
+        NodeKind::Token => {
             push_array(buf, 0);
-            return Ok(());
+            Ok(())
+        }
+        NodeKind::Deopt => {
+            match node.payload {
+                NodePayload::Deopt(Some(ref reason)) if !reason.is_empty() => {
+                    push_array(buf, 1);
+                    push_text(buf, reason);
+                }
+                _ => push_array(buf, 0),
+            }
+            Ok(())
         }
         NodeKind::Quote => match node.payload {
             NodePayload::Quote(cid) => {
                 push_bytes(buf, &cid);
-                return Ok(());
+                Ok(())
             }
             _ => bail!("QUOTE node requires quote payload"),
         },
@@ -248,7 +474,7 @@ fn encode_payload(buf: &mut Vec<u8>, node: &NodeCanon) -> Result<()> {
                     push_array(buf, 1);
                     push_bytes(buf, &qid);
                 }
-                return Ok(());
+                Ok(())
             }
             _ => bail!("APPLY node requires apply payload"),
         },
@@ -260,7 +486,7 @@ fn encode_payload(buf: &mut Vec<u8>, node: &NodeCanon) -> Result<()> {
                 push_array(buf, 2);
                 encode_input(buf, true_cont);
                 encode_input(buf, false_cont);
-                return Ok(());
+                Ok(())
             }
             _ => bail!("IF node requires branch payload"),
         },
@@ -274,7 +500,7 @@ fn encode_payload(buf: &mut Vec<u8>, node: &NodeCanon) -> Result<()> {
                 push_bytes(buf, &type_key);
                 encode_input(buf, match_cont);
                 encode_input(buf, else_cont);
-                return Ok(());
+                Ok(())
             }
             _ => bail!("GUARD node requires guard payload"),
         },
@@ -299,7 +525,7 @@ fn encode_payload(buf: &mut Vec<u8>, node: &NodeCanon) -> Result<()> {
                         push_bytes(buf, gid);
                     }
                 }
-                return Ok(());
+                Ok(())
             }
             _ => bail!("DISPATCH node requires dispatch payload"),
         },
@@ -360,7 +586,11 @@ fn validate_node(node: &NodeCanon) -> Result<()> {
         NodeKind::Return => Ok(()),
         NodeKind::Pair | NodeKind::Unpair => match node.payload {
             NodePayload::Empty => Ok(()),
-            _ => bail!("PAIR/UNPAIR nodes must not carry payload"),
+            NodePayload::Arity(0) => bail!("PAIR/UNPAIR arity must be at least 1"),
+            // Binary tuples have exactly one encoding: the empty payload.
+            NodePayload::Arity(2) => bail!("binary PAIR/UNPAIR must omit the arity payload"),
+            NodePayload::Arity(_) => Ok(()),
+            _ => bail!("PAIR/UNPAIR nodes only carry an optional arity"),
         },
         NodeKind::Quote => match node.payload {
             NodePayload::Quote(_) => Ok(()),
@@ -394,8 +624,8 @@ fn validate_node(node: &NodeCanon) -> Result<()> {
             _ => bail!("DISPATCH node requires dispatch payload"),
         },
         NodeKind::Deopt => match node.payload {
-            NodePayload::Deopt => Ok(()),
-            _ => bail!("DEOPT node must not carry payload"),
+            NodePayload::Deopt(_) => Ok(()),
+            _ => bail!("DEOPT node requires deopt payload"),
         },
     }
 }
@@ -413,6 +643,72 @@ mod tests {
         }
     }
 
+    fn edge(byte: u8) -> NodeInput {
+        NodeInput {
+            cid: [byte; 32],
+            port: 0,
+        }
+    }
+
+    /// Every permutation of `items`, by Heap's algorithm.
+    fn permutations(items: &[NodeInput]) -> Vec<Vec<NodeInput>> {
+        fn heap(k: usize, items: &mut Vec<NodeInput>, out: &mut Vec<Vec<NodeInput>>) {
+            if k <= 1 {
+                out.push(items.clone());
+                return;
+            }
+            for i in 0..k {
+                heap(k - 1, items, out);
+                let j = if k.is_multiple_of(2) { i } else { 0 };
+                items.swap(j, k - 1);
+            }
+        }
+        let mut out = Vec::new();
+        heap(items.len(), &mut items.to_vec(), &mut out);
+        out
+    }
+
+    #[test]
+    fn dep_order_is_canonical_but_pair_order_is_not() -> Result<()> {
+        let deps = [edge(0x31), edge(0x32), edge(0x33), edge(0x34)];
+        let ret = |deps: Vec<NodeInput>| NodeCanon {
+            kind: NodeKind::Return,
+            out: vec!["i64".to_string()],
+            inputs: Vec::new(),
+            vals: vec![edge(0x30)],
+            deps,
+            effects: Vec::new(),
+            payload: NodePayload::Return,
+        };
+        let expected = cid::compute(&encode(&ret(deps.to_vec()))?);
+        for shuffled in permutations(&deps) {
+            assert_eq!(cid::compute(&encode(&ret(shuffled))?), expected);
+        }
+
+        let elems = [edge(0x41), edge(0x42), edge(0x43)];
+        let pair = |inputs: Vec<NodeInput>| NodeCanon {
+            kind: NodeKind::Pair,
+            out: vec!["tuple".to_string()],
+            inputs,
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::Arity(3),
+        };
+        let mut seen = std::collections::HashSet::new();
+        for shuffled in permutations(&elems) {
+            assert!(seen.insert(cid::compute(&encode(&pair(shuffled))?)));
+        }
+        assert_eq!(seen.len(), 6);
+
+        let binary = NodeCanon {
+            payload: NodePayload::Arity(2),
+            ..pair(elems[..2].to_vec())
+        };
+        assert!(encode(&binary).is_err());
+        Ok(())
+    }
+
     #[test]
     fn encode_lit_node() {
         let node = NodeCanon {
@@ -602,11 +898,21 @@ mod tests {
             vals: Vec::new(),
             deps: Vec::new(),
             effects: Vec::new(),
-            payload: NodePayload::Deopt,
+            payload: NodePayload::Deopt(None),
         };
         let items = decode(&encode(&node).unwrap());
         assert_eq!(items[1], Value::Integer(13));
         assert!(matches!(items[5], Value::Array(ref arr) if arr.is_empty()));
+
+        let with_reason = NodeCanon {
+            payload: NodePayload::Deopt(Some("expected f64".to_string())),
+            ..node
+        };
+        let items = decode(&encode(&with_reason).unwrap());
+        assert_eq!(
+            items[5],
+            Value::Array(vec![Value::Text("expected f64".to_string())])
+        );
     }
 
     fn guard_key(tag: TypeTag) -> [u8; 32] {
@@ -5,7 +5,7 @@ use rusqlite::Connection;
 use serde::Deserialize;
 use serde_bytes::ByteBuf;
 
-use crate::cbor::{push_array, push_bytes, push_text};
+use crate::cbor::{push_array, push_bytes, push_null, push_text};
 use crate::types::{EffectMask, TypeTag, effect_mask};
 use crate::{cid, db};
 
@@ -19,6 +19,12 @@ pub struct PrimCanon<'a> {
     pub effects: &'a [[u8; 32]],
     /// Bitmask describing which effect domains this primitive touches.
     pub effect_mask: EffectMask,
+    /// Optional description; encoded only when present, so it leaves other CIDs alone.
+    pub doc: Option<&'a str>,
+    /// Type of zero or more trailing arguments accepted after `params`.
+    pub variadic: Option<TypeTag>,
+    /// Runner built-in this primitive stands for (one of [`BUILTINS`]).
+    pub builtin: Option<&'a str>,
 }
 
 /// Result of persisting a primitive descriptor.
@@ -36,13 +42,25 @@ pub struct PrimInfo {
     pub results: Vec<TypeTag>,
     pub effects: Vec<[u8; 32]>,
     pub effect_mask: EffectMask,
+    pub doc: Option<String>,
+    pub variadic: Option<TypeTag>,
+    pub builtin: Option<String>,
 }
 
 /// Encode a primitive into canonical CBOR.
 pub fn encode(prim: &PrimCanon) -> Vec<u8> {
     let mut buf = Vec::new();
-    // [tag, rootCID, params[], results[], effects[], mask]
-    push_array(&mut buf, 6);
+    // [tag, rootCID, params[], results[], effects[], mask, doc?, variadic?, builtin?]
+    let len = if prim.builtin.is_some() {
+        9
+    } else if prim.variadic.is_some() {
+        8
+    } else if prim.doc.is_some() {
+        7
+    } else {
+        6
+    };
+    push_array(&mut buf, len);
     crate::cbor::push_u32(&mut buf, 0); // object tag for "prim"
     push_bytes(&mut buf, &[0u8; 32]); // reserved root slot (always zero for prims)
 
@@ -58,6 +76,19 @@ pub fn encode(prim: &PrimCanon) -> Vec<u8> {
 
     encode_effects(&mut buf, prim.effects);
     crate::cbor::push_u32(&mut buf, prim.effect_mask);
+    match prim.doc {
+        Some(doc) => push_text(&mut buf, doc),
+        None if len > 6 => push_null(&mut buf),
+        None => {}
+    }
+    match prim.variadic {
+        Some(tag) => push_text(&mut buf, tag.as_atom()),
+        None if len > 8 => push_null(&mut buf),
+        None => {}
+    }
+    if let Some(builtin) = prim.builtin {
+        push_text(&mut buf, builtin);
+    }
     buf
 }
 
@@ -72,8 +103,17 @@ pub fn store_prim(conn: &Connection, prim: &PrimCanon) -> Result<PrimStoreOutcom
 /// Load primitive metadata required by the graph builder.
 pub fn load_prim_info(conn: &Connection, cid_bytes: &[u8; 32]) -> Result<PrimInfo> {
     let cbor = db::load_cbor_for_kind(conn, cid_bytes, "prim")?;
-    let PrimRecord(tag, root, params_raw, results_raw, effects_raw, mask_opt) =
-        serde_cbor::from_slice(&cbor).with_context(|| "failed to decode primitive CBOR payload")?;
+    let PrimRecord(
+        tag,
+        root,
+        params_raw,
+        results_raw,
+        effects_raw,
+        mask_opt,
+        doc,
+        variadic_raw,
+        builtin,
+    ) = serde_cbor::from_slice(&cbor).with_context(|| "failed to decode primitive CBOR payload")?;
     if tag != 0 {
         bail!("object tag mismatch while loading prim: {}", tag);
     }
@@ -116,9 +156,63 @@ pub fn load_prim_info(conn: &Connection, cid_bytes: &[u8; 32]) -> Result<PrimInf
         results,
         effects,
         effect_mask: effect_mask_value,
+        doc,
+        variadic: variadic_raw
+            .as_deref()
+            .map(TypeTag::from_atom)
+            .transpose()?,
+        builtin,
     })
 }
 
+/// Primitives the runner implements, by the id stored in a prim's `builtin` slot.
+///
+/// The id is part of the canonical form, so built-ins with the same signature
+/// (`add_i64`/`sub_i64`, `state.write_ptr`/`state.write_i64_array`, ...) get
+/// distinct CIDs.
+pub const BUILTINS: &[&str] = &[
+    "add_i64",
+    "sub_i64",
+    "neg_i64",
+    "abs_i64",
+    "sum_i64",
+    "tuple_len",
+    "tuple_get",
+    "eq_i64",
+    "lt_i64",
+    "gt_i64",
+    "le_i64",
+    "ge_i64",
+    "and",
+    "or",
+    "not",
+    "state.read_i64",
+    "state.read_f64",
+    "state.read_ptr",
+    "state.read_text",
+    "state.read_i64_array",
+    "state.write_i64",
+    "state.write_f64",
+    "state.write_ptr",
+    "state.write_i64_array",
+    "state.write_text",
+    "state.write_ttl_i64",
+    "state.delete",
+    "state.cas_i64",
+    "metric.incr",
+    "metric.read",
+];
+
+/// Built-in id for a prim registered as `name`, matching either the full name
+/// or its last path segment (`core/add_i64` is `add_i64`).
+pub fn builtin_id(name: &str) -> Option<&'static str> {
+    let base = name.rsplit('/').next().unwrap_or(name);
+    BUILTINS
+        .iter()
+        .find(|builtin| **builtin == name || **builtin == base)
+        .copied()
+}
+
 fn encode_effects(buf: &mut Vec<u8>, effects: &[[u8; 32]]) {
     let mut sorted = effects.to_vec();
     sorted.sort();
@@ -136,6 +230,9 @@ struct PrimRecord(
     Vec<String>,
     Vec<ByteBuf>,
     #[serde(default)] Option<u32>,
+    #[serde(default)] Option<String>,
+    #[serde(default)] Option<String>,
+    #[serde(default)] Option<String>,
 );
 
 #[cfg(test)]
@@ -152,6 +249,9 @@ mod tests {
             results: &results,
             effects: &[],
             effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let encoded = encode(&prim);
         let value: serde_cbor::Value =
@@ -186,6 +286,9 @@ mod tests {
             results: &results,
             effects: &[],
             effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let outcome = store_prim(&conn, &prim)?;
         let info = load_prim_info(&conn, &outcome.cid)?;
@@ -196,6 +299,80 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn doc_is_an_optional_trailing_slot() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+
+        let params = [TypeTag::I64];
+        let mut prim = PrimCanon {
+            params: &params,
+            results: &params,
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin: None,
+        };
+        // Objects written before the doc slot existed decode with no doc.
+        let plain = store_prim(&conn, &prim)?.cid;
+        assert_eq!(load_prim_info(&conn, &plain)?.doc, None);
+
+        prim.doc = Some("negate an i64");
+        let encoded = encode(&prim);
+        match serde_cbor::from_slice(&encoded)? {
+            serde_cbor::Value::Array(items) => {
+                assert_eq!(items.len(), 7);
+                assert_eq!(items[6], serde_cbor::Value::Text("negate an i64".into()));
+            }
+            other => panic!("expected array encoding, got {other:?}"),
+        }
+        let documented = store_prim(&conn, &prim)?.cid;
+        assert_ne!(documented, plain);
+        assert_eq!(
+            load_prim_info(&conn, &documented)?.doc.as_deref(),
+            Some("negate an i64")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn builtin_id_separates_prims_with_the_same_signature() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+
+        let params = [TypeTag::I64];
+        let prim = |builtin| PrimCanon {
+            params: &params,
+            results: &params,
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: None,
+            builtin,
+        };
+        let neg = store_prim(&conn, &prim(builtin_id("core/neg_i64")))?.cid;
+        let abs = store_prim(&conn, &prim(builtin_id("abs_i64")))?.cid;
+        assert_ne!(neg, abs);
+        assert_eq!(
+            load_prim_info(&conn, &abs)?.builtin.as_deref(),
+            Some("abs_i64")
+        );
+
+        // The doc and variadic slots are nulled so the id stays in the ninth slot.
+        match serde_cbor::from_slice(&encode(&prim(Some("neg_i64"))))? {
+            serde_cbor::Value::Array(items) => {
+                assert_eq!(items.len(), 9);
+                assert_eq!(items[6], serde_cbor::Value::Null);
+                assert_eq!(items[7], serde_cbor::Value::Null);
+                assert_eq!(items[8], serde_cbor::Value::Text("neg_i64".into()));
+            }
+            other => panic!("expected array encoding, got {other:?}"),
+        }
+        assert_eq!(builtin_id("demo/print"), None);
+        Ok(())
+    }
+
     #[test]
     fn roundtrip_effects() -> Result<()> {
         let params = [TypeTag::I64];
@@ -206,6 +383,9 @@ mod tests {
             results: &results,
             effects: &effects,
             effect_mask: effect_mask::STATE_WRITE,
+            doc: None,
+            variadic: None,
+            builtin: None,
         };
         let conn = Connection::open_in_memory()?;
         crate::db::install_schema(&conn)?;
@@ -215,4 +395,33 @@ mod tests {
         assert_eq!(info.effect_mask, effect_mask::STATE_WRITE);
         Ok(())
     }
+
+    #[test]
+    fn variadic_tail_follows_a_null_doc() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+
+        let results = [TypeTag::I64];
+        let prim = PrimCanon {
+            params: &[],
+            results: &results,
+            effects: &[],
+            effect_mask: effect_mask::NONE,
+            doc: None,
+            variadic: Some(TypeTag::I64),
+            builtin: None,
+        };
+        match serde_cbor::from_slice(&encode(&prim))? {
+            serde_cbor::Value::Array(items) => {
+                assert_eq!(items.len(), 8);
+                assert_eq!(items[6], serde_cbor::Value::Null);
+                assert_eq!(items[7], serde_cbor::Value::Text("i64".into()));
+            }
+            other => panic!("expected array encoding, got {other:?}"),
+        }
+        let info = load_prim_info(&conn, &store_prim(&conn, &prim)?.cid)?;
+        assert_eq!(info.variadic, Some(TypeTag::I64));
+        assert_eq!(info.doc, None);
+        Ok(())
+    }
 }
@@ -29,6 +29,29 @@ pub enum EffectDomain {
     Metric,
 }
 
+impl EffectDomain {
+    /// Lower-case domain name used in CLI flags and diagnostics.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EffectDomain::Io => "io",
+            EffectDomain::State => "state",
+            EffectDomain::Test => "test",
+            EffectDomain::Metric => "metric",
+        }
+    }
+
+    /// Parse a domain name produced by [`EffectDomain::as_str`].
+    pub fn from_name(name: &str) -> Result<EffectDomain> {
+        match name {
+            "io" => Ok(EffectDomain::Io),
+            "state" => Ok(EffectDomain::State),
+            "test" => Ok(EffectDomain::Test),
+            "metric" => Ok(EffectDomain::Metric),
+            other => bail!("unknown effect domain `{other}`"),
+        }
+    }
+}
+
 /// Translate a bitmask into the ordered set of effect domains it touches.
 pub fn effect_domains(mask: EffectMask) -> SmallVec<[EffectDomain; 4]> {
     let mut domains = SmallVec::<[EffectDomain; 4]>::new();
@@ -80,6 +103,8 @@ pub enum TypeTag {
     Ptr,
     Text,
     Unit,
+    U64,
+    Bytes,
     Token,
     StateToken,
     IoToken,
@@ -96,6 +121,8 @@ impl TypeTag {
             TypeTag::Ptr => "ptr",
             TypeTag::Text => "text",
             TypeTag::Unit => "unit",
+            TypeTag::U64 => "u64",
+            TypeTag::Bytes => "bytes",
             TypeTag::Token => "token",
             TypeTag::StateToken => "state.token",
             TypeTag::IoToken => "io.token",
@@ -104,6 +131,24 @@ impl TypeTag {
         }
     }
 
+    /// Every type tag, in declaration order.
+    pub fn all() -> &'static [TypeTag] {
+        &[
+            TypeTag::I64,
+            TypeTag::F64,
+            TypeTag::Ptr,
+            TypeTag::Text,
+            TypeTag::Unit,
+            TypeTag::U64,
+            TypeTag::Bytes,
+            TypeTag::Token,
+            TypeTag::StateToken,
+            TypeTag::IoToken,
+            TypeTag::TestToken,
+            TypeTag::MetricToken,
+        ]
+    }
+
     /// Parse a canonical atom into a `TypeTag`.
     pub fn from_atom(atom: &str) -> Result<TypeTag> {
         match atom {
@@ -112,6 +157,8 @@ impl TypeTag {
             "ptr" => Ok(TypeTag::Ptr),
             "text" => Ok(TypeTag::Text),
             "unit" => Ok(TypeTag::Unit),
+            "u64" => Ok(TypeTag::U64),
+            "bytes" => Ok(TypeTag::Bytes),
             "token" => Ok(TypeTag::Token),
             "state.token" => Ok(TypeTag::StateToken),
             "io.token" => Ok(TypeTag::IoToken),
@@ -121,6 +168,19 @@ impl TypeTag {
         }
     }
 
+    /// Parse a comma-separated atom list such as `i64, f64`; blank input is an empty list.
+    pub fn parse_comma_list(list: &str) -> Result<Vec<TypeTag>> {
+        if list.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        list.split(',')
+            .map(|atom| match atom.trim() {
+                "" => bail!("empty type atom in list `{list}`"),
+                atom => TypeTag::from_atom(atom),
+            })
+            .collect()
+    }
+
     /// Return the effect domain encoded by this token type, if any.
     pub fn token_domain(self) -> Option<EffectDomain> {
         match self {
@@ -145,6 +205,23 @@ impl TypeTag {
     }
 }
 
+/// Render tags as a `, `-separated atom list (the inverse of [`TypeTag::parse_comma_list`]).
+pub fn render_comma_list(tags: &[TypeTag]) -> String {
+    join_atoms(tags, ", ")
+}
+
+/// Render tags as a `,`-separated atom list without spaces, for use inside names.
+pub fn render_compact_list(tags: &[TypeTag]) -> String {
+    join_atoms(tags, ",")
+}
+
+fn join_atoms(tags: &[TypeTag], separator: &str) -> String {
+    tags.iter()
+        .map(|tag| tag.as_atom())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
 /// Encode a type signature object `{params: [...], results: [...]}`.
 pub fn encode_type_signature(buf: &mut Vec<u8>, params: &[&str], results: &[&str]) {
     push_map(buf, 2);
@@ -161,3 +238,40 @@ pub fn encode_type_signature(buf: &mut Vec<u8>, params: &[&str], results: &[&str
         push_text(buf, result);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_tag_round_trips_through_its_atom() -> Result<()> {
+        for tag in TypeTag::all() {
+            assert_eq!(TypeTag::from_atom(tag.as_atom())?, *tag);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn comma_lists_round_trip() -> Result<()> {
+        assert!(TypeTag::parse_comma_list("")?.is_empty());
+        assert!(TypeTag::parse_comma_list("  ")?.is_empty());
+        assert_eq!(render_comma_list(&[]), "");
+
+        assert_eq!(TypeTag::parse_comma_list("i64")?, vec![TypeTag::I64]);
+        assert_eq!(render_comma_list(&[TypeTag::I64]), "i64");
+
+        let tags = TypeTag::parse_comma_list("i64,f64 , io.token")?;
+        assert_eq!(tags, vec![TypeTag::I64, TypeTag::F64, TypeTag::IoToken]);
+        assert_eq!(render_comma_list(&tags), "i64, f64, io.token");
+        assert_eq!(render_compact_list(&tags), "i64,f64,io.token");
+        assert_eq!(TypeTag::parse_comma_list(&render_comma_list(&tags))?, tags);
+
+        let err = TypeTag::parse_comma_list("i64, i128").unwrap_err();
+        assert!(
+            err.to_string().contains("unknown type atom `i128`"),
+            "{err}"
+        );
+        assert!(TypeTag::parse_comma_list("i64,,f64").is_err());
+        Ok(())
+    }
+}
@@ -1,12 +1,18 @@
 //! Canonical encoding and persistence for word entrypoints.
 
-use anyhow::{Result, bail};
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow, bail};
 use rusqlite::Connection;
 use serde::Deserialize;
 use serde_bytes::ByteBuf;
 
 use crate::cbor::{push_array, push_bytes, push_text};
-use crate::types::{EffectMask, TypeTag, effect_mask};
+use crate::interp::Value;
+use crate::node::{self, NodeCanon, NodeInput, NodeKind, NodePayload};
+use crate::types::{
+    EffectDomain, EffectMask, TypeTag, effect_domains, effect_mask, token_tag_for_domain,
+};
 use crate::{cid, db};
 
 /// Structured word definition before encoding.
@@ -18,6 +24,8 @@ pub struct WordCanon {
     pub effects: Vec<[u8; 32]>,
     pub effect_mask: EffectMask,
     pub guards: Vec<[u8; 32]>,
+    /// Optional description; encoded only when present, so it leaves other CIDs alone.
+    pub doc: Option<String>,
 }
 
 /// Result of persisting a word object.
@@ -29,7 +37,7 @@ pub struct WordStoreOutcome {
 /// Encode a word into canonical CBOR.
 pub fn encode(word: &WordCanon) -> Vec<u8> {
     let mut buf = Vec::new();
-    push_array(&mut buf, 7);
+    push_array(&mut buf, if word.doc.is_some() { 8 } else { 7 });
     crate::cbor::push_u32(&mut buf, 1); // object tag for "word"
     push_bytes(&mut buf, &word.root);
 
@@ -59,6 +67,10 @@ pub fn encode(word: &WordCanon) -> Vec<u8> {
         push_bytes(&mut buf, &guard);
     }
 
+    if let Some(doc) = &word.doc {
+        push_text(&mut buf, doc);
+    }
+
     buf
 }
 
@@ -79,12 +91,13 @@ pub struct WordInfo {
     pub effects: Vec<[u8; 32]>,
     pub effect_mask: EffectMask,
     pub guards: Vec<[u8; 32]>,
+    pub doc: Option<String>,
 }
 
 /// Load word metadata from storage.
 pub fn load_word_info(conn: &Connection, cid_bytes: &[u8; 32]) -> Result<WordInfo> {
     let cbor = db::load_cbor_for_kind(conn, cid_bytes, "word")?;
-    let WordRecord(tag, root_buf, params_raw, results_raw, effects_raw, mask_opt, guards_raw) =
+    let WordRecord(tag, root_buf, params_raw, results_raw, effects_raw, mask_opt, guards_raw, doc) =
         serde_cbor::from_slice(&cbor)?;
     if tag != 1 {
         bail!("object tag mismatch while loading word: {tag}");
@@ -134,9 +147,568 @@ pub fn load_word_info(conn: &Connection, cid_bytes: &[u8; 32]) -> Result<WordInf
         effects,
         effect_mask: effect_mask_value,
         guards,
+        doc,
     })
 }
 
+/// True when two words compute the same graph over the same parameter types.
+///
+/// Nodes are content addressed and each node's CID covers its kind, output
+/// types, payload and input CIDs, so equal root CIDs already mean the whole
+/// graphs are structurally equal; no walk is needed. Parameters are compared
+/// separately because a graph that ignores an argument says nothing about its
+/// type. Metadata outside the graph (doc, guards, declared effects) is ignored,
+/// which is why two structurally equal words may still have different CIDs.
+pub fn structural_eq(conn: &Connection, a: &[u8; 32], b: &[u8; 32]) -> Result<bool> {
+    let a = load_word_info(conn, a)?;
+    let b = load_word_info(conn, b)?;
+    Ok(a.root == b.root && a.params == b.params)
+}
+
+/// Check that a word's declared effect CIDs and its effect mask agree.
+///
+/// The runner promotes a NONE mask to IO when effects are declared, which hides
+/// authoring mistakes; this reports that case, and masks whose domains have no
+/// effect descriptor behind them. Findings are advisory: such words still run.
+pub fn validate_effects(info: &WordInfo) -> Result<()> {
+    let issues = effect_issues(info);
+    if !issues.is_empty() {
+        bail!("{}", issues.join("; "));
+    }
+    Ok(())
+}
+
+/// Every disagreement [`validate_effects`] would report, one message each.
+pub fn effect_issues(info: &WordInfo) -> Vec<String> {
+    const KNOWN_BITS: EffectMask = effect_mask::IO
+        | effect_mask::STATE_READ
+        | effect_mask::STATE_WRITE
+        | effect_mask::TEST
+        | effect_mask::METRIC;
+    let mut issues = Vec::new();
+    if !info.effects.is_empty() && info.effect_mask == effect_mask::NONE {
+        issues.push(format!(
+            "word declares {} effect(s) but its effect mask is NONE (treated as io); name the domains explicitly",
+            info.effects.len()
+        ));
+    }
+    let domains = effect_domains(info.effect_mask)
+        .iter()
+        .map(|domain| domain.as_str())
+        .collect::<Vec<_>>();
+    if info.effects.is_empty() && !domains.is_empty() {
+        issues.push(format!(
+            "effect mask names domain(s) [{}] but the word declares no effect descriptors",
+            domains.join(", ")
+        ));
+    }
+    let unknown = info.effect_mask & !KNOWN_BITS;
+    if unknown != 0 {
+        issues.push(format!(
+            "effect mask sets unknown bit(s) {unknown:#x} outside every domain"
+        ));
+    }
+    for (idx, effect) in info.effects.iter().enumerate() {
+        if info.effects[..idx].contains(effect) {
+            issues.push(format!("effect {} is declared twice", cid::to_hex(effect)));
+        }
+    }
+    issues
+}
+
+/// Store a copy of `word_cid` with every CALL to one of `targets` replaced by
+/// the callee's body, and return the new word's CID.
+///
+/// Callee ARG nodes are wired to the CALL's data inputs and callee TOKEN nodes
+/// to its token inputs, so effects stay threaded through the caller's tokens.
+/// Callee RETURN deps are kept as deps of the caller's RETURN, which would make
+/// them unconditional, so a CALL reached only through an IF or GUARD continuation
+/// stays a CALL when its callee has deps. Calls nested in inlined bodies are
+/// inlined too; recursive targets are rejected.
+pub fn inline_calls(
+    conn: &Connection,
+    word_cid: &[u8; 32],
+    targets: &[[u8; 32]],
+) -> Result<[u8; 32]> {
+    let mut rewriter = Rewriter {
+        conn,
+        targets,
+        fold: false,
+        counter: None,
+        active: Vec::new(),
+        extra_deps: Vec::new(),
+        in_continuation: 0,
+    };
+    rewriter.rewrite_word(word_cid)
+}
+
+/// Store a copy of `word_cid` with PRIM nodes over constant LIT inputs replaced
+/// by LIT nodes holding their result, and return the new word's CID.
+///
+/// Only pure primitives the interpreter implements are folded, and only when
+/// they produce an `i64`; anything else (including prims that fail on their
+/// constants) is left for run time.
+pub fn fold_constants(conn: &Connection, word_cid: &[u8; 32]) -> Result<[u8; 32]> {
+    let mut rewriter = Rewriter {
+        conn,
+        targets: &[],
+        fold: true,
+        counter: None,
+        active: Vec::new(),
+        extra_deps: Vec::new(),
+        in_continuation: 0,
+    };
+    rewriter.rewrite_word(word_cid)
+}
+
+/// Store a copy of `word_cid` that counts how often each PRIM node runs, and
+/// return the new word's CID.
+///
+/// Each PRIM node in the word's own graph (callees are not entered) gets a
+/// `metric.incr` by one. The increments are chained on the METRIC token and the
+/// word returns the last one, so every run bumps each counter once. Prims inside
+/// IF/GUARD continuations are left alone: the token cannot be threaded through a
+/// branch, and counting them unconditionally would include untaken branches.
+/// The counter for a prim is read with [`instrument_count`]`(prefix, name)`,
+/// where `name` is the prim's registered name. Needs
+/// `metric.incr` registered under the `prim` scope; words that already use the
+/// metric domain are rejected.
+pub fn instrument(
+    conn: &Connection,
+    word_cid: &[u8; 32],
+    counter_prefix: &str,
+) -> Result<[u8; 32]> {
+    let info = load_word_info(conn, word_cid)?;
+    let mask = if info.effect_mask == effect_mask::NONE && !info.effects.is_empty() {
+        effect_mask::IO
+    } else {
+        info.effect_mask
+    };
+    if mask & effect_mask::METRIC != 0 {
+        bail!("word already uses the metric domain; instrumenting it is not supported");
+    }
+    let incr = db::get_name(conn, "prim", "metric.incr")?
+        .ok_or_else(|| anyhow!("instrumenting needs the `metric.incr` prim registered"))?;
+
+    let leaf = |kind, out: TypeTag, payload| -> Result<NodeInput> {
+        let canon = NodeCanon {
+            kind,
+            out: vec![out.as_atom().to_string()],
+            inputs: Vec::new(),
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload,
+        };
+        Ok(NodeInput {
+            cid: node::store_node(conn, &canon)?.cid,
+            port: 0,
+        })
+    };
+    let metric_token = token_tag_for_domain(EffectDomain::Metric);
+    let counter = PrimCounter {
+        prefix: counter_prefix,
+        incr,
+        one: leaf(NodeKind::Lit, TypeTag::I64, NodePayload::LitI64(1))?,
+        token: leaf(NodeKind::Token, metric_token, NodePayload::Token)?,
+    };
+    let mut rewriter = Rewriter {
+        conn,
+        targets: &[],
+        fold: false,
+        counter: Some(counter),
+        active: Vec::new(),
+        extra_deps: Vec::new(),
+        in_continuation: 0,
+    };
+    let root = rewriter.rewrite(&mut InlineFrame::default(), &info.root)?[0].cid;
+    let token = rewriter
+        .counter
+        .map(|counter| counter.token)
+        .ok_or_else(|| anyhow!("instrument rewrite lost its counter"))?;
+    let root = node::canon_from_info(&node::load_node_info(conn, &root)?)?;
+    if root.kind != NodeKind::Return {
+        bail!("word root is not a RETURN node");
+    }
+
+    // RETURN carries one token per domain (in domain order) ahead of the results.
+    let old_domains = effect_domains(mask);
+    let new_mask = mask | effect_mask::METRIC;
+    let (old_tokens, results) = root.vals.split_at(old_domains.len());
+    let mut vals = Vec::with_capacity(root.vals.len() + 1);
+    let mut out = Vec::with_capacity(root.vals.len() + 1);
+    for domain in effect_domains(new_mask) {
+        let val = match old_domains.iter().position(|d| *d == domain) {
+            Some(idx) => old_tokens[idx],
+            None => token,
+        };
+        vals.push(val);
+        out.push(token_tag_for_domain(domain).as_atom().to_string());
+    }
+    vals.extend_from_slice(results);
+    out.extend(info.results.iter().map(|t| t.as_atom().to_string()));
+    let new_root = node::store_node(
+        conn,
+        &NodeCanon {
+            kind: NodeKind::Return,
+            out,
+            inputs: Vec::new(),
+            vals,
+            deps: root.deps,
+            effects: Vec::new(),
+            payload: NodePayload::Return,
+        },
+    )?
+    .cid;
+    let word = WordCanon {
+        root: new_root,
+        params: info
+            .params
+            .iter()
+            .map(|t| t.as_atom().to_string())
+            .collect(),
+        results: info
+            .results
+            .iter()
+            .map(|t| t.as_atom().to_string())
+            .collect(),
+        effects: info.effects,
+        effect_mask: new_mask,
+        guards: info.guards,
+        doc: info.doc,
+    };
+    Ok(store_word(conn, &word)?.cid)
+}
+
+/// Quote CID standing for the counter `<prefix>/<prim_name>`.
+///
+/// Metric prims key counters by quote, and graphs have no text literals, so the
+/// metrics map holds this CID's hex rather than the readable name.
+pub fn instrument_key(counter_prefix: &str, prim_name: &str) -> [u8; 32] {
+    cid::compute(format!("{counter_prefix}/{prim_name}").as_bytes())
+}
+
+/// How often the prim named `prim_name` has run in words instrumented with `counter_prefix`.
+pub fn instrument_count(counter_prefix: &str, prim_name: &str) -> i64 {
+    crate::metrics::read(&cid::to_hex(&instrument_key(counter_prefix, prim_name)))
+}
+
+/// Graph rewrite shared by [`inline_calls`], [`fold_constants`] and [`instrument`].
+struct Rewriter<'a> {
+    conn: &'a Connection,
+    /// Words whose CALLs are inlined.
+    targets: &'a [[u8; 32]],
+    /// Fold pure PRIM nodes over constants.
+    fold: bool,
+    /// Count PRIM evaluations.
+    counter: Option<PrimCounter<'a>>,
+    /// Targets currently being inlined, to reject recursion.
+    active: Vec<[u8; 32]>,
+    /// RETURN deps of inlined bodies, attached to the caller's RETURN.
+    extra_deps: Vec<NodeInput>,
+    /// IF/GUARD continuations enclosing the node being rewritten.
+    in_continuation: usize,
+}
+
+/// Nodes [`instrument`] adds to count PRIM evaluations.
+struct PrimCounter<'a> {
+    prefix: &'a str,
+    /// `metric.incr` prim CID.
+    incr: [u8; 32],
+    /// LIT 1.
+    one: NodeInput,
+    /// METRIC token produced by the latest increment (the entry token at first).
+    token: NodeInput,
+}
+
+impl PrimCounter<'_> {
+    /// Add a `metric.incr` by one for the stored PRIM `prim`, chained on the
+    /// previous increment's token.
+    fn count(&mut self, conn: &Connection, prim: &NodeCanon) -> Result<()> {
+        let NodePayload::Prim(called) = prim.payload else {
+            bail!("only PRIM nodes are counted");
+        };
+        let name = db::list_names_for_cid(conn, "prim", &called)?
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| cid::to_hex(&called));
+        let quote = node::store_node(
+            conn,
+            &NodeCanon {
+                kind: NodeKind::Quote,
+                out: vec![TypeTag::Ptr.as_atom().to_string()],
+                inputs: Vec::new(),
+                vals: Vec::new(),
+                deps: Vec::new(),
+                effects: Vec::new(),
+                payload: NodePayload::Quote(instrument_key(self.prefix, &name)),
+            },
+        )?
+        .cid;
+        let bump = node::store_node(
+            conn,
+            &NodeCanon {
+                kind: NodeKind::Prim,
+                out: vec![
+                    token_tag_for_domain(EffectDomain::Metric)
+                        .as_atom()
+                        .to_string(),
+                    TypeTag::Unit.as_atom().to_string(),
+                ],
+                inputs: vec![
+                    NodeInput {
+                        cid: quote,
+                        port: 0,
+                    },
+                    self.one,
+                    self.token,
+                ],
+                vals: Vec::new(),
+                deps: Vec::new(),
+                effects: Vec::new(),
+                payload: NodePayload::Prim(self.incr),
+            },
+        )?
+        .cid;
+        self.token = NodeInput { cid: bump, port: 0 };
+        Ok(())
+    }
+}
+
+/// Substitutions for one graph being rewritten; the caller's own graph has none.
+#[derive(Default)]
+struct InlineFrame {
+    args: Option<Vec<NodeInput>>,
+    /// Token inputs of the inlined CALL, keyed by token type atom.
+    tokens: HashMap<String, NodeInput>,
+    /// Rewritten outputs (one edge per port) of already visited nodes.
+    memo: HashMap<[u8; 32], Vec<NodeInput>>,
+}
+
+impl Rewriter<'_> {
+    fn rewrite_word(&mut self, word_cid: &[u8; 32]) -> Result<[u8; 32]> {
+        let conn = self.conn;
+        let info = load_word_info(conn, word_cid)?;
+        let mut frame = InlineFrame::default();
+        let root = self.rewrite(&mut frame, &info.root)?;
+        let word = WordCanon {
+            root: root[0].cid,
+            params: info
+                .params
+                .iter()
+                .map(|t| t.as_atom().to_string())
+                .collect(),
+            results: info
+                .results
+                .iter()
+                .map(|t| t.as_atom().to_string())
+                .collect(),
+            effects: info.effects,
+            effect_mask: info.effect_mask,
+            guards: info.guards,
+            doc: info.doc,
+        };
+        Ok(store_word(conn, &word)?.cid)
+    }
+
+    fn rewrite(&mut self, frame: &mut InlineFrame, node_cid: &[u8; 32]) -> Result<Vec<NodeInput>> {
+        if let Some(outputs) = frame.memo.get(node_cid) {
+            return Ok(outputs.clone());
+        }
+        let info = node::load_node_info(self.conn, node_cid)?;
+        let outputs = match info.kind {
+            NodeKind::Arg if let Some(args) = &frame.args => {
+                let NodePayload::Arg(index) = node::canon_from_info(&info)?.payload else {
+                    bail!("ARG node without an index");
+                };
+                let arg = args
+                    .get(index as usize)
+                    .copied()
+                    .ok_or_else(|| anyhow!("inlined body reads missing argument {index}"))?;
+                vec![arg]
+            }
+            NodeKind::Token if frame.tokens.contains_key(&info.out[0]) => {
+                vec![frame.tokens[&info.out[0]]]
+            }
+            _ => {
+                let mut canon = node::canon_from_info(&info)?;
+                canon.inputs = self.rewrite_edges(frame, &canon.inputs)?;
+                canon.vals = self.rewrite_edges(frame, &canon.vals)?;
+                canon.deps = self.rewrite_edges(frame, &canon.deps)?;
+                let inline_target = match canon.payload {
+                    NodePayload::Word(callee) if self.targets.contains(&callee) => {
+                        self.can_inline_here(&callee)?.then_some(callee)
+                    }
+                    _ => None,
+                };
+                match &mut canon.payload {
+                    NodePayload::Word(callee) if inline_target == Some(*callee) => {
+                        let callee = *callee;
+                        self.inline_call(&canon, callee)?
+                    }
+                    payload => {
+                        self.in_continuation += 1;
+                        match payload {
+                            NodePayload::If {
+                                true_cont,
+                                false_cont,
+                            } => {
+                                *true_cont = self.rewrite_edge(frame, true_cont)?;
+                                *false_cont = self.rewrite_edge(frame, false_cont)?;
+                            }
+                            NodePayload::Guard {
+                                match_cont,
+                                else_cont,
+                                ..
+                            } => {
+                                *match_cont = self.rewrite_edge(frame, match_cont)?;
+                                *else_cont = self.rewrite_edge(frame, else_cont)?;
+                            }
+                            _ => {}
+                        }
+                        self.in_continuation -= 1;
+                        if canon.kind == NodeKind::Return && frame.args.is_none() {
+                            canon.deps.append(&mut self.extra_deps);
+                        }
+                        if self.fold
+                            && let Some(folded) = self.fold_prim(&canon)?
+                        {
+                            canon = folded;
+                        }
+                        let stored = node::store_node(self.conn, &canon)?.cid;
+                        if let Some(counter) = &mut self.counter
+                            && canon.kind == NodeKind::Prim
+                            && self.in_continuation == 0
+                        {
+                            counter.count(self.conn, &canon)?;
+                        }
+                        (0..canon.out.len() as u32)
+                            .map(|port| NodeInput { cid: stored, port })
+                            .collect()
+                    }
+                }
+            }
+        };
+        frame.memo.insert(*node_cid, outputs.clone());
+        Ok(outputs)
+    }
+
+    /// A LIT node replacing `canon` when it is a pure PRIM over constant inputs.
+    fn fold_prim(&self, canon: &NodeCanon) -> Result<Option<NodeCanon>> {
+        let NodePayload::Prim(prim_cid) = canon.payload else {
+            return Ok(None);
+        };
+        if !canon.effects.is_empty() || canon.out.len() != 1 {
+            return Ok(None);
+        }
+        let mut args = Vec::with_capacity(canon.inputs.len());
+        for input in &canon.inputs {
+            let info = node::load_node_info(self.conn, &input.cid)?;
+            match (info.kind, node::canon_from_info(&info)?.payload) {
+                (NodeKind::Lit, NodePayload::LitI64(value)) => args.push(Value::I64(value)),
+                _ => return Ok(None),
+            }
+        }
+        match crate::interp::eval_pure_prim(self.conn, &prim_cid, args) {
+            Ok(Value::I64(value)) => Ok(Some(NodeCanon {
+                kind: NodeKind::Lit,
+                out: vec![TypeTag::I64.as_atom().to_string()],
+                inputs: Vec::new(),
+                vals: Vec::new(),
+                deps: Vec::new(),
+                effects: Vec::new(),
+                payload: NodePayload::LitI64(value),
+            })),
+            _ => Ok(None),
+        }
+    }
+
+    fn rewrite_edge(&mut self, frame: &mut InlineFrame, edge: &NodeInput) -> Result<NodeInput> {
+        let outputs = self.rewrite(frame, &edge.cid)?;
+        outputs
+            .get(edge.port as usize)
+            .copied()
+            .ok_or_else(|| anyhow!("node {} missing port {}", cid::to_hex(&edge.cid), edge.port))
+    }
+
+    fn rewrite_edges(
+        &mut self,
+        frame: &mut InlineFrame,
+        edges: &[NodeInput],
+    ) -> Result<Vec<NodeInput>> {
+        edges
+            .iter()
+            .map(|edge| self.rewrite_edge(frame, edge))
+            .collect()
+    }
+
+    /// Whether hoisting `callee`'s RETURN deps keeps them conditional on the call.
+    fn can_inline_here(&self, callee: &[u8; 32]) -> Result<bool> {
+        if self.in_continuation == 0 {
+            return Ok(true);
+        }
+        let root = load_word_info(self.conn, callee)?.root;
+        Ok(
+            node::canon_from_info(&node::load_node_info(self.conn, &root)?)?
+                .deps
+                .is_empty(),
+        )
+    }
+
+    /// Splice the body of `callee` in place of `call`, whose inputs are already rewritten.
+    fn inline_call(&mut self, call: &NodeCanon, callee: [u8; 32]) -> Result<Vec<NodeInput>> {
+        if self.active.contains(&callee) {
+            bail!("cannot inline recursive word {}", cid::to_hex(&callee));
+        }
+        let info = load_word_info(self.conn, &callee)?;
+        let mask = if info.effect_mask == effect_mask::NONE && !info.effects.is_empty() {
+            effect_mask::IO
+        } else {
+            info.effect_mask
+        };
+        let domains = effect_domains(mask);
+        if call.inputs.len() != info.params.len() + domains.len() {
+            bail!(
+                "CALL to {} has {} input(s), expected {} argument(s) and {} token(s)",
+                cid::to_hex(&callee),
+                call.inputs.len(),
+                info.params.len(),
+                domains.len()
+            );
+        }
+        let (args, tokens) = call.inputs.split_at(info.params.len());
+        let mut frame = InlineFrame {
+            args: Some(args.to_vec()),
+            tokens: domains
+                .iter()
+                .zip(tokens)
+                .map(|(domain, token)| {
+                    (token_tag_for_domain(*domain).as_atom().to_string(), *token)
+                })
+                .collect(),
+            memo: HashMap::new(),
+        };
+        let root = node::canon_from_info(&node::load_node_info(self.conn, &info.root)?)?;
+        if root.kind != NodeKind::Return {
+            bail!("word {} has no RETURN root to inline", cid::to_hex(&callee));
+        }
+        self.active.push(callee);
+        let outputs = self.rewrite_edges(&mut frame, &root.vals)?;
+        let deps = self.rewrite_edges(&mut frame, &root.deps)?;
+        self.active.pop();
+        self.extra_deps.extend(deps);
+        if outputs.len() != call.out.len() {
+            bail!(
+                "word {} returns {} value(s) but its CALL expects {}",
+                cid::to_hex(&callee),
+                outputs.len(),
+                call.out.len()
+            );
+        }
+        Ok(outputs)
+    }
+}
+
 fn bytebuf_to_array(buf: &ByteBuf) -> Result<[u8; 32]> {
     let slice = buf.as_slice();
     if slice.len() != 32 {
@@ -156,12 +728,67 @@ struct WordRecord(
     Vec<ByteBuf>,
     #[serde(default)] Option<u32>,
     #[serde(default)] Vec<ByteBuf>,
+    #[serde(default)] Option<String>,
 );
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::{TypeTag, effect_mask};
+    use std::collections::HashSet;
+
+    /// The word's first (i64) argument; ARG nodes are shared by content.
+    fn first_arg(conn: &Connection) -> Result<NodeInput> {
+        let arg = NodeCanon {
+            kind: NodeKind::Arg,
+            out: vec![TypeTag::I64.as_atom().to_string()],
+            inputs: Vec::new(),
+            vals: Vec::new(),
+            deps: Vec::new(),
+            effects: Vec::new(),
+            payload: NodePayload::Arg(0),
+        };
+        Ok(NodeInput {
+            cid: node::store_node(conn, &arg)?.cid,
+            port: 0,
+        })
+    }
+
+    /// Every node reachable from `root` (edges, RETURN vals/deps and branch
+    /// continuations), in depth-first order with the first input visited first.
+    fn graph_nodes(conn: &Connection, root: &[u8; 32]) -> Result<Vec<([u8; 32], NodeCanon)>> {
+        let mut seen = HashSet::new();
+        let mut pending = vec![*root];
+        let mut nodes = Vec::new();
+        while let Some(node_cid) = pending.pop() {
+            if !seen.insert(node_cid) {
+                continue;
+            }
+            let canon = node::canon_from_info(&node::load_node_info(conn, &node_cid)?)?;
+            let mut edges: Vec<NodeInput> = canon
+                .inputs
+                .iter()
+                .chain(&canon.vals)
+                .chain(&canon.deps)
+                .copied()
+                .collect();
+            match &canon.payload {
+                NodePayload::If {
+                    true_cont,
+                    false_cont,
+                } => edges.extend([*true_cont, *false_cont]),
+                NodePayload::Guard {
+                    match_cont,
+                    else_cont,
+                    ..
+                } => edges.extend([*match_cont, *else_cont]),
+                _ => {}
+            }
+            pending.extend(edges.iter().rev().map(|edge| edge.cid));
+            nodes.push((node_cid, canon));
+        }
+        Ok(nodes)
+    }
 
     #[test]
     fn encode_word_empty_type() {
@@ -172,6 +799,7 @@ mod tests {
             effects: Vec::new(),
             effect_mask: effect_mask::NONE,
             guards: Vec::new(),
+            doc: None,
         };
         let encoded = encode(&word);
         let value: serde_cbor::Value =
@@ -200,6 +828,7 @@ mod tests {
             effects: vec![[0xAA; 32]],
             effect_mask: effect_mask::STATE_READ,
             guards: vec![[0xBB; 32]],
+            doc: None,
         };
         let outcome = store_word(&conn, &word)?;
         let info = load_word_info(&conn, &outcome.cid)?;
@@ -212,4 +841,489 @@ mod tests {
         assert_eq!(info.guards, vec![[0xBB; 32]]);
         Ok(())
     }
+
+    #[test]
+    fn doc_is_an_optional_trailing_slot() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+
+        let mut word = WordCanon {
+            root: [0x44; 32],
+            params: Vec::new(),
+            results: vec!["i64".to_string()],
+            effects: Vec::new(),
+            effect_mask: effect_mask::NONE,
+            guards: Vec::new(),
+            doc: None,
+        };
+        // Seven-slot objects predate the doc field and still decode.
+        let plain = store_word(&conn, &word)?.cid;
+        assert_eq!(load_word_info(&conn, &plain)?.doc, None);
+
+        word.doc = Some("the answer".to_string());
+        match serde_cbor::from_slice(&encode(&word))? {
+            serde_cbor::Value::Array(items) => {
+                assert_eq!(items.len(), 8);
+                assert_eq!(items[7], serde_cbor::Value::Text("the answer".into()));
+            }
+            other => panic!("expected array, got {other:?}"),
+        }
+        let documented = store_word(&conn, &word)?.cid;
+        assert_eq!(
+            load_word_info(&conn, &documented)?.doc.as_deref(),
+            Some("the answer")
+        );
+        Ok(())
+    }
+
+    fn count_calls(conn: &Connection, word_cid: &[u8; 32]) -> Result<usize> {
+        let root = load_word_info(conn, word_cid)?.root;
+        let nodes = graph_nodes(conn, &root)?;
+        Ok(nodes
+            .iter()
+            .filter(|(_, n)| n.kind == NodeKind::Call)
+            .count())
+    }
+
+    #[test]
+    fn inlining_removes_the_call_and_keeps_the_result() -> Result<()> {
+        use crate::builder::GraphBuilder;
+        use crate::interp::run_word;
+        use crate::prim::{PrimCanon, store_prim};
+
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        let pair = [TypeTag::I64, TypeTag::I64];
+        let one = [TypeTag::I64];
+        let add = store_prim(
+            &conn,
+            &PrimCanon {
+                params: &pair,
+                results: &one,
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            },
+        )?
+        .cid;
+        db::put_name(&conn, "prim", "core/add_i64", &add)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&pair)?;
+        builder.apply_prim(add)?;
+        let callee = builder.finish_word(&pair, &one, Some("demo/add"))?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&pair)?;
+        builder.apply_word(callee)?;
+        builder.push_lit_i64(10)?;
+        builder.apply_prim(add)?;
+        let caller = builder.finish_word(&pair, &one, Some("demo/add_ten"))?;
+
+        let inlined = inline_calls(&conn, &caller, &[callee])?;
+        assert_ne!(inlined, caller);
+        assert_eq!(count_calls(&conn, &caller)?, 1);
+        assert_eq!(count_calls(&conn, &inlined)?, 0);
+        let args = [Value::I64(3), Value::I64(4)];
+        assert_eq!(
+            run_word(&conn, &inlined, &args)?,
+            run_word(&conn, &caller, &args)?
+        );
+        assert_eq!(run_word(&conn, &inlined, &args)?, vec![Value::I64(17)]);
+
+        // Words without calls to the targets come back unchanged.
+        assert_eq!(inline_calls(&conn, &callee, &[callee])?, callee);
+        Ok(())
+    }
+
+    #[test]
+    fn inlining_leaves_guarded_calls_in_untaken_branches_lazy() -> Result<()> {
+        use crate::builder::GraphBuilder;
+        use crate::interp::{EvalError, run_word};
+
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        let pair = [TypeTag::I64, TypeTag::I64];
+        let one = [TypeTag::I64];
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_guard(&[])?;
+        builder.push_lit_i64(0)?;
+        let never = builder.finish_guard(&[], &one, Some("demo/never"))?;
+        builder.begin_word(&one)?;
+        builder.attach_guard(never);
+        let callee = builder.finish_word(&one, &one, Some("demo/guarded"))?;
+
+        // if a0 then callee(a1) else 7
+        builder.begin_word(&pair)?;
+        let call = builder.apply_word(callee)?;
+        let seven = NodeInput {
+            cid: builder.push_lit_i64(7)?,
+            port: 0,
+        };
+        let cond = first_arg(&conn)?;
+        let i64_out = || vec![TypeTag::I64.as_atom().to_string()];
+        let branch = node::store_node(
+            &conn,
+            &NodeCanon {
+                kind: NodeKind::If,
+                out: i64_out(),
+                inputs: vec![cond],
+                vals: Vec::new(),
+                deps: Vec::new(),
+                effects: Vec::new(),
+                payload: NodePayload::If {
+                    true_cont: NodeInput { cid: call, port: 0 },
+                    false_cont: seven,
+                },
+            },
+        )?
+        .cid;
+        let root = node::store_node(
+            &conn,
+            &NodeCanon {
+                kind: NodeKind::Return,
+                out: i64_out(),
+                inputs: Vec::new(),
+                vals: vec![NodeInput {
+                    cid: branch,
+                    port: 0,
+                }],
+                deps: Vec::new(),
+                effects: Vec::new(),
+                payload: NodePayload::Return,
+            },
+        )?
+        .cid;
+        let caller = store_word(
+            &conn,
+            &WordCanon {
+                root,
+                params: vec!["i64".to_string(); 2],
+                results: i64_out(),
+                effects: Vec::new(),
+                effect_mask: effect_mask::NONE,
+                guards: Vec::new(),
+                doc: None,
+            },
+        )?
+        .cid;
+
+        let inlined = inline_calls(&conn, &caller, &[callee])?;
+        assert_eq!(count_calls(&conn, &inlined)?, 1);
+        let untaken = [Value::I64(0), Value::I64(5)];
+        assert_eq!(run_word(&conn, &caller, &untaken)?, vec![Value::I64(7)]);
+        assert_eq!(run_word(&conn, &inlined, &untaken)?, vec![Value::I64(7)]);
+        let taken = [Value::I64(1), Value::I64(5)];
+        let err = run_word(&conn, &inlined, &taken).unwrap_err();
+        assert!(
+            matches!(
+                err.downcast_ref::<EvalError>(),
+                Some(EvalError::Deopt { .. })
+            ),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn folding_turns_constant_prims_into_literals() -> Result<()> {
+        use crate::builder::GraphBuilder;
+        use crate::interp::run_word;
+        use crate::prim::{PrimCanon, store_prim};
+
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        let pair = [TypeTag::I64, TypeTag::I64];
+        let one = [TypeTag::I64];
+        let add = store_prim(
+            &conn,
+            &PrimCanon {
+                params: &pair,
+                results: &one,
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            },
+        )?
+        .cid;
+        db::put_name(&conn, "prim", "core/add_i64", &add)?;
+
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&[])?;
+        builder.push_lit_i64(2)?;
+        builder.push_lit_i64(3)?;
+        builder.apply_prim(add)?;
+        let sum = builder.finish_word(&[], &one, Some("demo/five"))?;
+
+        let folded = fold_constants(&conn, &sum)?;
+        let root = node::load_node_info(&conn, &load_word_info(&conn, &folded)?.root)?;
+        let returned = node::canon_from_info(&root)?.vals[0];
+        let lit = node::canon_from_info(&node::load_node_info(&conn, &returned.cid)?)?;
+        assert_eq!(lit.kind, NodeKind::Lit);
+        assert!(matches!(lit.payload, NodePayload::LitI64(5)));
+        assert_eq!(run_word(&conn, &folded, &[])?, vec![Value::I64(5)]);
+        Ok(())
+    }
+
+    #[test]
+    fn instrumented_words_count_prim_runs() -> Result<()> {
+        use crate::builder::GraphBuilder;
+        use crate::interp::run_word;
+        use crate::metrics;
+        use crate::prim::{PrimCanon, store_prim};
+
+        let _lock = metrics::test_lock();
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        let mut prims = Vec::new();
+        for (name, params, results, mask) in [
+            (
+                "core/add_i64",
+                vec![TypeTag::I64, TypeTag::I64],
+                vec![TypeTag::I64],
+                effect_mask::NONE,
+            ),
+            (
+                "metric.incr",
+                vec![TypeTag::Ptr, TypeTag::I64],
+                vec![TypeTag::Unit],
+                effect_mask::METRIC,
+            ),
+        ] {
+            let spec = PrimCanon {
+                params: &params,
+                results: &results,
+                effects: &[],
+                effect_mask: mask,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            };
+            let cid = store_prim(&conn, &spec)?.cid;
+            db::put_name(&conn, "prim", name, &cid)?;
+            prims.push(cid);
+        }
+        let pair = [TypeTag::I64, TypeTag::I64];
+        let mut builder = GraphBuilder::new(&conn);
+        builder.begin_word(&pair)?;
+        builder.apply_prim(prims[0])?;
+        let add = builder.finish_word(&pair, &[TypeTag::I64], Some("demo/add"))?;
+
+        let instrumented = instrument(&conn, &add, "instrument-test")?;
+        let count = || instrument_count("instrument-test", "core/add_i64");
+        let before = count();
+        let args = [Value::I64(2), Value::I64(5)];
+        for _ in 0..2 {
+            assert_eq!(
+                run_word(&conn, &instrumented, &args)?,
+                vec![Value::Token(Some(EffectDomain::Metric)), Value::I64(7)]
+            );
+        }
+        assert_eq!(count() - before, 2);
+
+        // add(add(a0, a1), a2): the second increment consumes the first one's token,
+        // and the word returns the last token.
+        let triple = [TypeTag::I64; 3];
+        builder.begin_word(&triple)?;
+        builder.apply_prim(prims[0])?;
+        builder.apply_prim(prims[0])?;
+        let add3 = builder.finish_word(&triple, &[TypeTag::I64], None)?;
+        let instrumented = instrument(&conn, &add3, "instrument-test")?;
+        let root = load_word_info(&conn, &instrumented)?.root;
+        let root = node::canon_from_info(&node::load_node_info(&conn, &root)?)?;
+        let last = node::canon_from_info(&node::load_node_info(&conn, &root.vals[0].cid)?)?;
+        assert!(matches!(last.payload, NodePayload::Prim(cid) if cid == prims[1]));
+        let first = node::canon_from_info(&node::load_node_info(&conn, &last.inputs[2].cid)?)?;
+        assert!(matches!(first.payload, NodePayload::Prim(cid) if cid == prims[1]));
+        assert_eq!(
+            node::load_node_info(&conn, &first.inputs[2].cid)?.kind,
+            NodeKind::Token
+        );
+        let before = count();
+        let args = [Value::I64(1), Value::I64(2), Value::I64(3)];
+        assert_eq!(
+            run_word(&conn, &instrumented, &args)?,
+            vec![Value::Token(Some(EffectDomain::Metric)), Value::I64(6)]
+        );
+        assert_eq!(count() - before, 2);
+
+        // if a0 then add(a1, a2) else 7: prims inside a branch are not counted.
+        builder.begin_word(&triple)?;
+        let sum = builder.apply_prim(prims[0])?;
+        let seven = NodeInput {
+            cid: builder.push_lit_i64(7)?,
+            port: 0,
+        };
+        let cond = first_arg(&conn)?;
+        let i64_out = || vec![TypeTag::I64.as_atom().to_string()];
+        let branch = node::store_node(
+            &conn,
+            &NodeCanon {
+                kind: NodeKind::If,
+                out: i64_out(),
+                inputs: vec![cond],
+                vals: Vec::new(),
+                deps: Vec::new(),
+                effects: Vec::new(),
+                payload: NodePayload::If {
+                    true_cont: NodeInput { cid: sum, port: 0 },
+                    false_cont: seven,
+                },
+            },
+        )?
+        .cid;
+        let root = node::store_node(
+            &conn,
+            &NodeCanon {
+                kind: NodeKind::Return,
+                out: i64_out(),
+                inputs: Vec::new(),
+                vals: vec![NodeInput {
+                    cid: branch,
+                    port: 0,
+                }],
+                deps: Vec::new(),
+                effects: Vec::new(),
+                payload: NodePayload::Return,
+            },
+        )?
+        .cid;
+        let pick = store_word(
+            &conn,
+            &WordCanon {
+                root,
+                params: vec!["i64".to_string(); 3],
+                results: i64_out(),
+                effects: Vec::new(),
+                effect_mask: effect_mask::NONE,
+                guards: Vec::new(),
+                doc: None,
+            },
+        )?
+        .cid;
+        let instrumented = instrument(&conn, &pick, "instrument-test")?;
+        let before = count();
+        let untaken = [Value::I64(0), Value::I64(2), Value::I64(5)];
+        assert_eq!(
+            run_word(&conn, &instrumented, &untaken)?,
+            vec![Value::Token(Some(EffectDomain::Metric)), Value::I64(7)]
+        );
+        assert_eq!(count(), before);
+        let taken = [Value::I64(1), Value::I64(2), Value::I64(5)];
+        assert_eq!(
+            run_word(&conn, &instrumented, &taken)?,
+            vec![Value::Token(Some(EffectDomain::Metric)), Value::I64(7)]
+        );
+        assert_eq!(count(), before);
+        Ok(())
+    }
+
+    #[test]
+    fn structural_eq_compares_graphs_not_word_cids() -> Result<()> {
+        use crate::builder::GraphBuilder;
+        use crate::prim::{PrimCanon, store_prim};
+
+        let conn = Connection::open_in_memory()?;
+        crate::db::install_schema(&conn)?;
+        let pair = [TypeTag::I64, TypeTag::I64];
+        let one = [TypeTag::I64];
+        let sub = store_prim(
+            &conn,
+            &PrimCanon {
+                params: &pair,
+                results: &one,
+                effects: &[],
+                effect_mask: effect_mask::NONE,
+                doc: None,
+                variadic: None,
+                builtin: None,
+            },
+        )?
+        .cid;
+        let build = |swaps: usize| -> Result<[u8; 32]> {
+            let mut builder = GraphBuilder::new(&conn);
+            builder.begin_word(&pair)?;
+            for _ in 0..swaps {
+                builder.swap()?;
+            }
+            builder.apply_prim(sub)?;
+            builder.finish_word(&pair, &one, None)
+        };
+
+        let direct = build(0)?;
+        // Two swaps cancel out; documenting the word changes its CID but not its graph.
+        let swapped = load_word_info(&conn, &build(2)?)?;
+        let canon = WordCanon {
+            root: swapped.root,
+            params: vec!["i64".to_string(); 2],
+            results: vec!["i64".to_string()],
+            effects: Vec::new(),
+            effect_mask: effect_mask::NONE,
+            guards: Vec::new(),
+            doc: Some("subtract".to_string()),
+        };
+        let documented = store_word(&conn, &canon)?.cid;
+        assert_ne!(direct, documented);
+        assert!(structural_eq(&conn, &direct, &documented)?);
+
+        let flipped = build(1)?;
+        assert!(!structural_eq(&conn, &direct, &flipped)?);
+
+        // A constant graph ignores its argument, so only the params tell these apart.
+        let constant = |param: TypeTag| -> Result<[u8; 32]> {
+            let mut builder = GraphBuilder::new(&conn);
+            builder.begin_word(&[param])?;
+            builder.drop()?;
+            builder.push_lit_i64(7)?;
+            builder.finish_word(&[param], &one, None)
+        };
+        assert!(!structural_eq(
+            &conn,
+            &constant(TypeTag::I64)?,
+            &constant(TypeTag::F64)?
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn validate_effects_flags_mask_disagreements() {
+        let mut info = WordInfo {
+            root: [0x33; 32],
+            params: Vec::new(),
+            results: vec![TypeTag::I64],
+            effects: vec![[0xAA; 32]],
+            effect_mask: effect_mask::NONE,
+            guards: Vec::new(),
+            doc: None,
+        };
+        let err = validate_effects(&info).unwrap_err();
+        assert!(err.to_string().contains("effect mask is NONE"), "{err}");
+
+        info.effect_mask = effect_mask::IO;
+        assert!(validate_effects(&info).is_ok());
+
+        info.effects.clear();
+        let err = validate_effects(&info).unwrap_err();
+        assert!(err.to_string().contains("[io]"), "{err}");
+
+        info.effect_mask = effect_mask::NONE;
+        assert!(validate_effects(&info).is_ok());
+
+        info.effects = vec![[0xAA; 32], [0xAA; 32]];
+        info.effect_mask = 1 << 7;
+        let issues = effect_issues(&info);
+        assert_eq!(issues.len(), 2, "{issues:?}");
+        assert!(issues[0].contains("unknown bit(s) 0x80"), "{issues:?}");
+        assert!(issues[1].contains("declared twice"), "{issues:?}");
+        let err = validate_effects(&info).unwrap_err().to_string();
+        assert!(
+            err.contains("unknown bit") && err.contains("declared twice"),
+            "{err}"
+        );
+    }
 }
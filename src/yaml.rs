@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
@@ -15,39 +16,260 @@ pub enum Node {
     Tagged { tag: String, value: Box<Node> },
 }
 
+/// Source position for error messages: 1-based line and column plus the
+/// line's text.
+#[derive(Clone, Copy, Debug, Default)]
+struct Pos<'a> {
+    line: usize,
+    column: usize,
+    text: &'a str,
+}
+
+/// Where the parts of a parsed [`Node`] came from, mirroring its shape.
+///
+/// Kept beside the tree rather than inside it so `Node` stays a plain value.
+#[derive(Clone, Debug, Default)]
+struct Spans<'a> {
+    /// Position of the tag on a tagged node.
+    tag: Option<Pos<'a>>,
+    /// Spans of a tagged node's payload.
+    value: Option<Box<Spans<'a>>>,
+    /// Position and spans of each sequence item.
+    items: Vec<(Pos<'a>, Spans<'a>)>,
+    /// Position of each mapping key and spans of its value.
+    entries: BTreeMap<String, (Pos<'a>, Spans<'a>)>,
+}
+
+static NO_SPANS: Spans<'static> = Spans {
+    tag: None,
+    value: None,
+    items: Vec::new(),
+    entries: BTreeMap::new(),
+};
+
+impl<'a> Spans<'a> {
+    fn tagged(pos: Pos<'a>, value: Spans<'a>) -> Self {
+        Spans {
+            tag: Some(pos),
+            value: Some(Box::new(value)),
+            ..Spans::default()
+        }
+    }
+
+    /// Position of the node itself when it carries one, else `fallback` (its parent's).
+    fn at(&self, fallback: Pos<'a>) -> Pos<'a> {
+        self.tag.unwrap_or(fallback)
+    }
+
+    fn payload(&self) -> &Spans<'a> {
+        self.value.as_deref().unwrap_or(&NO_SPANS)
+    }
+
+    /// Position of `key` and spans of its value, falling back to the mapping's own position.
+    fn entry(&self, key: &str, fallback: Pos<'a>) -> (Pos<'a>, &Spans<'a>) {
+        match self.entries.get(key) {
+            Some((pos, spans)) => (*pos, spans),
+            None => (fallback, &NO_SPANS),
+        }
+    }
+
+    fn key_pos(&self, key: &str, fallback: Pos<'a>) -> Pos<'a> {
+        self.entry(key, fallback).0
+    }
+}
+
 struct Line<'a> {
+    /// 1-based line number in the source text.
+    number: usize,
     indent: usize,
     content: &'a str,
+    /// Body of a literal block scalar when `content` ends with `|`.
+    block: Option<String>,
 }
 
 fn preprocess(input: &str) -> Vec<Line<'_>> {
-    input
-        .lines()
-        .filter_map(|raw| {
-            let stripped = raw.split('#').next().unwrap_or("").trim_end();
-            let trimmed = stripped.trim_start();
-            if trimmed.is_empty() || trimmed.starts_with('%') || trimmed == "---" {
-                return None;
-            }
-            let indent = stripped.len() - trimmed.len();
-            Some(Line {
-                indent,
-                content: trimmed,
-            })
-        })
-        .collect()
+    let raw_lines: Vec<&str> = input.lines().collect();
+    let mut lines = Vec::new();
+    let mut pos = 0;
+    while pos < raw_lines.len() {
+        let raw = raw_lines[pos];
+        pos += 1;
+        let number = pos;
+        let stripped = raw.split('#').next().unwrap_or("").trim_end();
+        let trimmed = stripped.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('%') || trimmed == "---" {
+            continue;
+        }
+        let indent = stripped.len() - trimmed.len();
+        let block = if opens_block(trimmed) {
+            Some(collect_block(&raw_lines, &mut pos, indent))
+        } else {
+            None
+        };
+        lines.push(Line {
+            number,
+            indent,
+            content: trimmed,
+            block,
+        });
+    }
+    lines
+}
+
+/// True when a line's value is a bare `|` (after `key:`, `-`, or a `!tag`).
+fn opens_block(content: &str) -> bool {
+    let Some(head) = content.strip_suffix('|') else {
+        return false;
+    };
+    if !(head.is_empty() || head.ends_with(' ')) {
+        return false;
+    }
+    let head = head.trim_end();
+    head.is_empty()
+        || head.ends_with(':')
+        || head == "-"
+        || head
+            .rsplit(' ')
+            .next()
+            .is_some_and(|token| token.starts_with('!'))
+}
+
+/// Gather the lines of a literal block scalar that are indented past `parent_indent`.
+///
+/// Lines are taken verbatim (comments included) relative to the first line's
+/// indentation and joined with newlines; trailing blank lines are dropped.
+fn collect_block(raw_lines: &[&str], pos: &mut usize, parent_indent: usize) -> String {
+    let mut body: Vec<&str> = Vec::new();
+    let mut block_indent = None;
+    while *pos < raw_lines.len() {
+        let raw = raw_lines[*pos];
+        let content = raw.trim_start();
+        let indent = raw.len() - content.len();
+        if content.is_empty() {
+            body.push("");
+            *pos += 1;
+            continue;
+        }
+        if indent <= parent_indent {
+            break;
+        }
+        let base = *block_indent.get_or_insert(indent);
+        body.push(if indent >= base {
+            &raw[base..]
+        } else {
+            content
+        });
+        *pos += 1;
+    }
+    while body.last() == Some(&"") {
+        body.pop();
+    }
+    body.join("\n")
+}
+
+/// Quote block text so scalar decoding yields it unchanged (and always as text).
+fn quote_block(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            other => out.push(other),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl<'a> Line<'a> {
+    fn pos(&self) -> Pos<'a> {
+        Pos {
+            line: self.number,
+            column: self.indent + 1,
+            text: self.content,
+        }
+    }
 }
 
-fn parse_node(lines: &[Line<'_>], idx: &mut usize, indent: usize) -> Result<Node> {
+/// Parse or decode error tagged with the source position that failed.
+#[derive(Debug)]
+struct YamlError {
+    line: usize,
+    column: usize,
+    text: String,
+    message: String,
+}
+
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {} in `{}`",
+            self.line, self.column, self.message, self.text
+        )
+    }
+}
+
+impl std::error::Error for YamlError {}
+
+/// Build an error pointing at `pos`.
+fn error_at(pos: Pos<'_>, message: impl fmt::Display) -> anyhow::Error {
+    YamlError {
+        line: pos.line,
+        column: pos.column,
+        text: pos.text.to_string(),
+        message: message.to_string(),
+    }
+    .into()
+}
+
+/// Point `err` at `pos` unless a nested node already located it.
+fn locate(pos: Pos<'_>, err: anyhow::Error) -> anyhow::Error {
+    if err.is::<YamlError>() {
+        err
+    } else {
+        error_at(pos, format!("{err:#}"))
+    }
+}
+
+/// The line whose value is being parsed; callers advance `idx` past it first.
+fn owning_line<'l, 'a>(lines: &'l [Line<'a>], idx: usize) -> &'l Line<'a> {
+    &lines[idx.saturating_sub(1).min(lines.len() - 1)]
+}
+
+/// Reject lines the root parser left unconsumed (usually stray indentation).
+fn ensure_consumed(lines: &[Line<'_>], idx: usize) -> Result<()> {
+    match lines.get(idx) {
+        Some(line) => Err(error_at(
+            line.pos(),
+            "unexpected content; check indentation",
+        )),
+        None => Ok(()),
+    }
+}
+
+fn parse_node<'a>(lines: &[Line<'a>], idx: &mut usize, indent: usize) -> Result<(Node, Spans<'a>)> {
     if *idx >= lines.len() {
-        bail!("unexpected end of document");
+        return Err(error_at(
+            owning_line(lines, *idx).pos(),
+            "missing value at end of document",
+        ));
     }
     let line = &lines[*idx];
     if line.indent < indent {
-        bail!("invalid indentation at line {}", *idx + 1);
+        return Err(error_at(
+            line.pos(),
+            format!("invalid indentation (expected column {})", indent + 1),
+        ));
     }
     if line.content.starts_with("- ") {
         parse_sequence(lines, idx, indent)
+    } else if line.content.starts_with('{') || line.content.starts_with('[') {
+        parse_scalar_node(lines, idx, indent)
     } else if let Some(pos) = line.content.find(':') {
         if pos == line.content.len() - 1 {
             parse_mapping(lines, idx, indent)
@@ -65,8 +287,13 @@ fn parse_node(lines: &[Line<'_>], idx: &mut usize, indent: usize) -> Result<Node
     }
 }
 
-fn parse_sequence(lines: &[Line<'_>], idx: &mut usize, indent: usize) -> Result<Node> {
+fn parse_sequence<'a>(
+    lines: &[Line<'a>],
+    idx: &mut usize,
+    indent: usize,
+) -> Result<(Node, Spans<'a>)> {
     let mut items = Vec::new();
+    let mut spans = Spans::default();
     while *idx < lines.len() {
         let line = &lines[*idx];
         if line.indent < indent {
@@ -77,18 +304,24 @@ fn parse_sequence(lines: &[Line<'_>], idx: &mut usize, indent: usize) -> Result<
         }
         let remainder = &line.content[2..].trim_start();
         *idx += 1;
-        let item = if remainder.is_empty() {
+        let (item, item_spans) = if remainder.is_empty() {
             parse_node(lines, idx, indent + 2)?
         } else {
             parse_tag_or_scalar(lines, idx, indent + 2, remainder)?
         };
         items.push(item);
+        spans.items.push((line.pos(), item_spans));
     }
-    Ok(Node::Sequence(items))
+    Ok((Node::Sequence(items), spans))
 }
 
-fn parse_mapping(lines: &[Line<'_>], idx: &mut usize, indent: usize) -> Result<Node> {
+fn parse_mapping<'a>(
+    lines: &[Line<'a>],
+    idx: &mut usize,
+    indent: usize,
+) -> Result<(Node, Spans<'a>)> {
     let mut map = BTreeMap::new();
+    let mut spans = Spans::default();
     while *idx < lines.len() {
         let line = &lines[*idx];
         if line.indent < indent {
@@ -101,75 +334,80 @@ fn parse_mapping(lines: &[Line<'_>], idx: &mut usize, indent: usize) -> Result<N
         let key = parts.next().unwrap().trim();
         let rest = parts.next().unwrap_or("").trim_start();
         *idx += 1;
-        let value = if rest.is_empty() {
+        let (value, value_spans) = if rest.is_empty() {
             parse_node(lines, idx, indent + 2)?
         } else {
             parse_tag_or_scalar(lines, idx, indent + 2, rest)?
         };
         if map.insert(key.to_string(), value).is_some() {
-            bail!("duplicate key `{key}` in mapping");
+            return Err(error_at(line.pos(), format!("duplicate key `{key}`")));
         }
+        spans
+            .entries
+            .insert(key.to_string(), (line.pos(), value_spans));
     }
-    Ok(Node::Mapping(map))
+    Ok((Node::Mapping(map), spans))
 }
 
-fn parse_tag_or_scalar(
-    lines: &[Line<'_>],
+fn parse_tag_or_scalar<'a>(
+    lines: &[Line<'a>],
     idx: &mut usize,
     indent: usize,
     text: &str,
-) -> Result<Node> {
+) -> Result<(Node, Spans<'a>)> {
+    if text == "|" {
+        // Callers advance past the owning line before handing us its value.
+        let owner = owning_line(lines, *idx);
+        let block = owner
+            .block
+            .as_deref()
+            .ok_or_else(|| error_at(owner.pos(), "block scalar without body"))?;
+        return Ok((Node::Scalar(quote_block(block)), Spans::default()));
+    }
+    let pos = owning_line(lines, *idx).pos();
     if let Some(rest) = text.strip_prefix('!') {
         let mut parts = rest.splitn(2, char::is_whitespace);
         let tag = parts
             .next()
-            .ok_or_else(|| anyhow!("missing tag after `!`"))?
+            .filter(|tag| !tag.is_empty())
+            .ok_or_else(|| error_at(pos, "missing tag after `!`"))?
             .to_string();
         let remainder = parts
             .next()
             .map(str::trim)
             .filter(|s| !s.is_empty())
             .unwrap_or("");
-        let inner = if remainder.is_empty() {
+        let (inner, inner_spans) = if remainder.is_empty() {
             if *idx >= lines.len() || lines[*idx].indent < indent {
-                Node::Scalar(String::new())
+                (Node::Scalar(String::new()), Spans::default())
             } else {
                 parse_node(lines, idx, indent)?
             }
         } else {
             parse_tag_or_scalar(lines, idx, indent, remainder)?
         };
-        Ok(Node::Tagged {
-            tag,
-            value: Box::new(inner),
-        })
+        Ok((
+            Node::Tagged {
+                tag,
+                value: Box::new(inner),
+            },
+            Spans::tagged(pos, inner_spans),
+        ))
     } else {
-        if let Some(seq) = parse_inline_sequence(text) {
-            Ok(Node::Sequence(seq))
-        } else {
-            Ok(Node::Scalar(text.to_string()))
-        }
+        Ok(parse_flow_value(text, pos))
     }
 }
 
-fn parse_inline_sequence(text: &str) -> Option<Vec<Node>> {
+/// Parse `[a, b, ...]`; every item is recorded at `pos`.
+fn parse_inline_sequence<'a>(text: &str, pos: Pos<'a>) -> Option<(Node, Spans<'a>)> {
     let trimmed = text.trim();
     if !trimmed.starts_with('[') || !trimmed.ends_with(']') {
         return None;
     }
     let mut items = Vec::new();
-    let inner = &trimmed[1..trimmed.len() - 1];
-    if inner.trim().is_empty() {
-        return Some(items);
-    }
-    for entry in inner.split(',') {
-        let value = entry.trim();
-        if value.is_empty() {
-            continue;
-        }
-        if let Some(seq) = parse_inline_sequence(value) {
-            items.push(Node::Sequence(seq));
-        } else if let Some(rest) = value.strip_prefix('!') {
+    let mut spans = Spans::default();
+    for value in split_flow_items(&trimmed[1..trimmed.len() - 1]) {
+        let (item, item_spans) = if let Some(rest) = value.strip_prefix('!') {
             // treat inline tag without payload as scalar tagged later
             let mut parts = rest.splitn(2, char::is_whitespace);
             let tag = parts.next().unwrap().to_string();
@@ -178,25 +416,117 @@ fn parse_inline_sequence(text: &str) -> Option<Vec<Node>> {
                 .map(str::trim)
                 .filter(|s| !s.is_empty())
                 .unwrap_or("{}");
-            let inner = if remainder == "{}" {
-                Node::Scalar(String::new())
-            } else if let Some(seq) = parse_inline_sequence(remainder) {
-                Node::Sequence(seq)
+            let (inner, inner_spans) = if remainder == "{}" {
+                (Node::Scalar(String::new()), Spans::default())
             } else {
-                Node::Scalar(remainder.to_string())
+                parse_flow_value(remainder, pos)
             };
-            items.push(Node::Tagged {
-                tag,
-                value: Box::new(inner),
-            });
+            (
+                Node::Tagged {
+                    tag,
+                    value: Box::new(inner),
+                },
+                Spans::tagged(pos, inner_spans),
+            )
         } else {
-            items.push(Node::Scalar(value.to_string()));
+            parse_flow_value(value, pos)
+        };
+        items.push(item);
+        spans.items.push((pos, item_spans));
+    }
+    Some((Node::Sequence(items), spans))
+}
+
+/// Parse `{key: value, ...}`; values may nest inline sequences, mappings, and tags.
+fn parse_inline_mapping<'a>(text: &str, pos: Pos<'a>) -> Option<(Node, Spans<'a>)> {
+    let trimmed = text.trim();
+    if !trimmed.starts_with('{') || !trimmed.ends_with('}') {
+        return None;
+    }
+    let mut map = BTreeMap::new();
+    let mut spans = Spans::default();
+    for entry in split_flow_items(&trimmed[1..trimmed.len() - 1]) {
+        let (key, value) = entry.split_once(':')?;
+        let key = key.trim();
+        if key.is_empty() {
+            return None;
         }
+        let value = value.trim();
+        let (node, node_spans) = if let Some(rest) = value.strip_prefix('!') {
+            let mut parts = rest.splitn(2, char::is_whitespace);
+            let tag = parts.next().unwrap().to_string();
+            let (inner, inner_spans) = parts
+                .next()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map_or((Node::Scalar(String::new()), Spans::default()), |inner| {
+                    parse_flow_value(inner, pos)
+                });
+            (
+                Node::Tagged {
+                    tag,
+                    value: Box::new(inner),
+                },
+                Spans::tagged(pos, inner_spans),
+            )
+        } else {
+            parse_flow_value(value, pos)
+        };
+        if map.insert(key.to_string(), node).is_some() {
+            return None;
+        }
+        spans.entries.insert(key.to_string(), (pos, node_spans));
     }
-    Some(items)
+    Some((Node::Mapping(map), spans))
+}
+
+fn parse_flow_value<'a>(text: &str, pos: Pos<'a>) -> (Node, Spans<'a>) {
+    parse_inline_sequence(text, pos)
+        .or_else(|| parse_inline_mapping(text, pos))
+        .unwrap_or_else(|| (Node::Scalar(text.to_string()), Spans::default()))
 }
 
-fn parse_scalar_node(lines: &[Line<'_>], idx: &mut usize, _indent: usize) -> Result<Node> {
+/// Split the body of a flow collection on commas outside brackets, braces, and quotes.
+fn split_flow_items(inner: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut in_quote = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (pos, ch) in inner.char_indices() {
+        if in_quote {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_quote = false,
+                _ => {}
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_quote = true,
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(&inner[start..pos]);
+                start = pos + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&inner[start..]);
+    items
+        .into_iter()
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn parse_scalar_node<'a>(
+    lines: &[Line<'a>],
+    idx: &mut usize,
+    _indent: usize,
+) -> Result<(Node, Spans<'a>)> {
     let content = lines[*idx].content;
     *idx += 1;
     parse_tag_or_scalar(lines, idx, _indent, content)
@@ -204,7 +534,7 @@ fn parse_scalar_node(lines: &[Line<'_>], idx: &mut usize, _indent: usize) -> Res
 
 fn decode_hex(input: &str) -> Result<Vec<u8>> {
     let trimmed = input.trim();
-    if trimmed.len() % 2 != 0 {
+    if !trimmed.len().is_multiple_of(2) {
         bail!("hex string must have even length");
     }
     let mut bytes = Vec::with_capacity(trimmed.len() / 2);
@@ -257,9 +587,18 @@ pub fn parse_values_from_str(input: &str) -> Result<Vec<Value>> {
         return Ok(Vec::new());
     }
     let mut idx = 0;
-    match parse_node(&lines, &mut idx, 0)? {
-        Node::Sequence(items) => items.into_iter().map(decode_value).collect(),
-        other => bail!("expected YAML sequence at root, found {:?}", other),
+    let (root, spans) = parse_node(&lines, &mut idx, 0)?;
+    ensure_consumed(&lines, idx)?;
+    match root {
+        Node::Sequence(items) => items
+            .into_iter()
+            .zip(&spans.items)
+            .map(|(item, (pos, item_spans))| decode_value(item, item_spans, *pos))
+            .collect(),
+        other => Err(error_at(
+            lines[0].pos(),
+            format!("expected YAML sequence at root, found {other:?}"),
+        )),
     }
 }
 
@@ -268,7 +607,13 @@ pub fn parse_values_from_file(path: &Path) -> Result<Vec<Value>> {
     parse_values_from_str(&contents)
 }
 
-fn decode_value(node: Node) -> Result<Value> {
+/// Decode `node`, reporting failures at its own position or else at `pos`.
+fn decode_value(node: Node, spans: &Spans<'_>, pos: Pos<'_>) -> Result<Value> {
+    let pos = spans.at(pos);
+    decode_value_inner(node, spans, pos).map_err(|err| locate(pos, err))
+}
+
+fn decode_value_inner(node: Node, spans: &Spans<'_>, pos: Pos<'_>) -> Result<Value> {
     match node {
         Node::Scalar(text) => parse_scalar_value(&text),
         Node::Tagged { tag, value } => match tag.as_str() {
@@ -277,11 +622,20 @@ fn decode_value(node: Node) -> Result<Value> {
                 let number: i64 = scalar.parse()?;
                 Ok(Value::I64(number))
             }
+            "u64" => {
+                let scalar = as_scalar(&value)?;
+                let number: u64 = scalar.parse()?;
+                Ok(Value::U64(number))
+            }
             "f64" => {
                 let scalar = as_scalar(&value)?;
                 let number: f64 = scalar.parse()?;
                 Ok(Value::F64(number))
             }
+            "bytes" => {
+                let scalar = as_scalar(&value)?;
+                Ok(Value::Bytes(decode_hex(&scalar)?))
+            }
             "text" => {
                 let scalar = as_scalar(&value)?;
                 Ok(Value::Text(scalar))
@@ -289,12 +643,17 @@ fn decode_value(node: Node) -> Result<Value> {
             "tuple" => match *value {
                 Node::Sequence(items) => {
                     let mut elements = Vec::with_capacity(items.len());
-                    for item in items {
-                        elements.push(decode_value(item)?);
+                    for (item, (item_pos, item_spans)) in
+                        items.into_iter().zip(&spans.payload().items)
+                    {
+                        elements.push(decode_value(item, item_spans, *item_pos)?);
                     }
                     Ok(Value::Tuple(elements))
                 }
-                other => bail!("tuple payload must be sequence, found {:?}", other),
+                other => Err(error_at(
+                    pos,
+                    format!("tuple payload must be sequence, found {other:?}"),
+                )),
             },
             "unit" => Ok(Value::Unit),
             "quote" => {
@@ -311,12 +670,12 @@ fn decode_value(node: Node) -> Result<Value> {
         },
         Node::Sequence(items) => {
             let mut values = Vec::with_capacity(items.len());
-            for item in items {
-                values.push(decode_value(item)?);
+            for (item, (item_pos, item_spans)) in items.into_iter().zip(&spans.items) {
+                values.push(decode_value(item, item_spans, *item_pos)?);
             }
             Ok(Value::Tuple(values))
         }
-        Node::Mapping(_) => bail!("cannot decode mapping into Value"),
+        Node::Mapping(..) => bail!("cannot decode mapping into Value"),
     }
 }
 
@@ -381,6 +740,7 @@ pub enum WordOp {
     Over,
     Lit(Value),
     Quote([u8; 32]),
+    LoadGlobal([u8; 32], TypeTag),
 }
 
 pub type Catalog = BTreeMap<String, BTreeMap<String, CatalogItem>>;
@@ -391,42 +751,82 @@ pub fn parse_catalog_from_str(input: &str) -> Result<Catalog> {
         return Ok(BTreeMap::new());
     }
     let mut idx = 0;
-    let root = parse_node(&lines, &mut idx, 0)?;
+    let (root, spans) = parse_node(&lines, &mut idx, 0)?;
+    ensure_consumed(&lines, idx)?;
     match root {
         Node::Mapping(namespaces) => {
             let mut catalog = BTreeMap::new();
             for (ns, entries) in namespaces {
+                let (ns_pos, ns_spans) = spans.entry(&ns, lines[0].pos());
                 let mapping = match entries {
                     Node::Mapping(items) => items,
-                    other => bail!("namespace `{ns}` must be mapping, found {:?}", other),
+                    other => {
+                        return Err(error_at(
+                            ns_pos,
+                            format!("namespace `{ns}` must be mapping, found {other:?}"),
+                        ));
+                    }
                 };
                 let mut ns_entries = BTreeMap::new();
                 for (symbol, node) in mapping {
-                    let item = decode_catalog_entry(&symbol, node)?;
+                    let (pos, entry_spans) = ns_spans.entry(&symbol, ns_pos);
+                    let item = decode_catalog_entry(&symbol, node, entry_spans, pos)?;
                     ns_entries.insert(symbol, item);
                 }
                 catalog.insert(ns, ns_entries);
             }
             Ok(catalog)
         }
-        other => bail!("catalog root must be mapping, found {:?}", other),
+        Node::Sequence(..) => Err(error_at(
+            lines[0].pos(),
+            "catalog root is a sequence; expected a mapping of `namespace:` blocks (remove the leading `- `)",
+        )),
+        other => Err(error_at(
+            lines[0].pos(),
+            format!("catalog root must be mapping, found {other:?}"),
+        )),
     }
 }
 
+/// Number of entries across all namespaces.
+///
+/// Zero usually means a mistake (an all-comment file, or indentation that
+/// collapsed every entry) rather than an intentionally empty catalog.
+pub fn catalog_entry_count(catalog: &Catalog) -> usize {
+    catalog.values().map(BTreeMap::len).sum()
+}
+
 pub fn parse_catalog_from_file(path: &Path) -> Result<Catalog> {
     let contents = fs::read_to_string(path)?;
     parse_catalog_from_str(&contents)
 }
 
-fn decode_catalog_entry(symbol: &str, node: Node) -> Result<CatalogItem> {
+/// Decode the entry `symbol` whose key sits at `pos`.
+fn decode_catalog_entry(
+    symbol: &str,
+    node: Node,
+    spans: &Spans<'_>,
+    pos: Pos<'_>,
+) -> Result<CatalogItem> {
+    let pos = spans.at(pos);
+    decode_catalog_entry_inner(symbol, node, spans, pos).map_err(|err| locate(pos, err))
+}
+
+fn decode_catalog_entry_inner(
+    symbol: &str,
+    node: Node,
+    spans: &Spans<'_>,
+    pos: Pos<'_>,
+) -> Result<CatalogItem> {
+    let payload = spans.payload();
     match node {
         Node::Tagged { tag, value } => match tag.as_str() {
             "effect" => decode_effect_entry(*value),
-            "prim" => decode_prim_entry(*value),
-            "word" => decode_word_entry(symbol, *value),
-            "guard" => decode_guard_entry(symbol, *value),
-            "overloads" => decode_overloads_entry(*value),
-            "snapshot" => decode_snapshot_entry(*value),
+            "prim" => decode_prim_entry(*value, payload, pos),
+            "word" => decode_word_entry(symbol, *value, payload, pos),
+            "guard" => decode_guard_entry(symbol, *value, payload, pos),
+            "overloads" => decode_overloads_entry(*value, payload),
+            "snapshot" => decode_snapshot_entry(*value, payload, pos),
             other => bail!("unsupported catalog tag `{other}`"),
         },
         other => bail!(
@@ -444,39 +844,48 @@ pub struct OverloadSpec {
     pub stack: Vec<WordOp>,
 }
 
-fn decode_overloads_entry(node: Node) -> Result<CatalogItem> {
+fn decode_overloads_entry(node: Node, spans: &Spans<'_>) -> Result<CatalogItem> {
     // Require a sequence of overload entries; labels are not supported to avoid ambiguity.
     let list = match node {
         Node::Sequence(items) => items,
         other => bail!("overloads entry must be sequence, found {:?}", other),
     };
     let mut entries: Vec<OverloadSpec> = Vec::with_capacity(list.len());
-    for item in list {
-        let map = match item {
-            Node::Mapping(map) => map,
-            Node::Tagged { tag, value } if tag == "word" => match *value {
-                Node::Mapping(map) => map,
-                other => bail!("!word payload must be mapping, found {:?}", other),
-            },
-            other => bail!("overload spec must be mapping or !word, found {:?}", other),
-        };
-        let params = parse_type_list(map.get("params"))?;
-        let results = parse_type_list(map.get("results"))?;
-        let guards = parse_string_list(map.get("guards"))?;
-        let stack_node = map
-            .get("stack")
-            .ok_or_else(|| anyhow!("overload entry missing `stack` field"))?;
-        let stack = decode_word_ops(stack_node)?;
-        entries.push(OverloadSpec {
-            params,
-            results,
-            guards,
-            stack,
-        });
+    for (item, (item_pos, item_spans)) in list.into_iter().zip(&spans.items) {
+        let item_pos = item_spans.at(*item_pos);
+        let spec = decode_overload_spec(item, item_spans, item_pos)
+            .map_err(|err| locate(item_pos, err))?;
+        entries.push(spec);
     }
     Ok(CatalogItem::Overloads { entries })
 }
 
+fn decode_overload_spec(item: Node, spans: &Spans<'_>, pos: Pos<'_>) -> Result<OverloadSpec> {
+    let (map, spans) = match item {
+        Node::Mapping(map) => (map, spans),
+        Node::Tagged { tag, value } if tag == "word" => match *value {
+            Node::Mapping(map) => (map, spans.payload()),
+            other => bail!("!word payload must be mapping, found {:?}", other),
+        },
+        other => bail!("overload spec must be mapping or !word, found {:?}", other),
+    };
+    let field = |key: &str| spans.key_pos(key, pos);
+    let params = parse_type_list(map.get("params"), field("params"))?;
+    let results = parse_type_list(map.get("results"), field("results"))?;
+    let guards = parse_string_list(map.get("guards"), field("guards"))?;
+    let stack_node = map
+        .get("stack")
+        .ok_or_else(|| anyhow!("overload entry missing `stack` field"))?;
+    let (stack_pos, stack_spans) = spans.entry("stack", pos);
+    let stack = decode_word_ops(stack_node, stack_spans, stack_pos)?;
+    Ok(OverloadSpec {
+        params,
+        results,
+        guards,
+        stack,
+    })
+}
+
 fn decode_effect_entry(node: Node) -> Result<CatalogItem> {
     match node {
         Node::Mapping(map) => {
@@ -491,22 +900,21 @@ fn decode_effect_entry(node: Node) -> Result<CatalogItem> {
     }
 }
 
-fn decode_prim_entry(node: Node) -> Result<CatalogItem> {
+fn decode_prim_entry(node: Node, spans: &Spans<'_>, pos: Pos<'_>) -> Result<CatalogItem> {
     let map = match node {
         Node::Mapping(map) => map,
         other => bail!("prim entry must be mapping, found {:?}", other),
     };
-    let params = parse_type_list(map.get("params"))?;
-    let results = parse_type_list(map.get("results"))?;
-    let effects = parse_hex_list(map.get("effects"))?;
+    let field = |key: &str| spans.key_pos(key, pos);
+    let params = parse_type_list(map.get("params"), field("params"))?;
+    let results = parse_type_list(map.get("results"), field("results"))?;
+    let effects = parse_hex_list(map.get("effects"), field("effects"))?;
     let emask = match map.get("emask") {
-        Some(Node::Sequence(items)) => items
-            .iter()
-            .map(|n| as_scalar(n))
-            .collect::<Result<Vec<_>>>()?,
-        Some(other) => bail!("emask must be sequence, found {:?}", other),
-        None => Vec::new(),
-    };
+        Some(Node::Sequence(items)) => items.iter().map(as_scalar).collect::<Result<Vec<_>>>(),
+        Some(other) => Err(anyhow!("emask must be sequence, found {:?}", other)),
+        None => Ok(Vec::new()),
+    }
+    .map_err(|err| locate(field("emask"), err))?;
     Ok(CatalogItem::Prim {
         params,
         results,
@@ -515,18 +923,25 @@ fn decode_prim_entry(node: Node) -> Result<CatalogItem> {
     })
 }
 
-fn decode_word_entry(symbol: &str, node: Node) -> Result<CatalogItem> {
+fn decode_word_entry(
+    symbol: &str,
+    node: Node,
+    spans: &Spans<'_>,
+    pos: Pos<'_>,
+) -> Result<CatalogItem> {
     let map = match node {
         Node::Mapping(map) => map,
         other => bail!("word entry `{symbol}` must be mapping, found {:?}", other),
     };
-    let params = parse_type_list(map.get("params"))?;
-    let results = parse_type_list(map.get("results"))?;
+    let field = |key: &str| spans.key_pos(key, pos);
+    let params = parse_type_list(map.get("params"), field("params"))?;
+    let results = parse_type_list(map.get("results"), field("results"))?;
     let stack_node = map
         .get("stack")
         .ok_or_else(|| anyhow!("word `{symbol}` missing `stack` field"))?;
-    let ops = decode_word_ops(stack_node)?;
-    let guards = parse_string_list(map.get("guards"))?;
+    let (stack_pos, stack_spans) = spans.entry("stack", pos);
+    let ops = decode_word_ops(stack_node, stack_spans, stack_pos)?;
+    let guards = parse_string_list(map.get("guards"), field("guards"))?;
     Ok(CatalogItem::Word {
         params,
         results,
@@ -535,20 +950,27 @@ fn decode_word_entry(symbol: &str, node: Node) -> Result<CatalogItem> {
     })
 }
 
-fn decode_guard_entry(symbol: &str, node: Node) -> Result<CatalogItem> {
+fn decode_guard_entry(
+    symbol: &str,
+    node: Node,
+    spans: &Spans<'_>,
+    pos: Pos<'_>,
+) -> Result<CatalogItem> {
     let map = match node {
         Node::Mapping(map) => map,
         other => bail!("guard entry `{symbol}` must be mapping, found {:?}", other),
     };
-    let params = parse_type_list(map.get("params"))?;
-    let mut results = parse_type_list(map.get("results"))?;
+    let field = |key: &str| spans.key_pos(key, pos);
+    let params = parse_type_list(map.get("params"), field("params"))?;
+    let mut results = parse_type_list(map.get("results"), field("results"))?;
     if results.is_empty() {
         results.push(TypeTag::I64);
     }
     let stack_node = map
         .get("stack")
         .ok_or_else(|| anyhow!("guard `{symbol}` missing `stack` field"))?;
-    let ops = decode_word_ops(stack_node)?;
+    let (stack_pos, stack_spans) = spans.entry("stack", pos);
+    let ops = decode_word_ops(stack_node, stack_spans, stack_pos)?;
     Ok(CatalogItem::Guard {
         params,
         results,
@@ -556,79 +978,95 @@ fn decode_guard_entry(symbol: &str, node: Node) -> Result<CatalogItem> {
     })
 }
 
-fn decode_snapshot_entry(node: Node) -> Result<CatalogItem> {
+fn decode_snapshot_entry(node: Node, spans: &Spans<'_>, pos: Pos<'_>) -> Result<CatalogItem> {
     let map = match node {
         Node::Mapping(map) => map,
         other => bail!("snapshot entry must be mapping, found {:?}", other),
     };
     let mut values = BTreeMap::new();
     for (key, node) in map {
-        let value = decode_value(node)?;
+        let (value_pos, value_spans) = spans.entry(&key, pos);
+        let value = decode_value(node, value_spans, value_pos)?;
         values.insert(key, value);
     }
     Ok(CatalogItem::Snapshot { values })
 }
 
-fn parse_type_list(node: Option<&Node>) -> Result<Vec<TypeTag>> {
+/// Decode an optional `[type, ...]` field whose key sits at `pos`.
+fn parse_type_list(node: Option<&Node>, pos: Pos<'_>) -> Result<Vec<TypeTag>> {
     match node {
         None => Ok(Vec::new()),
         Some(Node::Sequence(items)) => items
             .iter()
             .map(|item| TypeTag::from_atom(&as_scalar(item)?))
             .collect(),
-        Some(other) => bail!("type list must be sequence, found {:?}", other),
+        Some(other) => Err(anyhow!("type list must be sequence, found {:?}", other)),
     }
+    .map_err(|err| locate(pos, err))
 }
 
-fn parse_hex_list(node: Option<&Node>) -> Result<Vec<[u8; 32]>> {
-    match node {
-        None => Ok(Vec::new()),
-        Some(Node::Sequence(items)) => {
-            let mut out = Vec::with_capacity(items.len());
-            for item in items {
-                let scalar = as_scalar(item)?;
-                if scalar.is_empty() {
-                    continue;
-                }
-                let bytes = decode_hex(&scalar)?;
-                if bytes.len() != 32 {
-                    bail!("effect CID must be 32 bytes, found {}", bytes.len());
-                }
-                let mut cid = [0u8; 32];
-                cid.copy_from_slice(&bytes);
-                out.push(cid);
-            }
-            Ok(out)
+fn parse_hex_list(node: Option<&Node>, pos: Pos<'_>) -> Result<Vec<[u8; 32]>> {
+    let items = match node {
+        None => return Ok(Vec::new()),
+        Some(Node::Sequence(items)) => items,
+        Some(other) => {
+            return Err(error_at(
+                pos,
+                format!("effects list must be sequence, found {other:?}"),
+            ));
+        }
+    };
+    let mut out = Vec::with_capacity(items.len());
+    for item in items {
+        let scalar = as_scalar(item).map_err(|err| locate(pos, err))?;
+        if scalar.is_empty() {
+            continue;
+        }
+        let bytes = decode_hex(&scalar).map_err(|err| locate(pos, err))?;
+        if bytes.len() != 32 {
+            return Err(error_at(
+                pos,
+                format!("effect CID must be 32 bytes, found {}", bytes.len()),
+            ));
         }
-        Some(other) => bail!("effects list must be sequence, found {:?}", other),
+        let mut cid = [0u8; 32];
+        cid.copy_from_slice(&bytes);
+        out.push(cid);
     }
+    Ok(out)
 }
 
-fn parse_string_list(node: Option<&Node>) -> Result<Vec<String>> {
+fn parse_string_list(node: Option<&Node>, pos: Pos<'_>) -> Result<Vec<String>> {
     match node {
         None => Ok(Vec::new()),
-        Some(Node::Sequence(items)) => items
-            .iter()
-            .map(|item| as_scalar(item))
-            .collect::<Result<Vec<_>>>(),
-        Some(other) => bail!("string list must be sequence, found {:?}", other),
+        Some(Node::Sequence(items)) => items.iter().map(as_scalar).collect::<Result<Vec<_>>>(),
+        Some(other) => Err(anyhow!("string list must be sequence, found {:?}", other)),
     }
+    .map_err(|err| locate(pos, err))
 }
 
-fn decode_word_ops(node: &Node) -> Result<Vec<WordOp>> {
+/// Decode a `stack:` sequence whose key sits at `pos`.
+fn decode_word_ops(node: &Node, spans: &Spans<'_>, pos: Pos<'_>) -> Result<Vec<WordOp>> {
     match node {
         Node::Sequence(items) => {
             let mut ops = Vec::with_capacity(items.len());
-            for item in items {
-                ops.push(decode_word_op(item)?);
+            for (item, (item_pos, item_spans)) in items.iter().zip(&spans.items) {
+                let item_pos = item_spans.at(*item_pos);
+                ops.push(
+                    decode_word_op(item, item_spans, item_pos)
+                        .map_err(|err| locate(item_pos, err))?,
+                );
             }
             Ok(ops)
         }
-        other => bail!("word stack must be sequence, found {:?}", other),
+        other => Err(error_at(
+            pos,
+            format!("word stack must be sequence, found {other:?}"),
+        )),
     }
 }
 
-fn decode_word_op(node: &Node) -> Result<WordOp> {
+fn decode_word_op(node: &Node, spans: &Spans<'_>, pos: Pos<'_>) -> Result<WordOp> {
     match node {
         Node::Tagged { tag, value } => match tag.as_str() {
             "prim" => Ok(WordOp::Prim(as_scalar(value)?)),
@@ -636,22 +1074,29 @@ fn decode_word_op(node: &Node) -> Result<WordOp> {
             "dup" => Ok(WordOp::Dup),
             "swap" => Ok(WordOp::Swap),
             "over" => Ok(WordOp::Over),
-            "quote" => {
-                let scalar = as_scalar(value)?;
-                let bytes = decode_hex(&scalar)?;
-                if bytes.len() != 32 {
-                    bail!("quote op requires 32-byte hex, found {}", bytes.len());
+            "quote" => Ok(WordOp::Quote(decode_op_cid("quote", &as_scalar(value)?)?)),
+            "load-global" => match &**value {
+                Node::Sequence(items) if items.len() == 2 => {
+                    let key = decode_op_cid("load-global", &as_scalar(&items[0])?)?;
+                    let ty = TypeTag::from_atom(&as_scalar(&items[1])?)?;
+                    Ok(WordOp::LoadGlobal(key, ty))
                 }
-                let mut cid = [0u8; 32];
-                cid.copy_from_slice(&bytes);
-                Ok(WordOp::Quote(cid))
-            }
+                Node::Sequence(..) => bail!("load-global op expects `<hex>` or `[<hex>, <type>]`"),
+                other => Ok(WordOp::LoadGlobal(
+                    decode_op_cid("load-global", &as_scalar(other)?)?,
+                    TypeTag::I64,
+                )),
+            },
             "lit" => {
-                let value_node = match &**value {
-                    Node::Sequence(seq) if !seq.is_empty() => seq[0].clone(),
-                    other => other.clone(),
+                let payload = spans.payload();
+                let (value_node, value_spans) = match &**value {
+                    Node::Sequence(seq) if !seq.is_empty() => (
+                        seq[0].clone(),
+                        payload.items.first().map_or(&NO_SPANS, |(_, s)| s),
+                    ),
+                    other => (other.clone(), payload),
                 };
-                let lit = decode_value(value_node)?;
+                let lit = decode_value(value_node, value_spans, pos)?;
                 Ok(WordOp::Lit(lit))
             }
             other => bail!("unsupported word stack tag `{other}`"),
@@ -663,6 +1108,16 @@ fn decode_word_op(node: &Node) -> Result<WordOp> {
     }
 }
 
+fn decode_op_cid(op: &str, scalar: &str) -> Result<[u8; 32]> {
+    let bytes = decode_hex(scalar)?;
+    if bytes.len() != 32 {
+        bail!("{op} op requires 32-byte hex, found {}", bytes.len());
+    }
+    let mut cid = [0u8; 32];
+    cid.copy_from_slice(&bytes);
+    Ok(cid)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -684,6 +1139,166 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_errors_report_line_numbers() {
+        let duplicate = "core:\n  io: !effect\n  add_i64: !prim\n    params: [i64, i64]\n    results: [i64]\n    params: [i64]\n";
+        let err = parse_catalog_from_str(duplicate).unwrap_err().to_string();
+        assert!(
+            err.starts_with("line 6, column 5: duplicate key `params`"),
+            "{err}"
+        );
+
+        let stray = "# header comment\ncore:\n  io: !effect\n      doc: misplaced\n";
+        let err = parse_catalog_from_str(stray).unwrap_err().to_string();
+        assert!(err.starts_with("line 4, column 7:"), "{err}");
+        assert!(err.contains("doc: misplaced"), "{err}");
+
+        let err = parse_values_from_str("\nkey: 1\n").unwrap_err().to_string();
+        assert!(
+            err.starts_with("line 2, column 1: expected YAML sequence"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn decode_errors_report_line_numbers() {
+        let cases = [
+            (
+                "core: plain\n",
+                "line 1, column 1: namespace `core` must be mapping",
+            ),
+            (
+                "core:\n  io: !effect\n  x: !bogus\n",
+                "line 3, column 3: unsupported catalog tag",
+            ),
+            (
+                "demo:\n  w: !word plain\n",
+                "line 2, column 3: word entry `w` must be mapping",
+            ),
+            (
+                "demo:\n  w: !word\n    params: [i64]\n    stack: plain\n",
+                "line 4, column 5: word stack must be sequence",
+            ),
+            (
+                "demo:\n  w: !word\n    stack:\n      - !dup\n      - !bogus\n",
+                "line 5, column 7: unsupported word stack tag",
+            ),
+            (
+                "demo:\n  w: !word\n    params: [i64, nope]\n    stack: []\n",
+                "line 3, column 5:",
+            ),
+        ];
+        for (doc, expected) in cases {
+            let err = parse_catalog_from_str(doc).unwrap_err().to_string();
+            assert!(err.starts_with(expected), "{err}");
+        }
+
+        let err = parse_values_from_str("- !i64 1\n- !tuple\n  - !i64 x\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("line 3, column 3:"), "{err}");
+        assert!(err.ends_with("in `- !i64 x`"), "{err}");
+    }
+
+    #[test]
+    fn empty_and_sequence_rooted_catalogs() -> Result<()> {
+        let comments = "# nothing here yet\n---\n  # indented comment\n";
+        let catalog = parse_catalog_from_str(comments)?;
+        assert_eq!(catalog_entry_count(&catalog), 0);
+
+        let sequence = "- core\n- demo\n";
+        let err = parse_catalog_from_str(sequence).unwrap_err().to_string();
+        assert!(
+            err.starts_with("line 1, column 1: catalog root is a sequence"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_inline_mapping_matches_block_form() -> Result<()> {
+        let inline = r#"
+core:
+  add: !prim {params: [i64, i64], results: [i64], emask: [io]}
+  io: !effect {doc: "a, b: c"}
+"#;
+        let block = r#"
+core:
+  add: !prim
+    params: [i64, i64]
+    results: [i64]
+    emask: [io]
+  io: !effect
+    doc: "a, b: c"
+"#;
+        let inline = parse_catalog_from_str(inline)?;
+        let block = parse_catalog_from_str(block)?;
+        assert_eq!(format!("{inline:?}"), format!("{block:?}"));
+        assert!(matches!(
+            inline["core"].get("add"),
+            Some(CatalogItem::Prim { params, .. }) if params == &[TypeTag::I64, TypeTag::I64]
+        ));
+
+        let Some((Node::Mapping(nested), _)) =
+            parse_inline_mapping("{a: {b: [1, [2, 3]]}, c: !i64 4}", Pos::default())
+        else {
+            panic!("expected inline mapping");
+        };
+        assert!(matches!(&nested["a"], Node::Mapping(inner) if matches!(
+            &inner["b"],
+            Node::Sequence(items) if items.len() == 2
+        )));
+        assert!(matches!(&nested["c"], Node::Tagged { tag, .. } if tag == "i64"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_block_scalar_doc() -> Result<()> {
+        let doc = r#"
+core:
+  io: !effect
+    doc: |
+      Performs IO.
+        # indented detail kept verbatim
+
+      Ends here.
+  text: !effect
+    doc: plain
+"#;
+        let catalog = parse_catalog_from_str(doc)?;
+        let core = catalog.get("core").unwrap();
+        match core.get("io") {
+            Some(CatalogItem::Effect { doc }) => assert_eq!(
+                doc.as_deref(),
+                Some("Performs IO.\n  # indented detail kept verbatim\n\nEnds here.")
+            ),
+            other => panic!("expected effect, got {other:?}"),
+        }
+        assert!(matches!(
+            core.get("text"),
+            Some(CatalogItem::Effect { doc: Some(d) }) if d == "plain"
+        ));
+
+        let values = parse_values_from_str("- |\n  \"quoted\" 42\n  second\n- 7\n")?;
+        assert_eq!(values[0], Value::Text("\"quoted\" 42\nsecond".to_string()));
+        assert_eq!(values[1], Value::I64(7));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_u64_and_bytes_values() -> Result<()> {
+        let values = parse_values_from_str("- !u64 18446744073709551615\n- !bytes deadbeef\n")?;
+        assert_eq!(values[0], Value::U64(u64::MAX));
+        assert_eq!(values[1], Value::Bytes(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(values[0].to_string(), "18446744073709551615u64");
+        assert_eq!(values[1].to_string(), "<bytes:deadbeef>");
+        assert!(parse_values_from_str("- !u64 -1\n").is_err());
+        assert_eq!(TypeTag::from_atom("u64")?, TypeTag::U64);
+        assert_eq!(TypeTag::Bytes.as_atom(), "bytes");
+        assert_eq!(values[1].type_name(), "bytes");
+        Ok(())
+    }
+
     #[test]
     fn parse_catalog_structures() -> Result<()> {
         let doc = r#"